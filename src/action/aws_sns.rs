@@ -0,0 +1,494 @@
+use super::Action;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+// Instance metadata service (IMDSv2) for instance role credentials.
+const IMDS_URL: &str = "http://169.254.169.254";
+
+#[derive(Clone, PartialEq, Debug)]
+struct Credentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InstanceRoleCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    token: String,
+}
+
+pub struct AwsSns {
+    topic_arn: String,
+    region: String,
+    endpoint: String,
+    subject: Option<String>,
+    message: String,
+    message_attributes: Vec<(String, String)>,
+    credentials: Option<Credentials>,
+    imds_url: String,
+    client: reqwest::Client,
+}
+
+impl AwsSns {
+    fn sha256_hex(data: &[u8]) -> String {
+        Self::hex(&openssl::sha::sha256(data))
+    }
+
+    fn hex(data: &[u8]) -> String {
+        data.iter().map(|x| format!("{:02x}", x)).collect()
+    }
+
+    fn hmac(key: &[u8], data: &str) -> Result<Vec<u8>> {
+        let error = |x| Error(format!("Could not calculate HMAC: {}", x));
+        let key = openssl::pkey::PKey::hmac(key).map_err(error)?;
+        let mut signer = openssl::sign::Signer::new(openssl::hash::MessageDigest::sha256(), &key)
+            .map_err(error)?;
+        signer.update(data.as_bytes()).map_err(error)?;
+        signer.sign_to_vec().map_err(error)
+    }
+
+    // see https://docs.aws.amazon.com/general/latest/gr/signature-v4-examples.html
+    fn signing_key(
+        secret_access_key: &str,
+        date: &str,
+        region: &str,
+        service: &str,
+    ) -> Result<Vec<u8>> {
+        let key = Self::hmac(format!("AWS4{}", secret_access_key).as_bytes(), date)?;
+        let key = Self::hmac(&key, region)?;
+        let key = Self::hmac(&key, service)?;
+        Self::hmac(&key, "aws4_request")
+    }
+
+    // Signs the request with AWS Signature Version 4 by adding the required headers.
+    fn sign(
+        &self,
+        request: &mut reqwest::Request,
+        credentials: &Credentials,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<()> {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let url = request.url();
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => String::from(url.host_str().unwrap_or_default()),
+        };
+        let mut headers = vec![
+            (
+                "content-type",
+                String::from("application/x-www-form-urlencoded"),
+            ),
+            ("host", host),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(session_token) = &credentials.session_token {
+            headers.push(("x-amz-security-token", session_token.clone()));
+        }
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<&str>>()
+            .join(";");
+        let body = request
+            .body()
+            .and_then(|x| x.as_bytes())
+            .unwrap_or_default();
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            request.method(),
+            url.path(),
+            url.query().unwrap_or_default(),
+            headers
+                .iter()
+                .map(|(name, value)| format!("{}:{}\n", name, value))
+                .collect::<String>(),
+            signed_headers,
+            Self::sha256_hex(body)
+        );
+        let scope = format!("{}/{}/sns/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            Self::sha256_hex(canonical_request.as_bytes())
+        );
+        let signature = Self::hex(&Self::hmac(
+            &Self::signing_key(&credentials.secret_access_key, &date, &self.region, "sns")?,
+            &string_to_sign,
+        )?);
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            credentials.access_key_id, scope, signed_headers, signature
+        );
+        for (name, value) in headers
+            .into_iter()
+            .filter(|(name, _)| *name != "host")
+            .chain(std::iter::once(("authorization", authorization)))
+        {
+            request.headers_mut().insert(
+                name,
+                reqwest::header::HeaderValue::from_str(&value)
+                    .map_err(|x| Error(format!("Invalid header value for '{}': {}", name, x)))?,
+            );
+        }
+        Ok(())
+    }
+
+    fn env_credentials() -> Option<Credentials> {
+        Some(Credentials {
+            access_key_id: std::env::var("AWS_ACCESS_KEY_ID").ok()?,
+            secret_access_key: std::env::var("AWS_SECRET_ACCESS_KEY").ok()?,
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+
+    async fn instance_role_credentials(&self) -> Result<Credentials> {
+        let error = |x| Error(format!("Could not get instance role credentials: {}", x));
+        let token = self
+            .client
+            .put(format!("{}/latest/api/token", self.imds_url))
+            .header("X-aws-ec2-metadata-token-ttl-seconds", "60")
+            .timeout(std::time::Duration::from_secs(2))
+            .send()
+            .await
+            .and_then(|x| x.error_for_status())
+            .map_err(error)?
+            .text()
+            .await
+            .map_err(error)?;
+        let get = |path: String| {
+            self.client
+                .get(format!(
+                    "{}/latest/meta-data/iam/security-credentials/{}",
+                    self.imds_url, path
+                ))
+                .header("X-aws-ec2-metadata-token", &token)
+                .timeout(std::time::Duration::from_secs(2))
+                .send()
+        };
+        let role = get(String::new())
+            .await
+            .and_then(|x| x.error_for_status())
+            .map_err(error)?
+            .text()
+            .await
+            .map_err(error)?;
+        let role = role
+            .lines()
+            .next()
+            .ok_or_else(|| Error(String::from("No instance role found.")))?;
+        let credentials: InstanceRoleCredentials = get(String::from(role))
+            .await
+            .and_then(|x| x.error_for_status())
+            .map_err(error)?
+            .json()
+            .await
+            .map_err(error)?;
+        Ok(Credentials {
+            access_key_id: credentials.access_key_id,
+            secret_access_key: credentials.secret_access_key,
+            session_token: Some(credentials.token),
+        })
+    }
+
+    // Credentials are taken from the config, the environment or the instance role (in that order).
+    async fn get_credentials(&self) -> Result<Credentials> {
+        if let Some(credentials) = &self.credentials {
+            return Ok(credentials.clone());
+        }
+        match Self::env_credentials() {
+            Some(credentials) => Ok(credentials),
+            None => self.instance_role_credentials().await,
+        }
+    }
+
+    fn form(&self, placeholders: &PlaceholderMap) -> Vec<(String, String)> {
+        let mut form = vec![
+            (String::from("Action"), String::from("Publish")),
+            (String::from("Version"), String::from("2010-03-31")),
+            (String::from("TopicArn"), self.topic_arn.clone()),
+            (
+                String::from("Message"),
+                crate::fill_placeholders(&self.message, placeholders),
+            ),
+        ];
+        if let Some(subject) = &self.subject {
+            form.push((
+                String::from("Subject"),
+                crate::fill_placeholders(subject, placeholders),
+            ));
+        }
+        for (i, (name, value)) in self.message_attributes.iter().enumerate() {
+            let prefix = format!("MessageAttributes.entry.{}", i + 1);
+            form.push((format!("{}.Name", prefix), name.clone()));
+            form.push((format!("{}.Value.DataType", prefix), String::from("String")));
+            form.push((
+                format!("{}.Value.StringValue", prefix),
+                crate::fill_placeholders(value, placeholders),
+            ));
+        }
+        form
+    }
+}
+
+impl TryFrom<&config::Action> for AwsSns {
+    type Error = Error;
+
+    fn try_from(action: &config::Action) -> std::result::Result<Self, Self::Error> {
+        if let config::ActionType::AwsSns(aws_sns) = &action.type_ {
+            if aws_sns.topic_arn.is_empty() {
+                Err(Error(String::from("'topic_arn' cannot be empty.")))
+            } else if aws_sns.region.is_empty() {
+                Err(Error(String::from("'region' cannot be empty.")))
+            } else if aws_sns.access_key_id.is_some() != aws_sns.secret_access_key.is_some() {
+                Err(Error(String::from(
+                    "'access_key_id' and 'secret_access_key' need to be set together.",
+                )))
+            } else {
+                let mut message_attributes: Vec<(String, String)> = aws_sns
+                    .message_attributes
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect();
+                message_attributes.sort();
+                Ok(Self {
+                    topic_arn: aws_sns.topic_arn.clone(),
+                    region: aws_sns.region.clone(),
+                    endpoint: aws_sns.endpoint.clone().unwrap_or_else(|| {
+                        format!("https://sns.{}.amazonaws.com/", aws_sns.region)
+                    }),
+                    subject: aws_sns.subject.clone(),
+                    message: aws_sns.message.clone(),
+                    message_attributes,
+                    credentials: match (&aws_sns.access_key_id, &aws_sns.secret_access_key) {
+                        (Some(access_key_id), Some(secret_access_key)) => Some(Credentials {
+                            access_key_id: access_key_id.clone(),
+                            secret_access_key: secret_access_key.clone(),
+                            session_token: aws_sns.session_token.clone(),
+                        }),
+                        _ => None,
+                    },
+                    imds_url: String::from(IMDS_URL),
+                    client: crate::http_client_builder(action.resolver.as_ref())?
+                        .build()
+                        .map_err(|x| Error(format!("Could not create HTTP client: {}", x)))?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl Action for AwsSns {
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        let credentials = self.get_credentials().await?;
+        let mut request = self
+            .client
+            .post(&self.endpoint)
+            .header(reqwest::header::USER_AGENT, crate::user_agent())
+            .form(&self.form(&placeholders))
+            .build()
+            .map_err(|x| Error(format!("Could not build request: {}", x)))?;
+        self.sign(&mut request, &credentials, chrono::Utc::now())?;
+        let response = self
+            .client
+            .execute(request)
+            .await
+            .map_err(|x| Error(format!("HTTP request failed: {}", x)))?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            // The error details are in the XML response body.
+            let body = response.text().await.unwrap_or_default();
+            Err(Error(format!(
+                "SNS API returned HTTP status code {}: {}",
+                status.as_u16(),
+                body.trim()
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::mock_server;
+    use std::str::FromStr;
+
+    fn aws_sns(endpoint: &str, credentials: &str) -> AwsSns {
+        let config = config::Config::try_from(
+            format!(
+                r#"
+                [[actions]]
+                name = "test-action"
+                type = "AwsSns"
+                topic_arn = "arn:aws:sns:eu-central-1:123456789012:alarms"
+                region = "eu-central-1"
+                endpoint = "{}"
+                subject = "Alarm {{{{alarm_name}}}}"
+                message = "Check {{{{check_name}}}} failed."
+                message_attributes = {{severity = "high", check = "{{{{check_name}}}}"}}
+                {}
+                "#,
+                endpoint, credentials
+            )
+            .as_str(),
+        )
+        .unwrap();
+        AwsSns::try_from(&config.actions[0]).unwrap()
+    }
+
+    fn placeholders() -> PlaceholderMap {
+        PlaceholderMap::from([
+            (String::from("alarm_name"), String::from("disk")),
+            (String::from("check_name"), String::from("fs usage")),
+        ])
+    }
+
+    #[test]
+    fn test_signing_key() {
+        // example from the AWS documentation
+        let key = AwsSns::signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        )
+        .unwrap();
+        assert_eq!(
+            AwsSns::hex(&key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn test_form() {
+        let form = aws_sns("http://localhost/", "").form(&placeholders());
+        assert_eq!(
+            form,
+            [
+                ("Action", "Publish"),
+                ("Version", "2010-03-31"),
+                ("TopicArn", "arn:aws:sns:eu-central-1:123456789012:alarms"),
+                ("Message", "Check fs usage failed."),
+                ("Subject", "Alarm disk"),
+                ("MessageAttributes.entry.1.Name", "check"),
+                ("MessageAttributes.entry.1.Value.DataType", "String"),
+                ("MessageAttributes.entry.1.Value.StringValue", "fs usage"),
+                ("MessageAttributes.entry.2.Name", "severity"),
+                ("MessageAttributes.entry.2.Value.DataType", "String"),
+                ("MessageAttributes.entry.2.Value.StringValue", "high"),
+            ]
+            .map(|(k, v)| (String::from(k), String::from(v)))
+        );
+    }
+
+    #[test]
+    fn test_sign() {
+        let aws_sns = aws_sns("https://sns.eu-central-1.amazonaws.com/", "");
+        let mut request = reqwest::Client::new()
+            .post(&aws_sns.endpoint)
+            .form(&[("Action", "Publish")])
+            .build()
+            .unwrap();
+        let credentials = Credentials {
+            access_key_id: String::from("AKIDEXAMPLE"),
+            secret_access_key: String::from("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY"),
+            session_token: Some(String::from("session")),
+        };
+        aws_sns
+            .sign(
+                &mut request,
+                &credentials,
+                chrono::DateTime::<chrono::Utc>::from_str("2023-01-02T03:04:05Z").unwrap(),
+            )
+            .unwrap();
+        let headers = request.headers();
+        assert_eq!(headers["x-amz-date"], "20230102T030405Z");
+        assert_eq!(headers["x-amz-security-token"], "session");
+        let authorization = headers["authorization"].to_str().unwrap();
+        assert!(authorization.starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20230102/eu-central-1/sns/aws4_request, SignedHeaders=content-type;host;x-amz-date;x-amz-security-token, Signature="
+        ));
+        assert_eq!(authorization.rsplit_once('=').unwrap().1.len(), 64);
+    }
+
+    #[tokio::test]
+    async fn test_publish() {
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests_clone = requests.clone();
+        let url = mock_server(move |request| {
+            let mut requests = requests_clone.lock().unwrap();
+            requests.push(String::from(request));
+            if requests.len() == 1 {
+                (200, String::from("<PublishResponse/>"))
+            } else {
+                (
+                    403,
+                    String::from("<ErrorResponse><Error><Code>AuthorizationError</Code></Error></ErrorResponse>"),
+                )
+            }
+        })
+        .await;
+        let aws_sns = aws_sns(
+            &format!("{}/", url),
+            r#"access_key_id = "AKIDEXAMPLE"
+                secret_access_key = "secret""#,
+        );
+        aws_sns.trigger(placeholders()).await.unwrap();
+        let error = aws_sns.trigger(placeholders()).await.err().unwrap();
+        assert!(error.to_string().contains("403"));
+        assert!(error.to_string().contains("AuthorizationError"));
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("POST / "));
+        assert!(requests[0].contains("authorization: AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+        assert!(requests[0].contains("x-amz-date: "));
+        assert!(!requests[0].contains("x-amz-security-token"));
+        assert!(requests[0].contains(
+            "Action=Publish&Version=2010-03-31&TopicArn=arn%3Aaws%3Asns%3Aeu-central-1%3A123456789012%3Aalarms&Message=Check+fs+usage+failed.&Subject=Alarm+disk"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_instance_role_credentials() {
+        let url = mock_server(|request| {
+            if request.starts_with("PUT /latest/api/token ") {
+                (200, String::from("imds-token"))
+            } else if !request.contains("x-aws-ec2-metadata-token: imds-token") {
+                (401, String::new())
+            } else if request.starts_with("GET /latest/meta-data/iam/security-credentials/ ") {
+                (200, String::from("minmon-role\n"))
+            } else if request
+                .starts_with("GET /latest/meta-data/iam/security-credentials/minmon-role ")
+            {
+                (
+                    200,
+                    String::from(
+                        r#"{"Code": "Success", "AccessKeyId": "ASIA", "SecretAccessKey": "secret", "Token": "session", "Expiration": "2023-01-01T00:00:00Z"}"#,
+                    ),
+                )
+            } else {
+                (404, String::new())
+            }
+        })
+        .await;
+        let mut aws_sns = aws_sns("http://localhost/", "");
+        aws_sns.imds_url = url;
+        assert_eq!(
+            aws_sns.instance_role_credentials().await.unwrap(),
+            Credentials {
+                access_key_id: String::from("ASIA"),
+                secret_access_key: String::from("secret"),
+                session_token: Some(String::from("session")),
+            }
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{Error, PlaceholderMap, Result};
+
+// Predicates over placeholders, e.g. "check_name matches 'prod-*' && level >= 90".
+// Operands are placeholder names or quoted strings. Unknown placeholders are empty.
+#[derive(PartialEq, Debug)]
+pub enum Condition {
+    Compare(String, Operand, Operand),
+    Not(Box<Condition>),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Operand {
+    Placeholder(String),
+    Literal(String),
+}
+
+const OPERATORS: [&str; 7] = ["==", "!=", "<=", ">=", "<", ">", "matches"];
+
+impl Operand {
+    fn evaluate<'a>(&'a self, placeholders: &'a PlaceholderMap) -> &'a str {
+        match self {
+            Operand::Placeholder(name) => placeholders.get(name).map(|x| x.as_str()).unwrap_or(""),
+            Operand::Literal(value) => value,
+        }
+    }
+}
+
+impl Condition {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: Parser::tokenize(text)?,
+            position: 0,
+        };
+        let condition = parser.parse_or()?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(condition),
+            Some(token) => Err(Error(format!("Unexpected token '{}'.", token.text()))),
+        }
+    }
+
+    // Numeric comparisons are false if one of the operands is not a number.
+    pub fn evaluate(&self, placeholders: &PlaceholderMap) -> bool {
+        match self {
+            Condition::Compare(operator, left, right) => {
+                let left = left.evaluate(placeholders);
+                let right = right.evaluate(placeholders);
+                match operator.as_str() {
+                    "==" => left == right,
+                    "!=" => left != right,
+                    "matches" => crate::wildcard_match(right, left),
+                    _ => match (left.parse::<f64>(), right.parse::<f64>()) {
+                        (Ok(left), Ok(right)) => match operator.as_str() {
+                            "<=" => left <= right,
+                            ">=" => left >= right,
+                            "<" => left < right,
+                            _ => left > right,
+                        },
+                        _ => false,
+                    },
+                }
+            }
+            Condition::Not(operand) => !operand.evaluate(placeholders),
+            Condition::And(left, right) => {
+                left.evaluate(placeholders) && right.evaluate(placeholders)
+            }
+            Condition::Or(left, right) => {
+                left.evaluate(placeholders) || right.evaluate(placeholders)
+            }
+        }
+    }
+}
+
+enum Token {
+    Word(String),
+    Literal(String),
+}
+
+impl Token {
+    fn text(&self) -> &str {
+        match self {
+            Token::Word(text) | Token::Literal(text) => text,
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn tokenize(text: &str) -> Result<Vec<Token>> {
+        let mut res = Vec::new();
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c.is_whitespace() {
+                continue;
+            }
+            if c == '"' || c == '\'' {
+                let mut literal = String::new();
+                loop {
+                    match chars.next() {
+                        Some(x) if x == c => break,
+                        Some(x) => literal.push(x),
+                        None => return Err(Error(String::from("Missing closing quote."))),
+                    }
+                }
+                res.push(Token::Literal(literal));
+                continue;
+            }
+            let mut token = String::from(c);
+            if c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-' {
+                while let Some(c) = chars
+                    .next_if(|x| x.is_ascii_alphanumeric() || *x == '_' || *x == '.' || *x == '-')
+                {
+                    token.push(c);
+                }
+            } else if "=!<>&|".contains(c) {
+                if let Some(c) = chars.next_if(|x| "=&|".contains(*x)) {
+                    token.push(c);
+                }
+            } else if !"()".contains(c) {
+                return Err(Error(format!("Invalid character '{}'.", c)));
+            }
+            res.push(Token::Word(token));
+        }
+        Ok(res)
+    }
+
+    fn next_if(&mut self, tokens: &[&str]) -> Option<String> {
+        match self.tokens.get(self.position)? {
+            Token::Word(token) if tokens.contains(&token.as_str()) => {
+                self.position += 1;
+                Some(token.clone())
+            }
+            _ => None,
+        }
+    }
+
+    // or := and ("||" and)*
+    fn parse_or(&mut self) -> Result<Condition> {
+        let mut res = self.parse_and()?;
+        while self.next_if(&["||"]).is_some() {
+            res = Condition::Or(Box::new(res), Box::new(self.parse_and()?));
+        }
+        Ok(res)
+    }
+
+    // and := not ("&&" not)*
+    fn parse_and(&mut self) -> Result<Condition> {
+        let mut res = self.parse_not()?;
+        while self.next_if(&["&&"]).is_some() {
+            res = Condition::And(Box::new(res), Box::new(self.parse_not()?));
+        }
+        Ok(res)
+    }
+
+    // not := "!" not | "(" or ")" | operand operator operand
+    fn parse_not(&mut self) -> Result<Condition> {
+        if self.next_if(&["!"]).is_some() {
+            return Ok(Condition::Not(Box::new(self.parse_not()?)));
+        }
+        if self.next_if(&["("]).is_some() {
+            let res = self.parse_or()?;
+            return match self.next_if(&[")"]) {
+                Some(_) => Ok(res),
+                None => Err(Error(String::from("Missing closing parenthesis."))),
+            };
+        }
+        let left = self.parse_operand()?;
+        let operator = self.next_if(&OPERATORS).ok_or_else(|| {
+            Error(format!(
+                "Expected one of {} after operand.",
+                OPERATORS.join(", ")
+            ))
+        })?;
+        Ok(Condition::Compare(operator, left, self.parse_operand()?))
+    }
+
+    // operand := placeholder | number | quoted string
+    fn parse_operand(&mut self) -> Result<Operand> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .ok_or_else(|| Error(String::from("Unexpected end of condition.")))?;
+        self.position += 1;
+        match token {
+            Token::Literal(literal) => Ok(Operand::Literal(literal.clone())),
+            Token::Word(word) if word.parse::<f64>().is_ok() => Ok(Operand::Literal(word.clone())),
+            Token::Word(word)
+                if word.starts_with(|x: char| x.is_ascii_alphabetic() || x == '_')
+                    && !OPERATORS.contains(&word.as_str()) =>
+            {
+                Ok(Operand::Placeholder(word.clone()))
+            }
+            Token::Word(word) => Err(Error(format!("Unexpected token '{}'.", word))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Condition::parse("!(a == 'x') || b matches \"prod-*\"").unwrap(),
+            Condition::Or(
+                Box::new(Condition::Not(Box::new(Condition::Compare(
+                    String::from("=="),
+                    Operand::Placeholder(String::from("a")),
+                    Operand::Literal(String::from("x"))
+                )))),
+                Box::new(Condition::Compare(
+                    String::from("matches"),
+                    Operand::Placeholder(String::from("b")),
+                    Operand::Literal(String::from("prod-*"))
+                ))
+            )
+        );
+        assert!(Condition::parse("a == 'x").is_err());
+        assert!(Condition::parse("(a == b").is_err());
+        assert!(Condition::parse("a").is_err());
+        assert!(Condition::parse("a == ").is_err());
+        assert!(Condition::parse("a == b c").is_err());
+        assert!(Condition::parse("a = b").is_err());
+        assert!(Condition::parse("a == b; c").is_err());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let placeholders = PlaceholderMap::from([
+            (String::from("check_name"), String::from("prod-web")),
+            (String::from("severity"), String::from("critical")),
+            (String::from("level"), String::from("95")),
+        ]);
+        let evaluate = |x| Condition::parse(x).unwrap().evaluate(&placeholders);
+        assert!(evaluate("severity == 'critical'"));
+        assert!(!evaluate("severity != 'critical'"));
+        assert!(evaluate("check_name matches 'prod-*'"));
+        assert!(!evaluate("check_name matches 'staging-*'"));
+        assert!(evaluate("level >= 90 && level < 100"));
+        assert!(!evaluate("level > 95"));
+        assert!(!evaluate("severity > 1"));
+        assert!(evaluate("unknown == ''"));
+        assert!(evaluate("severity == 'warning' || !(level <= 90)"));
+        assert!(!evaluate(
+            "severity == 'warning' || level <= 90 && level > 0"
+        ));
+    }
+}
@@ -0,0 +1,218 @@
+use super::{Action, Severity};
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+pub struct DesktopNotification {
+    summary: String,
+    body: String,
+    urgency: Option<config::Urgency>,
+    app_name: String,
+    icon: Option<String>,
+    notify_send: std::path::PathBuf,
+}
+
+impl DesktopNotification {
+    // Maps the `severity` placeholder to the urgency levels of the notification.
+    fn urgency(severity: Severity) -> config::Urgency {
+        match severity {
+            Severity::Critical | Severity::Error => config::Urgency::Critical,
+            Severity::Warning => config::Urgency::Normal,
+            Severity::Info => config::Urgency::Low,
+        }
+    }
+
+    fn arguments(&self, placeholders: &PlaceholderMap) -> Vec<String> {
+        let urgency = self
+            .urgency
+            .or_else(|| {
+                placeholders
+                    .get("severity")
+                    .and_then(|x| Severity::parse(x))
+                    .map(Self::urgency)
+            })
+            .unwrap_or_default();
+        let mut res = vec![
+            format!("--urgency={}", urgency),
+            format!("--app-name={}", self.app_name),
+        ];
+        if let Some(icon) = &self.icon {
+            res.push(format!("--icon={}", icon));
+        }
+        // The summary and body could start with a dash.
+        res.push(String::from("--"));
+        res.push(crate::fill_placeholders(
+            self.summary.as_str(),
+            placeholders,
+        ));
+        let body = crate::fill_placeholders(self.body.as_str(), placeholders);
+        if !body.is_empty() {
+            res.push(body);
+        }
+        res
+    }
+}
+
+impl TryFrom<&config::Action> for DesktopNotification {
+    type Error = Error;
+
+    fn try_from(action: &config::Action) -> std::result::Result<Self, Self::Error> {
+        if let config::ActionType::DesktopNotification(desktop_notification) = &action.type_ {
+            if desktop_notification.summary.is_empty() {
+                Err(Error(String::from("'summary' cannot be empty.")))
+            } else if desktop_notification.app_name.is_empty() {
+                Err(Error(String::from("'app_name' cannot be empty.")))
+            } else if desktop_notification
+                .icon
+                .as_ref()
+                .is_some_and(|x| x.is_empty())
+            {
+                Err(Error(String::from("'icon' cannot be empty.")))
+            } else if desktop_notification.notify_send.as_os_str().is_empty() {
+                Err(Error(String::from("'notify_send' cannot be empty.")))
+            } else {
+                Ok(Self {
+                    summary: desktop_notification.summary.clone(),
+                    body: desktop_notification.body.clone(),
+                    urgency: desktop_notification.urgency,
+                    app_name: desktop_notification.app_name.clone(),
+                    icon: desktop_notification.icon.clone(),
+                    notify_send: desktop_notification.notify_send.clone(),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl Action for DesktopNotification {
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        let mut command = tokio::process::Command::new(&self.notify_send);
+        command.args(self.arguments(&placeholders));
+        log::debug!("Calling process: {}", self.notify_send.display());
+        let output = command.output().await.map_err(|x| match x.kind() {
+            std::io::ErrorKind::NotFound => Error(format!(
+                "{} not found, is libnotify installed?",
+                self.notify_send.display()
+            )),
+            _ => Error(format!("Failed to run process: {}", x)),
+        })?;
+        match output.status.code() {
+            Some(0) => Ok(()),
+            Some(code) => {
+                if output.stderr.is_empty() {
+                    Err(Error(format!("Process failed with code {}.", code)))
+                } else {
+                    Err(Error(format!(
+                        "Process failed with code {}: {}",
+                        code,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )))
+                }
+            }
+            None => Err(Error(String::from("Process was terminated by a signal."))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config(options: &str) -> config::Config {
+        config::Config::try_from(
+            format!(
+                r#"
+                [[actions]]
+                name = "test-action"
+                type = "DesktopNotification"
+                summary = "{{{{alarm_name}}}}"
+                body = "{{{{check_error}}}}"
+                {}
+                "#,
+                options
+            )
+            .as_str(),
+        )
+        .unwrap()
+    }
+
+    fn placeholders(severity: Option<&str>) -> PlaceholderMap {
+        let mut res = PlaceholderMap::from([
+            (String::from("alarm_name"), String::from("usage")),
+            (String::from("check_error"), String::from("/srv is full")),
+        ]);
+        if let Some(severity) = severity {
+            res.insert(String::from("severity"), String::from(severity));
+        }
+        res
+    }
+
+    #[test]
+    fn test_arguments() {
+        let desktop_notification_config = config(r#"icon = "dialog-warning""#);
+        let desktop_notification =
+            DesktopNotification::try_from(&desktop_notification_config.actions[0]).unwrap();
+        assert_eq!(
+            desktop_notification.arguments(&placeholders(Some("High"))),
+            [
+                "--urgency=critical",
+                "--app-name=MinMon",
+                "--icon=dialog-warning",
+                "--",
+                "usage",
+                "/srv is full"
+            ]
+        );
+        assert_eq!(
+            desktop_notification.arguments(&placeholders(Some("info")))[0],
+            "--urgency=low"
+        );
+        assert_eq!(
+            desktop_notification.arguments(&placeholders(None))[0],
+            "--urgency=normal"
+        );
+        let desktop_notification_config = config(r#"urgency = "Low""#);
+        let desktop_notification =
+            DesktopNotification::try_from(&desktop_notification_config.actions[0]).unwrap();
+        assert_eq!(
+            desktop_notification.arguments(&placeholders(Some("critical")))[0],
+            "--urgency=low"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trigger() {
+        let desktop_notification_config = config(r#"notify_send = "true""#);
+        let desktop_notification =
+            DesktopNotification::try_from(&desktop_notification_config.actions[0]).unwrap();
+        desktop_notification
+            .trigger(placeholders(None))
+            .await
+            .unwrap();
+        let desktop_notification_config = config(r#"notify_send = "false""#);
+        let desktop_notification =
+            DesktopNotification::try_from(&desktop_notification_config.actions[0]).unwrap();
+        assert_eq!(
+            desktop_notification
+                .trigger(placeholders(None))
+                .await
+                .unwrap_err()
+                .to_string(),
+            "Process failed with code 1."
+        );
+        let desktop_notification_config = config(r#"notify_send = "/nonexistent/notify-send""#);
+        let desktop_notification =
+            DesktopNotification::try_from(&desktop_notification_config.actions[0]).unwrap();
+        assert_eq!(
+            desktop_notification
+                .trigger(placeholders(None))
+                .await
+                .unwrap_err()
+                .to_string(),
+            "/nonexistent/notify-send not found, is libnotify installed?"
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use super::Action;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+// Discord rejects messages with a longer content.
+const MAX_CONTENT_LENGTH: usize = 2000;
+
+pub struct Discord {
+    url: String,
+    content: String,
+    embeds: Vec<config::DiscordEmbed>,
+    username: Option<String>,
+    client: reqwest::Client,
+}
+
+impl Discord {
+    fn truncate(content: String) -> String {
+        if content.chars().count() > MAX_CONTENT_LENGTH {
+            content
+                .chars()
+                .take(MAX_CONTENT_LENGTH - 1)
+                .chain(std::iter::once('…'))
+                .collect()
+        } else {
+            content
+        }
+    }
+
+    fn payload(&self, placeholders: &PlaceholderMap) -> serde_json::Value {
+        let mut res = serde_json::json!({
+            "content": Self::truncate(crate::fill_placeholders(self.content.as_str(), placeholders))
+        });
+        if !self.embeds.is_empty() {
+            res["embeds"] = self
+                .embeds
+                .iter()
+                .map(|embed| {
+                    let mut res = serde_json::json!({
+                        "title": crate::fill_placeholders(embed.title.as_str(), placeholders),
+                        "description":
+                            crate::fill_placeholders(embed.description.as_str(), placeholders),
+                    });
+                    if let Some(color) = embed.color {
+                        res["color"] = serde_json::Value::from(color);
+                    }
+                    res
+                })
+                .collect();
+        }
+        if let Some(username) = &self.username {
+            res["username"] = serde_json::Value::String(username.clone());
+        }
+        res
+    }
+}
+
+impl TryFrom<&config::Action> for Discord {
+    type Error = Error;
+
+    fn try_from(action: &config::Action) -> std::result::Result<Self, Self::Error> {
+        if let config::ActionType::Discord(discord) = &action.type_ {
+            if discord.url.is_empty() {
+                Err(Error(String::from("'url' cannot be empty.")))
+            } else if discord.content.is_empty() {
+                Err(Error(String::from("'content' cannot be empty.")))
+            } else if discord
+                .embeds
+                .iter()
+                .any(|x| x.title.is_empty() && x.description.is_empty())
+            {
+                Err(Error(String::from(
+                    "'embeds' need a 'title' or a 'description'.",
+                )))
+            } else if discord.embeds.iter().any(|x| x.color > Some(0xffffff)) {
+                Err(Error(String::from(
+                    "'color' of 'embeds' cannot be larger than 0xffffff.",
+                )))
+            } else if discord.username.as_ref().is_some_and(|x| x.is_empty()) {
+                Err(Error(String::from("'username' cannot be empty.")))
+            } else {
+                Ok(Self {
+                    url: discord.url.clone(),
+                    content: discord.content.clone(),
+                    embeds: discord.embeds.clone(),
+                    username: discord.username.clone(),
+                    client: crate::http_client_builder(action.resolver.as_ref())?
+                        .build()
+                        .map_err(|x| Error(format!("Could not create HTTP client: {}", x)))?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl Action for Discord {
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&self.payload(&placeholders))
+            .send()
+            .await
+            .map_err(|x| Error(format!("HTTP request failed: {}", x)))?;
+        super::check_status(response.status())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::mock_server;
+
+    fn config(url: &str, options: &str) -> config::Config {
+        config::Config::try_from(
+            format!(
+                r#"
+                [[actions]]
+                name = "test-action"
+                type = "Discord"
+                url = "{}"
+                content = "{{{{alarm_name}}}}: {{{{check_error}}}}"
+                {}
+                "#,
+                url, options
+            )
+            .as_str(),
+        )
+        .unwrap()
+    }
+
+    fn placeholders(check_error: &str) -> PlaceholderMap {
+        PlaceholderMap::from([
+            (String::from("alarm_name"), String::from("usage")),
+            (String::from("check_error"), String::from(check_error)),
+        ])
+    }
+
+    #[test]
+    fn test_payload() {
+        let discord_config = config(
+            "https://discord.com/api/webhooks/1/x",
+            r#"username = "minmon"
+            [[actions.embeds]]
+            title = "{{alarm_name}}"
+            description = "{{check_error}}"
+            color = 0xff0000"#,
+        );
+        let discord = Discord::try_from(&discord_config.actions[0]).unwrap();
+        assert_eq!(
+            discord.payload(&placeholders("\"/srv\" is full")),
+            serde_json::json!({
+                "content": "usage: \"/srv\" is full",
+                "embeds": [{
+                    "title": "usage",
+                    "description": "\"/srv\" is full",
+                    "color": 0xff0000
+                }],
+                "username": "minmon"
+            })
+        );
+        let content = discord.payload(&placeholders(&"ü".repeat(3000)))["content"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(content.chars().count(), 2000);
+        assert!(content.starts_with("usage: üü"));
+        assert!(content.ends_with("ü…"));
+    }
+
+    #[test]
+    fn test_try_from() {
+        let discord_config = config(
+            "https://discord.com/api/webhooks/1/x",
+            r#"[[actions.embeds]]
+            color = 0xff0000
+            [[actions.embeds]]
+            title = "{{alarm_name}}"
+            color = 0x1000000"#,
+        );
+        assert_eq!(
+            Discord::try_from(&discord_config.actions[0])
+                .err()
+                .unwrap()
+                .to_string(),
+            "'embeds' need a 'title' or a 'description'."
+        );
+        let mut discord_config = discord_config;
+        discord_config.actions[0].type_ = match &discord_config.actions[0].type_ {
+            config::ActionType::Discord(discord) => {
+                let mut discord = discord.clone();
+                discord.embeds.remove(0);
+                config::ActionType::Discord(discord)
+            }
+            _ => panic!(),
+        };
+        assert_eq!(
+            Discord::try_from(&discord_config.actions[0])
+                .err()
+                .unwrap()
+                .to_string(),
+            "'color' of 'embeds' cannot be larger than 0xffffff."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trigger() {
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests_clone = requests.clone();
+        let url = mock_server(move |request| {
+            requests_clone.lock().unwrap().push(String::from(request));
+            (204, String::new())
+        })
+        .await;
+        let discord_config = config(&format!("{}/api/webhooks/1/x", url), "");
+        let discord = Discord::try_from(&discord_config.actions[0]).unwrap();
+        discord.trigger(placeholders("full")).await.unwrap();
+        let request = requests.lock().unwrap()[0].clone();
+        assert!(request.starts_with("POST /api/webhooks/1/x "));
+        let payload: serde_json::Value =
+            serde_json::from_str(request.split_once("\r\n\r\n").unwrap().1).unwrap();
+        assert_eq!(payload, serde_json::json!({"content": "usage: full"}));
+        let url = mock_server(|_| (400, String::from("{}"))).await;
+        let discord_config = config(&url, "");
+        let discord = Discord::try_from(&discord_config.actions[0]).unwrap();
+        assert_eq!(
+            discord
+                .trigger(placeholders("full"))
+                .await
+                .unwrap_err()
+                .to_string(),
+            "HTTP status code 400 indicates error."
+        );
+    }
+}
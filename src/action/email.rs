@@ -14,6 +14,7 @@ pub struct Email {
     smtp_security: config::SmtpSecurity,
     username: String,
     password: String,
+    resolver: Option<std::sync::Arc<crate::resolver::Resolver>>,
 }
 
 impl TryFrom<&config::Action> for Email {
@@ -53,6 +54,7 @@ impl TryFrom<&config::Action> for Email {
                     smtp_security: email.smtp_security,
                     username: email.username.clone(),
                     password: email.password.clone(),
+                    resolver: crate::resolver::from_config(action.resolver.as_ref())?,
                 })
             }
         } else {
@@ -61,10 +63,29 @@ impl TryFrom<&config::Action> for Email {
     }
 }
 
+impl Email {
+    // With a configured resolver, the SMTP server is connected by its address.
+    async fn server(&self, port: u16) -> Result<String> {
+        match &self.resolver {
+            Some(resolver) => {
+                let address = format!("{}:{}", self.smtp_server, port);
+                crate::resolver::lookup_host(Some(resolver), &address)
+                    .await?
+                    .first()
+                    .map(|x| x.ip().to_string())
+                    .ok_or_else(|| Error(format!("No addresses found for {}.", self.smtp_server)))
+            }
+            None => Ok(self.smtp_server.clone()),
+        }
+    }
+}
+
 #[async_trait]
 impl Action for Email {
     async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
         use lettre::transport::smtp::authentication::Credentials;
+        use lettre::transport::smtp::client::{Tls, TlsParameters};
+        use lettre::transport::smtp::{SMTP_PORT, SUBMISSIONS_PORT, SUBMISSION_PORT};
         use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
 
         let subject = crate::fill_placeholders(self.subject.as_str(), &placeholders);
@@ -81,25 +102,21 @@ impl Action for Email {
             .body(body)
             .map_err(|x| Error(x.to_string()))?;
         let credentials = Credentials::new(self.username.clone(), self.password.clone());
-        let mut mailer_builder = match self.smtp_security {
-            config::SmtpSecurity::TLS => {
-                AsyncSmtpTransport::<Tokio1Executor>::relay(&self.smtp_server)
-                    .map_err(|x| Error(x.to_string()))
-            }
-            config::SmtpSecurity::STARTTLS => {
-                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.smtp_server)
-                    .map_err(|x| Error(x.to_string()))
-            }
-            config::SmtpSecurity::Plain => Ok(
-                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&self.smtp_server),
-            ),
-        }?;
-
-        mailer_builder = mailer_builder.credentials(credentials);
-        if let Some(port) = self.smtp_port {
-            mailer_builder = mailer_builder.port(port);
-        }
-        let mailer = mailer_builder.build();
+        // The certificate is always verified against the host name, see `server`.
+        let tls_parameters =
+            || TlsParameters::new(self.smtp_server.clone()).map_err(|x| Error(x.to_string()));
+        let (port, tls) = match self.smtp_security {
+            config::SmtpSecurity::TLS => (SUBMISSIONS_PORT, Tls::Wrapper(tls_parameters()?)),
+            config::SmtpSecurity::STARTTLS => (SUBMISSION_PORT, Tls::Required(tls_parameters()?)),
+            config::SmtpSecurity::Plain => (SMTP_PORT, Tls::None),
+        };
+        let port = self.smtp_port.unwrap_or(port);
+        let mailer =
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(self.server(port).await?)
+                .port(port)
+                .tls(tls)
+                .credentials(credentials)
+                .build();
         mailer
             .send(email)
             .await
@@ -0,0 +1,184 @@
+use super::Action;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+pub struct Healthchecks {
+    // ping URL of the check, e.g. "https://hc-ping.com/<uuid>"
+    url: String,
+    endpoint: Option<config::HealthchecksEndpoint>,
+    body: String,
+    client: reqwest::Client,
+}
+
+impl Healthchecks {
+    // Without a configured endpoint, a recovery (alarm state "Good") is a success ping and
+    // everything else a failure.
+    fn url(&self, placeholders: &PlaceholderMap) -> String {
+        let endpoint = self.endpoint.unwrap_or_else(|| {
+            if placeholders.get("alarm_state").map(String::as_str) == Some("Good") {
+                config::HealthchecksEndpoint::Ping
+            } else {
+                config::HealthchecksEndpoint::Fail
+            }
+        });
+        match endpoint {
+            config::HealthchecksEndpoint::Ping => self.url.clone(),
+            config::HealthchecksEndpoint::Start => format!("{}/start", self.url),
+            config::HealthchecksEndpoint::Fail => format!("{}/fail", self.url),
+        }
+    }
+}
+
+impl TryFrom<&config::Action> for Healthchecks {
+    type Error = Error;
+
+    fn try_from(action: &config::Action) -> std::result::Result<Self, Self::Error> {
+        if let config::ActionType::Healthchecks(healthchecks) = &action.type_ {
+            if healthchecks.url.is_empty() {
+                Err(Error(String::from("'url' cannot be empty.")))
+            } else if uuid::Uuid::parse_str(&healthchecks.uuid).is_err() {
+                Err(Error(format!(
+                    "'{}' is not a valid UUID.",
+                    healthchecks.uuid
+                )))
+            } else {
+                Ok(Self {
+                    url: format!(
+                        "{}/{}",
+                        healthchecks.url.trim_end_matches('/'),
+                        healthchecks.uuid
+                    ),
+                    endpoint: healthchecks.endpoint,
+                    body: healthchecks.body.clone(),
+                    client: crate::http_client_builder(action.resolver.as_ref())?
+                        .build()
+                        .map_err(|x| Error(format!("Could not create HTTP client: {}", x)))?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl Action for Healthchecks {
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        // The body shows up in the event log of the check.
+        let response = self
+            .client
+            .post(self.url(&placeholders))
+            .body(crate::fill_placeholders(self.body.as_str(), &placeholders))
+            .send()
+            .await
+            .map_err(|x| Error(format!("HTTP request failed: {}", x)))?;
+        super::check_status(response.status())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::mock_server;
+
+    const UUID: &str = "5bf66975-d4c7-4bf5-bcc8-b8d8a82ea278";
+
+    fn config(url: &str, options: &str) -> config::Config {
+        config::Config::try_from(
+            format!(
+                r#"
+                [[actions]]
+                name = "test-action"
+                type = "Healthchecks"
+                url = "{}"
+                uuid = "{}"
+                body = "{{{{alarm_name}}}}: {{{{check_error}}}}"
+                {}
+                "#,
+                url, UUID, options
+            )
+            .as_str(),
+        )
+        .unwrap()
+    }
+
+    fn placeholders(alarm_state: &str) -> PlaceholderMap {
+        PlaceholderMap::from([
+            (String::from("alarm_name"), String::from("usage")),
+            (String::from("alarm_state"), String::from(alarm_state)),
+            (String::from("check_error"), String::from("/srv is full")),
+        ])
+    }
+
+    #[test]
+    fn test_url() {
+        let healthchecks_config = config("https://hc.example.com/", "");
+        let healthchecks = Healthchecks::try_from(&healthchecks_config.actions[0]).unwrap();
+        assert_eq!(
+            healthchecks.url(&placeholders("Bad")),
+            format!("https://hc.example.com/{}/fail", UUID)
+        );
+        assert_eq!(
+            healthchecks.url(&placeholders("Error")),
+            format!("https://hc.example.com/{}/fail", UUID)
+        );
+        assert_eq!(
+            healthchecks.url(&placeholders("Good")),
+            format!("https://hc.example.com/{}", UUID)
+        );
+        let healthchecks_config = config("https://hc-ping.com", r#"endpoint = "Start""#);
+        let healthchecks = Healthchecks::try_from(&healthchecks_config.actions[0]).unwrap();
+        assert_eq!(
+            healthchecks.url(&placeholders("Good")),
+            format!("https://hc-ping.com/{}/start", UUID)
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut healthchecks_config = config("https://hc-ping.com", "");
+        if let config::ActionType::Healthchecks(healthchecks) =
+            &mut healthchecks_config.actions[0].type_
+        {
+            healthchecks.uuid = String::from("my-check");
+        }
+        assert_eq!(
+            Healthchecks::try_from(&healthchecks_config.actions[0])
+                .err()
+                .unwrap()
+                .to_string(),
+            "'my-check' is not a valid UUID."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trigger() {
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests_clone = requests.clone();
+        let url = mock_server(move |request| {
+            requests_clone.lock().unwrap().push(String::from(request));
+            (200, String::from("OK"))
+        })
+        .await;
+        let healthchecks_config = config(&url, "");
+        let healthchecks = Healthchecks::try_from(&healthchecks_config.actions[0]).unwrap();
+        healthchecks.trigger(placeholders("Bad")).await.unwrap();
+        healthchecks.trigger(placeholders("Good")).await.unwrap();
+        let requests = requests.lock().unwrap().clone();
+        assert!(requests[0].starts_with(&format!("POST /{}/fail ", UUID)));
+        assert!(requests[0].ends_with("\r\n\r\nusage: /srv is full"));
+        assert!(requests[1].starts_with(&format!("POST /{} ", UUID)));
+        let url = mock_server(|_| (404, String::from("not found"))).await;
+        let healthchecks_config = config(&url, "");
+        let healthchecks = Healthchecks::try_from(&healthchecks_config.actions[0]).unwrap();
+        assert_eq!(
+            healthchecks
+                .trigger(placeholders("Bad"))
+                .await
+                .unwrap_err()
+                .to_string(),
+            "HTTP status code 404 indicates error."
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use super::Action;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+pub struct Matrix {
+    // URL of the room's m.room.message endpoint without the transaction ID
+    url: reqwest::Url,
+    access_token: String,
+    body: String,
+    formatted_body: String,
+    client: reqwest::Client,
+}
+
+impl Matrix {
+    fn content(&self, placeholders: &PlaceholderMap) -> serde_json::Value {
+        let mut res = serde_json::json!({
+            "msgtype": "m.text",
+            "body": crate::fill_placeholders(self.body.as_str(), placeholders),
+        });
+        if !self.formatted_body.is_empty() {
+            res["format"] = serde_json::Value::from("org.matrix.custom.html");
+            res["formatted_body"] = serde_json::Value::from(crate::fill_placeholders(
+                self.formatted_body.as_str(),
+                placeholders,
+            ));
+        }
+        res
+    }
+
+    // e.g. {"errcode": "M_FORBIDDEN", "error": "User is not in the room."}
+    fn homeserver_error(body: &str) -> String {
+        match serde_json::from_str::<serde_json::Value>(body) {
+            Ok(error) if error["errcode"].is_string() => format!(
+                "{}: {}",
+                error["errcode"].as_str().unwrap_or_default(),
+                error["error"].as_str().unwrap_or_default()
+            ),
+            _ => String::from(body.trim()),
+        }
+    }
+}
+
+impl TryFrom<&config::Action> for Matrix {
+    type Error = Error;
+
+    fn try_from(action: &config::Action) -> std::result::Result<Self, Self::Error> {
+        if let config::ActionType::Matrix(matrix) = &action.type_ {
+            if matrix.homeserver.is_empty() {
+                Err(Error(String::from("'homeserver' cannot be empty.")))
+            } else if matrix.room_id.is_empty() {
+                Err(Error(String::from("'room_id' cannot be empty.")))
+            } else if matrix.access_token.is_empty() {
+                Err(Error(String::from("'access_token' cannot be empty.")))
+            } else if matrix.body.is_empty() {
+                Err(Error(String::from("'body' cannot be empty.")))
+            } else {
+                let mut url = reqwest::Url::parse(&matrix.homeserver)
+                    .map_err(|x| Error(format!("Could not parse 'homeserver': {}", x)))?;
+                // The room ID (e.g. "!abc:example.com") is percent-encoded as a path segment.
+                url.path_segments_mut()
+                    .map_err(|_| Error(String::from("'homeserver' cannot be a base URL.")))?
+                    .pop_if_empty()
+                    .extend([
+                        "_matrix",
+                        "client",
+                        "v3",
+                        "rooms",
+                        matrix.room_id.as_str(),
+                        "send",
+                        "m.room.message",
+                    ]);
+                Ok(Self {
+                    url,
+                    access_token: matrix.access_token.clone(),
+                    body: matrix.body.clone(),
+                    formatted_body: matrix.formatted_body.clone(),
+                    client: crate::http_client_builder(action.resolver.as_ref())?
+                        .build()
+                        .map_err(|x| Error(format!("Could not create HTTP client: {}", x)))?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl Action for Matrix {
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        // The transaction ID makes retries of the same event idempotent.
+        let mut url = self.url.clone();
+        url.path_segments_mut()
+            .unwrap()
+            .push(&uuid::Uuid::new_v4().to_string());
+        let response = self
+            .client
+            .put(url)
+            .bearer_auth(&self.access_token)
+            .json(&self.content(&placeholders))
+            .send()
+            .await
+            .map_err(|x| Error(format!("HTTP request failed: {}", x)))?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            let body = response.text().await.unwrap_or_default();
+            Err(Error(format!(
+                "Homeserver returned HTTP status code {}: {}",
+                status.as_u16(),
+                Self::homeserver_error(&body)
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::mock_server;
+
+    fn config(homeserver: &str, options: &str) -> config::Config {
+        config::Config::try_from(
+            format!(
+                r#"
+                [[actions]]
+                name = "test-action"
+                type = "Matrix"
+                homeserver = "{}"
+                room_id = "!ops:example.com"
+                access_token = "syt_secret"
+                body = "{{{{alarm_name}}}}: {{{{check_error}}}}"
+                {}
+                "#,
+                homeserver, options
+            )
+            .as_str(),
+        )
+        .unwrap()
+    }
+
+    fn placeholders() -> PlaceholderMap {
+        PlaceholderMap::from([
+            (String::from("alarm_name"), String::from("usage")),
+            (String::from("check_error"), String::from("/srv is full")),
+        ])
+    }
+
+    #[test]
+    fn test_content() {
+        let matrix_config = config(
+            "https://matrix.example.com/",
+            r#"formatted_body = "<b>{{alarm_name}}</b>: {{check_error}}""#,
+        );
+        let matrix = Matrix::try_from(&matrix_config.actions[0]).unwrap();
+        assert_eq!(
+            matrix.url.as_str(),
+            "https://matrix.example.com/_matrix/client/v3/rooms/!ops:example.com/send/m.room.message"
+        );
+        assert_eq!(
+            matrix.content(&placeholders()),
+            serde_json::json!({
+                "msgtype": "m.text",
+                "body": "usage: /srv is full",
+                "format": "org.matrix.custom.html",
+                "formatted_body": "<b>usage</b>: /srv is full"
+            })
+        );
+        let matrix_config = config("https://example.com/matrix", "");
+        let matrix = Matrix::try_from(&matrix_config.actions[0]).unwrap();
+        assert!(matrix
+            .url
+            .as_str()
+            .starts_with("https://example.com/matrix/_matrix/"));
+        assert!(matrix.content(&placeholders()).get("format").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_trigger() {
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests_clone = requests.clone();
+        let url = mock_server(move |request| {
+            requests_clone.lock().unwrap().push(String::from(request));
+            (200, String::from(r#"{"event_id":"$1"}"#))
+        })
+        .await;
+        let matrix_config = config(&url, "");
+        let matrix = Matrix::try_from(&matrix_config.actions[0]).unwrap();
+        matrix.trigger(placeholders()).await.unwrap();
+        let request = requests.lock().unwrap()[0].clone();
+        assert!(request
+            .starts_with("PUT /_matrix/client/v3/rooms/!ops:example.com/send/m.room.message/"));
+        assert!(request
+            .to_lowercase()
+            .contains("\r\nauthorization: bearer syt_secret\r\n"));
+        let url = mock_server(|_| {
+            (
+                403,
+                String::from(r#"{"errcode":"M_FORBIDDEN","error":"User is not in the room."}"#),
+            )
+        })
+        .await;
+        let matrix_config = config(&url, "");
+        let matrix = Matrix::try_from(&matrix_config.actions[0]).unwrap();
+        assert_eq!(
+            matrix
+                .trigger(placeholders())
+                .await
+                .unwrap_err()
+                .to_string(),
+            "Homeserver returned HTTP status code 403: M_FORBIDDEN: User is not in the room."
+        );
+    }
+}
@@ -2,15 +2,38 @@ use crate::config;
 use crate::ActionMap;
 use crate::{Error, PlaceholderMap, Result};
 use async_trait::async_trait;
+use std::collections::HashMap;
 extern crate log as log_ext;
 
+mod aws_sns;
+mod condition;
+mod desktop_notification;
+mod discord;
 mod email;
+mod healthchecks;
 mod log;
+mod matrix;
+mod nagios;
+mod ntfy;
+mod oauth2;
+mod pager_duty;
 mod process;
+mod pushover;
+mod slack;
 mod webhook;
 pub use self::log::Log;
+pub use aws_sns::AwsSns;
+pub use desktop_notification::DesktopNotification;
+pub use discord::Discord;
 pub use email::Email;
+pub use healthchecks::Healthchecks;
+pub use matrix::Matrix;
+pub use nagios::Nagios;
+pub use ntfy::Ntfy;
+pub use pager_duty::PagerDuty;
 pub use process::Process;
+pub use pushover::Pushover;
+pub use slack::Slack;
 pub use webhook::Webhook;
 
 #[cfg_attr(test, mockall::automock)]
@@ -19,6 +42,40 @@ pub trait Action: Send + Sync {
     async fn trigger(&self, mut placeholders: PlaceholderMap) -> Result<()>;
 }
 
+// Common values of the `severity` placeholder, which the actions map to the priorities of their
+// providers.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Severity {
+    Critical,
+    Error,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    pub fn parse(severity: &str) -> Option<Self> {
+        match severity.to_ascii_lowercase().as_str() {
+            "critical" => Some(Self::Critical),
+            "error" | "high" => Some(Self::Error),
+            "warning" | "medium" => Some(Self::Warning),
+            "info" | "low" | "ok" => Some(Self::Info),
+            _ => None,
+        }
+    }
+}
+
+// Checks the status code of the response of an HTTP based action.
+fn check_status(status: reqwest::StatusCode) -> Result<()> {
+    if status.is_success() {
+        Ok(())
+    } else {
+        Err(Error(format!(
+            "HTTP status code {} indicates error.",
+            status.as_u16()
+        )))
+    }
+}
+
 pub struct ActionBase<T>
 where
     T: Action,
@@ -27,6 +84,15 @@ where
     timeout: std::time::Duration,
     placeholders: PlaceholderMap,
     action: T,
+    state_actions: HashMap<config::AlarmState, T>,
+    condition: Option<condition::Condition>,
+    critical: bool,
+    // shared by all actions of the same type if `action_concurrency` is configured for it
+    concurrency: Option<std::sync::Arc<tokio::sync::Semaphore>>,
+    // only set if the degraded mode is configured
+    degraded_mode: Option<std::sync::Arc<crate::degraded_mode::DegradedMode>>,
+    // failed actions count towards the ErrorRate check
+    error_count: Option<std::sync::Arc<std::sync::atomic::AtomicU64>>,
 }
 
 impl<T> ActionBase<T>
@@ -38,6 +104,8 @@ where
         timeout: std::time::Duration,
         placeholders: PlaceholderMap,
         action: T,
+        state_actions: HashMap<config::AlarmState, T>,
+        condition: Option<&str>,
     ) -> Result<Self> {
         if name.is_empty() {
             Err(Error(String::from("'name' cannot be empty.")))
@@ -49,14 +117,62 @@ where
                 timeout,
                 placeholders,
                 action,
+                state_actions,
+                condition: condition
+                    .map(|x| {
+                        condition::Condition::parse(x)
+                            .map_err(|x| Error(format!("Invalid 'condition': {}", x)))
+                    })
+                    .transpose()?,
+                critical: false,
+                concurrency: None,
+                degraded_mode: None,
+                error_count: None,
             })
         }
     }
 
+    pub fn set_critical(&mut self, critical: bool) {
+        self.critical = critical;
+    }
+
+    pub fn set_concurrency(&mut self, concurrency: Option<std::sync::Arc<tokio::sync::Semaphore>>) {
+        self.concurrency = concurrency;
+    }
+
+    pub fn set_degraded_mode(
+        &mut self,
+        degraded_mode: Option<std::sync::Arc<crate::degraded_mode::DegradedMode>>,
+    ) {
+        self.degraded_mode = degraded_mode;
+    }
+
+    pub fn set_error_count(&mut self, error_count: std::sync::Arc<std::sync::atomic::AtomicU64>) {
+        self.error_count = Some(error_count);
+    }
+
+    // Only critical actions are executed while the degraded mode is active.
+    fn is_suppressed(&self) -> bool {
+        !self.critical && self.degraded_mode.as_ref().is_some_and(|x| x.is_active())
+    }
+
     fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
         placeholders.insert(String::from("action_name"), self.name.clone());
         crate::merge_placeholders(placeholders, &self.placeholders);
     }
+
+    // Alarm states without their own templates use the default ones.
+    fn get_action(&self, placeholders: &PlaceholderMap) -> &T {
+        placeholders
+            .get("alarm_state")
+            .and_then(|x| {
+                self.state_actions
+                    .iter()
+                    .find(|(state, _)| state.to_string() == *x)
+            })
+            .map(|(_, action)| action)
+            .unwrap_or(&self.action)
+    }
 }
 
 #[async_trait]
@@ -81,15 +197,41 @@ where
                 placeholders.get("check_name").unwrap()
             );
         }
-        let res = tokio::time::timeout(self.timeout, self.action.trigger(placeholders)).await;
-        match res {
+        if let Some(condition) = &self.condition {
+            if !condition.evaluate(&placeholders) {
+                log_ext::info!(
+                    "Action '{}' skipped because its condition is not met.",
+                    self.name
+                );
+                return Ok(());
+            }
+        }
+        if self.is_suppressed() {
+            log_ext::info!(
+                "Action '{}' skipped because of the degraded mode.",
+                self.name
+            );
+            return Ok(());
+        }
+        // Waiting for a permit doesn't count towards the timeout. The semaphore is never closed.
+        let _permit = match &self.concurrency {
+            Some(concurrency) => Some(concurrency.acquire().await.unwrap()),
+            None => None,
+        };
+        let action = self.get_action(&placeholders);
+        let res = tokio::time::timeout(self.timeout, action.trigger(placeholders)).await;
+        let res = match res {
             Ok(inner) => inner,
             Err(_) => Err(Error(format!(
                 "Action '{}' timed out after {} seconds.",
                 self.name,
                 self.timeout.as_secs()
             ))),
+        };
+        if let (Err(_), Some(error_count)) = (&res, &self.error_count) {
+            error_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
         }
+        res
     }
 }
 
@@ -117,7 +259,40 @@ impl Action for DisabledAction {
     }
 }
 
-pub fn from_action_config(action_config: &config::Action) -> Result<std::sync::Arc<dyn Action>> {
+fn action_base<T>(
+    action_config: &config::Action,
+    concurrency: Option<std::sync::Arc<tokio::sync::Semaphore>>,
+    degraded_mode: Option<std::sync::Arc<crate::degraded_mode::DegradedMode>>,
+    error_count: std::sync::Arc<std::sync::atomic::AtomicU64>,
+) -> Result<std::sync::Arc<dyn Action>>
+where
+    T: Action + for<'a> TryFrom<&'a config::Action, Error = Error> + 'static,
+{
+    let mut state_actions = HashMap::new();
+    for state in action_config.templates.keys() {
+        state_actions.insert(*state, T::try_from(&action_config.with_templates(*state)?)?);
+    }
+    let mut action = ActionBase::new(
+        action_config.name.clone(),
+        std::time::Duration::from_secs(action_config.timeout as u64),
+        action_config.placeholders.clone(),
+        T::try_from(action_config)?,
+        state_actions,
+        action_config.condition.as_deref(),
+    )?;
+    action.set_critical(action_config.critical);
+    action.set_concurrency(concurrency);
+    action.set_degraded_mode(degraded_mode);
+    action.set_error_count(error_count);
+    Ok(std::sync::Arc::new(action))
+}
+
+pub fn from_action_config(
+    action_config: &config::Action,
+    concurrency: Option<std::sync::Arc<tokio::sync::Semaphore>>,
+    degraded_mode: Option<std::sync::Arc<crate::degraded_mode::DegradedMode>>,
+    error_count: std::sync::Arc<std::sync::atomic::AtomicU64>,
+) -> Result<std::sync::Arc<dyn Action>> {
     if action_config.disable {
         log_ext::info!(
             "Action {}::'{}' is disabled.",
@@ -129,34 +304,57 @@ pub fn from_action_config(action_config: &config::Action) -> Result<std::sync::A
             std::time::Duration::from_secs(action_config.timeout as u64),
             action_config.placeholders.clone(),
             DisabledAction {},
+            HashMap::new(),
+            action_config.condition.as_deref(),
         )?))
     } else {
-        Ok(match &action_config.type_ {
-            config::ActionType::Email(_) => std::sync::Arc::new(ActionBase::new(
-                action_config.name.clone(),
-                std::time::Duration::from_secs(action_config.timeout as u64),
-                action_config.placeholders.clone(),
-                Email::try_from(action_config)?,
-            )?),
-            config::ActionType::Log(_) => std::sync::Arc::new(ActionBase::new(
-                action_config.name.clone(),
-                std::time::Duration::from_secs(action_config.timeout as u64),
-                action_config.placeholders.clone(),
-                Log::try_from(action_config)?,
-            )?),
-            config::ActionType::Process(_) => std::sync::Arc::new(ActionBase::new(
-                action_config.name.clone(),
-                std::time::Duration::from_secs(action_config.timeout as u64),
-                action_config.placeholders.clone(),
-                Process::try_from(action_config)?,
-            )?),
-            config::ActionType::Webhook(_) => std::sync::Arc::new(ActionBase::new(
-                action_config.name.clone(),
-                std::time::Duration::from_secs(action_config.timeout as u64),
-                action_config.placeholders.clone(),
-                Webhook::try_from(action_config)?,
-            )?),
-        })
+        match &action_config.type_ {
+            config::ActionType::AwsSns(_) => {
+                action_base::<AwsSns>(action_config, concurrency, degraded_mode, error_count)
+            }
+            config::ActionType::DesktopNotification(_) => action_base::<DesktopNotification>(
+                action_config,
+                concurrency,
+                degraded_mode,
+                error_count,
+            ),
+            config::ActionType::Discord(_) => {
+                action_base::<Discord>(action_config, concurrency, degraded_mode, error_count)
+            }
+            config::ActionType::Email(_) => {
+                action_base::<Email>(action_config, concurrency, degraded_mode, error_count)
+            }
+            config::ActionType::Healthchecks(_) => {
+                action_base::<Healthchecks>(action_config, concurrency, degraded_mode, error_count)
+            }
+            config::ActionType::Log(_) => {
+                action_base::<Log>(action_config, concurrency, degraded_mode, error_count)
+            }
+            config::ActionType::Matrix(_) => {
+                action_base::<Matrix>(action_config, concurrency, degraded_mode, error_count)
+            }
+            config::ActionType::Nagios(_) => {
+                action_base::<Nagios>(action_config, concurrency, degraded_mode, error_count)
+            }
+            config::ActionType::Ntfy(_) => {
+                action_base::<Ntfy>(action_config, concurrency, degraded_mode, error_count)
+            }
+            config::ActionType::PagerDuty(_) => {
+                action_base::<PagerDuty>(action_config, concurrency, degraded_mode, error_count)
+            }
+            config::ActionType::Process(_) => {
+                action_base::<Process>(action_config, concurrency, degraded_mode, error_count)
+            }
+            config::ActionType::Pushover(_) => {
+                action_base::<Pushover>(action_config, concurrency, degraded_mode, error_count)
+            }
+            config::ActionType::Slack(_) => {
+                action_base::<Slack>(action_config, concurrency, degraded_mode, error_count)
+            }
+            config::ActionType::Webhook(_) => {
+                action_base::<Webhook>(action_config, concurrency, degraded_mode, error_count)
+            }
+        }
     }
 }
 
@@ -176,6 +374,15 @@ mod test {
     use super::*;
     use mockall::predicate::*;
 
+    #[test]
+    fn test_severity() {
+        assert_eq!(Severity::parse("Critical"), Some(Severity::Critical));
+        assert_eq!(Severity::parse("high"), Some(Severity::Error));
+        assert_eq!(Severity::parse("MEDIUM"), Some(Severity::Warning));
+        assert_eq!(Severity::parse("ok"), Some(Severity::Info));
+        assert_eq!(Severity::parse("unknown"), None);
+    }
+
     #[tokio::test]
     async fn test_placeholders() {
         let mut mock_action = MockAction::new();
@@ -193,6 +400,8 @@ mod test {
             std::time::Duration::from_secs(1),
             PlaceholderMap::from([(String::from("Hello"), String::from("World"))]),
             mock_action,
+            HashMap::new(),
+            None,
         )
         .unwrap();
         action
@@ -204,6 +413,31 @@ mod test {
             .unwrap();
     }
 
+    #[test]
+    fn test_degraded_mode() {
+        let config = config::Config::try_from("degraded_mode = {min_checks = 1}").unwrap();
+        let degraded_mode =
+            crate::degraded_mode::DegradedMode::try_from(config.degraded_mode.as_ref().unwrap())
+                .unwrap();
+        let mut action = ActionBase::new(
+            String::from("Name"),
+            std::time::Duration::from_secs(1),
+            PlaceholderMap::new(),
+            MockAction::new(),
+            HashMap::new(),
+            None,
+        )
+        .unwrap();
+        assert!(!action.is_suppressed());
+        let degraded_mode = std::sync::Arc::new(degraded_mode);
+        action.set_degraded_mode(Some(degraded_mode.clone()));
+        assert!(!action.is_suppressed());
+        degraded_mode.update(0, true);
+        assert!(action.is_suppressed());
+        action.set_critical(true);
+        assert!(!action.is_suppressed());
+    }
+
     #[tokio::test]
     async fn test_timeout() {
         struct TimeoutMockAction {}
@@ -214,16 +448,176 @@ mod test {
                 Ok(())
             }
         }
-        let action = ActionBase::new(
+        let mut action = ActionBase::new(
             String::from("Name"),
             std::time::Duration::from_secs(1),
             PlaceholderMap::new(),
             TimeoutMockAction {},
+            HashMap::new(),
+            None,
         )
         .unwrap();
+        let error_count = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
+        action.set_error_count(error_count.clone());
         assert!(matches!(
             action.trigger(PlaceholderMap::new()).await,
             Err(_)
         ));
+        assert_eq!(error_count.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_state_actions() {
+        let mock_action = |template: &'static str, times: usize| {
+            let mut mock_action = MockAction::new();
+            mock_action
+                .expect_trigger()
+                .times(times)
+                .returning(move |placeholders| {
+                    assert_eq!(placeholders.get("template").unwrap().as_str(), template);
+                    Ok(())
+                });
+            mock_action
+        };
+        let action = ActionBase::new(
+            String::from("Name"),
+            std::time::Duration::from_secs(1),
+            PlaceholderMap::new(),
+            mock_action("default", 2),
+            HashMap::from([
+                (config::AlarmState::Bad, mock_action("bad", 1)),
+                (config::AlarmState::Good, mock_action("good", 1)),
+            ]),
+            None,
+        )
+        .unwrap();
+        for state in ["Bad", "Good", "Error", ""] {
+            let mut placeholders = PlaceholderMap::from([
+                (String::from("alarm_name"), String::from("Alarm")),
+                (String::from("alarm_id"), String::from("Id")),
+                (String::from("check_name"), String::from("Check")),
+            ]);
+            if !state.is_empty() {
+                placeholders.insert(String::from("alarm_state"), String::from(state));
+            }
+            placeholders.insert(
+                String::from("template"),
+                match state {
+                    "Bad" => String::from("bad"),
+                    "Good" => String::from("good"),
+                    _ => String::from("default"),
+                },
+            );
+            action.trigger(placeholders).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_condition() {
+        let mut mock_action = MockAction::new();
+        mock_action
+            .expect_trigger()
+            .once()
+            .withf(|placeholders| placeholders.get("check_name").unwrap() == "prod-web")
+            .returning(|_| Ok(()));
+        let action = ActionBase::new(
+            String::from("Name"),
+            std::time::Duration::from_secs(1),
+            PlaceholderMap::new(),
+            mock_action,
+            HashMap::new(),
+            Some("check_name matches 'prod-*' && severity == 'critical'"),
+        )
+        .unwrap();
+        for (check_name, severity) in [
+            ("prod-web", "critical"),
+            ("prod-web", "warning"),
+            ("staging-web", "critical"),
+        ] {
+            action
+                .trigger(PlaceholderMap::from([
+                    (String::from("alarm_name"), String::from("Alarm")),
+                    (String::from("alarm_id"), String::from("Id")),
+                    (String::from("check_name"), String::from(check_name)),
+                    (String::from("severity"), String::from(severity)),
+                ]))
+                .await
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_invalid_condition() {
+        assert!(ActionBase::new(
+            String::from("Name"),
+            std::time::Duration::from_secs(1),
+            PlaceholderMap::new(),
+            MockAction::new(),
+            HashMap::new(),
+            Some("check_name ="),
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_concurrency() {
+        // counts the running triggers and remembers the maximum
+        struct ConcurrencyMockAction {
+            running: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+            max: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        }
+        #[async_trait]
+        impl Action for ConcurrencyMockAction {
+            async fn trigger(&self, mut _placeholders: PlaceholderMap) -> Result<()> {
+                use std::sync::atomic::Ordering;
+                let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+                self.max.fetch_max(running, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                self.running.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+        let placeholders = PlaceholderMap::from([
+            (String::from("alarm_name"), String::from("Alarm")),
+            (String::from("alarm_id"), String::from("Id")),
+            (String::from("check_name"), String::from("Check")),
+        ]);
+        let mut handles = Vec::new();
+        let mut maxima = Vec::new();
+        // two action types with two actions each
+        for limit in [2, 5] {
+            let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(limit));
+            let running = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+            let max = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+            for _ in 0..2 {
+                let mut action = ActionBase::new(
+                    String::from("Name"),
+                    std::time::Duration::from_secs(5),
+                    PlaceholderMap::new(),
+                    ConcurrencyMockAction {
+                        running: running.clone(),
+                        max: max.clone(),
+                    },
+                    HashMap::new(),
+                    None,
+                )
+                .unwrap();
+                action.set_concurrency(Some(semaphore.clone()));
+                let action = std::sync::Arc::new(action);
+                for _ in 0..10 {
+                    let action = action.clone();
+                    let placeholders = placeholders.clone();
+                    handles.push(tokio::spawn(
+                        async move { action.trigger(placeholders).await },
+                    ));
+                }
+            }
+            maxima.push(max);
+        }
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+        assert_eq!(maxima[0].load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(maxima[1].load(std::sync::atomic::Ordering::SeqCst), 5);
     }
 }
@@ -0,0 +1,216 @@
+use super::{Action, Severity};
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+pub struct Ntfy {
+    url: String,
+    title: String,
+    message: String,
+    priority: String,
+    tags: Vec<String>,
+    token: Option<String>,
+    client: reqwest::Client,
+}
+
+impl Ntfy {
+    // Maps the `severity` placeholder to the priorities of ntfy (1 to 5).
+    fn priority(severity: Severity) -> &'static str {
+        match severity {
+            Severity::Critical => "5",
+            Severity::Error => "4",
+            Severity::Warning => "3",
+            Severity::Info => "2",
+        }
+    }
+
+    // Header values are limited to ASCII, others are encoded according to RFC 2047 which ntfy
+    // supports.
+    fn encode(value: &str) -> String {
+        let value = value.replace(['\r', '\n'], " ");
+        if value.is_ascii() {
+            value
+        } else {
+            format!("=?UTF-8?B?{}?=", base64::encode(value))
+        }
+    }
+
+    fn headers(&self, placeholders: &PlaceholderMap) -> Vec<(&'static str, String)> {
+        let mut res = Vec::new();
+        let title = crate::fill_placeholders(self.title.as_str(), placeholders);
+        if !title.is_empty() {
+            res.push(("Title", Self::encode(&title)));
+        }
+        let priority = crate::fill_placeholders(self.priority.as_str(), placeholders);
+        let priority = if priority.is_empty() {
+            placeholders
+                .get("severity")
+                .and_then(|x| Severity::parse(x))
+                .map(|x| String::from(Self::priority(x)))
+        } else {
+            Some(priority)
+        };
+        if let Some(priority) = priority {
+            res.push(("Priority", Self::encode(&priority)));
+        }
+        let tags: Vec<String> = self
+            .tags
+            .iter()
+            .map(|x| crate::fill_placeholders(x.as_str(), placeholders))
+            .filter(|x| !x.is_empty())
+            .collect();
+        if !tags.is_empty() {
+            res.push(("Tags", Self::encode(&tags.join(","))));
+        }
+        res
+    }
+}
+
+impl TryFrom<&config::Action> for Ntfy {
+    type Error = Error;
+
+    fn try_from(action: &config::Action) -> std::result::Result<Self, Self::Error> {
+        if let config::ActionType::Ntfy(ntfy) = &action.type_ {
+            if ntfy.url.is_empty() {
+                Err(Error(String::from("'url' cannot be empty.")))
+            } else if ntfy.topic.is_empty() {
+                Err(Error(String::from("'topic' cannot be empty.")))
+            } else if ntfy.topic.contains('/') {
+                Err(Error(String::from("'topic' cannot contain '/'.")))
+            } else if ntfy.message.is_empty() {
+                Err(Error(String::from("'message' cannot be empty.")))
+            } else if ntfy.token.as_ref().is_some_and(|x| x.is_empty()) {
+                Err(Error(String::from("'token' cannot be empty.")))
+            } else {
+                Ok(Self {
+                    url: format!("{}/{}", ntfy.url.trim_end_matches('/'), ntfy.topic),
+                    title: ntfy.title.clone(),
+                    message: ntfy.message.clone(),
+                    priority: ntfy.priority.clone(),
+                    tags: ntfy.tags.clone(),
+                    token: ntfy.token.clone(),
+                    client: crate::http_client_builder(action.resolver.as_ref())?
+                        .build()
+                        .map_err(|x| Error(format!("Could not create HTTP client: {}", x)))?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl Action for Ntfy {
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        let mut request = self.client.post(&self.url).body(crate::fill_placeholders(
+            self.message.as_str(),
+            &placeholders,
+        ));
+        for (name, value) in self.headers(&placeholders) {
+            request = request.header(name, value);
+        }
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|x| Error(format!("HTTP request failed: {}", x)))?;
+        super::check_status(response.status())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::mock_server;
+
+    fn config(url: &str, options: &str) -> config::Config {
+        config::Config::try_from(
+            format!(
+                r#"
+                [[actions]]
+                name = "test-action"
+                type = "Ntfy"
+                url = "{}"
+                topic = "alarms"
+                message = "{{{{check_error}}}}"
+                {}
+                "#,
+                url, options
+            )
+            .as_str(),
+        )
+        .unwrap()
+    }
+
+    fn placeholders(severity: Option<&str>) -> PlaceholderMap {
+        let mut res = PlaceholderMap::from([
+            (String::from("alarm_name"), String::from("usage")),
+            (String::from("check_error"), String::from("/srv is full")),
+        ]);
+        if let Some(severity) = severity {
+            res.insert(String::from("severity"), String::from(severity));
+        }
+        res
+    }
+
+    #[test]
+    fn test_headers() {
+        let ntfy_config = config(
+            "https://ntfy.example.com/",
+            r#"title = "{{alarm_name}} ✗"
+            tags = ["warning", "{{alarm_name}}", "{{missing}}"]"#,
+        );
+        let ntfy = Ntfy::try_from(&ntfy_config.actions[0]).unwrap();
+        assert_eq!(ntfy.url, "https://ntfy.example.com/alarms");
+        assert_eq!(
+            ntfy.headers(&placeholders(Some("Critical"))),
+            vec![
+                ("Title", String::from("=?UTF-8?B?dXNhZ2Ug4pyX?=")),
+                ("Priority", String::from("5")),
+                ("Tags", String::from("warning,usage")),
+            ]
+        );
+        assert_eq!(
+            ntfy.headers(&placeholders(Some("whatever")))[1],
+            ("Tags", String::from("warning,usage"))
+        );
+        let ntfy_config = config("https://ntfy.sh", r#"priority = "min""#);
+        let ntfy = Ntfy::try_from(&ntfy_config.actions[0]).unwrap();
+        assert_eq!(
+            ntfy.headers(&placeholders(Some("critical"))),
+            vec![("Priority", String::from("min"))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trigger() {
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests_clone = requests.clone();
+        let url = mock_server(move |request| {
+            requests_clone.lock().unwrap().push(String::from(request));
+            (200, String::from("{}"))
+        })
+        .await;
+        let ntfy_config = config(&url, r#"token = "tk_secret""#);
+        let ntfy = Ntfy::try_from(&ntfy_config.actions[0]).unwrap();
+        ntfy.trigger(placeholders(Some("warning"))).await.unwrap();
+        let request = requests.lock().unwrap()[0].to_lowercase();
+        assert!(request.starts_with("post /alarms "));
+        assert!(request.contains("\r\npriority: 3\r\n"));
+        assert!(request.contains("\r\nauthorization: bearer tk_secret\r\n"));
+        assert!(request.ends_with("\r\n\r\n/srv is full"));
+        let url = mock_server(|_| (403, String::from("{}"))).await;
+        let ntfy_config = config(&url, "");
+        let ntfy = Ntfy::try_from(&ntfy_config.actions[0]).unwrap();
+        assert_eq!(
+            ntfy.trigger(placeholders(None))
+                .await
+                .unwrap_err()
+                .to_string(),
+            "HTTP status code 403 indicates error."
+        );
+    }
+}
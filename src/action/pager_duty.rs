@@ -0,0 +1,259 @@
+use super::{Action, Severity};
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+// PagerDuty rejects events with a longer summary.
+const MAX_SUMMARY_LENGTH: usize = 1024;
+
+pub struct PagerDuty {
+    url: String,
+    routing_key: String,
+    summary: String,
+    source: String,
+    dedup_key: String,
+    severity: Option<config::PagerDutySeverity>,
+    client: reqwest::Client,
+}
+
+impl PagerDuty {
+    // Maps the `severity` placeholder to the severities of PagerDuty.
+    fn severity(severity: Severity) -> config::PagerDutySeverity {
+        match severity {
+            Severity::Critical => config::PagerDutySeverity::Critical,
+            Severity::Error => config::PagerDutySeverity::Error,
+            Severity::Warning => config::PagerDutySeverity::Warning,
+            Severity::Info => config::PagerDutySeverity::Info,
+        }
+    }
+
+    // A recovery (alarm state "Good") resolves the incident that was triggered with the same
+    // dedup key.
+    fn event(&self, placeholders: &PlaceholderMap) -> serde_json::Value {
+        let dedup_key = crate::fill_placeholders(self.dedup_key.as_str(), placeholders);
+        if placeholders.get("alarm_state").map(String::as_str) == Some("Good") {
+            return serde_json::json!({
+                "routing_key": self.routing_key,
+                "event_action": "resolve",
+                "dedup_key": dedup_key,
+            });
+        }
+        let severity = self
+            .severity
+            .or_else(|| {
+                placeholders
+                    .get("severity")
+                    .and_then(|x| Severity::parse(x))
+                    .map(Self::severity)
+            })
+            .unwrap_or_default();
+        serde_json::json!({
+            "routing_key": self.routing_key,
+            "event_action": "trigger",
+            "dedup_key": dedup_key,
+            "payload": {
+                "summary": crate::fill_placeholders(self.summary.as_str(), placeholders)
+                    .chars()
+                    .take(MAX_SUMMARY_LENGTH)
+                    .collect::<String>(),
+                "source": crate::fill_placeholders(self.source.as_str(), placeholders),
+                "severity": severity.to_string(),
+            },
+        })
+    }
+}
+
+impl TryFrom<&config::Action> for PagerDuty {
+    type Error = Error;
+
+    fn try_from(action: &config::Action) -> std::result::Result<Self, Self::Error> {
+        if let config::ActionType::PagerDuty(pager_duty) = &action.type_ {
+            if pager_duty.url.is_empty() {
+                Err(Error(String::from("'url' cannot be empty.")))
+            } else if pager_duty.routing_key.is_empty() {
+                Err(Error(String::from("'routing_key' cannot be empty.")))
+            } else if pager_duty.summary.is_empty() {
+                Err(Error(String::from("'summary' cannot be empty.")))
+            } else if pager_duty.dedup_key.is_empty() {
+                Err(Error(String::from("'dedup_key' cannot be empty.")))
+            } else if pager_duty.source.as_ref().is_some_and(|x| x.is_empty()) {
+                Err(Error(String::from("'source' cannot be empty.")))
+            } else {
+                // PagerDuty requires a source, which is usually the affected host.
+                let source = match &pager_duty.source {
+                    Some(source) => source.clone(),
+                    None => nix::unistd::gethostname()
+                        .map_err(|x| Error(format!("Could not get hostname: {}", x)))?
+                        .to_string_lossy()
+                        .into_owned(),
+                };
+                Ok(Self {
+                    url: pager_duty.url.clone(),
+                    routing_key: pager_duty.routing_key.clone(),
+                    summary: pager_duty.summary.clone(),
+                    source,
+                    dedup_key: pager_duty.dedup_key.clone(),
+                    severity: pager_duty.severity,
+                    client: crate::http_client_builder(action.resolver.as_ref())?
+                        .build()
+                        .map_err(|x| Error(format!("Could not create HTTP client: {}", x)))?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl Action for PagerDuty {
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&self.event(&placeholders))
+            .send()
+            .await
+            .map_err(|x| Error(format!("HTTP request failed: {}", x)))?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            // e.g. {"status": "invalid event", "message": "Event object is invalid", "errors": [..]}
+            let body = response.text().await.unwrap_or_default();
+            Err(Error(format!(
+                "PagerDuty returned HTTP status code {}: {}",
+                status.as_u16(),
+                body.trim()
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::mock_server;
+
+    fn config(url: &str, options: &str) -> config::Config {
+        config::Config::try_from(
+            format!(
+                r#"
+                [[actions]]
+                name = "test-action"
+                type = "PagerDuty"
+                url = "{}"
+                routing_key = "R0UT1NGK3Y"
+                summary = "{{{{alarm_name}}}} on {{{{alarm_id}}}}: {{{{check_error}}}}"
+                source = "db1"
+                {}
+                "#,
+                url, options
+            )
+            .as_str(),
+        )
+        .unwrap()
+    }
+
+    fn placeholders(alarm_state: &str, severity: Option<&str>) -> PlaceholderMap {
+        let mut res = PlaceholderMap::from([
+            (String::from("check_name"), String::from("Filesystem")),
+            (String::from("alarm_name"), String::from("usage")),
+            (String::from("alarm_id"), String::from("/srv")),
+            (String::from("alarm_state"), String::from(alarm_state)),
+            (String::from("check_error"), String::from("/srv is full")),
+        ]);
+        if let Some(severity) = severity {
+            res.insert(String::from("severity"), String::from(severity));
+        }
+        res
+    }
+
+    #[test]
+    fn test_event() {
+        let pager_duty_config = config("https://events.pagerduty.com/v2/enqueue", "");
+        let pager_duty = PagerDuty::try_from(&pager_duty_config.actions[0]).unwrap();
+        assert_eq!(
+            pager_duty.event(&placeholders("Bad", Some("Warning"))),
+            serde_json::json!({
+                "routing_key": "R0UT1NGK3Y",
+                "event_action": "trigger",
+                "dedup_key": "Filesystem/usage//srv",
+                "payload": {
+                    "summary": "usage on /srv: /srv is full",
+                    "source": "db1",
+                    "severity": "warning"
+                }
+            })
+        );
+        assert_eq!(
+            pager_duty.event(&placeholders("Error", None))["payload"]["severity"],
+            "error"
+        );
+        // the recovery resolves the incident with the same dedup key
+        assert_eq!(
+            pager_duty.event(&placeholders("Good", Some("critical"))),
+            serde_json::json!({
+                "routing_key": "R0UT1NGK3Y",
+                "event_action": "resolve",
+                "dedup_key": "Filesystem/usage//srv"
+            })
+        );
+        let pager_duty_config = config(
+            "https://events.pagerduty.com/v2/enqueue",
+            r#"severity = "Critical""#,
+        );
+        let pager_duty = PagerDuty::try_from(&pager_duty_config.actions[0]).unwrap();
+        assert_eq!(
+            pager_duty.event(&placeholders("Bad", Some("info")))["payload"]["severity"],
+            "critical"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trigger() {
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests_clone = requests.clone();
+        let url = mock_server(move |request| {
+            requests_clone.lock().unwrap().push(String::from(request));
+            (
+                202,
+                String::from(r#"{"status":"success","dedup_key":"Filesystem/usage//srv"}"#),
+            )
+        })
+        .await;
+        let pager_duty_config = config(&format!("{}/v2/enqueue", url), "");
+        let pager_duty = PagerDuty::try_from(&pager_duty_config.actions[0]).unwrap();
+        pager_duty.trigger(placeholders("Bad", None)).await.unwrap();
+        pager_duty
+            .trigger(placeholders("Good", None))
+            .await
+            .unwrap();
+        let requests = requests.lock().unwrap().clone();
+        let event_action = |request: &str| {
+            assert!(request.starts_with("POST /v2/enqueue "));
+            let event: serde_json::Value =
+                serde_json::from_str(request.split_once("\r\n\r\n").unwrap().1).unwrap();
+            event["event_action"].clone()
+        };
+        assert_eq!(event_action(&requests[0]), "trigger");
+        assert_eq!(event_action(&requests[1]), "resolve");
+        let url = mock_server(|_| {
+            (
+                400,
+                String::from(r#"{"status":"invalid event","message":"Event object is invalid"}"#),
+            )
+        })
+        .await;
+        let pager_duty_config = config(&url, "");
+        let pager_duty = PagerDuty::try_from(&pager_duty_config.actions[0]).unwrap();
+        assert_eq!(
+            pager_duty
+                .trigger(placeholders("Bad", None))
+                .await
+                .unwrap_err()
+                .to_string(),
+            r#"PagerDuty returned HTTP status code 400: {"status":"invalid event","message":"Event object is invalid"}"#
+        );
+    }
+}
@@ -0,0 +1,301 @@
+use super::{Action, Severity};
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+// Emergency notifications are repeated until they're acknowledged.
+const EMERGENCY_PRIORITY: i8 = 2;
+
+pub struct Pushover {
+    url: String,
+    token: String,
+    user: String,
+    title: String,
+    message: String,
+    priority: Option<i8>,
+    retry: Option<u32>,
+    expire: Option<u32>,
+    client: reqwest::Client,
+}
+
+impl Pushover {
+    // Maps the `severity` placeholder to the priorities of Pushover. The emergency priority needs
+    // to be configured explicitly.
+    fn priority(severity: Severity) -> i8 {
+        match severity {
+            Severity::Critical | Severity::Error => 1,
+            Severity::Warning => 0,
+            Severity::Info => -1,
+        }
+    }
+
+    // Every accepted message gets a request id, emergency priority messages also get a receipt
+    // that can be used to query the acknowledgement.
+    fn receipt(body: &str) -> Option<(String, Option<String>)> {
+        let response: serde_json::Value = serde_json::from_str(body).ok()?;
+        let request = response.get("request")?.as_str()?;
+        let receipt = response.get("receipt").and_then(|x| x.as_str());
+        Some((String::from(request), receipt.map(String::from)))
+    }
+
+    fn form(&self, placeholders: &PlaceholderMap) -> Vec<(&'static str, String)> {
+        let mut res = vec![
+            ("token", self.token.clone()),
+            ("user", self.user.clone()),
+            (
+                "message",
+                crate::fill_placeholders(self.message.as_str(), placeholders),
+            ),
+        ];
+        let title = crate::fill_placeholders(self.title.as_str(), placeholders);
+        if !title.is_empty() {
+            res.push(("title", title));
+        }
+        let priority = self.priority.or_else(|| {
+            placeholders
+                .get("severity")
+                .and_then(|x| Severity::parse(x))
+                .map(Self::priority)
+        });
+        if let Some(priority) = priority {
+            res.push(("priority", priority.to_string()));
+        }
+        if let (Some(retry), Some(expire)) = (self.retry, self.expire) {
+            res.push(("retry", retry.to_string()));
+            res.push(("expire", expire.to_string()));
+        }
+        res
+    }
+}
+
+impl TryFrom<&config::Action> for Pushover {
+    type Error = Error;
+
+    fn try_from(action: &config::Action) -> std::result::Result<Self, Self::Error> {
+        if let config::ActionType::Pushover(pushover) = &action.type_ {
+            if pushover.url.is_empty() {
+                return Err(Error(String::from("'url' cannot be empty.")));
+            } else if pushover.token.is_empty() {
+                return Err(Error(String::from("'token' cannot be empty.")));
+            } else if pushover.user.is_empty() {
+                return Err(Error(String::from("'user' cannot be empty.")));
+            } else if pushover.message.is_empty() {
+                return Err(Error(String::from("'message' cannot be empty.")));
+            } else if pushover.priority.is_some_and(|x| !(-2..=2).contains(&x)) {
+                return Err(Error(String::from(
+                    "'priority' needs to be between -2 and 2.",
+                )));
+            }
+            if pushover.priority == Some(EMERGENCY_PRIORITY) {
+                match (pushover.retry, pushover.expire) {
+                    (None, _) | (_, None) => {
+                        return Err(Error(String::from(
+                            "'retry' and 'expire' are required for priority 2.",
+                        )))
+                    }
+                    (Some(retry), _) if retry < 30 => {
+                        return Err(Error(String::from("'retry' cannot be less than 30.")))
+                    }
+                    (_, Some(expire)) if expire > 10800 => {
+                        return Err(Error(String::from(
+                            "'expire' cannot be greater than 10800.",
+                        )))
+                    }
+                    _ => {}
+                }
+            } else if pushover.retry.is_some() || pushover.expire.is_some() {
+                return Err(Error(String::from(
+                    "'retry' and 'expire' are only used with priority 2.",
+                )));
+            }
+            Ok(Self {
+                url: pushover.url.clone(),
+                token: pushover.token.clone(),
+                user: pushover.user.clone(),
+                title: pushover.title.clone(),
+                message: pushover.message.clone(),
+                priority: pushover.priority,
+                retry: pushover.retry,
+                expire: pushover.expire,
+                client: crate::http_client_builder(action.resolver.as_ref())?
+                    .build()
+                    .map_err(|x| Error(format!("Could not create HTTP client: {}", x)))?,
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl Action for Pushover {
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        let response = self
+            .client
+            .post(&self.url)
+            .form(&self.form(&placeholders))
+            .send()
+            .await
+            .map_err(|x| Error(format!("HTTP request failed: {}", x)))?;
+        super::check_status(response.status())?;
+        let body = response.text().await.unwrap_or_default();
+        match Self::receipt(&body) {
+            Some((request, Some(receipt))) => log::info!(
+                "Pushover accepted the message with request '{}' and receipt '{}'.",
+                request,
+                receipt
+            ),
+            Some((request, None)) => {
+                log::info!("Pushover accepted the message with request '{}'.", request)
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::mock_server;
+
+    fn config(url: &str, options: &str) -> config::Config {
+        config::Config::try_from(
+            format!(
+                r#"
+                [[actions]]
+                name = "test-action"
+                type = "Pushover"
+                url = "{}"
+                token = "azGDORePK8gMaC0QOYAMyEEuzJnyUi"
+                user = "uQiRzpo4DXghDmr9QzzfQu27cmVRsG"
+                title = "{{{{alarm_name}}}}"
+                message = "{{{{check_error}}}}"
+                {}
+                "#,
+                url, options
+            )
+            .as_str(),
+        )
+        .unwrap()
+    }
+
+    fn placeholders(severity: Option<&str>) -> PlaceholderMap {
+        let mut res = PlaceholderMap::from([
+            (String::from("alarm_name"), String::from("usage")),
+            (String::from("check_error"), String::from("/srv is full")),
+        ]);
+        if let Some(severity) = severity {
+            res.insert(String::from("severity"), String::from(severity));
+        }
+        res
+    }
+
+    fn error(options: &str) -> String {
+        let pushover_config = config("https://api.pushover.net/1/messages.json", options);
+        Pushover::try_from(&pushover_config.actions[0])
+            .err()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_validation() {
+        assert_eq!(
+            error("priority = 3"),
+            "'priority' needs to be between -2 and 2."
+        );
+        assert_eq!(
+            error("priority = 2"),
+            "'retry' and 'expire' are required for priority 2."
+        );
+        assert_eq!(
+            error("priority = 2\nretry = 10\nexpire = 3600"),
+            "'retry' cannot be less than 30."
+        );
+        assert_eq!(
+            error("priority = 2\nretry = 60\nexpire = 86400"),
+            "'expire' cannot be greater than 10800."
+        );
+        assert_eq!(
+            error("priority = 1\nretry = 60"),
+            "'retry' and 'expire' are only used with priority 2."
+        );
+    }
+
+    #[test]
+    fn test_form() {
+        let pushover_config = config("https://api.pushover.net/1/messages.json", "");
+        let pushover = Pushover::try_from(&pushover_config.actions[0]).unwrap();
+        let form = pushover.form(&placeholders(Some("Critical")));
+        assert_eq!(form[2], ("message", String::from("/srv is full")));
+        assert_eq!(form[3], ("title", String::from("usage")));
+        assert_eq!(form[4], ("priority", String::from("1")));
+        assert_eq!(pushover.form(&placeholders(None)).len(), 4);
+        let pushover_config = config(
+            "https://api.pushover.net/1/messages.json",
+            "priority = 2\nretry = 60\nexpire = 3600",
+        );
+        let pushover = Pushover::try_from(&pushover_config.actions[0]).unwrap();
+        assert_eq!(
+            pushover.form(&placeholders(Some("low")))[4..],
+            [
+                ("priority", String::from("2")),
+                ("retry", String::from("60")),
+                ("expire", String::from("3600")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_receipt() {
+        assert_eq!(
+            Pushover::receipt(r#"{"status":1,"request":"647d2300-702c-4b38-8b2f-d56326ae460b"}"#),
+            Some((String::from("647d2300-702c-4b38-8b2f-d56326ae460b"), None))
+        );
+        assert_eq!(
+            Pushover::receipt(
+                r#"{"status":1,"request":"647d2300","receipt":"rLqVuqTRh62UzxtmqiaLzQmVcPgiCy"}"#
+            ),
+            Some((
+                String::from("647d2300"),
+                Some(String::from("rLqVuqTRh62UzxtmqiaLzQmVcPgiCy"))
+            ))
+        );
+        assert_eq!(Pushover::receipt(r#"{"status":1}"#), None);
+        assert_eq!(Pushover::receipt("<html>"), None);
+    }
+
+    #[tokio::test]
+    async fn test_trigger() {
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests_clone = requests.clone();
+        let url = mock_server(move |request| {
+            requests_clone.lock().unwrap().push(String::from(request));
+            (
+                200,
+                String::from(r#"{"status":1,"request":"647d2300-702c-4b38-8b2f-d56326ae460b"}"#),
+            )
+        })
+        .await;
+        let pushover_config = config(&format!("{}/1/messages.json", url), "");
+        let pushover = Pushover::try_from(&pushover_config.actions[0]).unwrap();
+        pushover.trigger(placeholders(None)).await.unwrap();
+        let request = requests.lock().unwrap()[0].clone();
+        assert!(request.starts_with("POST /1/messages.json "));
+        assert!(request.ends_with(
+            "\r\n\r\ntoken=azGDORePK8gMaC0QOYAMyEEuzJnyUi&user=uQiRzpo4DXghDmr9QzzfQu27cmVRsG&message=%2Fsrv+is+full&title=usage"
+        ));
+        let url = mock_server(|_| (400, String::from(r#"{"status":0}"#))).await;
+        let pushover_config = config(&url, "");
+        let pushover = Pushover::try_from(&pushover_config.actions[0]).unwrap();
+        assert_eq!(
+            pushover
+                .trigger(placeholders(None))
+                .await
+                .unwrap_err()
+                .to_string(),
+            "HTTP status code 400 indicates error."
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use super::Action;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+pub struct Slack {
+    url: String,
+    message: String,
+    channel: Option<String>,
+    username: Option<String>,
+    client: reqwest::Client,
+}
+
+impl Slack {
+    // Maps common values of the `severity` placeholder to the attachment colors of Slack.
+    fn color(severity: &str) -> Option<&'static str> {
+        match severity.to_ascii_lowercase().as_str() {
+            "critical" | "error" | "high" => Some("danger"),
+            "warning" | "medium" => Some("warning"),
+            "info" | "low" | "ok" => Some("good"),
+            _ => None,
+        }
+    }
+
+    // Incoming webhooks answer with a plain "ok", the Web API answers with JSON that includes the
+    // timestamp identifying the message.
+    fn message_ts(body: &str) -> Option<String> {
+        let response: serde_json::Value = serde_json::from_str(body).ok()?;
+        response.get("ts")?.as_str().map(String::from)
+    }
+
+    fn payload(&self, placeholders: &PlaceholderMap) -> serde_json::Value {
+        let message = crate::fill_placeholders(self.message.as_str(), placeholders);
+        let mut res = match placeholders.get("severity").and_then(|x| Self::color(x)) {
+            Some(color) => serde_json::json!({
+                "attachments": [{"color": color, "text": message, "fallback": message}]
+            }),
+            None => serde_json::json!({ "text": message }),
+        };
+        if let Some(channel) = &self.channel {
+            res["channel"] = serde_json::Value::String(channel.clone());
+        }
+        if let Some(username) = &self.username {
+            res["username"] = serde_json::Value::String(username.clone());
+        }
+        res
+    }
+}
+
+impl TryFrom<&config::Action> for Slack {
+    type Error = Error;
+
+    fn try_from(action: &config::Action) -> std::result::Result<Self, Self::Error> {
+        if let config::ActionType::Slack(slack) = &action.type_ {
+            if slack.url.is_empty() {
+                Err(Error(String::from("'url' cannot be empty.")))
+            } else if slack.message.is_empty() {
+                Err(Error(String::from("'message' cannot be empty.")))
+            } else if slack.channel.as_ref().is_some_and(|x| x.is_empty()) {
+                Err(Error(String::from("'channel' cannot be empty.")))
+            } else if slack.username.as_ref().is_some_and(|x| x.is_empty()) {
+                Err(Error(String::from("'username' cannot be empty.")))
+            } else {
+                Ok(Self {
+                    url: slack.url.clone(),
+                    message: slack.message.clone(),
+                    channel: slack.channel.clone(),
+                    username: slack.username.clone(),
+                    client: crate::http_client_builder(action.resolver.as_ref())?
+                        .build()
+                        .map_err(|x| Error(format!("Could not create HTTP client: {}", x)))?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl Action for Slack {
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&self.payload(&placeholders))
+            .send()
+            .await
+            .map_err(|x| Error(format!("HTTP request failed: {}", x)))?;
+        super::check_status(response.status())?;
+        let body = response.text().await.unwrap_or_default();
+        if let Some(ts) = Self::message_ts(&body) {
+            log::info!("Slack accepted the message with ts '{}'.", ts);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::mock_server;
+
+    fn config(url: &str, options: &str) -> config::Config {
+        config::Config::try_from(
+            format!(
+                r#"
+                [[actions]]
+                name = "test-action"
+                type = "Slack"
+                url = "{}"
+                message = "{{{{alarm_name}}}}: {{{{check_error}}}}"
+                {}
+                "#,
+                url, options
+            )
+            .as_str(),
+        )
+        .unwrap()
+    }
+
+    fn placeholders(severity: Option<&str>) -> PlaceholderMap {
+        let mut res = PlaceholderMap::from([
+            (String::from("alarm_name"), String::from("usage")),
+            (
+                String::from("check_error"),
+                String::from("\"/srv\" is full"),
+            ),
+        ]);
+        if let Some(severity) = severity {
+            res.insert(String::from("severity"), String::from(severity));
+        }
+        res
+    }
+
+    #[test]
+    fn test_payload() {
+        let slack_config = config(
+            "https://hooks.slack.com/services/T0/B0/x",
+            r##"channel = "#ops"
+            username = "minmon""##,
+        );
+        let slack = Slack::try_from(&slack_config.actions[0]).unwrap();
+        assert_eq!(
+            slack.payload(&placeholders(None)),
+            serde_json::json!({
+                "text": "usage: \"/srv\" is full",
+                "channel": "#ops",
+                "username": "minmon"
+            })
+        );
+        let payload = slack.payload(&placeholders(Some("Critical")));
+        assert_eq!(payload["attachments"][0]["color"], "danger");
+        assert_eq!(payload["attachments"][0]["text"], "usage: \"/srv\" is full");
+        assert!(payload.get("text").is_none());
+        assert_eq!(
+            slack.payload(&placeholders(Some("warning")))["attachments"][0]["color"],
+            "warning"
+        );
+        assert!(slack
+            .payload(&placeholders(Some("whatever")))
+            .get("attachments")
+            .is_none());
+    }
+
+    #[test]
+    fn test_message_ts() {
+        assert_eq!(
+            Slack::message_ts(r#"{"ok":true,"channel":"C0","ts":"1503435956.000247"}"#),
+            Some(String::from("1503435956.000247"))
+        );
+        assert_eq!(Slack::message_ts("ok"), None);
+        assert_eq!(Slack::message_ts(r#"{"ok":true}"#), None);
+    }
+
+    #[tokio::test]
+    async fn test_trigger() {
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests_clone = requests.clone();
+        let url = mock_server(move |request| {
+            requests_clone.lock().unwrap().push(String::from(request));
+            (200, String::from("ok"))
+        })
+        .await;
+        let slack_config = config(&format!("{}/services/T0/B0/x", url), "");
+        let slack = Slack::try_from(&slack_config.actions[0]).unwrap();
+        slack.trigger(placeholders(None)).await.unwrap();
+        let request = requests.lock().unwrap()[0].clone();
+        assert!(request.starts_with("POST /services/T0/B0/x "));
+        let payload: serde_json::Value =
+            serde_json::from_str(request.split_once("\r\n\r\n").unwrap().1).unwrap();
+        assert_eq!(payload["text"], "usage: \"/srv\" is full");
+        let url = mock_server(|_| {
+            (
+                200,
+                String::from(r#"{"ok":true,"channel":"C0","ts":"1503435956.000247"}"#),
+            )
+        })
+        .await;
+        let slack_config = config(&url, "");
+        let slack = Slack::try_from(&slack_config.actions[0]).unwrap();
+        slack.trigger(placeholders(None)).await.unwrap();
+        let url = mock_server(|_| (404, String::from("no_service"))).await;
+        let slack_config = config(&url, "");
+        let slack = Slack::try_from(&slack_config.actions[0]).unwrap();
+        assert_eq!(
+            slack
+                .trigger(placeholders(None))
+                .await
+                .unwrap_err()
+                .to_string(),
+            "HTTP status code 404 indicates error."
+        );
+    }
+}
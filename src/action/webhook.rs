@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use super::oauth2::OAuth2;
 use super::Action;
 use crate::config;
 use crate::{Error, PlaceholderMap, Result};
@@ -10,6 +11,9 @@ pub struct Webhook {
     method: reqwest::Method,
     headers: reqwest::header::HeaderMap<reqwest::header::HeaderValue>,
     body: String,
+    escape_json: bool,
+    oauth2: Option<OAuth2>,
+    client: reqwest::Client,
 }
 
 impl Webhook {
@@ -30,6 +34,32 @@ impl Webhook {
             )
             .collect()
     }
+
+    fn is_json(headers: &HashMap<String, String>) -> bool {
+        headers.iter().any(|(k, v)| {
+            k.eq_ignore_ascii_case("Content-Type") && v.to_ascii_lowercase().contains("json")
+        })
+    }
+
+    // Escapes the values so they can be put into JSON strings. The surrounding quotes are part of
+    // the template, so they're removed again.
+    fn escape_json(placeholders: &PlaceholderMap) -> PlaceholderMap {
+        placeholders
+            .iter()
+            .map(|(k, v)| {
+                let escaped = serde_json::Value::String(v.clone()).to_string();
+                (k.clone(), String::from(&escaped[1..escaped.len() - 1]))
+            })
+            .collect()
+    }
+
+    fn fill_body(&self, placeholders: &PlaceholderMap) -> String {
+        if self.escape_json {
+            crate::fill_placeholders(self.body.as_str(), &Self::escape_json(placeholders))
+        } else {
+            crate::fill_placeholders(self.body.as_str(), placeholders)
+        }
+    }
 }
 
 impl TryFrom<&config::Action> for Webhook {
@@ -49,6 +79,24 @@ impl TryFrom<&config::Action> for Webhook {
                     method: reqwest::Method::from(web_hook.method),
                     headers: Self::transform_header_map(&headers)?,
                     body: web_hook.body.clone(),
+                    escape_json: match web_hook.escape {
+                        config::WebhookEscape::Auto => {
+                            Self::is_json(&headers)
+                                || action
+                                    .template_files
+                                    .get("body")
+                                    .is_some_and(|x| x.format == config::TemplateFormat::Json)
+                        }
+                        config::WebhookEscape::Json => true,
+                        config::WebhookEscape::Off => false,
+                    },
+                    oauth2: match &web_hook.oauth2 {
+                        Some(oauth2) => Some(OAuth2::try_from(oauth2)?),
+                        None => None,
+                    },
+                    client: crate::http_client_builder(action.resolver.as_ref())?
+                        .build()
+                        .map_err(|x| Error(format!("Could not create HTTP client: {}", x)))?,
                 })
             }
         } else {
@@ -61,24 +109,26 @@ impl TryFrom<&config::Action> for Webhook {
 impl Action for Webhook {
     async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
         let url = crate::fill_placeholders(self.url.as_str(), &placeholders);
-        let body = crate::fill_placeholders(self.body.as_str(), &placeholders);
-        let client = reqwest::Client::new();
-        let response = client
+        let body = self.fill_body(&placeholders);
+        let mut request = self
+            .client
             .request(self.method.clone(), &url)
             .headers(self.headers.clone())
-            .body(body)
+            .body(body);
+        if let Some(oauth2) = &self.oauth2 {
+            request = request.bearer_auth(oauth2.token(&self.client).await?);
+        }
+        let response = request
             .send()
             .await
             .map_err(|x| Error(format!("HTTP request failed: {}", x)))?;
         let status = response.status();
-        if status.is_success() {
-            Ok(())
-        } else {
-            Err(Error(format!(
-                "HTTP status code {} indicates error.",
-                status.as_u16()
-            )))
+        if let Some(oauth2) = &self.oauth2 {
+            if status == reqwest::StatusCode::UNAUTHORIZED {
+                oauth2.invalidate().await;
+            }
         }
+        super::check_status(status)
     }
 }
 
@@ -93,3 +143,147 @@ impl From<config::HttpMethod> for reqwest::Method {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::mock_server;
+
+    fn webhook(headers: &str, escape: &str) -> Webhook {
+        let config = config::Config::try_from(
+            format!(
+                r#"
+                [[actions]]
+                name = "test-action"
+                type = "Webhook"
+                url = "http://example.com/webhook"
+                headers = {}
+                escape = "{}"
+                body = """{{"text": "{{{{check_error}}}}", "level": {{{{level}}}}}}"""
+                "#,
+                headers, escape
+            )
+            .as_str(),
+        )
+        .unwrap();
+        Webhook::try_from(&config.actions[0]).unwrap()
+    }
+
+    fn placeholders() -> PlaceholderMap {
+        PlaceholderMap::from([
+            (
+                String::from("check_error"),
+                String::from("Could not open \"/srv\":\n\tpermission denied \\o/"),
+            ),
+            (String::from("level"), String::from("42")),
+        ])
+    }
+
+    #[test]
+    fn test_fill_body_escape_auto() {
+        let webhook = webhook(r#"{"content-type" = "application/json"}"#, "Auto");
+        let body = webhook.fill_body(&placeholders());
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            json["text"],
+            "Could not open \"/srv\":\n\tpermission denied \\o/"
+        );
+        assert_eq!(json["level"], 42);
+    }
+
+    #[test]
+    fn test_fill_body_escape_json() {
+        let webhook = webhook("{}", "Json");
+        let body = webhook.fill_body(&placeholders());
+        assert!(serde_json::from_str::<serde_json::Value>(&body).is_ok());
+    }
+
+    #[test]
+    fn test_fill_body_escape_off() {
+        let body =
+            webhook(r#"{"Content-Type" = "application/json"}"#, "Off").fill_body(&placeholders());
+        assert!(serde_json::from_str::<serde_json::Value>(&body).is_err());
+        let plain_body =
+            webhook(r#"{"Content-Type" = "text/plain"}"#, "Auto").fill_body(&placeholders());
+        assert_eq!(plain_body, body);
+    }
+
+    #[test]
+    fn test_template_file() {
+        let path = std::env::temp_dir().join(format!("minmon-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"{
+                "blocks": [{"type": "section", "text": {"type": "mrkdwn", "text": "{{check_error}}"}}],
+                "level": {{level}}
+            }"#,
+        )
+        .unwrap();
+        let config = config::Config::try_from(
+            format!(
+                r#"
+                [[actions]]
+                name = "test-action"
+                type = "Webhook"
+                url = "http://example.com/webhook"
+                template_files = {{body = {{path = "{}", format = "Json"}}}}
+                "#,
+                path.display()
+            )
+            .as_str(),
+        );
+        std::fs::remove_file(&path).unwrap();
+        let webhook = Webhook::try_from(&config.unwrap().actions[0]).unwrap();
+        let body = webhook.fill_body(&placeholders());
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            json["blocks"][0]["text"]["text"],
+            "Could not open \"/srv\":\n\tpermission denied \\o/"
+        );
+        assert_eq!(json["level"], 42);
+    }
+
+    #[tokio::test]
+    async fn test_oauth2() {
+        let (token_url, token_requests) =
+            super::super::oauth2::test::mock_token_endpoint(3600).await;
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests_clone = requests.clone();
+        let url = mock_server(move |request| {
+            let mut requests = requests_clone.lock().unwrap();
+            requests.push(String::from(request));
+            // The first token gets rejected.
+            if requests.len() == 2 {
+                (401, String::new())
+            } else {
+                (200, String::new())
+            }
+        })
+        .await;
+        let config = config::Config::try_from(
+            format!(
+                r#"
+                [[actions]]
+                name = "test-action"
+                type = "Webhook"
+                url = "{}/webhook"
+                body = "{{{{level}}}}"
+                oauth2 = {{token_url = "{}", client_id = "minmon", client_secret = "secret"}}
+                "#,
+                url, token_url
+            )
+            .as_str(),
+        )
+        .unwrap();
+        let webhook = Webhook::try_from(&config.actions[0]).unwrap();
+        webhook.trigger(placeholders()).await.unwrap();
+        assert!(webhook.trigger(placeholders()).await.is_err());
+        webhook.trigger(placeholders()).await.unwrap();
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].contains("authorization: Bearer token-1"));
+        assert!(requests[1].contains("authorization: Bearer token-1"));
+        assert!(requests[2].contains("authorization: Bearer token-2"));
+        assert!(requests[2].ends_with("\r\n\r\n42"));
+        assert_eq!(token_requests.lock().unwrap().len(), 2);
+    }
+}
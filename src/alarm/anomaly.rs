@@ -0,0 +1,189 @@
+use crate::{Error, PlaceholderMap, Result};
+
+use super::{DataSink, LevelItem, SinkDecision};
+use crate::config;
+
+// Compares the data to an exponentially weighted moving mean and standard deviation (the
+// baseline) instead of a fixed level. The baseline is updated with every value, so a lasting
+// change becomes the new normal after a while.
+pub struct Anomaly<T> {
+    deviations: f64,
+    warmup: u32,
+    // weight of a new value
+    alpha: f64,
+    samples: u32,
+    mean: f64,
+    variance: f64,
+    // baseline and deviation of the last value
+    last: Option<(f64, f64, f64)>,
+    item: std::marker::PhantomData<T>,
+}
+
+impl<T> Anomaly<T> {
+    // Until the window is full, all values are weighted equally.
+    fn update(&mut self, value: f64) {
+        self.samples = self.samples.saturating_add(1);
+        let alpha = self.alpha.max(1.0 / self.samples as f64);
+        let diff = value - self.mean;
+        let increment = alpha * diff;
+        self.mean += increment;
+        self.variance = (1.0 - alpha) * (self.variance + diff * increment);
+    }
+
+    // Returns the deviation of the value from the mean in standard deviations.
+    fn deviation(&self, value: f64) -> f64 {
+        let diff = (value - self.mean).abs();
+        let stddev = self.variance.sqrt();
+        if stddev > 0.0 {
+            diff / stddev
+        } else if diff > 0.0 {
+            f64::INFINITY
+        } else {
+            0.0
+        }
+    }
+}
+
+impl<T> TryFrom<&config::Alarm> for Anomaly<T>
+where
+    T: LevelItem,
+{
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, self::Error> {
+        if let config::AlarmType::Anomaly(anomaly) = &alarm.type_ {
+            if anomaly.deviations <= 0.0 {
+                Err(Error(String::from("'deviations' must be positive.")))
+            } else if anomaly.warmup < 2 {
+                Err(Error(String::from("'warmup' cannot be less than 2.")))
+            } else if anomaly.window == 0 {
+                Err(Error(String::from("'window' cannot be 0.")))
+            } else {
+                Ok(Self {
+                    deviations: anomaly.deviations,
+                    warmup: anomaly.warmup,
+                    alpha: 2.0 / (anomaly.window as f64 + 1.0),
+                    samples: 0,
+                    mean: 0.0,
+                    variance: 0.0,
+                    last: None,
+                    item: std::marker::PhantomData,
+                })
+            }
+        } else {
+            Err(Error(String::from("Missing 'deviations' option.")))
+        }
+    }
+}
+
+impl<T> DataSink for Anomaly<T>
+where
+    T: LevelItem,
+{
+    type Item = T;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        let value = data.level();
+        let decision = if self.samples < self.warmup {
+            self.last = None;
+            SinkDecision::Good
+        } else {
+            let deviation = self.deviation(value);
+            self.last = Some((self.mean, self.variance.sqrt(), deviation));
+            if deviation > self.deviations {
+                SinkDecision::Bad
+            } else {
+                SinkDecision::Good
+            }
+        };
+        self.update(value);
+        Ok(decision)
+    }
+
+    fn add_placeholders(data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("level"), data.to_string());
+        data.add_placeholders(placeholders);
+    }
+
+    fn add_episode_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        if let Some((mean, stddev, deviation)) = self.last {
+            placeholders.insert(String::from("baseline_mean"), format!("{:.2}", mean));
+            placeholders.insert(String::from("baseline_stddev"), format!("{:.2}", stddev));
+            placeholders.insert(String::from("deviation"), format!("{:.2}", deviation));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sink(alarm: &str) -> Result<Anomaly<u64>> {
+        let config = config::Config::try_from(
+            format!(
+                r#"
+                [[checks]]
+                name = "Test"
+                type = "FileSize"
+                paths = ["/tmp/test"]
+                [[checks.alarms]]
+                name = "Test"
+                action = "Test"
+                {}
+                "#,
+                alarm
+            )
+            .as_str(),
+        )
+        .unwrap();
+        Anomaly::try_from(&config.checks[0].alarms[0])
+    }
+
+    fn is_bad(anomaly: &mut Anomaly<u64>, value: u64) -> bool {
+        matches!(anomaly.put_data(&value).unwrap(), SinkDecision::Bad)
+    }
+
+    #[test]
+    fn test_outlier() {
+        let mut anomaly = sink("deviations = 3\nwarmup = 10\nwindow = 20").unwrap();
+        // the baseline is not active during the warmup
+        assert!(!is_bad(&mut anomaly, 100));
+        assert!(!is_bad(&mut anomaly, 130));
+        for i in 0..30 {
+            assert!(!is_bad(&mut anomaly, 100 + i % 2 * 4));
+        }
+        assert!(is_bad(&mut anomaly, 150));
+        let mut placeholders = PlaceholderMap::new();
+        anomaly.add_episode_placeholders(&mut placeholders);
+        assert!(
+            placeholders
+                .get("deviation")
+                .unwrap()
+                .parse::<f64>()
+                .unwrap()
+                > 3.0
+        );
+        assert!(placeholders.contains_key("baseline_mean"));
+        assert!(!is_bad(&mut anomaly, 104));
+    }
+
+    #[test]
+    fn test_level_shift() {
+        let mut anomaly = sink("deviations = 3\nwarmup = 5\nwindow = 5").unwrap();
+        for i in 0..10 {
+            assert!(!is_bad(&mut anomaly, 10 + i % 3));
+        }
+        assert!(is_bad(&mut anomaly, 50));
+        // the new level becomes the baseline
+        let bad = (0..30).filter(|i| is_bad(&mut anomaly, 50 + i % 3)).count();
+        assert!(bad < 30);
+        assert!(!is_bad(&mut anomaly, 51));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(sink("deviations = 0").is_err());
+        assert!(sink("deviations = 3\nwarmup = 1").is_err());
+        assert!(sink("level = 10").is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use crate::config;
+use crate::{Error, Result};
+
+// Deduplicates the notifications of a firing alarm by value bucket. Within the window after a
+// notification, repeats are suppressed unless the value moved into a different bucket, which
+// re-notifies right away.
+pub struct Dedup {
+    buckets: Vec<f64>,
+    window: u32,
+    // bucket of the last notification and cycles since then
+    last: Option<(usize, u32)>,
+}
+
+impl Dedup {
+    pub fn new(dedup: &config::Dedup) -> Result<Self> {
+        if dedup.buckets.is_empty() {
+            Err(Error(String::from("'buckets' cannot be empty.")))
+        } else if dedup.buckets.windows(2).any(|x| x[0] >= x[1]) {
+            Err(Error(String::from(
+                "'buckets' must be in strictly ascending order.",
+            )))
+        } else if dedup.window == 0 {
+            Err(Error(String::from("'window' cannot be 0.")))
+        } else {
+            Ok(Self {
+                buckets: dedup.buckets.clone(),
+                window: dedup.window,
+                last: None,
+            })
+        }
+    }
+
+    fn bucket(&self, value: f64) -> usize {
+        self.buckets.iter().filter(|x| value >= **x).count()
+    }
+
+    // Decides whether a bad cycle of a firing alarm notifies. `trigger` is the decision without
+    // deduplication (e.g. a repeat), data without a value is not deduplicated.
+    pub fn notify(&mut self, trigger: bool, value: Option<f64>) -> bool {
+        let bucket = match value {
+            Some(value) => self.bucket(value),
+            None => return trigger,
+        };
+        let notify = match &mut self.last {
+            Some((last_bucket, cycles)) => {
+                *cycles += 1;
+                bucket != *last_bucket || (trigger && *cycles >= self.window)
+            }
+            None => trigger,
+        };
+        if notify {
+            self.last = Some((bucket, 0));
+        }
+        notify
+    }
+
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn dedup(window: u32) -> Dedup {
+        Dedup::new(&config::Dedup {
+            buckets: vec![90.0, 95.0, 99.0],
+            window,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_validation() {
+        let config = |buckets: Vec<f64>, window| config::Dedup { buckets, window };
+        assert!(Dedup::new(&config(vec![], 10)).is_err());
+        assert!(Dedup::new(&config(vec![95.0, 90.0], 10)).is_err());
+        assert!(Dedup::new(&config(vec![90.0, 90.0], 10)).is_err());
+        assert!(Dedup::new(&config(vec![90.0], 0)).is_err());
+    }
+
+    #[test]
+    fn test_notify_on_bucket_change() {
+        let mut dedup = dedup(10);
+        // first notification
+        assert!(dedup.notify(true, Some(91.0)));
+        // repeats within the same bucket are suppressed
+        assert!(!dedup.notify(true, Some(92.0)));
+        assert!(!dedup.notify(false, Some(94.9)));
+        // rising into the next buckets re-notifies
+        assert!(dedup.notify(false, Some(96.0)));
+        assert!(!dedup.notify(true, Some(97.0)));
+        assert!(dedup.notify(false, Some(99.5)));
+        // falling back is a change, too
+        assert!(dedup.notify(false, Some(96.0)));
+        // without a value, the decision is passed through
+        assert!(dedup.notify(true, None));
+        assert!(!dedup.notify(false, None));
+    }
+
+    #[test]
+    fn test_window() {
+        let mut dedup = dedup(3);
+        assert!(dedup.notify(true, Some(91.0)));
+        assert!(!dedup.notify(true, Some(91.0)));
+        assert!(!dedup.notify(true, Some(91.0)));
+        // after the window, repeats are back to normal
+        assert!(!dedup.notify(false, Some(91.0)));
+        assert!(dedup.notify(true, Some(91.0)));
+        assert!(!dedup.notify(true, Some(91.0)));
+        dedup.reset();
+        assert!(dedup.notify(true, Some(91.0)));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{Error, PlaceholderMap, Result};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Check index, alarm index and ID of an alarm. Unlike the log ID, it is unique even with duplicate
+// check or alarm names.
+pub type Member = (usize, usize, String);
+
+struct OpenIncident {
+    uuid: String,
+    // firing alarms
+    members: BTreeSet<Member>,
+}
+
+// Open incidents by their group, shared by all alarms of the monitor.
+#[derive(Default)]
+pub struct Incidents(std::sync::Mutex<BTreeMap<String, OpenIncident>>);
+
+// Membership of an alarm in an incident group. Firing alarms of the same group share one incident
+// that opens with the first of them and closes when the last one recovers.
+pub struct Incident {
+    incidents: std::sync::Arc<Incidents>,
+    group: String,
+    member: Member,
+    log_id: String,
+}
+
+impl Incident {
+    pub fn new(
+        incidents: std::sync::Arc<Incidents>,
+        group: &str,
+        member: Member,
+        log_id: &str,
+    ) -> Result<Self> {
+        if group.is_empty() {
+            Err(Error(String::from("'incident' cannot be empty.")))
+        } else {
+            Ok(Self {
+                incidents,
+                group: String::from(group),
+                member,
+                log_id: String::from(log_id),
+            })
+        }
+    }
+
+    pub fn join(&self, placeholders: &mut PlaceholderMap) {
+        let mut incidents = self.incidents.0.lock().unwrap();
+        let opened = !incidents.contains_key(&self.group);
+        let incident = incidents
+            .entry(self.group.clone())
+            .or_insert_with(|| OpenIncident {
+                uuid: uuid::Uuid::new_v4().to_string(),
+                members: BTreeSet::new(),
+            });
+        incident.members.insert(self.member.clone());
+        if opened {
+            log::info!(
+                "Incident '{}' of group '{}' opened by {}.",
+                incident.uuid,
+                self.group,
+                self.log_id
+            );
+        }
+        Self::add_placeholders(
+            placeholders,
+            incident,
+            if opened { "Opened" } else { "Open" },
+        );
+    }
+
+    pub fn leave(&self, placeholders: &mut PlaceholderMap) {
+        let mut incidents = self.incidents.0.lock().unwrap();
+        let incident = match incidents.get_mut(&self.group) {
+            Some(incident) => incident,
+            None => return,
+        };
+        if !incident.members.remove(&self.member) {
+            return;
+        }
+        if incident.members.is_empty() {
+            log::info!(
+                "Incident '{}' of group '{}' closed.",
+                incident.uuid,
+                self.group
+            );
+            Self::add_placeholders(placeholders, incident, "Closed");
+            incidents.remove(&self.group);
+        } else {
+            Self::add_placeholders(placeholders, incident, "Open");
+        }
+    }
+
+    // Errors do not change the incident, but refer to it while the alarm is part of it.
+    pub fn current(&self, placeholders: &mut PlaceholderMap) {
+        let incidents = self.incidents.0.lock().unwrap();
+        if let Some(incident) = incidents.get(&self.group) {
+            if incident.members.contains(&self.member) {
+                Self::add_placeholders(placeholders, incident, "Open");
+            }
+        }
+    }
+
+    fn add_placeholders(placeholders: &mut PlaceholderMap, incident: &OpenIncident, state: &str) {
+        placeholders.insert(String::from("incident_id"), incident.uuid.clone());
+        placeholders.insert(String::from("incident_state"), String::from(state));
+        placeholders.insert(
+            String::from("incident_alarms"),
+            incident.members.len().to_string(),
+        );
+    }
+}
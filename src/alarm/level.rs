@@ -1,34 +1,116 @@
 use crate::{Error, PlaceholderMap, Result};
 
-use super::{DataSink, SinkDecision};
+use super::{Anomaly, DataSink, SinkDecision};
 use crate::config;
 
-pub struct Level {
-    level: u8,
+pub trait LevelItem: std::fmt::Display + Send + Sync + Clone + PartialEq {
+    fn level(&self) -> f64;
+
+    fn validate_level(_level: f64) -> Result<()> {
+        Ok(())
+    }
+
+    fn add_placeholders(&self, _placeholders: &mut PlaceholderMap) {}
+}
+
+impl LevelItem for u8 {
+    fn level(&self) -> f64 {
+        *self as f64
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else if level > 100.0 {
+            Err(Error(String::from("'level' cannot be greater than 100.")))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl LevelItem for u64 {
+    fn level(&self) -> f64 {
+        *self as f64
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl LevelItem for i16 {
+    fn level(&self) -> f64 {
+        *self as f64
+    }
+}
+
+impl LevelItem for f64 {
+    fn level(&self) -> f64 {
+        *self
+    }
 }
 
-impl TryFrom<&config::Alarm> for Level {
+// Compares the data against a fixed level or, with the anomaly options, against a moving
+// baseline.
+pub struct Level<T> {
+    level: f64,
+    anomaly: Option<Anomaly<T>>,
+    // minimum and maximum level of the current episode
+    episode: Option<(f64, f64)>,
+    item: std::marker::PhantomData<T>,
+}
+
+impl<T> TryFrom<&config::Alarm> for Level<T>
+where
+    T: LevelItem,
+{
     type Error = Error;
 
     fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, self::Error> {
-        #[allow(irrefutable_let_patterns)] // there are no other types yet
-        if let config::AlarmType::Level(level) = &alarm.type_ {
-            if level.level > 100 {
-                Err(Error(String::from("'level' cannot be greater than 100.")))
-            } else {
-                Ok(Self { level: level.level })
+        match &alarm.type_ {
+            config::AlarmType::Level(level) => {
+                T::validate_level(level.level)?;
+                Ok(Self {
+                    level: level.level,
+                    anomaly: None,
+                    episode: None,
+                    item: std::marker::PhantomData,
+                })
             }
-        } else {
-            panic!();
+            config::AlarmType::Anomaly(_) => Ok(Self {
+                level: 0.0,
+                anomaly: Some(Anomaly::try_from(alarm)?),
+                episode: None,
+                item: std::marker::PhantomData,
+            }),
+            _ => Err(Error(String::from("Missing 'level' option."))),
         }
     }
 }
 
-impl DataSink for Level {
-    type Item = u8;
+impl<T> DataSink for Level<T>
+where
+    T: LevelItem,
+{
+    type Item = T;
+
+    const LEVEL: bool = true;
 
     fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
-        Ok(if *data > self.level {
+        let level = data.level();
+        self.episode = Some(match self.episode {
+            Some((min, max)) => (min.min(level), max.max(level)),
+            None => (level, level),
+        });
+        if let Some(anomaly) = &mut self.anomaly {
+            return anomaly.put_data(data);
+        }
+        Ok(if level > self.level {
             SinkDecision::Bad
         } else {
             SinkDecision::Good
@@ -37,5 +119,24 @@ impl DataSink for Level {
 
     fn add_placeholders(data: &Self::Item, placeholders: &mut PlaceholderMap) {
         placeholders.insert(String::from("level"), data.to_string());
+        data.add_placeholders(placeholders);
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.level())
+    }
+
+    fn add_episode_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        if let Some((min, max)) = self.episode {
+            placeholders.insert(String::from("episode_min"), min.to_string());
+            placeholders.insert(String::from("episode_max"), max.to_string());
+        }
+        if let Some(anomaly) = &self.anomaly {
+            anomaly.add_episode_placeholders(placeholders);
+        }
+    }
+
+    fn reset_episode(&mut self) {
+        self.episode = None;
     }
 }
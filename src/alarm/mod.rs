@@ -1,19 +1,45 @@
 use crate::action;
+use crate::redaction::Redaction;
 use crate::{Error, PlaceholderMap, Result};
 use async_trait::async_trait;
 
+mod anomaly;
+mod dedup;
+mod incident;
 mod level;
+mod recover_confirmation;
 mod state_machine;
+mod status;
+mod status_code;
 
-pub use level::Level;
+pub use anomaly::Anomaly;
+pub use dedup::Dedup;
+pub use incident::{Incident, Incidents};
+pub use level::{Level, LevelItem};
+pub use recover_confirmation::RecoverConfirmation;
 pub use state_machine::{StateHandler, StateMachine};
+pub use status::{Status, StatusItem};
+pub use status_code::{StatusCode, StatusCodeItem};
 
 #[cfg_attr(test, mockall::automock(type Item=u8;))]
 pub trait DataSink: Send + Sync + Sized {
-    type Item: Send + Sync;
+    type Item: Send + Sync + Clone + PartialEq;
+
+    // Only level alarms compare the data as a number, see `config::Check::transform`.
+    const LEVEL: bool = false;
 
     fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision>;
     fn add_placeholders(data: &Self::Item, placeholders: &mut PlaceholderMap);
+
+    // Numeric representation of the data for the deduplication by value bucket.
+    fn value(_data: &Self::Item) -> Option<f64> {
+        None
+    }
+
+    // The episode covers the data since the alarm's last recovery or good cycle outside of an
+    // alarm, i.e. the bad streak leading to the alarm and the alarm itself.
+    fn add_episode_placeholders(&self, _placeholders: &mut PlaceholderMap) {}
+    fn reset_episode(&mut self) {}
 }
 
 pub enum SinkDecision {
@@ -57,9 +83,19 @@ where
     error_action: Option<std::sync::Arc<dyn action::Action>>,
     error_placeholders: PlaceholderMap,
     invert: bool,
+    stale_cycles: u32,
+    heartbeat_cycles: u32,
     state_machine: U,
     data_sink: T,
     log_id: String,
+    last_data: Option<T::Item>,
+    identical_cycles: u32,
+    missing_cycles: u32,
+    episode: bool,
+    recover_confirmation: Option<RecoverConfirmation>,
+    incident: Option<Incident>,
+    dedup: Option<Dedup>,
+    redaction: Option<std::sync::Arc<Redaction>>,
 }
 
 impl<T, U> AlarmBase<T, U>
@@ -94,29 +130,145 @@ where
                 error_action,
                 error_placeholders,
                 invert,
+                stale_cycles: 0,
+                heartbeat_cycles: 0,
                 state_machine,
                 data_sink,
                 log_id,
+                last_data: None,
+                identical_cycles: 0,
+                missing_cycles: 0,
+                episode: false,
+                recover_confirmation: None,
+                incident: None,
+                dedup: None,
+                redaction: None,
             })
         }
     }
 
-    async fn error(&mut self, placeholders: PlaceholderMap) -> Result<()> {
+    pub fn set_recover_confirmation(&mut self, recover_confirmation: RecoverConfirmation) {
+        self.recover_confirmation = Some(recover_confirmation);
+    }
+
+    pub fn set_incident(&mut self, incident: Incident) {
+        self.incident = Some(incident);
+    }
+
+    pub fn set_dedup(&mut self, dedup: Dedup) {
+        self.dedup = Some(dedup);
+    }
+
+    // `cycles` is the number of bad cycles that trigger the alarm. Stale data has to be detected
+    // before, otherwise the alarm is triggered by it first.
+    pub fn set_stale_cycles(&mut self, stale_cycles: u32, cycles: u32) -> Result<()> {
+        if stale_cycles == 1 {
+            return Err(Error(String::from("'stale_cycles' cannot be 1.")));
+        } else if stale_cycles > cycles {
+            return Err(Error(String::from(
+                "'stale_cycles' cannot be greater than 'cycles'.",
+            )));
+        }
+        self.stale_cycles = stale_cycles;
+        Ok(())
+    }
+
+    pub fn set_heartbeat_cycles(&mut self, heartbeat_cycles: u32) {
+        self.heartbeat_cycles = heartbeat_cycles;
+    }
+
+    pub fn set_redaction(&mut self, redaction: std::sync::Arc<Redaction>) {
+        self.redaction = Some(redaction);
+    }
+
+    fn redact(&self, placeholders: &mut PlaceholderMap) {
+        if let Some(redaction) = &self.redaction {
+            redaction.redact_placeholders(placeholders);
+        }
+    }
+
+    // Good cycles of a firing alarm only count if the recovery is confirmed.
+    fn is_recovery_held(&self) -> bool {
+        let recover_confirmation = match &self.recover_confirmation {
+            Some(recover_confirmation) if self.episode => recover_confirmation,
+            _ => return false,
+        };
+        match recover_confirmation.confirm() {
+            Ok(true) => false,
+            Ok(false) => {
+                log::info!(
+                    "{}: Recovery is held because it is not confirmed by {}.",
+                    self.log_id,
+                    recover_confirmation.log_id()
+                );
+                true
+            }
+            Err(err) => {
+                log::warn!(
+                    "{}: Recovery is deferred because it could not be confirmed: {}",
+                    self.log_id,
+                    err
+                );
+                true
+            }
+        }
+    }
+
+    async fn error(&mut self, mut placeholders: PlaceholderMap) -> Result<()> {
         if self.state_machine.error() {
+            if let Some(incident) = &self.incident {
+                incident.current(&mut placeholders);
+            }
             self.trigger_error(placeholders).await?;
         }
         Ok(())
     }
 
-    async fn bad(&mut self, placeholders: PlaceholderMap) -> Result<()> {
-        if self.state_machine.bad() {
+    // A missing heartbeat is already debounced by `heartbeat_cycles`, so it doesn't wait for
+    // `cycles` bad cycles.
+    async fn bad(
+        &mut self,
+        mut placeholders: PlaceholderMap,
+        value: Option<f64>,
+        heartbeat: bool,
+    ) -> Result<()> {
+        let mut trigger = if heartbeat {
+            self.state_machine.bad_now()
+        } else {
+            self.state_machine.bad()
+        };
+        if let Some(dedup) = &mut self.dedup {
+            // Only a firing alarm is deduplicated, i.e. not before its first notification.
+            if self.episode {
+                trigger = dedup.notify(trigger, value);
+            } else if trigger {
+                dedup.reset();
+                dedup.notify(true, value);
+            }
+        }
+        if trigger {
+            self.episode = true;
+            if let Some(incident) = &self.incident {
+                incident.join(&mut placeholders);
+            }
             self.trigger(placeholders).await?;
         }
         Ok(())
     }
 
-    async fn good(&mut self, placeholders: PlaceholderMap) -> Result<()> {
-        if self.state_machine.good() {
+    async fn good(&mut self, mut placeholders: PlaceholderMap) -> Result<()> {
+        if self.is_recovery_held() {
+            return Ok(());
+        }
+        let recovered = self.state_machine.good();
+        if recovered || !self.episode {
+            self.episode = false;
+            self.data_sink.reset_episode();
+        }
+        if recovered {
+            if let Some(incident) = &self.incident {
+                incident.leave(&mut placeholders);
+            }
             self.trigger_recover(placeholders).await?;
         }
         Ok(())
@@ -124,12 +276,14 @@ where
 
     async fn trigger(&self, mut placeholders: PlaceholderMap) -> Result<()> {
         self.state_machine.add_placeholders(&mut placeholders);
+        self.redact(&mut placeholders);
         self.action.trigger(placeholders).await
     }
 
     async fn trigger_recover(&self, mut placeholders: PlaceholderMap) -> Result<()> {
         self.state_machine.add_placeholders(&mut placeholders);
         crate::merge_placeholders(&mut placeholders, &self.recover_placeholders);
+        self.redact(&mut placeholders);
         match &self.recover_action {
             Some(action) => action.trigger(placeholders).await,
             None => Ok(()),
@@ -139,12 +293,30 @@ where
     async fn trigger_error(&self, mut placeholders: PlaceholderMap) -> Result<()> {
         self.state_machine.add_placeholders(&mut placeholders);
         crate::merge_placeholders(&mut placeholders, &self.error_placeholders);
+        self.redact(&mut placeholders);
         match &self.error_action {
             Some(action) => action.trigger(placeholders).await,
             None => Ok(()),
         }
     }
 
+    fn update_identical_cycles(&mut self, data: &T::Item) {
+        if self.last_data.as_ref() == Some(data) {
+            self.identical_cycles += 1;
+        } else {
+            self.identical_cycles = 1;
+            self.last_data = Some(data.clone());
+        }
+    }
+
+    fn is_stale(&self) -> bool {
+        self.stale_cycles != 0 && self.identical_cycles >= self.stale_cycles
+    }
+
+    fn is_heartbeat_missing(&self) -> bool {
+        self.heartbeat_cycles != 0 && self.missing_cycles >= self.heartbeat_cycles
+    }
+
     fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
         placeholders.insert(String::from("alarm_name"), self.name.clone());
         placeholders.insert(String::from("alarm_id"), self.id.clone());
@@ -172,14 +344,25 @@ where
         T::add_placeholders(data, &mut placeholders);
         self.add_placeholders(&mut placeholders);
         let mut decision = self.data_sink.put_data(data)?;
+        self.data_sink.add_episode_placeholders(&mut placeholders);
         if self.invert {
             decision = !decision;
         }
+        self.update_identical_cycles(data);
+        self.missing_cycles = 0;
         match decision {
             SinkDecision::Good => self.good(placeholders).await,
+            SinkDecision::Bad if self.is_stale() => {
+                // A bad value that does not change at all might come from a stuck sensor.
+                let error = format!("Data has not changed for {} cycles.", self.identical_cycles);
+                log::error!("{} got stale data: {}", self.log_id, error);
+                placeholders.insert(String::from("check_error"), error);
+                self.error(placeholders).await
+            }
             SinkDecision::Bad => {
                 log::warn!("{}: Data exceeds limit.", self.log_id);
-                self.bad(placeholders).await
+                let value = self.dedup.as_ref().and_then(|_| T::value(data));
+                self.bad(placeholders, value, false).await
             }
         }
     }
@@ -187,7 +370,18 @@ where
     async fn put_error(&mut self, error: &Error, mut placeholders: PlaceholderMap) -> Result<()> {
         log::error!("{} got an error: {}", self.log_id, error);
         self.add_placeholders(&mut placeholders);
-        self.error(placeholders).await
+        self.missing_cycles += 1;
+        if self.is_heartbeat_missing() {
+            // No data for too long is an alarm by itself, not just an error.
+            log::warn!(
+                "{}: No data for {} cycles.",
+                self.log_id,
+                self.missing_cycles
+            );
+            self.bad(placeholders, None, true).await
+        } else {
+            self.error(placeholders).await
+        }
     }
 }
 
@@ -218,6 +412,10 @@ mod test {
             .with(eq(20))
             .returning(|_| Ok(SinkDecision::Bad));
         mock_data_sink
+            .expect_add_episode_placeholders()
+            .return_const(());
+        mock_data_sink.expect_reset_episode().return_const(());
+        mock_data_sink
     }
 
     #[tokio::test]
@@ -391,6 +589,155 @@ mod test {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_stale_data() {
+        let _permit = SEMAPHORE.acquire().await.unwrap();
+        let data_sink_ctx = MockDataSink::add_placeholders_context();
+        data_sink_ctx.expect().return_const(());
+        let mock_data_sink = mock_data_sink();
+        let mut mock_error_action = action::MockAction::new();
+        mock_error_action
+            .expect_trigger()
+            .once()
+            .with(function(|placeholders: &PlaceholderMap| {
+                assert_eq!(
+                    placeholders.get("check_error").unwrap(),
+                    "Data has not changed for 3 cycles."
+                );
+                true
+            }))
+            .returning(|_| Ok(()));
+        let mut mock_state_machine = state_machine::MockStateHandler::new();
+        mock_state_machine.expect_bad().times(4).return_const(false);
+        mock_state_machine.expect_error().once().return_const(true);
+        mock_state_machine
+            .expect_add_placeholders()
+            .once()
+            .return_const(());
+        let mut alarm = AlarmBase::new(
+            String::from("Name"),
+            String::from("ID"),
+            times_action(0),
+            PlaceholderMap::new(),
+            Some(times_action(0)),
+            PlaceholderMap::new(),
+            Some(std::sync::Arc::new(mock_error_action)),
+            PlaceholderMap::new(),
+            false,
+            mock_state_machine,
+            mock_data_sink,
+            String::from(""),
+        )
+        .unwrap();
+        // stale data would only be detected after the alarm was triggered
+        assert!(alarm.set_stale_cycles(3, 2).is_err());
+        alarm.set_stale_cycles(3, 5).unwrap();
+        // changing bad values are not stale
+        alarm
+            .data_sink
+            .expect_put_data()
+            .with(eq(21))
+            .returning(|_| Ok(SinkDecision::Bad));
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&21, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat() {
+        let _permit = SEMAPHORE.acquire().await.unwrap();
+        let data_sink_ctx = MockDataSink::add_placeholders_context();
+        data_sink_ctx.expect().return_const(());
+        let mock_data_sink = mock_data_sink();
+        let mut mock_state_machine = state_machine::MockStateHandler::new();
+        let mut sequence = mockall::Sequence::new();
+        mock_state_machine
+            .expect_error()
+            .times(2)
+            .in_sequence(&mut sequence)
+            .return_const(false);
+        mock_state_machine
+            .expect_bad_now()
+            .times(2)
+            .in_sequence(&mut sequence)
+            .return_const(true);
+        mock_state_machine
+            .expect_good()
+            .once()
+            .in_sequence(&mut sequence)
+            .return_const(false);
+        mock_state_machine
+            .expect_error()
+            .once()
+            .in_sequence(&mut sequence)
+            .return_const(false);
+        mock_state_machine
+            .expect_add_placeholders()
+            .times(2)
+            .return_const(());
+        let mut alarm = AlarmBase::new(
+            String::from("Name"),
+            String::from("ID"),
+            times_action(2),
+            PlaceholderMap::new(),
+            Some(times_action(0)),
+            PlaceholderMap::new(),
+            Some(times_action(0)),
+            PlaceholderMap::new(),
+            false,
+            mock_state_machine,
+            mock_data_sink,
+            String::from(""),
+        )
+        .unwrap();
+        alarm.set_heartbeat_cycles(3);
+        let error = Error(String::from("No data."));
+        // data stops flowing, the heartbeat alarm fires on the third missing cycle
+        for _ in 0..4 {
+            alarm
+                .put_error(&error, PlaceholderMap::new())
+                .await
+                .unwrap();
+        }
+        // data is back, so a single error is just an error again
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        alarm
+            .put_error(&error, PlaceholderMap::new())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_cycles() {
+        let mut alarm = AlarmBase::new(
+            String::from("Name"),
+            String::from("ID"),
+            times_action(1),
+            PlaceholderMap::new(),
+            Some(times_action(0)),
+            PlaceholderMap::new(),
+            Some(times_action(1)),
+            PlaceholderMap::new(),
+            false,
+            StateMachine::new(3, 0, 1, 0, String::new()).unwrap(),
+            mock_data_sink(),
+            String::from(""),
+        )
+        .unwrap();
+        alarm.set_heartbeat_cycles(2);
+        let error = Error(String::from("No data."));
+        // the heartbeat alarm fires on the second missing cycle, not only after `cycles` more
+        for _ in 0..2 {
+            alarm
+                .put_error(&error, PlaceholderMap::new())
+                .await
+                .unwrap();
+        }
+        assert!(alarm.episode);
+    }
+
     #[tokio::test]
     async fn test_invert() {
         let _permit = SEMAPHORE.acquire().await.unwrap();
@@ -428,4 +775,275 @@ mod test {
         alarm.recover_action = Some(times_action(1));
         alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_episode_placeholders() {
+        let config = crate::config::Config::try_from(
+            r#"
+            [[checks]]
+            name = "Name"
+            type = "FilesystemUsage"
+            mountpoints = ["/"]
+
+            [[checks.alarms]]
+            name = "Name"
+            action = "Action"
+            level = 80
+        "#,
+        )
+        .unwrap();
+        let level = Level::<u8>::try_from(&config.checks[0].alarms[0]).unwrap();
+        let triggered = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let action = |triggered: std::sync::Arc<std::sync::Mutex<Vec<PlaceholderMap>>>| {
+            let mut mock_action = action::MockAction::new();
+            mock_action.expect_trigger().returning(move |placeholders| {
+                triggered.lock().unwrap().push(placeholders);
+                Ok(())
+            });
+            std::sync::Arc::new(mock_action)
+        };
+        let mut alarm = AlarmBase::new(
+            String::from("Name"),
+            String::from("ID"),
+            action(triggered.clone()),
+            PlaceholderMap::new(),
+            Some(action(triggered.clone())),
+            PlaceholderMap::new(),
+            Some(times_action(0)),
+            PlaceholderMap::new(),
+            false,
+            StateMachine::new(1, 0, 1, 0, String::new()).unwrap(),
+            level,
+            String::from(""),
+        )
+        .unwrap();
+        // the good values before an alarm are not part of its episode
+        for data in [10u8, 50, 85, 98, 92, 60, 95, 81, 70] {
+            alarm.put_data(&data, PlaceholderMap::new()).await.unwrap();
+        }
+        let triggered = triggered.lock().unwrap();
+        let episode = |index: usize| -> (&str, &str, &str) {
+            (
+                triggered[index].get("alarm_state").unwrap(),
+                triggered[index].get("episode_min").unwrap(),
+                triggered[index].get("episode_max").unwrap(),
+            )
+        };
+        assert_eq!(triggered.len(), 4);
+        assert_eq!(episode(0), ("Bad", "85", "85"));
+        assert_eq!(episode(1), ("Good", "60", "98"));
+        assert_eq!(episode(2), ("Bad", "95", "95"));
+        assert_eq!(episode(3), ("Good", "70", "95"));
+    }
+
+    #[tokio::test]
+    async fn test_incident() {
+        let config = crate::config::Config::try_from(
+            r#"
+            [[checks]]
+            name = "Name"
+            type = "FilesystemUsage"
+            mountpoints = ["/"]
+
+            [[checks.alarms]]
+            name = "Name"
+            action = "Action"
+            level = 80
+        "#,
+        )
+        .unwrap();
+        let triggered = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let action = |triggered: std::sync::Arc<std::sync::Mutex<Vec<PlaceholderMap>>>| {
+            let mut mock_action = action::MockAction::new();
+            mock_action.expect_trigger().returning(move |placeholders| {
+                triggered.lock().unwrap().push(placeholders);
+                Ok(())
+            });
+            std::sync::Arc::new(mock_action)
+        };
+        let incidents = std::sync::Arc::new(Incidents::default());
+        let mut alarms = Vec::new();
+        for (check_index, name) in ["Database", "Web"].into_iter().enumerate() {
+            let mut alarm = AlarmBase::new(
+                String::from(name),
+                String::from("ID"),
+                action(triggered.clone()),
+                PlaceholderMap::new(),
+                Some(action(triggered.clone())),
+                PlaceholderMap::new(),
+                Some(times_action(0)),
+                PlaceholderMap::new(),
+                false,
+                StateMachine::new(1, 0, 1, 0, String::new()).unwrap(),
+                Level::<u8>::try_from(&config.checks[0].alarms[0]).unwrap(),
+                String::from(name),
+            )
+            .unwrap();
+            // the same log ID, e.g. with duplicate names
+            alarm.set_incident(
+                Incident::new(
+                    incidents.clone(),
+                    "Group",
+                    (check_index, 0, String::from("ID")),
+                    "Alarm",
+                )
+                .unwrap(),
+            );
+            alarms.push(alarm);
+        }
+        for (index, data) in [(0, 90u8), (1, 90), (1, 95), (0, 10), (1, 10), (1, 90)] {
+            alarms[index]
+                .put_data(&data, PlaceholderMap::new())
+                .await
+                .unwrap();
+        }
+        let triggered = triggered.lock().unwrap();
+        let incident = |index: usize| -> (&str, &str, &str) {
+            (
+                triggered[index].get("alarm_state").unwrap(),
+                triggered[index].get("incident_state").unwrap(),
+                triggered[index].get("incident_alarms").unwrap(),
+            )
+        };
+        assert_eq!(triggered.len(), 5);
+        assert_eq!(incident(0), ("Bad", "Opened", "1"));
+        assert_eq!(incident(1), ("Bad", "Open", "2"));
+        assert_eq!(incident(2), ("Good", "Open", "1"));
+        assert_eq!(incident(3), ("Good", "Closed", "0"));
+        assert_eq!(incident(4), ("Bad", "Opened", "1"));
+        // one incident from the first fire until the last recovery
+        for index in 1..4 {
+            assert_eq!(
+                triggered[index].get("incident_id"),
+                triggered[0].get("incident_id")
+            );
+        }
+        assert_ne!(
+            triggered[4].get("incident_id"),
+            triggered[0].get("incident_id")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recover_confirmation() {
+        let config = crate::config::Config::try_from(
+            r#"
+            [[checks]]
+            name = "Name"
+            type = "FilesystemUsage"
+            mountpoints = ["/"]
+
+            [[checks.alarms]]
+            name = "Name"
+            action = "Action"
+            level = 80
+            recover_confirmation = {check = "Health", id = "api", level = 0}
+        "#,
+        )
+        .unwrap();
+        let alarm_config = &config.checks[0].alarms[0];
+        let values = crate::check::ValueStore::default();
+        let set_health = |value: f64| {
+            values.write().unwrap().insert(
+                (String::from("Health"), String::from("api")),
+                crate::check::Value::new(value, std::time::Duration::from_secs(60)),
+            );
+        };
+        let mut alarm = AlarmBase::new(
+            String::from("Name"),
+            String::from("ID"),
+            times_action(1),
+            PlaceholderMap::new(),
+            Some(times_action(0)),
+            PlaceholderMap::new(),
+            Some(times_action(0)),
+            PlaceholderMap::new(),
+            false,
+            StateMachine::new(1, 0, 1, 0, String::new()).unwrap(),
+            Level::<u8>::try_from(alarm_config).unwrap(),
+            String::from(""),
+        )
+        .unwrap();
+        alarm.set_recover_confirmation(
+            RecoverConfirmation::new(
+                alarm_config.recover_confirmation.as_ref().unwrap(),
+                values.clone(),
+            )
+            .unwrap(),
+        );
+        // good values without an alarm don't need a confirmation
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&90, PlaceholderMap::new()).await.unwrap();
+        // the secondary source is not available
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        // the secondary source disagrees
+        set_health(1.0);
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        alarm.recover_action = Some(times_action(1));
+        set_health(0.0);
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dedup() {
+        let config = crate::config::Config::try_from(
+            r#"
+            [[checks]]
+            name = "Name"
+            type = "FilesystemUsage"
+            mountpoints = ["/"]
+
+            [[checks.alarms]]
+            name = "Name"
+            action = "Action"
+            level = 90
+            repeat_cycles = 1
+            dedup = {buckets = [95, 99], window = 10}
+        "#,
+        )
+        .unwrap();
+        let alarm_config = &config.checks[0].alarms[0];
+        let triggered = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let triggered_clone = triggered.clone();
+        let mut mock_action = action::MockAction::new();
+        mock_action
+            .expect_trigger()
+            .returning(move |placeholders: PlaceholderMap| {
+                triggered_clone
+                    .lock()
+                    .unwrap()
+                    .push(placeholders.get("level").unwrap().clone());
+                Ok(())
+            });
+        let mut alarm = AlarmBase::new(
+            String::from("Name"),
+            String::from("ID"),
+            std::sync::Arc::new(mock_action),
+            PlaceholderMap::new(),
+            Some(times_action(1)),
+            PlaceholderMap::new(),
+            Some(times_action(0)),
+            PlaceholderMap::new(),
+            false,
+            StateMachine::new(
+                alarm_config.cycles,
+                alarm_config.repeat_cycles,
+                alarm_config.recover_cycles,
+                alarm_config.error_repeat_cycles,
+                String::new(),
+            )
+            .unwrap(),
+            Level::<u8>::try_from(alarm_config).unwrap(),
+            String::from(""),
+        )
+        .unwrap();
+        alarm.set_dedup(Dedup::new(alarm_config.dedup.as_ref().unwrap()).unwrap());
+        // rising across the buckets within the window, repeating every cycle without dedup
+        for level in [91, 92, 94, 96, 97, 96, 99, 100, 10, 93] {
+            alarm.put_data(&level, PlaceholderMap::new()).await.unwrap();
+        }
+        // the recovery starts over
+        assert_eq!(*triggered.lock().unwrap(), ["91", "96", "99", "93"]);
+    }
 }
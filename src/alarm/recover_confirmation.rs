@@ -0,0 +1,56 @@
+use crate::check::ValueStore;
+use crate::config;
+use crate::{Error, Result};
+
+// Confirms the recovery of an alarm by the latest value of another check, e.g. a health endpoint.
+pub struct RecoverConfirmation {
+    check: String,
+    id: String,
+    level: f64,
+    invert: bool,
+    values: ValueStore,
+}
+
+impl RecoverConfirmation {
+    pub fn new(
+        recover_confirmation: &config::RecoverConfirmation,
+        values: ValueStore,
+    ) -> Result<Self> {
+        if recover_confirmation.check.is_empty() {
+            Err(Error(String::from("'check' cannot be empty.")))
+        } else {
+            Ok(Self {
+                check: recover_confirmation.check.clone(),
+                id: recover_confirmation.id.clone(),
+                level: recover_confirmation.level,
+                invert: recover_confirmation.invert,
+                values,
+            })
+        }
+    }
+
+    // The recovery is confirmed if the value of the other check does not exceed the level (or is
+    // not below it if inverted).
+    pub fn confirm(&self) -> Result<bool> {
+        let values = self.values.read().unwrap();
+        let value = values
+            .get(&(self.check.clone(), self.id.clone()))
+            .ok_or_else(|| {
+                Error(format!(
+                    "No value available for check '{}', id '{}'.",
+                    self.check, self.id
+                ))
+            })?;
+        if value.is_stale() {
+            return Err(Error(format!(
+                "Value of check '{}', id '{}' is stale.",
+                self.check, self.id
+            )));
+        }
+        Ok((value.value() > self.level) == self.invert)
+    }
+
+    pub fn log_id(&self) -> String {
+        format!("check '{}', id '{}'", self.check, self.id)
+    }
+}
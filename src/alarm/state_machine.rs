@@ -7,6 +7,8 @@ pub trait StateHandler: Send + Sync + Sized {
 
     fn error(&mut self) -> bool;
     fn bad(&mut self) -> bool;
+    // Like `bad`, but changes to the bad state right away instead of after `cycles` bad cycles.
+    fn bad_now(&mut self) -> bool;
     fn good(&mut self) -> bool;
 }
 
@@ -88,6 +90,55 @@ impl StateMachine {
             })
         }
     }
+
+    // The state changes to bad after `cycles` consecutive bad cycles.
+    fn bad_after(&mut self, cycles: u32) -> bool {
+        let mut trigger = false;
+        self.state = match &self.state {
+            State::Good(good) => {
+                if good.bad_cycles + 1 >= cycles {
+                    trigger = true;
+                    log::warn!("{} changing from good to bad state.", self.log_id);
+                    State::Bad(BadState {
+                        timestamp: std::time::SystemTime::now(),
+                        uuid: uuid::Uuid::new_v4().to_string(),
+                        cycles: 1,
+                        good_cycles: 0,
+                    })
+                } else {
+                    State::Good(GoodState {
+                        timestamp: good.timestamp,
+                        last_alarm: None,
+                        bad_cycles: good.bad_cycles + 1,
+                    })
+                }
+            }
+
+            State::Bad(bad) => {
+                let cycles = if bad.cycles == self.repeat_cycles {
+                    trigger = true;
+                    1
+                } else {
+                    bad.cycles + 1
+                };
+                State::Bad(BadState {
+                    timestamp: bad.timestamp,
+                    uuid: bad.uuid.clone(),
+                    cycles,
+                    good_cycles: 0,
+                })
+            }
+
+            State::Error(error) => {
+                self.state = *error.shadowed_state.clone();
+                let shadowed_trigger = self.bad_after(cycles);
+                trigger = shadowed_trigger;
+                log::warn!("{} changing from error to bad state.", self.log_id);
+                self.state.clone()
+            }
+        };
+        trigger
+    }
 }
 
 impl StateHandler for StateMachine {
@@ -170,51 +221,11 @@ impl StateHandler for StateMachine {
     }
 
     fn bad(&mut self) -> bool {
-        let mut trigger = false;
-        self.state = match &self.state {
-            State::Good(good) => {
-                if good.bad_cycles + 1 == self.cycles {
-                    trigger = true;
-                    log::warn!("{} changing from good to bad state.", self.log_id);
-                    State::Bad(BadState {
-                        timestamp: std::time::SystemTime::now(),
-                        uuid: uuid::Uuid::new_v4().to_string(),
-                        cycles: 1,
-                        good_cycles: 0,
-                    })
-                } else {
-                    State::Good(GoodState {
-                        timestamp: good.timestamp,
-                        last_alarm: None,
-                        bad_cycles: good.bad_cycles + 1,
-                    })
-                }
-            }
-
-            State::Bad(bad) => {
-                let cycles = if bad.cycles == self.repeat_cycles {
-                    trigger = true;
-                    1
-                } else {
-                    bad.cycles + 1
-                };
-                State::Bad(BadState {
-                    timestamp: bad.timestamp,
-                    uuid: bad.uuid.clone(),
-                    cycles,
-                    good_cycles: 0,
-                })
-            }
+        self.bad_after(self.cycles)
+    }
 
-            State::Error(error) => {
-                self.state = *error.shadowed_state.clone();
-                let shadowed_trigger = self.bad();
-                trigger = shadowed_trigger;
-                log::warn!("{} changing from error to bad state.", self.log_id);
-                self.state.clone()
-            }
-        };
-        trigger
+    fn bad_now(&mut self) -> bool {
+        self.bad_after(1)
     }
 
     fn good(&mut self) -> bool {
@@ -285,6 +296,14 @@ mod test {
         assert!(state_machine.bad());
     }
 
+    #[test]
+    fn test_trigger_action_now() {
+        let mut state_machine = StateMachine::new(3, 0, 1, 0, String::from("")).unwrap();
+        assert!(!state_machine.bad());
+        assert!(state_machine.bad_now());
+        assert!(!state_machine.bad_now());
+    }
+
     #[test]
     fn test_trigger_recover_action() {
         let mut state_machine = StateMachine::new(1, 0, 5, 0, String::from("")).unwrap();
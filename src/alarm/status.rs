@@ -0,0 +1,51 @@
+use crate::{Error, PlaceholderMap, Result};
+
+use super::{DataSink, SinkDecision};
+use crate::config;
+
+pub trait StatusItem: Send + Sync + Clone + PartialEq {
+    fn is_ok(&self) -> bool;
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap);
+}
+
+pub struct Status<T> {
+    item: std::marker::PhantomData<T>,
+}
+
+impl<T> TryFrom<&config::Alarm> for Status<T>
+where
+    T: StatusItem,
+{
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, self::Error> {
+        if let config::AlarmType::Status(_) = &alarm.type_ {
+            Ok(Self {
+                item: std::marker::PhantomData,
+            })
+        } else {
+            Err(Error(String::from(
+                "Status alarms do not take any type specific options.",
+            )))
+        }
+    }
+}
+
+impl<T> DataSink for Status<T>
+where
+    T: StatusItem,
+{
+    type Item = T;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        Ok(if data.is_ok() {
+            SinkDecision::Good
+        } else {
+            SinkDecision::Bad
+        })
+    }
+
+    fn add_placeholders(data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        data.add_placeholders(placeholders);
+    }
+}
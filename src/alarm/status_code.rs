@@ -0,0 +1,159 @@
+use crate::{Error, PlaceholderMap, Result};
+
+use super::{DataSink, Level, LevelItem, SinkDecision};
+use crate::config;
+
+pub trait StatusCodeItem: LevelItem {
+    fn status_code(&self) -> u16;
+
+    // e.g. the body of the response didn't contain the expected text
+    fn criteria_failed(&self) -> bool {
+        false
+    }
+}
+
+// Either checks the status code against a list of allowed ones or works like a level alarm (e.g.
+// for the response time), depending on the alarm options.
+pub enum StatusCode<T> {
+    Allowed(Vec<u16>),
+    Level(Level<T>),
+}
+
+impl<T> TryFrom<&config::Alarm> for StatusCode<T>
+where
+    T: StatusCodeItem,
+{
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, self::Error> {
+        match &alarm.type_ {
+            config::AlarmType::StatusCode(status_code) => {
+                if status_code.status_codes.is_empty() {
+                    Err(Error(String::from("'status_codes' cannot be empty.")))
+                } else {
+                    Ok(Self::Allowed(status_code.status_codes.clone()))
+                }
+            }
+            config::AlarmType::Level(_) | config::AlarmType::Anomaly(_) => {
+                Ok(Self::Level(Level::try_from(alarm)?))
+            }
+            config::AlarmType::Status(_) => Err(Error(String::from(
+                "Missing 'status_codes' or 'level' option.",
+            ))),
+        }
+    }
+}
+
+impl<T> DataSink for StatusCode<T>
+where
+    T: StatusCodeItem,
+{
+    type Item = T;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        match self {
+            Self::Allowed(status_codes) => Ok(
+                if status_codes.contains(&data.status_code()) && !data.criteria_failed() {
+                    SinkDecision::Good
+                } else {
+                    SinkDecision::Bad
+                },
+            ),
+            Self::Level(level) => level.put_data(data),
+        }
+    }
+
+    fn add_placeholders(data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        Level::add_placeholders(data, placeholders);
+    }
+
+    fn add_episode_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        if let Self::Level(level) = self {
+            level.add_episode_placeholders(placeholders);
+        }
+    }
+
+    fn reset_episode(&mut self) {
+        if let Self::Level(level) = self {
+            level.reset_episode();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, PartialEq)]
+    struct Response(u16, u64);
+
+    impl std::fmt::Display for Response {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "{}", self.1)
+        }
+    }
+
+    impl LevelItem for Response {
+        fn level(&self) -> f64 {
+            self.1 as f64
+        }
+    }
+
+    impl StatusCodeItem for Response {
+        fn status_code(&self) -> u16 {
+            self.0
+        }
+    }
+
+    fn sink(alarm: &str) -> Result<StatusCode<Response>> {
+        let config = config::Config::try_from(
+            format!(
+                r#"
+                [[checks]]
+                name = "Test"
+                type = "HttpStatus"
+                targets = [{{url = "http://example.com"}}]
+                [[checks.alarms]]
+                name = "Test"
+                action = "Test"
+                {}
+                "#,
+                alarm
+            )
+            .as_str(),
+        )
+        .unwrap();
+        StatusCode::try_from(&config.checks[0].alarms[0])
+    }
+
+    #[test]
+    fn test_allowed() {
+        let mut status_code = sink("status_codes = [200, 204]").unwrap();
+        assert!(matches!(
+            status_code.put_data(&Response(204, 5000)).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            status_code.put_data(&Response(503, 10)).unwrap(),
+            SinkDecision::Bad
+        ));
+        assert!(sink("status_codes = []").is_err());
+    }
+
+    #[test]
+    fn test_level() {
+        let mut status_code = sink("level = 100").unwrap();
+        assert!(matches!(
+            status_code.put_data(&Response(503, 10)).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            status_code.put_data(&Response(200, 150)).unwrap(),
+            SinkDecision::Bad
+        ));
+        let mut placeholders = PlaceholderMap::new();
+        status_code.add_episode_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("episode_max").unwrap(), "150");
+        assert!(sink("").is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use crate::action;
+use crate::config;
+use crate::ActionMap;
+use crate::{Error, PlaceholderMap, Result};
+
+pub struct Canary {
+    name: String,
+    interval: u32,
+    placeholders: PlaceholderMap,
+    action: std::sync::Arc<dyn action::Action>,
+}
+
+impl Canary {
+    fn new(
+        name: String,
+        interval: u32,
+        placeholders: PlaceholderMap,
+        action: std::sync::Arc<dyn action::Action>,
+    ) -> Result<Self> {
+        if name.is_empty() {
+            Err(Error(String::from("'name' cannot be empty.")))
+        } else if interval == 0 {
+            Err(Error(String::from("'interval' cannot be 0.")))
+        } else {
+            Ok(Self {
+                name,
+                interval,
+                placeholders,
+                action,
+            })
+        }
+    }
+
+    // Mimic the placeholders of a real alarm so the action's templates render like they would
+    // during an incident.
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("canary_name"), self.name.clone());
+        placeholders.insert(String::from("check_name"), String::from("Canary"));
+        placeholders.insert(String::from("alarm_name"), self.name.clone());
+        placeholders.insert(String::from("alarm_id"), String::from("canary"));
+        placeholders.insert(String::from("alarm_state"), String::from("Bad"));
+        placeholders.insert(String::from("alarm_uuid"), uuid::Uuid::new_v4().to_string());
+        placeholders.insert(
+            String::from("alarm_timestamp"),
+            crate::iso8601(std::time::SystemTime::now()),
+        );
+        crate::merge_placeholders(placeholders, &self.placeholders);
+    }
+
+    pub async fn trigger(&mut self) {
+        let mut placeholders = crate::global_placeholders();
+        self.add_placeholders(&mut placeholders);
+        log::info!("Canary '{}' triggered.", self.name);
+        if let Err(err) = self.action.trigger(placeholders).await {
+            log::error!(
+                "Canary '{}' failed, the notification path might be broken: {}",
+                self.name,
+                err
+            );
+        }
+    }
+
+    pub fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.interval.into())
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+}
+
+pub fn from_canary_config(canary_config: &config::Canary, actions: &ActionMap) -> Result<Canary> {
+    Canary::new(
+        canary_config.name.clone(),
+        canary_config.interval,
+        canary_config.placeholders.clone(),
+        action::get_action(&canary_config.action, actions)?,
+    )
+    .map_err(|x| {
+        Error(format!(
+            "Failed to create canary '{}' from config: {}",
+            canary_config.name, x
+        ))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use mockall::predicate::*;
+
+    #[test]
+    fn test_canary_validation() {
+        let action = std::sync::Arc::new(action::MockAction::new());
+        assert!(Canary::new(String::from(""), 1, PlaceholderMap::new(), action.clone()).is_err());
+        assert!(Canary::new(String::from("Name"), 0, PlaceholderMap::new(), action).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_trigger() {
+        crate::uptime::init().unwrap();
+        let mut mock_action = action::MockAction::new();
+        mock_action
+            .expect_trigger()
+            .once()
+            .with(function(|placeholders: &PlaceholderMap| {
+                assert_eq!(placeholders.get("canary_name").unwrap(), "Name");
+                assert_eq!(placeholders.get("check_name").unwrap(), "Canary");
+                assert_eq!(placeholders.get("alarm_name").unwrap(), "Name");
+                assert_eq!(placeholders.get("alarm_id").unwrap(), "canary");
+                assert_eq!(placeholders.get("alarm_state").unwrap(), "Bad");
+                assert_eq!(placeholders.get("Hello").unwrap(), "World");
+                uuid::Uuid::parse_str(placeholders.get("alarm_uuid").unwrap()).unwrap();
+                true
+            }))
+            .returning(|_| Ok(()));
+        let mut canary = Canary::new(
+            String::from("Name"),
+            60,
+            PlaceholderMap::from([(String::from("Hello"), String::from("World"))]),
+            std::sync::Arc::new(mock_action),
+        )
+        .unwrap();
+        canary.trigger().await;
+        assert_eq!(canary.interval(), std::time::Duration::from_secs(60));
+    }
+}
@@ -0,0 +1,190 @@
+use crate::config;
+use crate::{Error, Result};
+use tokio::io::AsyncReadExt;
+
+// Bounds the memory used for the output of the commands and the HTTP response bodies read by the
+// checks, so a runaway command or endpoint cannot exhaust the memory.
+#[derive(Clone)]
+pub struct Capture {
+    max_size: usize,
+    policy: config::CapturePolicy,
+}
+
+impl Default for Capture {
+    fn default() -> Self {
+        Self {
+            max_size: 16 * 1024 * 1024,
+            policy: config::CapturePolicy::Truncate,
+        }
+    }
+}
+
+impl Capture {
+    fn exceeded(&self, what: &str) -> String {
+        format!(
+            "{} exceeds the capture limit of {} bytes.",
+            what, self.max_size
+        )
+    }
+
+    // Anything beyond the limit is read but discarded when truncating, so the writer doesn't
+    // block.
+    async fn read<R>(&self, mut reader: R, what: &str) -> std::io::Result<Vec<u8>>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        let mut res = Vec::new();
+        let mut buffer = [0; 8192];
+        loop {
+            let n = reader.read(&mut buffer).await?;
+            if n == 0 {
+                return Ok(res);
+            }
+            let remaining = self.max_size - res.len();
+            if n > remaining && self.policy == config::CapturePolicy::Error {
+                return Err(std::io::Error::other(self.exceeded(what)));
+            }
+            res.extend_from_slice(&buffer[..n.min(remaining)]);
+        }
+    }
+
+    // Drop-in replacement for `Command::output()`. The command is killed if its output exceeds
+    // the limit and the policy is `Error`.
+    pub async fn output(
+        &self,
+        command: &mut tokio::process::Command,
+    ) -> std::io::Result<std::process::Output> {
+        let mut child = command
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdout = child.stdout.take().unwrap();
+        let stderr = child.stderr.take().unwrap();
+        let (stdout, stderr) = tokio::try_join!(
+            self.read(stdout, "Output"),
+            self.read(stderr, "Error output")
+        )?;
+        Ok(std::process::Output {
+            status: child.wait().await?,
+            stdout,
+            stderr,
+        })
+    }
+
+    pub async fn body(&self, mut response: reqwest::Response) -> Result<Vec<u8>> {
+        let what = "Response body";
+        if self.policy == config::CapturePolicy::Error
+            && response
+                .content_length()
+                .is_some_and(|x| x > self.max_size as u64)
+        {
+            return Err(Error(self.exceeded(what)));
+        }
+        let mut res = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|x| Error(x.to_string()))? {
+            let remaining = self.max_size - res.len();
+            if chunk.len() > remaining && self.policy == config::CapturePolicy::Error {
+                return Err(Error(self.exceeded(what)));
+            }
+            res.extend_from_slice(&chunk[..chunk.len().min(remaining)]);
+        }
+        Ok(res)
+    }
+
+    pub async fn text(&self, response: reqwest::Response) -> Result<String> {
+        Ok(String::from_utf8_lossy(&self.body(response).await?).into_owned())
+    }
+}
+
+// The checks get a copy of the `capture` section of the config.
+pub fn from_config(capture: Option<&config::Capture>) -> Result<Capture> {
+    capture.map_or_else(|| Ok(Capture::default()), Capture::try_from)
+}
+
+impl TryFrom<&config::Capture> for Capture {
+    type Error = Error;
+
+    fn try_from(capture: &config::Capture) -> std::result::Result<Self, self::Error> {
+        if capture.max_size == 0 {
+            Err(Error(String::from("'max_size' cannot be 0.")))
+        } else {
+            Ok(Self {
+                max_size: capture.max_size as usize,
+                policy: capture.policy,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::mock_server;
+
+    fn capture(policy: config::CapturePolicy) -> Capture {
+        Capture {
+            max_size: 1000,
+            policy,
+        }
+    }
+
+    async fn run(capture: &Capture, script: &str) -> std::io::Result<std::process::Output> {
+        capture
+            .output(tokio::process::Command::new("sh").args(["-c", script]))
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_output() {
+        let truncate = capture(config::CapturePolicy::Truncate);
+        let error = capture(config::CapturePolicy::Error);
+        let script = "head -c 100000 /dev/zero; echo failed >&2; exit 3";
+        let output = run(&truncate, script).await.unwrap();
+        assert_eq!(output.stdout.len(), 1000);
+        assert_eq!(output.stderr, b"failed\n");
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(
+            run(&error, script).await.unwrap_err().to_string(),
+            "Output exceeds the capture limit of 1000 bytes."
+        );
+        assert_eq!(
+            run(&error, "head -c 5000 /dev/zero >&2")
+                .await
+                .unwrap_err()
+                .to_string(),
+            "Error output exceeds the capture limit of 1000 bytes."
+        );
+        let output = run(&error, "echo ok").await.unwrap();
+        assert_eq!(output.stdout, b"ok\n");
+    }
+
+    #[tokio::test]
+    async fn test_body() {
+        let url = mock_server(|_| (200, "x".repeat(100000))).await;
+        let get = || async { reqwest::get(&url).await.unwrap() };
+        let body = capture(config::CapturePolicy::Truncate)
+            .body(get().await)
+            .await
+            .unwrap();
+        assert_eq!(body.len(), 1000);
+        assert_eq!(
+            capture(config::CapturePolicy::Error)
+                .body(get().await)
+                .await
+                .unwrap_err()
+                .to_string(),
+            "Response body exceeds the capture limit of 1000 bytes."
+        );
+        let url = mock_server(|_| (200, String::from("ok"))).await;
+        let response = reqwest::get(&url).await.unwrap();
+        assert_eq!(
+            capture(config::CapturePolicy::Error)
+                .text(response)
+                .await
+                .unwrap(),
+            "ok"
+        );
+    }
+}
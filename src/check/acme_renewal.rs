@@ -0,0 +1,308 @@
+use super::DataSource;
+use crate::alarm::StatusItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct RenewalState {
+    expiry: Option<chrono::DateTime<chrono::Utc>>,
+    next_renewal: Option<chrono::DateTime<chrono::Utc>>,
+    authenticator: String,
+    // Why the renewal is not working, empty if it is.
+    problem: String,
+}
+
+impl StatusItem for RenewalState {
+    fn is_ok(&self) -> bool {
+        self.problem.is_empty()
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        let format = |x: Option<chrono::DateTime<chrono::Utc>>| {
+            x.map(|x| crate::iso8601(std::time::SystemTime::from(x)))
+                .unwrap_or_default()
+        };
+        placeholders.insert(String::from("expiry"), format(self.expiry));
+        placeholders.insert(String::from("next_renewal"), format(self.next_renewal));
+        placeholders.insert(String::from("authenticator"), self.authenticator.clone());
+        placeholders.insert(String::from("renewal_problem"), self.problem.clone());
+    }
+}
+
+// The parts of certbot's renewal configuration (/etc/letsencrypt/renewal/<domain>.conf) that
+// are relevant for the renewal.
+#[derive(PartialEq, Debug)]
+struct RenewalConfig {
+    cert: std::path::PathBuf,
+    renew_before_expiry: chrono::Duration,
+    // e.g. "nginx" or "webroot", only set if there are renewal parameters at all
+    authenticator: Option<String>,
+}
+
+pub struct AcmeRenewal {
+    id: Vec<String>,
+    renewal_dir: std::path::PathBuf,
+    grace_days: u32,
+}
+
+impl AcmeRenewal {
+    // e.g. "30 days" or "2 weeks"
+    fn parse_duration(text: &str) -> Result<chrono::Duration> {
+        let error = || Error(format!("Invalid renew_before_expiry '{}'.", text));
+        let (number, unit) = text.trim().split_once(' ').ok_or_else(error)?;
+        let number: i64 = number.parse().map_err(|_| error())?;
+        match unit.trim().trim_end_matches('s') {
+            "hour" => Ok(chrono::Duration::hours(number)),
+            "day" => Ok(chrono::Duration::days(number)),
+            "week" => Ok(chrono::Duration::weeks(number)),
+            "month" => Ok(chrono::Duration::days(number * 30)),
+            _ => Err(error()),
+        }
+    }
+
+    fn parse_config(content: &str) -> Result<RenewalConfig> {
+        let mut cert = None;
+        // certbot's default
+        let mut renew_before_expiry = chrono::Duration::days(30);
+        let mut authenticator = None;
+        let mut section = "";
+        for line in content.lines().map(str::trim) {
+            if let Some(name) = line.strip_prefix('[').and_then(|x| x.strip_suffix(']')) {
+                section = name;
+                if section == "renewalparams" {
+                    authenticator.get_or_insert_with(String::new);
+                }
+                continue;
+            }
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) if !line.starts_with('#') => (key.trim(), value.trim()),
+                _ => continue,
+            };
+            match (section, key) {
+                ("", "cert") => cert = Some(std::path::PathBuf::from(value)),
+                ("", "renew_before_expiry") => renew_before_expiry = Self::parse_duration(value)?,
+                ("renewalparams", "authenticator") => authenticator = Some(String::from(value)),
+                _ => {}
+            }
+        }
+        Ok(RenewalConfig {
+            cert: cert.ok_or_else(|| Error(String::from("'cert' not found.")))?,
+            renew_before_expiry,
+            authenticator,
+        })
+    }
+
+    // certbot usually tries to renew twice a day, so the renewal is only considered overdue
+    // after the grace period.
+    fn renewal_state(
+        &self,
+        config: &RenewalConfig,
+        expiry: chrono::DateTime<chrono::Utc>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> RenewalState {
+        let next_renewal = expiry - config.renew_before_expiry;
+        let authenticator = config.authenticator.clone().unwrap_or_default();
+        let problem = if config.authenticator.is_none() {
+            String::from("Renewal parameters missing")
+        } else if authenticator.is_empty() {
+            String::from("No authenticator configured")
+        } else if expiry <= now {
+            String::from("Certificate expired")
+        } else if now - next_renewal > chrono::Duration::days(self.grace_days.into()) {
+            format!(
+                "Renewal overdue since {}",
+                crate::iso8601(std::time::SystemTime::from(next_renewal))
+            )
+        } else {
+            String::new()
+        };
+        RenewalState {
+            expiry: Some(expiry),
+            next_renewal: Some(next_renewal),
+            authenticator,
+            problem,
+        }
+    }
+
+    async fn read_state(&self, domain: &str) -> Result<RenewalState> {
+        let path = self.renewal_dir.join(format!("{}.conf", domain));
+        let content = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(RenewalState {
+                    expiry: None,
+                    next_renewal: None,
+                    authenticator: String::new(),
+                    problem: format!("No renewal configuration {}", path.display()),
+                })
+            }
+            Err(err) => {
+                return Err(Error(format!(
+                    "Could not read from {}: {}",
+                    path.display(),
+                    err
+                )))
+            }
+        };
+        let config = Self::parse_config(&content)
+            .map_err(|x| Error(format!("Could not parse {}: {}", path.display(), x)))?;
+        let certificate = tokio::fs::read(&config.cert).await.map_err(|x| {
+            Error(format!(
+                "Could not read from {}: {}",
+                config.cert.display(),
+                x
+            ))
+        })?;
+        let certificate =
+            super::local_cert_expiry::LocalCertExpiry::parse_certificate(&certificate)
+                .map_err(|x| Error(format!("Could not parse {}: {}", config.cert.display(), x)))?;
+        Ok(self.renewal_state(&config, certificate.expiry, chrono::Utc::now()))
+    }
+}
+
+impl TryFrom<&config::Check> for AcmeRenewal {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::AcmeRenewal(acme_renewal) = &check.type_ {
+            if acme_renewal.domains.iter().any(|x| x.is_empty()) {
+                Err(Error(String::from("'domains' cannot contain empty names.")))
+            } else {
+                Ok(Self {
+                    id: acme_renewal.domains.clone(),
+                    renewal_dir: acme_renewal.renewal_dir.clone(),
+                    grace_days: acme_renewal.grace_days,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for AcmeRenewal {
+    type Item = RenewalState;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mut res = Vec::new();
+        for domain in self.id.iter() {
+            res.push(self.read_state(domain).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        match data.next_renewal {
+            Some(next_renewal) => format!(
+                "next renewal {}",
+                crate::iso8601(std::time::SystemTime::from(next_renewal))
+            ),
+            None => String::from("no renewal configuration"),
+        }
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    const RENEWAL_CONFIG: &str = r#"# renew_before_expiry = 30 days
+version = 2.9.0
+archive_dir = /etc/letsencrypt/archive/example.com
+cert = /etc/letsencrypt/live/example.com/cert.pem
+privkey = /etc/letsencrypt/live/example.com/privkey.pem
+chain = /etc/letsencrypt/live/example.com/chain.pem
+fullchain = /etc/letsencrypt/live/example.com/fullchain.pem
+
+# Options used in the renewal process
+[renewalparams]
+account = 0123456789abcdef0123456789abcdef
+authenticator = nginx
+installer = nginx
+server = https://acme-v02.api.letsencrypt.org/directory
+key_type = ecdsa
+"#;
+
+    fn utc(text: &str) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::<chrono::Utc>::from_str(text).unwrap()
+    }
+
+    fn acme_renewal(renewal_dir: std::path::PathBuf) -> AcmeRenewal {
+        AcmeRenewal {
+            id: vec![String::from("example.com")],
+            renewal_dir,
+            grace_days: 2,
+        }
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = AcmeRenewal::parse_config(RENEWAL_CONFIG).unwrap();
+        assert_eq!(
+            config,
+            RenewalConfig {
+                cert: std::path::PathBuf::from("/etc/letsencrypt/live/example.com/cert.pem"),
+                renew_before_expiry: chrono::Duration::days(30),
+                authenticator: Some(String::from("nginx")),
+            }
+        );
+        let config = AcmeRenewal::parse_config(&format!(
+            "renew_before_expiry = 2 weeks\n{}",
+            RENEWAL_CONFIG
+        ))
+        .unwrap();
+        assert_eq!(config.renew_before_expiry, chrono::Duration::days(14));
+        // without the [renewalparams] section
+        let config = AcmeRenewal::parse_config(RENEWAL_CONFIG.split('[').next().unwrap()).unwrap();
+        assert_eq!(config.authenticator, None);
+        assert!(AcmeRenewal::parse_config("version = 2.9.0\n").is_err());
+        assert!(AcmeRenewal::parse_config("renew_before_expiry = soon\n").is_err());
+    }
+
+    #[test]
+    fn test_renewal_state() {
+        let acme_renewal = acme_renewal(std::path::PathBuf::new());
+        let config = AcmeRenewal::parse_config(RENEWAL_CONFIG).unwrap();
+        let expiry = utc("2030-01-31T00:00:00Z");
+        let state = acme_renewal.renewal_state(&config, expiry, utc("2029-12-01T00:00:00Z"));
+        assert!(state.is_ok());
+        assert_eq!(state.next_renewal, Some(utc("2030-01-01T00:00:00Z")));
+        // certbot had a day to renew
+        assert!(acme_renewal
+            .renewal_state(&config, expiry, utc("2030-01-02T00:00:00Z"))
+            .is_ok());
+        let state = acme_renewal.renewal_state(&config, expiry, utc("2030-01-10T00:00:00Z"));
+        assert!(!state.is_ok());
+        let mut placeholders = PlaceholderMap::new();
+        state.add_placeholders(&mut placeholders);
+        assert_eq!(
+            placeholders.get("renewal_problem").unwrap(),
+            "Renewal overdue since 2030-01-01T00:00:00Z"
+        );
+        assert_eq!(placeholders.get("authenticator").unwrap(), "nginx");
+        assert_eq!(placeholders.get("expiry").unwrap(), "2030-01-31T00:00:00Z");
+        let config = AcmeRenewal::parse_config(RENEWAL_CONFIG.split('[').next().unwrap()).unwrap();
+        assert!(!acme_renewal
+            .renewal_state(&config, expiry, utc("2029-12-01T00:00:00Z"))
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_missing_config() {
+        let renewal_dir = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&renewal_dir).unwrap();
+        let data = acme_renewal(renewal_dir.clone()).get_data().await;
+        std::fs::remove_dir_all(&renewal_dir).unwrap();
+        let state = data.unwrap().remove(0).unwrap();
+        assert!(!state.is_ok());
+        assert!(state.problem.starts_with("No renewal configuration "));
+        assert_eq!(AcmeRenewal::format_data(&state), "no renewal configuration");
+    }
+}
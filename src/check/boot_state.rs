@@ -0,0 +1,246 @@
+use super::DataSource;
+use crate::alarm::StatusItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SystemState {
+    // e.g. "running", "degraded" or "starting"
+    state: String,
+    jobs: u32,
+    // Why the state is bad, empty if it's not.
+    reason: String,
+}
+
+impl StatusItem for SystemState {
+    fn is_ok(&self) -> bool {
+        self.reason.is_empty()
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("system_state"), self.state.clone());
+        placeholders.insert(String::from("pending_jobs"), self.jobs.to_string());
+        placeholders.insert(String::from("degraded_reason"), self.reason.clone());
+    }
+}
+
+pub struct BootState {
+    id: Vec<String>,
+    startup_timeout: u64,
+    uptime_path: std::path::PathBuf,
+    proc_path: std::path::PathBuf,
+    capture: crate::capture::Capture,
+}
+
+impl BootState {
+    async fn systemctl(&self, args: &[&str]) -> Result<String> {
+        let output = self
+            .capture
+            .output(tokio::process::Command::new("systemctl").args(args))
+            .await
+            .map_err(|x| Error(format!("Failed to run systemctl: {}", x)))?;
+        if !output.status.success() {
+            return Err(Error(format!(
+                "systemctl failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        String::from_utf8(output.stdout)
+            .map_err(|x| Error(format!("Output of systemctl is not valid UTF-8: {}", x)))
+    }
+
+    // e.g. "SystemState=running\nNJobs=0"
+    fn parse_manager(output: &str) -> Result<(String, u32)> {
+        let get = |name: &str| -> Result<&str> {
+            output
+                .lines()
+                .find_map(|x| x.strip_prefix(name)?.strip_prefix('='))
+                .ok_or_else(|| Error(format!("{} not found.", name)))
+        };
+        let jobs = get("NJobs")?;
+        Ok((
+            String::from(get("SystemState")?),
+            jobs.parse()
+                .map_err(|x| Error(format!("Invalid job count '{}': {}", jobs, x)))?,
+        ))
+    }
+
+    // The system is only considered stuck if it's still starting after the startup timeout.
+    fn system_state(
+        &self,
+        state: String,
+        jobs: u32,
+        failed_units: &[String],
+        uptime: u64,
+    ) -> SystemState {
+        let reason = match state.as_str() {
+            "degraded" if failed_units.is_empty() => String::from("Degraded"),
+            "degraded" => format!("Failed units: {}", failed_units.join(",")),
+            "maintenance" => String::from("Maintenance mode"),
+            "initializing" | "starting" if uptime > self.startup_timeout => format!(
+                "Still {} after {} seconds with {} pending job(s)",
+                state, uptime, jobs
+            ),
+            _ => String::new(),
+        };
+        SystemState {
+            state,
+            jobs,
+            reason,
+        }
+    }
+
+    async fn read_uptime(&self) -> Result<u64> {
+        let content = tokio::fs::read_to_string(&self.uptime_path)
+            .await
+            .map_err(|x| {
+                super::proc_error(
+                    &self.proc_path,
+                    format!("Could not read from {}: {}", self.uptime_path.display(), x),
+                )
+            })?;
+        let uptime: f64 = crate::get_number(
+            &format!("Could not read uptime from {}", self.uptime_path.display()),
+            &content,
+            0,
+        )?;
+        Ok(uptime as u64)
+    }
+}
+
+impl TryFrom<&config::Check> for BootState {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::BootState(boot_state) = &check.type_ {
+            Ok(Self {
+                id: vec![String::from("System")],
+                startup_timeout: boot_state.startup_timeout.into(),
+                uptime_path: check.proc_path.join("uptime"),
+                proc_path: check.proc_path.clone(),
+                capture: crate::capture::from_config(check.capture.as_ref())?,
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for BootState {
+    type Item = SystemState;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let output = self
+            .systemctl(&["show", "--property=SystemState,NJobs"])
+            .await?;
+        let (state, jobs) = Self::parse_manager(&output)?;
+        let failed_units = if state == "degraded" {
+            let output = self
+                .systemctl(&["list-units", "--state=failed", "--no-legend", "--plain"])
+                .await?;
+            super::systemd_failed_units::SystemdFailedUnits::parse_units(&output).units
+        } else {
+            Vec::new()
+        };
+        let uptime = self.read_uptime().await?;
+        Ok(vec![Ok(self.system_state(
+            state,
+            jobs,
+            &failed_units,
+            uptime,
+        ))])
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("system state {}, {} pending job(s)", data.state, data.jobs)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.jobs as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn boot_state() -> BootState {
+        BootState {
+            id: vec![String::from("System")],
+            startup_timeout: 600,
+            uptime_path: std::path::PathBuf::from("/proc/uptime"),
+            proc_path: std::path::PathBuf::from("/proc"),
+            capture: crate::capture::Capture::default(),
+        }
+    }
+
+    fn placeholders(system_state: &SystemState) -> PlaceholderMap {
+        let mut placeholders = PlaceholderMap::new();
+        system_state.add_placeholders(&mut placeholders);
+        placeholders
+    }
+
+    #[test]
+    fn test_parse_manager() {
+        assert_eq!(
+            BootState::parse_manager("NJobs=3\nSystemState=starting\n").unwrap(),
+            (String::from("starting"), 3)
+        );
+        assert!(BootState::parse_manager("SystemState=running\n").is_err());
+        assert!(BootState::parse_manager("SystemState=running\nNJobs=x\n").is_err());
+    }
+
+    #[test]
+    fn test_running() {
+        let (state, jobs) = BootState::parse_manager("SystemState=running\nNJobs=0").unwrap();
+        let system_state = boot_state().system_state(state, jobs, &[], 86400);
+        assert!(system_state.is_ok());
+        assert_eq!(
+            placeholders(&system_state).get("degraded_reason").unwrap(),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_degraded() {
+        let (state, jobs) = BootState::parse_manager("SystemState=degraded\nNJobs=0").unwrap();
+        let system_state = boot_state().system_state(
+            state,
+            jobs,
+            &[String::from("foo.service"), String::from("bar.mount")],
+            86400,
+        );
+        assert!(!system_state.is_ok());
+        let placeholders = placeholders(&system_state);
+        assert_eq!(placeholders.get("system_state").unwrap(), "degraded");
+        assert_eq!(
+            placeholders.get("degraded_reason").unwrap(),
+            "Failed units: foo.service,bar.mount"
+        );
+    }
+
+    #[test]
+    fn test_starting() {
+        let boot_state = boot_state();
+        let starting = |uptime: u64| {
+            let (state, jobs) = BootState::parse_manager("SystemState=starting\nNJobs=12").unwrap();
+            boot_state.system_state(state, jobs, &[], uptime)
+        };
+        // still booting
+        assert!(starting(30).is_ok());
+        let stuck = starting(900);
+        assert!(!stuck.is_ok());
+        let placeholders = placeholders(&stuck);
+        assert_eq!(placeholders.get("pending_jobs").unwrap(), "12");
+        assert_eq!(
+            placeholders.get("degraded_reason").unwrap(),
+            "Still starting after 900 seconds with 12 pending job(s)"
+        );
+    }
+}
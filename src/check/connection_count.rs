@@ -0,0 +1,193 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+pub struct ConnectionCount {
+    id: Vec<String>,
+    ports: Vec<u16>,
+    states: Vec<u8>,
+    proc_path: std::path::PathBuf,
+}
+
+impl ConnectionCount {
+    fn state_code(state: config::TcpState) -> u8 {
+        // see include/net/tcp_states.h in the Linux kernel sources
+        match state {
+            config::TcpState::Established => 0x01,
+            config::TcpState::SynSent => 0x02,
+            config::TcpState::SynRecv => 0x03,
+            config::TcpState::FinWait1 => 0x04,
+            config::TcpState::FinWait2 => 0x05,
+            config::TcpState::TimeWait => 0x06,
+            config::TcpState::Close => 0x07,
+            config::TcpState::CloseWait => 0x08,
+            config::TcpState::LastAck => 0x09,
+            config::TcpState::Listen => 0x0A,
+            config::TcpState::Closing => 0x0B,
+        }
+    }
+
+    fn count_connections(&self, content: &str, counts: &mut [u64]) -> Result<()> {
+        // skip header line
+        for line in content.lines().skip(1) {
+            let mut columns = line.split_whitespace();
+            let local_address = columns
+                .nth(1)
+                .ok_or_else(|| Error(String::from("Local address column not found.")))?;
+            let state = columns
+                .nth(1)
+                .ok_or_else(|| Error(String::from("State column not found.")))?;
+            let port = local_address
+                .rsplit(':')
+                .next()
+                .ok_or_else(|| Error(format!("Invalid local address: {}", local_address)))?;
+            let port = u16::from_str_radix(port, 16)
+                .map_err(|x| Error(format!("Invalid local port '{}': {}", port, x)))?;
+            let state = u8::from_str_radix(state, 16)
+                .map_err(|x| Error(format!("Invalid state '{}': {}", state, x)))?;
+            if !self.states.contains(&state) {
+                continue;
+            }
+            for (count, _) in counts
+                .iter_mut()
+                .zip(self.ports.iter())
+                .filter(|(_, x)| **x == port)
+            {
+                *count += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&config::Check> for ConnectionCount {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::ConnectionCount(connection_count) = &check.type_ {
+            if connection_count.ports.contains(&0) {
+                Err(Error(String::from("'ports' cannot contain 0.")))
+            } else if connection_count.states.is_empty() {
+                Err(Error(String::from("'states' cannot be empty.")))
+            } else {
+                Ok(Self {
+                    id: connection_count
+                        .ports
+                        .iter()
+                        .map(|x| x.to_string())
+                        .collect(),
+                    ports: connection_count.ports.clone(),
+                    states: connection_count
+                        .states
+                        .iter()
+                        .map(|x| Self::state_code(*x))
+                        .collect(),
+                    proc_path: check.proc_path.clone(),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for ConnectionCount {
+    type Item = u64;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mut counts = vec![0; self.ports.len()];
+        for (i, path) in ["net/tcp", "net/tcp6"]
+            .iter()
+            .map(|x| self.proc_path.join(x))
+            .enumerate()
+        {
+            let content = match tokio::fs::read_to_string(&path).await {
+                Ok(content) => content,
+                // tcp6 is missing if IPv6 is disabled
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound && i == 1 => continue,
+                Err(err) => {
+                    return Err(super::proc_error(
+                        &self.proc_path,
+                        format!("Could not read from {}: {}", path.display(), err),
+                    ))
+                }
+            };
+            self.count_connections(&content, &mut counts)
+                .map_err(|x| Error(format!("Could not parse {}: {}", path.display(), x)))?;
+        }
+        Ok(counts.into_iter().map(Ok).collect())
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("connection count {}", data)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PROC_NET_TCP: &str = r#"  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 21371 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 31337 1 0000000000000000 100 0 0 10 0
+   2: 0A00000A:0016 0200000A:D4C2 01 00000000:00000000 02:0009B5A3 00000000     0        0 41234 2 0000000000000000 20 4 29 10 -1
+   3: 0A00000A:0016 0300000A:D4C4 01 00000000:00000000 02:0009B5A3 00000000     0        0 41235 2 0000000000000000 20 4 29 10 -1
+   4: 0100007F:1F90 0100007F:9C40 06 00000000:00000000 03:00000F5D 00000000     0        0 0 3 0000000000000000
+   5: 0100007F:1F90 0100007F:9C42 01 00000000:00000000 02:0009B5A3 00000000  1000        0 41236 2 0000000000000000 20 4 29 10 -1
+"#;
+
+    fn connection_count(ports: Vec<u16>, states: Vec<config::TcpState>) -> ConnectionCount {
+        ConnectionCount {
+            id: ports.iter().map(|x| x.to_string()).collect(),
+            ports,
+            states: states
+                .into_iter()
+                .map(ConnectionCount::state_code)
+                .collect(),
+            proc_path: std::path::PathBuf::from("/proc"),
+        }
+    }
+
+    #[test]
+    fn test_count_established() {
+        let connection_count =
+            connection_count(vec![22, 8080, 443], vec![config::TcpState::Established]);
+        let mut counts = vec![0; 3];
+        connection_count
+            .count_connections(PROC_NET_TCP, &mut counts)
+            .unwrap();
+        assert_eq!(counts, vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_count_multiple_states() {
+        let connection_count = connection_count(
+            vec![8080],
+            vec![config::TcpState::Established, config::TcpState::TimeWait],
+        );
+        let mut counts = vec![0; 1];
+        connection_count
+            .count_connections(PROC_NET_TCP, &mut counts)
+            .unwrap();
+        assert_eq!(counts, vec![2]);
+    }
+
+    #[test]
+    fn test_count_invalid() {
+        let connection_count = connection_count(vec![22], vec![config::TcpState::Established]);
+        let mut counts = vec![0; 1];
+        assert!(connection_count
+            .count_connections("header\n   0: 00000000:XYZ 00000000:0000 01", &mut counts)
+            .is_err());
+    }
+}
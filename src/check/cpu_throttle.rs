@@ -0,0 +1,341 @@
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct CpuSample {
+    // current and rated maximum frequency in kHz
+    frequency: u64,
+    max_frequency: u64,
+    // cumulative thermal throttling events (core and package), if the platform reports them
+    throttle_count: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Throttling {
+    // percentage below the rated maximum frequency, 100 if thermally throttled since last cycle
+    level: u8,
+    frequency: u64,
+    max_frequency: u64,
+    throttle_events: Option<u64>,
+}
+
+impl std::fmt::Display for Throttling {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.level)
+    }
+}
+
+impl LevelItem for Throttling {
+    fn level(&self) -> f64 {
+        self.level as f64
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else if level > 100.0 {
+            Err(Error(String::from("'level' cannot be greater than 100.")))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(
+            String::from("frequency"),
+            (self.frequency / 1000).to_string(),
+        );
+        placeholders.insert(
+            String::from("max_frequency"),
+            (self.max_frequency / 1000).to_string(),
+        );
+        if let Some(throttle_events) = self.throttle_events {
+            placeholders.insert(String::from("throttle_events"), throttle_events.to_string());
+        }
+    }
+}
+
+pub struct CpuThrottle {
+    id: Vec<String>,
+    // None for the total of all CPUs
+    cores: Vec<Option<u32>>,
+    cpu_path: std::path::PathBuf,
+    sys_path: std::path::PathBuf,
+    samples: std::sync::Mutex<HashMap<String, u64>>,
+}
+
+impl CpuThrottle {
+    async fn read_number(path: &std::path::Path) -> Result<u64> {
+        tokio::fs::read_to_string(path)
+            .await
+            .map_err(|x| Error(format!("Could not read from {}: {}", path.display(), x)))?
+            .trim()
+            .parse()
+            .map_err(|x| Error(format!("Could not parse {}: {}", path.display(), x)))
+    }
+
+    async fn read_cpu(&self, core: u32) -> Result<CpuSample> {
+        let path = self.cpu_path.join(format!("cpu{}", core));
+        let cpufreq = path.join("cpufreq");
+        if !cpufreq.is_dir() {
+            return Err(Error(format!(
+                "CPU frequency scaling (cpufreq) is not available for cpu{}.",
+                core
+            )));
+        }
+        let mut throttle_count = None;
+        for file in ["core_throttle_count", "package_throttle_count"] {
+            if let Ok(count) = Self::read_number(&path.join("thermal_throttle").join(file)).await {
+                throttle_count = Some(throttle_count.unwrap_or(0) + count);
+            }
+        }
+        Ok(CpuSample {
+            frequency: Self::read_number(&cpufreq.join("scaling_cur_freq")).await?,
+            max_frequency: Self::read_number(&cpufreq.join("cpuinfo_max_freq")).await?,
+            throttle_count,
+        })
+    }
+
+    // CPUs without cpufreq (e.g. offline ones) don't count towards the total.
+    async fn read_total(&self) -> Result<Vec<CpuSample>> {
+        let mut entries = tokio::fs::read_dir(&self.cpu_path).await.map_err(|x| {
+            super::sys_error(
+                &self.sys_path,
+                format!(
+                    "Could not read directory {}: {}",
+                    self.cpu_path.display(),
+                    x
+                ),
+            )
+        })?;
+        let mut res = Vec::new();
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let core = match entry
+                .file_name()
+                .to_str()
+                .and_then(|x| x.strip_prefix("cpu"))
+                .and_then(|x| x.parse().ok())
+            {
+                Some(core) => core,
+                None => continue,
+            };
+            if let Ok(sample) = self.read_cpu(core).await {
+                res.push(sample);
+            }
+        }
+        if res.is_empty() {
+            Err(Error(String::from(
+                "CPU frequency scaling (cpufreq) is not available.",
+            )))
+        } else {
+            Ok(res)
+        }
+    }
+
+    fn compute(&self, id: &str, cpus: &[CpuSample]) -> Result<Throttling> {
+        let frequency: u64 = cpus.iter().map(|x| x.frequency).sum();
+        let max_frequency: u64 = cpus.iter().map(|x| x.max_frequency).sum();
+        if max_frequency == 0 {
+            return Err(Error(String::from("Maximum CPU frequency is 0.")));
+        }
+        let throttle_count = cpus
+            .iter()
+            .filter_map(|x| x.throttle_count)
+            .reduce(|a, b| a + b);
+        // The counters only count from the first cycle on.
+        let throttle_events = throttle_count.map(|count| {
+            let previous = self.samples.lock().unwrap().insert(String::from(id), count);
+            previous.map_or(0, |x| count.saturating_sub(x))
+        });
+        let level = if throttle_events.unwrap_or(0) > 0 {
+            100
+        } else {
+            (max_frequency.saturating_sub(frequency) * 100 / max_frequency) as u8
+        };
+        Ok(Throttling {
+            level,
+            frequency: frequency / cpus.len() as u64,
+            max_frequency: max_frequency / cpus.len() as u64,
+            throttle_events,
+        })
+    }
+}
+
+impl TryFrom<&config::Check> for CpuThrottle {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::CpuThrottle(cpu_throttle) = &check.type_ {
+            if !cpu_throttle.total && cpu_throttle.cores.is_empty() {
+                Err(Error(String::from(
+                    "At least one of 'total' and 'cores' needs to be enabled.",
+                )))
+            } else {
+                let mut id = Vec::new();
+                let mut cores = Vec::new();
+                if cpu_throttle.total {
+                    id.push(String::from("total"));
+                    cores.push(None);
+                }
+                for core in cpu_throttle.cores.iter() {
+                    id.push(format!("cpu{}", core));
+                    cores.push(Some(*core));
+                }
+                Ok(Self {
+                    id,
+                    cores,
+                    cpu_path: check.sys_path.join("devices/system/cpu"),
+                    sys_path: check.sys_path.clone(),
+                    samples: std::sync::Mutex::new(HashMap::new()),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for CpuThrottle {
+    type Item = Throttling;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mut res = Vec::new();
+        for (id, core) in self.id.iter().zip(self.cores.iter()) {
+            let cpus = match core {
+                Some(core) => self.read_cpu(*core).await.map(|x| vec![x]),
+                None => self.read_total().await,
+            };
+            res.push(cpus.and_then(|x| self.compute(id, &x)));
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        match data.throttle_events {
+            Some(throttle_events) if throttle_events > 0 => format!(
+                "thermally throttled ({} events), running at {} of {} MHz",
+                throttle_events,
+                data.frequency / 1000,
+                data.max_frequency / 1000
+            ),
+            _ => format!(
+                "running at {} of {} MHz",
+                data.frequency / 1000,
+                data.max_frequency / 1000
+            ),
+        }
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.level())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn write_cpu(root: &std::path::Path, core: u32, frequency: u64, throttle_count: Option<u64>) {
+        let path = root.join(format!("devices/system/cpu/cpu{}", core));
+        std::fs::create_dir_all(path.join("cpufreq")).unwrap();
+        std::fs::write(
+            path.join("cpufreq/scaling_cur_freq"),
+            format!("{}\n", frequency),
+        )
+        .unwrap();
+        std::fs::write(path.join("cpufreq/cpuinfo_max_freq"), "4000000\n").unwrap();
+        if let Some(throttle_count) = throttle_count {
+            std::fs::create_dir_all(path.join("thermal_throttle")).unwrap();
+            std::fs::write(
+                path.join("thermal_throttle/core_throttle_count"),
+                format!("{}\n", throttle_count),
+            )
+            .unwrap();
+            std::fs::write(path.join("thermal_throttle/package_throttle_count"), "1\n").unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        // cpu0 normal, cpu1 running below its rated frequency, cpu2 thermally throttled
+        write_cpu(&root, 0, 4000000, Some(5));
+        write_cpu(&root, 1, 1000000, None);
+        write_cpu(&root, 2, 3000000, Some(5));
+        // without cpufreq
+        std::fs::create_dir_all(root.join("devices/system/cpu/cpu3")).unwrap();
+        std::fs::create_dir_all(root.join("devices/system/cpu/cpufreq")).unwrap();
+        let cpu_throttle = CpuThrottle {
+            id: ["total", "cpu0", "cpu1", "cpu2", "cpu3"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            cores: vec![None, Some(0), Some(1), Some(2), Some(3)],
+            cpu_path: root.join("devices/system/cpu"),
+            sys_path: root.clone(),
+            samples: std::sync::Mutex::new(HashMap::new()),
+        };
+        let first = cpu_throttle.get_data().await.unwrap();
+        write_cpu(&root, 2, 3000000, Some(8));
+        let second = cpu_throttle.get_data().await.unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        // 8000 of 12000 MHz
+        let total = first[0].as_ref().unwrap();
+        assert_eq!(total.level, 33);
+        assert_eq!(total.throttle_events, Some(0));
+        assert_eq!(first[1].as_ref().unwrap().level, 0);
+        let cpu1 = first[2].as_ref().unwrap();
+        assert_eq!(cpu1.level, 75);
+        assert_eq!(cpu1.throttle_events, None);
+        assert_eq!(
+            CpuThrottle::format_data(cpu1),
+            "running at 1000 of 4000 MHz"
+        );
+        assert_eq!(first[3].as_ref().unwrap().level, 25);
+        assert_eq!(
+            first[4].as_ref().unwrap_err().to_string(),
+            "CPU frequency scaling (cpufreq) is not available for cpu3."
+        );
+        assert_eq!(second[0].as_ref().unwrap().level, 100);
+        assert_eq!(second[1].as_ref().unwrap().level, 0);
+        let cpu2 = second[3].as_ref().unwrap();
+        assert_eq!(cpu2.level, 100);
+        let mut placeholders = PlaceholderMap::new();
+        cpu2.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("frequency").unwrap(), "3000");
+        assert_eq!(placeholders.get("max_frequency").unwrap(), "4000");
+        assert_eq!(placeholders.get("throttle_events").unwrap(), "3");
+        assert_eq!(
+            CpuThrottle::format_data(cpu2),
+            "thermally throttled (3 events), running at 3000 of 4000 MHz"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_without_cpufreq() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("devices/system/cpu/cpu0")).unwrap();
+        let cpu_throttle = CpuThrottle {
+            id: vec![String::from("total")],
+            cores: vec![None],
+            cpu_path: root.join("devices/system/cpu"),
+            sys_path: root.clone(),
+            samples: std::sync::Mutex::new(HashMap::new()),
+        };
+        let data = cpu_throttle.get_data().await.unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            data[0].as_ref().unwrap_err().to_string(),
+            "CPU frequency scaling (cpufreq) is not available."
+        );
+    }
+}
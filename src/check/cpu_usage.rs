@@ -0,0 +1,219 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct CpuTimes {
+    // all jiffies except guest time (which is already included in user time)
+    total: u64,
+    // idle and iowait jiffies
+    idle: u64,
+}
+
+pub struct CpuUsage {
+    id: Vec<String>,
+    // "cpu" for the total, "cpu0", "cpu1", .. for the cores
+    rows: Vec<String>,
+    stat_path: std::path::PathBuf,
+    proc_path: std::path::PathBuf,
+    samples: std::sync::Mutex<HashMap<String, CpuTimes>>,
+}
+
+impl CpuUsage {
+    // e.g. "cpu0 4705 356 584 3699176 23060 0 277 0 0 0"
+    // see https://www.kernel.org/doc/Documentation/filesystems/proc.txt
+    fn parse_stat(content: &str, row: &str) -> Result<CpuTimes> {
+        let line = content
+            .lines()
+            .find(|x| x.split_whitespace().next() == Some(row))
+            .ok_or_else(|| Error(format!("Row '{}' not found.", row)))?;
+        let mut columns = Vec::new();
+        for index in 1..=8 {
+            columns.push(crate::get_number::<u64>(
+                &format!("Could not read column {} of row '{}'", index, row),
+                line,
+                index,
+            )?);
+        }
+        Ok(CpuTimes {
+            total: columns.iter().sum(),
+            idle: columns[3] + columns[4],
+        })
+    }
+
+    fn usage(previous: &CpuTimes, current: &CpuTimes) -> Result<u8> {
+        let total = current.total.saturating_sub(previous.total);
+        let idle = current.idle.saturating_sub(previous.idle);
+        (total.saturating_sub(idle) * 100)
+            .checked_div(total)
+            .map(|x| x as u8)
+            .ok_or_else(|| Error(String::from("No CPU time elapsed since last cycle.")))
+    }
+
+    fn sample(&self, content: &str, row: &str) -> Result<u8> {
+        let current = Self::parse_stat(content, row)?;
+        let previous = self
+            .samples
+            .lock()
+            .unwrap()
+            .insert(String::from(row), current);
+        match previous {
+            Some(previous) => Self::usage(&previous, &current),
+            None => Err(Error(String::from(
+                "No previous sample available yet (first cycle).",
+            ))),
+        }
+    }
+}
+
+impl TryFrom<&config::Check> for CpuUsage {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::CpuUsage(cpu_usage) = &check.type_ {
+            if !cpu_usage.total && cpu_usage.cores.is_empty() {
+                Err(Error(String::from(
+                    "At least one of 'total' and 'cores' needs to be enabled.",
+                )))
+            } else {
+                let mut id = Vec::new();
+                let mut rows = Vec::new();
+                if cpu_usage.total {
+                    id.push(String::from("total"));
+                    rows.push(String::from("cpu"));
+                }
+                for core in cpu_usage.cores.iter() {
+                    id.push(format!("cpu{}", core));
+                    rows.push(format!("cpu{}", core));
+                }
+                Ok(Self {
+                    id,
+                    rows,
+                    stat_path: check.proc_path.join("stat"),
+                    proc_path: check.proc_path.clone(),
+                    samples: std::sync::Mutex::new(HashMap::new()),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for CpuUsage {
+    type Item = u8;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let content = tokio::fs::read_to_string(&self.stat_path)
+            .await
+            .map_err(|x| {
+                super::proc_error(
+                    &self.proc_path,
+                    format!("Could not read from {}: {}", self.stat_path.display(), x),
+                )
+            })?;
+        Ok(self
+            .rows
+            .iter()
+            .map(|row| self.sample(&content, row))
+            .collect())
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("usage level {}", data)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const STAT_1: &str = "cpu  1000 0 1000 7000 1000 0 0 0 0 0
+cpu0 500 0 500 3500 500 0 0 0 0 0
+cpu1 500 0 500 3500 500 0 0 0 0 0
+intr 114930548 113199788 3 0 5 263 0 4 [... lots more numbers ...]
+ctxt 1990473
+";
+
+    const STAT_2: &str = "cpu  1600 0 1200 7100 1100 0 0 0 50 0
+cpu0 1050 0 550 3550 550 0 0 0 50 0
+cpu1 550 0 650 3550 550 0 0 0 0 0
+intr 114930548 113199788 3 0 5 263 0 4 [... lots more numbers ...]
+ctxt 1990473
+";
+
+    fn cpu_usage(proc_path: std::path::PathBuf) -> CpuUsage {
+        CpuUsage {
+            id: vec![
+                String::from("total"),
+                String::from("cpu0"),
+                String::from("cpu1"),
+            ],
+            rows: vec![
+                String::from("cpu"),
+                String::from("cpu0"),
+                String::from("cpu1"),
+            ],
+            stat_path: proc_path.join("stat"),
+            proc_path,
+            samples: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    #[test]
+    fn test_parse_stat() {
+        assert_eq!(
+            CpuUsage::parse_stat(STAT_1, "cpu").unwrap(),
+            CpuTimes {
+                total: 10000,
+                idle: 8000
+            }
+        );
+        // guest time is not counted twice
+        assert_eq!(CpuUsage::parse_stat(STAT_2, "cpu0").unwrap().total, 5700);
+        assert!(CpuUsage::parse_stat(STAT_1, "cpu2").is_err());
+        assert!(CpuUsage::parse_stat("cpu 1 2 3", "cpu").is_err());
+    }
+
+    #[test]
+    fn test_sample() {
+        let cpu_usage = cpu_usage(std::path::PathBuf::new());
+        assert!(cpu_usage.sample(STAT_1, "cpu").is_err());
+        assert!(cpu_usage.sample(STAT_1, "cpu0").is_err());
+        assert!(cpu_usage.sample(STAT_1, "cpu1").is_err());
+        // 800 busy of 1000
+        assert_eq!(cpu_usage.sample(STAT_2, "cpu").unwrap(), 80);
+        // 600 busy of 700
+        assert_eq!(cpu_usage.sample(STAT_2, "cpu0").unwrap(), 85);
+        // 200 busy of 300
+        assert_eq!(cpu_usage.sample(STAT_2, "cpu1").unwrap(), 66);
+        // no time elapsed
+        assert!(cpu_usage.sample(STAT_2, "cpu").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let cpu_usage = cpu_usage(root.clone());
+        std::fs::write(root.join("stat"), STAT_1).unwrap();
+        let first = cpu_usage.get_data().await;
+        std::fs::write(root.join("stat"), STAT_2).unwrap();
+        let second = cpu_usage.get_data().await;
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(first.unwrap().iter().all(|x| x.is_err()));
+        let data: Vec<u8> = second.unwrap().into_iter().map(|x| x.unwrap()).collect();
+        assert_eq!(data, vec![80, 85, 66]);
+    }
+}
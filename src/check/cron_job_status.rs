@@ -0,0 +1,198 @@
+use super::DataSource;
+use crate::alarm::StatusItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct JobStatus {
+    // UNIX timestamp
+    last_run: u64,
+    // in seconds
+    age: u64,
+    max_age: u64,
+    exit_code: i32,
+}
+
+impl StatusItem for JobStatus {
+    fn is_ok(&self) -> bool {
+        self.exit_code == 0 && self.age <= self.max_age
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(
+            String::from("last_run"),
+            crate::iso8601(std::time::UNIX_EPOCH + std::time::Duration::from_secs(self.last_run)),
+        );
+        placeholders.insert(String::from("age"), self.age.to_string());
+        placeholders.insert(String::from("exit_code"), self.exit_code.to_string());
+    }
+}
+
+pub struct CronJobStatus {
+    id: Vec<String>,
+    jobs: Vec<config::CronJob>,
+}
+
+impl CronJobStatus {
+    // e.g. "1700000000 0" (written by `echo "$(date +%s) $?" > marker_file` at the end of the job)
+    fn parse_marker(content: &str, max_age: u64, now: u64) -> Result<JobStatus> {
+        let mut columns = content.split_whitespace();
+        let last_run = columns
+            .next()
+            .ok_or_else(|| Error(String::from("Timestamp not found.")))?;
+        let last_run: u64 = last_run
+            .parse()
+            .map_err(|x| Error(format!("Invalid timestamp '{}': {}", last_run, x)))?;
+        let exit_code = columns
+            .next()
+            .ok_or_else(|| Error(String::from("Exit code not found.")))?;
+        let exit_code: i32 = exit_code
+            .parse()
+            .map_err(|x| Error(format!("Invalid exit code '{}': {}", exit_code, x)))?;
+        Ok(JobStatus {
+            last_run,
+            // Timestamps in the future count as age 0.
+            age: now.saturating_sub(last_run),
+            max_age,
+            exit_code,
+        })
+    }
+
+    async fn get_status(job: &config::CronJob, now: u64) -> Result<JobStatus> {
+        let content = tokio::fs::read_to_string(&job.marker_file)
+            .await
+            .map_err(|x| {
+                Error(format!(
+                    "Could not read from {}: {}",
+                    job.marker_file.display(),
+                    x
+                ))
+            })?;
+        Self::parse_marker(&content, job.max_age.into(), now).map_err(|x| {
+            Error(format!(
+                "Could not parse {}: {}",
+                job.marker_file.display(),
+                x
+            ))
+        })
+    }
+}
+
+impl TryFrom<&config::Check> for CronJobStatus {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::CronJobStatus(cron_job_status) = &check.type_ {
+            for job in cron_job_status.jobs.iter() {
+                if job.name.is_empty() {
+                    return Err(Error(String::from("'name' cannot be empty.")));
+                } else if job.marker_file.as_os_str().is_empty() {
+                    return Err(Error(String::from("'marker_file' cannot be empty.")));
+                } else if job.max_age == 0 {
+                    return Err(Error(String::from("'max_age' cannot be 0.")));
+                }
+            }
+            Ok(Self {
+                id: cron_job_status
+                    .jobs
+                    .iter()
+                    .map(|x| x.name.clone())
+                    .collect(),
+                jobs: cron_job_status.jobs.clone(),
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for CronJobStatus {
+    type Item = JobStatus;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let mut res = Vec::new();
+        for job in self.jobs.iter() {
+            res.push(Self::get_status(job, now).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("last run {}s ago, exit code {}", data.age, data.exit_code)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.age as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NOW: u64 = 1700000000;
+
+    #[test]
+    fn test_parse_marker() {
+        let fresh_success = CronJobStatus::parse_marker("1699999000 0\n", 3600, NOW).unwrap();
+        assert_eq!(fresh_success.age, 1000);
+        assert!(fresh_success.is_ok());
+        let stale = CronJobStatus::parse_marker("1699990000 0\n", 3600, NOW).unwrap();
+        assert!(!stale.is_ok());
+        let failure = CronJobStatus::parse_marker("1699999000 2\n", 3600, NOW).unwrap();
+        assert!(!failure.is_ok());
+        let mut placeholders = PlaceholderMap::new();
+        failure.add_placeholders(&mut placeholders);
+        assert_eq!(
+            placeholders.get("last_run").unwrap(),
+            "2023-11-14T21:56:40Z"
+        );
+        assert_eq!(placeholders.get("age").unwrap(), "1000");
+        assert_eq!(placeholders.get("exit_code").unwrap(), "2");
+        assert!(CronJobStatus::parse_marker("", 3600, NOW).is_err());
+        assert!(CronJobStatus::parse_marker("1699999000", 3600, NOW).is_err());
+        assert!(CronJobStatus::parse_marker("yesterday 0", 3600, NOW).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        for (name, content) in [
+            ("fresh", "1699999000 0"),
+            ("stale", "1699000000 0"),
+            ("failure", "1699999000 1"),
+        ] {
+            std::fs::write(root.join(name), content).unwrap();
+        }
+        let jobs: Vec<config::CronJob> = ["fresh", "stale", "failure", "missing"]
+            .iter()
+            .map(|x| config::CronJob {
+                name: String::from(*x),
+                marker_file: root.join(x),
+                max_age: 3600,
+            })
+            .collect();
+        let mut data = Vec::new();
+        for job in jobs.iter() {
+            data.push(CronJobStatus::get_status(job, NOW).await);
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+        let is_ok: Vec<bool> = data[..3]
+            .iter()
+            .map(|x| x.as_ref().unwrap().is_ok())
+            .collect();
+        assert_eq!(is_ok, vec![true, false, false]);
+        assert!(data[3].is_err());
+    }
+}
@@ -0,0 +1,190 @@
+use super::expression::Expression;
+use super::{DataSource, ValueStore};
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+pub struct Derived {
+    id: Vec<String>,
+    expression: Expression,
+    inputs: Vec<(String, config::DerivedInput)>,
+    values: ValueStore,
+}
+
+impl Derived {
+    pub fn new(check: &config::Check, values: ValueStore) -> Result<Self> {
+        if let config::CheckType::Derived(derived) = &check.type_ {
+            if derived.expression.is_empty() {
+                return Err(Error(String::from("'expression' cannot be empty.")));
+            }
+            let expression = Expression::parse(&derived.expression)
+                .map_err(|x| Error(format!("Invalid 'expression': {}", x)))?;
+            let mut variables = Vec::new();
+            expression.variables(&mut variables);
+            if let Some(variable) = variables.iter().find(|x| !derived.inputs.contains_key(**x)) {
+                return Err(Error(format!(
+                    "Variable '{}' is not defined in 'inputs'.",
+                    variable
+                )));
+            }
+            Ok(Self {
+                id: vec![derived.expression.clone()],
+                expression,
+                inputs: derived
+                    .inputs
+                    .iter()
+                    .map(|(name, input)| (name.clone(), input.clone()))
+                    .collect(),
+                values,
+            })
+        } else {
+            panic!();
+        }
+    }
+
+    fn evaluate(&self) -> Result<f64> {
+        let mut variables = std::collections::HashMap::new();
+        {
+            let values = self.values.read().unwrap();
+            for (name, input) in self.inputs.iter() {
+                let value = values
+                    .get(&(input.check.clone(), input.id.clone()))
+                    .ok_or_else(|| {
+                        Error(format!(
+                            "No value available for input '{}' (check '{}', id '{}').",
+                            name, input.check, input.id
+                        ))
+                    })?;
+                if value.is_stale() {
+                    return Err(Error(format!(
+                        "Value of input '{}' (check '{}', id '{}') is stale.",
+                        name, input.check, input.id
+                    )));
+                }
+                variables.insert(name.as_str(), value.value());
+            }
+        }
+        self.expression.evaluate(&variables)
+    }
+}
+
+#[async_trait]
+impl DataSource for Derived {
+    type Item = f64;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        Ok(vec![self.evaluate()])
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("value {}", data)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Check, CheckBase};
+    use super::*;
+    use crate::alarm;
+
+    struct Upstream {
+        id: Vec<String>,
+        data: Vec<u8>,
+    }
+
+    #[async_trait]
+    impl DataSource for Upstream {
+        type Item = u8;
+
+        async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+            Ok(self.data.iter().map(|x| Ok(*x)).collect())
+        }
+
+        fn format_data(data: &Self::Item) -> String {
+            data.to_string()
+        }
+
+        fn ids(&self) -> &[String] {
+            &self.id[..]
+        }
+
+        fn value(data: &Self::Item) -> Option<f64> {
+            Some(*data as f64)
+        }
+    }
+
+    fn derived(expression: &str, values: ValueStore) -> Result<Derived> {
+        let config = config::Config::try_from(
+            format!(
+                r#"
+                [[checks]]
+                name = "derived"
+                type = "Derived"
+                expression = "{}"
+                inputs = {{a = {{check = "upstream", id = "A"}}, b = {{check = "upstream", id = "B"}}}}
+                "#,
+                expression
+            )
+            .as_str(),
+        )
+        .unwrap();
+        Derived::new(&config.checks[0], values)
+    }
+
+    #[test]
+    fn test_undefined_variable() {
+        assert!(derived("a + c", ValueStore::default()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_upstream_values() {
+        let values = ValueStore::default();
+        let derived = derived("a - 2 * b", values.clone()).unwrap();
+        assert!(derived.get_data().await.unwrap()[0].is_err());
+        let mut upstream = CheckBase::<_, alarm::AlarmBase<alarm::Level<u8>>>::new(
+            60,
+            false,
+            String::from("upstream"),
+            crate::PlaceholderMap::new(),
+            Upstream {
+                id: vec![String::from("A"), String::from("B")],
+                data: vec![50, 20],
+            },
+            vec![Vec::new(), Vec::new()],
+            values,
+        )
+        .unwrap();
+        upstream.trigger().await;
+        assert_eq!(
+            *derived.get_data().await.unwrap()[0].as_ref().unwrap(),
+            10.0
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stale_upstream_values() {
+        let values = ValueStore::default();
+        let derived = derived("a + b", values.clone()).unwrap();
+        let mut upstream = CheckBase::<_, alarm::AlarmBase<alarm::Level<u8>>>::new(
+            60,
+            false,
+            String::from("upstream"),
+            crate::PlaceholderMap::new(),
+            Upstream {
+                id: vec![String::from("A"), String::from("B")],
+                data: vec![1, 2],
+            },
+            vec![Vec::new(), Vec::new()],
+            values,
+        )
+        .unwrap();
+        upstream.trigger().await;
+        assert_eq!(*derived.get_data().await.unwrap()[0].as_ref().unwrap(), 3.0);
+        tokio::time::advance(std::time::Duration::from_secs(121)).await;
+        assert!(derived.get_data().await.unwrap()[0].is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+pub struct DirEntryCount {
+    id: Vec<String>,
+    paths: Vec<std::path::PathBuf>,
+    pattern: Option<String>,
+}
+
+impl DirEntryCount {
+    fn is_match(&self, name: &str) -> bool {
+        match &self.pattern {
+            Some(pattern) => crate::wildcard_match(pattern, name),
+            None => true,
+        }
+    }
+
+    async fn count(&self, path: &std::path::Path) -> Result<u64> {
+        let error = |x| {
+            Error(format!(
+                "Could not read directory {}: {}",
+                path.display(),
+                x
+            ))
+        };
+        let mut entries = tokio::fs::read_dir(path).await.map_err(error)?;
+        let mut count = 0;
+        while let Some(entry) = entries.next_entry().await.map_err(error)? {
+            if !self.is_match(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+            // The file might have been processed in the meantime.
+            match entry.metadata().await {
+                Ok(metadata) if metadata.is_file() => count += 1,
+                _ => continue,
+            }
+        }
+        Ok(count)
+    }
+}
+
+impl TryFrom<&config::Check> for DirEntryCount {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::DirEntryCount(dir_entry_count) = &check.type_ {
+            if dir_entry_count
+                .paths
+                .iter()
+                .any(|x| x.as_os_str().is_empty())
+            {
+                Err(Error(String::from("'paths' cannot contain empty paths.")))
+            } else if dir_entry_count.pattern.as_deref() == Some("") {
+                Err(Error(String::from("'pattern' cannot be empty.")))
+            } else {
+                Ok(Self {
+                    id: dir_entry_count
+                        .paths
+                        .iter()
+                        .map(|x| x.display().to_string())
+                        .collect(),
+                    paths: dir_entry_count.paths.clone(),
+                    pattern: dir_entry_count.pattern.clone(),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for DirEntryCount {
+    type Item = u64;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mut res = Vec::new();
+        for path in self.paths.iter() {
+            res.push(self.count(path).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("entry count {}", data)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("queue/subdirectory.job")).unwrap();
+        std::fs::create_dir_all(root.join("empty")).unwrap();
+        for name in ["1.job", "2.job", "3.job", "4.tmp"] {
+            std::fs::write(root.join("queue").join(name), "").unwrap();
+        }
+        let dir_entry_count = |pattern: Option<&str>| DirEntryCount {
+            id: Vec::new(),
+            paths: vec![root.join("queue"), root.join("empty"), root.join("missing")],
+            pattern: pattern.map(String::from),
+        };
+        let all = dir_entry_count(None).get_data().await.unwrap();
+        let jobs = dir_entry_count(Some("*.job")).get_data().await.unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(*all[0].as_ref().unwrap(), 4);
+        assert_eq!(*jobs[0].as_ref().unwrap(), 3);
+        assert_eq!(*jobs[1].as_ref().unwrap(), 0);
+        // an unreadable directory is an error for its ID only
+        assert!(jobs[2].is_err());
+    }
+}
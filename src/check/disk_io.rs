@@ -0,0 +1,281 @@
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+// /proc/diskstats always counts in 512 byte sectors, independent of the device.
+const SECTOR_SIZE: u64 = 512;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct IoRate {
+    metric: config::DiskIoMetric,
+    read_bytes_per_second: f64,
+    write_bytes_per_second: f64,
+    // share of the time the device was busy in percent
+    utilization: f64,
+}
+
+impl std::fmt::Display for IoRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:.0}", self.level())
+    }
+}
+
+impl LevelItem for IoRate {
+    fn level(&self) -> f64 {
+        match self.metric {
+            config::DiskIoMetric::ReadBytes => self.read_bytes_per_second,
+            config::DiskIoMetric::WriteBytes => self.write_bytes_per_second,
+            config::DiskIoMetric::Utilization => self.utilization,
+        }
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(
+            String::from("read_bytes_per_second"),
+            format!("{:.0}", self.read_bytes_per_second),
+        );
+        placeholders.insert(
+            String::from("write_bytes_per_second"),
+            format!("{:.0}", self.write_bytes_per_second),
+        );
+        placeholders.insert(
+            String::from("utilization"),
+            format!("{:.1}", self.utilization),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct DiskStats {
+    sectors_read: u64,
+    sectors_written: u64,
+    // time spent doing I/Os in milliseconds
+    io_ms: u64,
+}
+
+pub struct DiskIo {
+    id: Vec<String>,
+    metric: config::DiskIoMetric,
+    diskstats_path: std::path::PathBuf,
+    proc_path: std::path::PathBuf,
+    samples: std::sync::Mutex<HashMap<String, (std::time::Instant, DiskStats)>>,
+}
+
+impl DiskIo {
+    // e.g. "   8       0 sda 5126 1208 352158 3217 9023 6617 523944 11233 2 12988 14920 0 0 0 0"
+    // see https://www.kernel.org/doc/Documentation/ABI/testing/procfs-diskstats
+    fn parse_diskstats(content: &str, device: &str) -> Result<DiskStats> {
+        let line = content
+            .lines()
+            .find(|x| x.split_whitespace().nth(2) == Some(device))
+            .ok_or_else(|| Error(format!("Device '{}' not found.", device)))?;
+        let column = |index: usize| -> Result<u64> {
+            crate::get_number(
+                &format!("Could not read column {} of device '{}'", index, device),
+                line,
+                index,
+            )
+        };
+        Ok(DiskStats {
+            sectors_read: column(5)?,
+            sectors_written: column(9)?,
+            io_ms: column(12)?,
+        })
+    }
+
+    fn compute(
+        metric: config::DiskIoMetric,
+        previous: &DiskStats,
+        current: &DiskStats,
+        elapsed: std::time::Duration,
+    ) -> Result<IoRate> {
+        let elapsed_s = elapsed.as_secs_f64();
+        if elapsed_s == 0.0 {
+            return Err(Error(String::from("No time elapsed since last cycle.")));
+        }
+        let rate = |previous: u64, current: u64| {
+            current.saturating_sub(previous) as f64 * SECTOR_SIZE as f64 / elapsed_s
+        };
+        Ok(IoRate {
+            metric,
+            read_bytes_per_second: rate(previous.sectors_read, current.sectors_read),
+            write_bytes_per_second: rate(previous.sectors_written, current.sectors_written),
+            utilization: (current.io_ms.saturating_sub(previous.io_ms) as f64 / 10.0 / elapsed_s)
+                .min(100.0),
+        })
+    }
+
+    fn sample(&self, content: &str, device: &str, now: std::time::Instant) -> Result<IoRate> {
+        let current = Self::parse_diskstats(content, device)?;
+        let previous = self
+            .samples
+            .lock()
+            .unwrap()
+            .insert(String::from(device), (now, current));
+        match previous {
+            Some((timestamp, previous)) => Self::compute(
+                self.metric,
+                &previous,
+                &current,
+                now.saturating_duration_since(timestamp),
+            ),
+            None => Err(Error(String::from(
+                "No previous sample available yet (first cycle).",
+            ))),
+        }
+    }
+}
+
+impl TryFrom<&config::Check> for DiskIo {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::DiskIo(disk_io) = &check.type_ {
+            if disk_io.devices.iter().any(|x| x.is_empty()) {
+                Err(Error(String::from(
+                    "'devices' cannot contain empty device names.",
+                )))
+            } else {
+                Ok(Self {
+                    id: disk_io.devices.clone(),
+                    metric: disk_io.metric,
+                    diskstats_path: check.proc_path.join("diskstats"),
+                    proc_path: check.proc_path.clone(),
+                    samples: std::sync::Mutex::new(HashMap::new()),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for DiskIo {
+    type Item = IoRate;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let content = tokio::fs::read_to_string(&self.diskstats_path)
+            .await
+            .map_err(|x| {
+                super::proc_error(
+                    &self.proc_path,
+                    format!(
+                        "Could not read from {}: {}",
+                        self.diskstats_path.display(),
+                        x
+                    ),
+                )
+            })?;
+        let now = std::time::Instant::now();
+        Ok(self
+            .id
+            .iter()
+            .map(|device| self.sample(&content, device, now))
+            .collect())
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!(
+            "read {:.0}B/s, write {:.0}B/s, utilization {:.1}%",
+            data.read_bytes_per_second, data.write_bytes_per_second, data.utilization
+        )
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.level())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DISKSTATS_1: &str =
+        "   8       0 sda 5000 1208 352000 3000 9000 6617 524000 11000 2 12000 14000 0 0 0 0
+ 259       0 nvme0n1 100 0 800 10 200 0 1600 20 0 30 30
+";
+
+    const DISKSTATS_2: &str =
+        "   8       0 sda 5100 1210 354000 3400 9100 6620 528000 12600 5 14500 19000 0 0 0 0
+ 259       0 nvme0n1 100 0 800 10 200 0 1600 20 0 30 30
+";
+
+    fn disk_io(metric: config::DiskIoMetric) -> DiskIo {
+        DiskIo {
+            id: vec![String::from("sda"), String::from("nvme0n1")],
+            metric,
+            diskstats_path: std::path::PathBuf::new(),
+            proc_path: std::path::PathBuf::new(),
+            samples: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    #[test]
+    fn test_parse_diskstats() {
+        assert_eq!(
+            DiskIo::parse_diskstats(DISKSTATS_1, "sda").unwrap(),
+            DiskStats {
+                sectors_read: 352000,
+                sectors_written: 524000,
+                io_ms: 12000,
+            }
+        );
+        assert!(DiskIo::parse_diskstats(DISKSTATS_1, "sdb").is_err());
+        assert!(DiskIo::parse_diskstats("8 0 sda 1 2 3", "sda").is_err());
+    }
+
+    #[test]
+    fn test_sample() {
+        let disk_io = disk_io(config::DiskIoMetric::Utilization);
+        let now = std::time::Instant::now();
+        assert!(disk_io.sample(DISKSTATS_1, "sda", now).is_err());
+        assert!(disk_io.sample(DISKSTATS_1, "nvme0n1", now).is_err());
+        let now = now + std::time::Duration::from_secs(10);
+        // 2000 sectors read and 4000 sectors written in 10s, busy for 2500ms
+        let io_rate = disk_io.sample(DISKSTATS_2, "sda", now).unwrap();
+        assert_eq!(io_rate.read_bytes_per_second, 102400.0);
+        assert_eq!(io_rate.write_bytes_per_second, 204800.0);
+        assert_eq!(io_rate.utilization, 25.0);
+        assert_eq!(io_rate.level(), 25.0);
+        let mut placeholders = PlaceholderMap::new();
+        io_rate.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("read_bytes_per_second").unwrap(), "102400");
+        assert_eq!(
+            placeholders.get("write_bytes_per_second").unwrap(),
+            "204800"
+        );
+        assert_eq!(placeholders.get("utilization").unwrap(), "25.0");
+        // idle device
+        let io_rate = disk_io.sample(DISKSTATS_2, "nvme0n1", now).unwrap();
+        assert_eq!(io_rate.read_bytes_per_second, 0.0);
+        assert_eq!(io_rate.utilization, 0.0);
+    }
+
+    #[test]
+    fn test_write_bytes_metric() {
+        let disk_io = disk_io(config::DiskIoMetric::WriteBytes);
+        let now = std::time::Instant::now();
+        assert!(disk_io.sample(DISKSTATS_1, "sda", now).is_err());
+        let io_rate = disk_io
+            .sample(DISKSTATS_2, "sda", now + std::time::Duration::from_secs(2))
+            .unwrap();
+        assert_eq!(io_rate.level(), 1024000.0);
+        assert_eq!(io_rate.to_string(), "1024000");
+    }
+}
@@ -0,0 +1,285 @@
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct IoLatency {
+    metric: config::DiskLatencyMetric,
+    // average time per completed request in milliseconds
+    await_ms: f64,
+    // average number of requests in the queue
+    queue_depth: f64,
+    in_flight: u64,
+}
+
+impl std::fmt::Display for IoLatency {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:.2}", self.level())
+    }
+}
+
+impl LevelItem for IoLatency {
+    fn level(&self) -> f64 {
+        match self.metric {
+            config::DiskLatencyMetric::Await => self.await_ms,
+            config::DiskLatencyMetric::QueueDepth => self.queue_depth,
+        }
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("await"), format!("{:.2}", self.await_ms));
+        placeholders.insert(
+            String::from("queue_depth"),
+            format!("{:.2}", self.queue_depth),
+        );
+        placeholders.insert(String::from("in_flight"), self.in_flight.to_string());
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct DiskStats {
+    // reads and writes completed
+    requests: u64,
+    // time spent reading and writing in milliseconds
+    request_ms: u64,
+    in_flight: u64,
+    // weighted time spent doing I/Os in milliseconds
+    queue_ms: u64,
+}
+
+pub struct DiskLatency {
+    id: Vec<String>,
+    metric: config::DiskLatencyMetric,
+    diskstats_path: std::path::PathBuf,
+    proc_path: std::path::PathBuf,
+    samples: std::sync::Mutex<HashMap<String, (std::time::Instant, DiskStats)>>,
+}
+
+impl DiskLatency {
+    // e.g. "   8       0 sda 5126 1208 352158 3217 9023 6617 523944 11233 2 12988 14920 0 0 0 0"
+    // see https://www.kernel.org/doc/Documentation/ABI/testing/procfs-diskstats
+    fn parse_diskstats(content: &str, device: &str) -> Result<DiskStats> {
+        let line = content
+            .lines()
+            .find(|x| x.split_whitespace().nth(2) == Some(device))
+            .ok_or_else(|| Error(format!("Device '{}' not found.", device)))?;
+        let column = |index: usize| -> Result<u64> {
+            crate::get_number(
+                &format!("Could not read column {} of device '{}'", index, device),
+                line,
+                index,
+            )
+        };
+        Ok(DiskStats {
+            requests: column(3)? + column(7)?,
+            request_ms: column(6)? + column(10)?,
+            in_flight: column(11)?,
+            queue_ms: column(13)?,
+        })
+    }
+
+    fn compute(
+        metric: config::DiskLatencyMetric,
+        previous: &DiskStats,
+        current: &DiskStats,
+        elapsed: std::time::Duration,
+    ) -> IoLatency {
+        let requests = current.requests.saturating_sub(previous.requests);
+        let request_ms = current.request_ms.saturating_sub(previous.request_ms);
+        let queue_ms = current.queue_ms.saturating_sub(previous.queue_ms);
+        let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+        IoLatency {
+            metric,
+            await_ms: if requests == 0 {
+                0.0
+            } else {
+                request_ms as f64 / requests as f64
+            },
+            queue_depth: if elapsed_ms == 0.0 {
+                0.0
+            } else {
+                queue_ms as f64 / elapsed_ms
+            },
+            in_flight: current.in_flight,
+        }
+    }
+
+    fn sample(&self, content: &str, device: &str, now: std::time::Instant) -> Result<IoLatency> {
+        let current = Self::parse_diskstats(content, device)?;
+        let previous = self
+            .samples
+            .lock()
+            .unwrap()
+            .insert(String::from(device), (now, current));
+        match previous {
+            Some((timestamp, previous)) => Ok(Self::compute(
+                self.metric,
+                &previous,
+                &current,
+                now.saturating_duration_since(timestamp),
+            )),
+            None => Err(Error(String::from(
+                "No previous sample available yet (first cycle).",
+            ))),
+        }
+    }
+}
+
+impl TryFrom<&config::Check> for DiskLatency {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::DiskLatency(disk_latency) = &check.type_ {
+            if disk_latency.devices.iter().any(|x| x.is_empty()) {
+                Err(Error(String::from(
+                    "'devices' cannot contain empty device names.",
+                )))
+            } else {
+                Ok(Self {
+                    id: disk_latency.devices.clone(),
+                    metric: disk_latency.metric,
+                    diskstats_path: check.proc_path.join("diskstats"),
+                    proc_path: check.proc_path.clone(),
+                    samples: std::sync::Mutex::new(HashMap::new()),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for DiskLatency {
+    type Item = IoLatency;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let content = tokio::fs::read_to_string(&self.diskstats_path)
+            .await
+            .map_err(|x| {
+                super::proc_error(
+                    &self.proc_path,
+                    format!(
+                        "Could not read from {}: {}",
+                        self.diskstats_path.display(),
+                        x
+                    ),
+                )
+            })?;
+        let now = std::time::Instant::now();
+        Ok(self
+            .id
+            .iter()
+            .map(|device| self.sample(&content, device, now))
+            .collect())
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!(
+            "await {:.2}ms, queue depth {:.2}",
+            data.await_ms, data.queue_depth
+        )
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.level())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DISKSTATS_1: &str =
+        "   8       0 sda 5000 1208 352158 3000 9000 6617 523944 11000 2 12988 14000 0 0 0 0
+   8       1 sda1 4900 1208 350000 2950 8990 6617 523900 10990 2 12900 13950 0 0 0 0
+ 259       0 nvme0n1 100 0 800 10 200 0 1600 20 0 30 30
+";
+
+    const DISKSTATS_2: &str =
+        "   8       0 sda 5100 1210 353000 3400 9100 6620 524500 12600 5 14988 19000 0 0 0 0
+   8       1 sda1 5000 1210 351000 3350 9090 6620 524400 12590 5 14900 18950 0 0 0 0
+ 259       0 nvme0n1 100 0 800 10 200 0 1600 20 0 30 30
+";
+
+    fn disk_latency(metric: config::DiskLatencyMetric) -> DiskLatency {
+        DiskLatency {
+            id: vec![String::from("sda"), String::from("nvme0n1")],
+            metric,
+            diskstats_path: std::path::PathBuf::new(),
+            proc_path: std::path::PathBuf::new(),
+            samples: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    #[test]
+    fn test_parse_diskstats() {
+        assert_eq!(
+            DiskLatency::parse_diskstats(DISKSTATS_1, "sda").unwrap(),
+            DiskStats {
+                requests: 14000,
+                request_ms: 14000,
+                in_flight: 2,
+                queue_ms: 14000,
+            }
+        );
+        assert_eq!(
+            DiskLatency::parse_diskstats(DISKSTATS_1, "nvme0n1")
+                .unwrap()
+                .queue_ms,
+            30
+        );
+        assert!(DiskLatency::parse_diskstats(DISKSTATS_1, "sdb").is_err());
+        assert!(DiskLatency::parse_diskstats("8 0 sda 1 2 3", "sda").is_err());
+    }
+
+    #[test]
+    fn test_sample() {
+        let disk_latency = disk_latency(config::DiskLatencyMetric::Await);
+        let now = std::time::Instant::now();
+        assert!(disk_latency.sample(DISKSTATS_1, "sda", now).is_err());
+        assert!(disk_latency.sample(DISKSTATS_1, "nvme0n1", now).is_err());
+        let now = now + std::time::Duration::from_secs(10);
+        // 200 requests in 2000ms, 5000ms weighted queue time in 10s
+        let io_latency = disk_latency.sample(DISKSTATS_2, "sda", now).unwrap();
+        assert_eq!(io_latency.await_ms, 10.0);
+        assert_eq!(io_latency.queue_depth, 0.5);
+        assert_eq!(io_latency.in_flight, 5);
+        assert_eq!(io_latency.level(), 10.0);
+        let mut placeholders = PlaceholderMap::new();
+        io_latency.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("await").unwrap(), "10.00");
+        assert_eq!(placeholders.get("queue_depth").unwrap(), "0.50");
+        assert_eq!(placeholders.get("in_flight").unwrap(), "5");
+        // idle device
+        let io_latency = disk_latency.sample(DISKSTATS_2, "nvme0n1", now).unwrap();
+        assert_eq!(io_latency.await_ms, 0.0);
+        assert_eq!(io_latency.queue_depth, 0.0);
+    }
+
+    #[test]
+    fn test_queue_depth_metric() {
+        let disk_latency = disk_latency(config::DiskLatencyMetric::QueueDepth);
+        let now = std::time::Instant::now();
+        assert!(disk_latency.sample(DISKSTATS_1, "sda", now).is_err());
+        let io_latency = disk_latency
+            .sample(DISKSTATS_2, "sda", now + std::time::Duration::from_secs(2))
+            .unwrap();
+        assert_eq!(io_latency.level(), 2.5);
+    }
+}
@@ -0,0 +1,205 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+pub struct DiskTemperature {
+    devices: Vec<String>,
+    tool: config::DiskTemperatureTool,
+    capture: crate::capture::Capture,
+}
+
+impl DiskTemperature {
+    fn select_tool(&self, device: &str) -> config::DiskTemperatureTool {
+        match self.tool {
+            config::DiskTemperatureTool::Auto => {
+                if device.starts_with("/dev/nvme") {
+                    config::DiskTemperatureTool::Nvme
+                } else {
+                    config::DiskTemperatureTool::Smartctl
+                }
+            }
+            tool => tool,
+        }
+    }
+
+    async fn run(&self, program: &str, arguments: &[&str]) -> Result<String> {
+        let output = self
+            .capture
+            .output(tokio::process::Command::new(program).args(arguments))
+            .await
+            .map_err(|x| Error(format!("Failed to run {}: {}", program, x)))?;
+        // smartctl uses the exit code as a bit mask, so some non-zero codes still come with usable
+        // output. The parsers will fail if the output is not usable.
+        String::from_utf8(output.stdout)
+            .map_err(|x| Error(format!("Output of {} is not valid UTF-8: {}", program, x)))
+    }
+
+    fn parse_number(text: &str) -> Option<i16> {
+        text.split_whitespace().next()?.parse().ok()
+    }
+
+    fn parse_nvme(output: &str) -> Option<i16> {
+        // temperature                             : 38 C (311 Kelvin)
+        output
+            .lines()
+            .filter(|x| x.starts_with("temperature"))
+            .find_map(|x| Self::parse_number(x.split_once(':')?.1))
+    }
+
+    fn parse_smartctl(output: &str) -> Option<i16> {
+        for line in output.lines() {
+            // NVMe: "Temperature:                        38 Celsius"
+            // SCSI: "Current Drive Temperature:     34 C"
+            if let Some(value) = line
+                .strip_prefix("Temperature:")
+                .or_else(|| line.strip_prefix("Current Drive Temperature:"))
+            {
+                return Self::parse_number(value);
+            }
+            // ATA: "194 Temperature_Celsius 0x0022 036 052 000 Old_age Always - 36 (Min/Max 20/52)"
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            if columns.len() >= 10
+                && (columns[1] == "Temperature_Celsius" || columns[1] == "Airflow_Temperature_Cel")
+            {
+                return columns[9].parse().ok();
+            }
+        }
+        None
+    }
+
+    fn parse_hddtemp(output: &str) -> Option<i16> {
+        Self::parse_number(output)
+    }
+
+    async fn get_temperature(&self, device: &str) -> Result<i16> {
+        let temperature = match self.select_tool(device) {
+            config::DiskTemperatureTool::Nvme => {
+                Self::parse_nvme(&self.run("nvme", &["smart-log", device]).await?)
+            }
+            config::DiskTemperatureTool::Hddtemp => {
+                Self::parse_hddtemp(&self.run("hddtemp", &["-n", device]).await?)
+            }
+            // "Auto" is already resolved by select_tool()
+            config::DiskTemperatureTool::Smartctl | config::DiskTemperatureTool::Auto => {
+                Self::parse_smartctl(&self.run("smartctl", &["-A", device]).await?)
+            }
+        };
+        temperature.ok_or_else(|| Error(format!("Device {} did not report a temperature.", device)))
+    }
+}
+
+impl TryFrom<&config::Check> for DiskTemperature {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::DiskTemperature(disk_temperature) = &check.type_ {
+            if disk_temperature.devices.iter().any(|x| x.is_empty()) {
+                Err(Error(String::from("'devices' cannot contain empty paths.")))
+            } else {
+                Ok(Self {
+                    devices: disk_temperature.devices.clone(),
+                    tool: disk_temperature.tool,
+                    capture: crate::capture::from_config(check.capture.as_ref())?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for DiskTemperature {
+    type Item = i16;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mut res = Vec::new();
+        for device in self.devices.iter() {
+            res.push(self.get_temperature(device).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("temperature {}°C", data)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.devices[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_nvme() {
+        let output = "Smart Log for NVME device:nvme0 namespace-id:ffffffff
+critical_warning                        : 0
+temperature                             : 38 C (311 Kelvin)
+available_spare                         : 100%
+";
+        assert_eq!(DiskTemperature::parse_nvme(output), Some(38));
+        assert_eq!(DiskTemperature::parse_nvme("critical_warning : 0"), None);
+    }
+
+    #[test]
+    fn test_parse_smartctl_ata() {
+        let output = "=== START OF READ SMART DATA SECTION ===
+SMART Attributes Data Structure revision number: 16
+ID# ATTRIBUTE_NAME          FLAG     VALUE WORST THRESH TYPE      UPDATED  WHEN_FAILED RAW_VALUE
+  9 Power_On_Hours          0x0032   095   095   000    Old_age   Always       -       23651
+194 Temperature_Celsius     0x0022   036   052   000    Old_age   Always       -       36 (Min/Max 20/52)
+";
+        assert_eq!(DiskTemperature::parse_smartctl(output), Some(36));
+    }
+
+    #[test]
+    fn test_parse_smartctl_nvme() {
+        let output = "=== START OF SMART DATA SECTION ===
+SMART/Health Information (NVMe Log 0x02)
+Critical Warning:                   0x00
+Temperature:                        41 Celsius
+Available Spare:                    100%
+";
+        assert_eq!(DiskTemperature::parse_smartctl(output), Some(41));
+    }
+
+    #[test]
+    fn test_parse_smartctl_missing() {
+        let output = "Read SMART Data failed: scsi error badly formed scsi parameters\n";
+        assert_eq!(DiskTemperature::parse_smartctl(output), None);
+    }
+
+    #[test]
+    fn test_parse_hddtemp() {
+        assert_eq!(DiskTemperature::parse_hddtemp("35\n"), Some(35));
+        assert_eq!(
+            DiskTemperature::parse_hddtemp("/dev/sda: not available\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_select_tool() {
+        let disk_temperature = DiskTemperature {
+            devices: Vec::new(),
+            tool: config::DiskTemperatureTool::Auto,
+            capture: crate::capture::Capture::default(),
+        };
+        assert_eq!(
+            disk_temperature.select_tool("/dev/nvme0"),
+            config::DiskTemperatureTool::Nvme
+        );
+        assert_eq!(
+            disk_temperature.select_tool("/dev/sda"),
+            config::DiskTemperatureTool::Smartctl
+        );
+    }
+}
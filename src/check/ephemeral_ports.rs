@@ -0,0 +1,196 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+use std::collections::BTreeSet;
+
+pub struct EphemeralPorts {
+    id: Vec<String>,
+    port_range_path: std::path::PathBuf,
+    proc_path: std::path::PathBuf,
+}
+
+impl EphemeralPorts {
+    // e.g. "32768\t60999"
+    fn parse_port_range(content: &str) -> Result<(u16, u16)> {
+        let mut columns = content.split_whitespace();
+        let mut get = |name: &str| -> Result<u16> {
+            let value = columns
+                .next()
+                .ok_or_else(|| Error(format!("{} port not found.", name)))?;
+            value
+                .parse()
+                .map_err(|x| Error(format!("Invalid {} port '{}': {}", name, value, x)))
+        };
+        let (first, last) = (get("First")?, get("Last")?);
+        if first > last {
+            Err(Error(format!("Invalid port range {}-{}.", first, last)))
+        } else {
+            Ok((first, last))
+        }
+    }
+
+    // Every socket bound to a local port of the range takes it away from new outgoing
+    // connections, independent of its state.
+    fn collect_ports(content: &str, range: (u16, u16), ports: &mut BTreeSet<u16>) -> Result<()> {
+        // skip header line
+        for line in content.lines().skip(1) {
+            let local_address = line
+                .split_whitespace()
+                .nth(1)
+                .ok_or_else(|| Error(String::from("Local address column not found.")))?;
+            let port = local_address
+                .rsplit(':')
+                .next()
+                .ok_or_else(|| Error(format!("Invalid local address: {}", local_address)))?;
+            let port = u16::from_str_radix(port, 16)
+                .map_err(|x| Error(format!("Invalid local port '{}': {}", port, x)))?;
+            if port >= range.0 && port <= range.1 {
+                ports.insert(port);
+            }
+        }
+        Ok(())
+    }
+
+    fn percent(range: (u16, u16), used: usize) -> u8 {
+        let size = (range.1 - range.0) as usize + 1;
+        (used * 100 / size) as u8
+    }
+}
+
+impl TryFrom<&config::Check> for EphemeralPorts {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::EphemeralPorts(_) = &check.type_ {
+            Ok(Self {
+                id: vec![String::from("Ephemeral")],
+                port_range_path: check.proc_path.join("sys/net/ipv4/ip_local_port_range"),
+                proc_path: check.proc_path.clone(),
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for EphemeralPorts {
+    type Item = u8;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let content = tokio::fs::read_to_string(&self.port_range_path)
+            .await
+            .map_err(|x| {
+                super::proc_error(
+                    &self.proc_path,
+                    format!(
+                        "Could not read from {}: {}",
+                        self.port_range_path.display(),
+                        x
+                    ),
+                )
+            })?;
+        let range = Self::parse_port_range(&content).map_err(|x| {
+            Error(format!(
+                "Could not parse {}: {}",
+                self.port_range_path.display(),
+                x
+            ))
+        })?;
+        let mut ports = BTreeSet::new();
+        for (i, path) in ["net/tcp", "net/tcp6"]
+            .iter()
+            .map(|x| self.proc_path.join(x))
+            .enumerate()
+        {
+            let content = match tokio::fs::read_to_string(&path).await {
+                Ok(content) => content,
+                // tcp6 is missing if IPv6 is disabled
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound && i == 1 => continue,
+                Err(err) => {
+                    return Err(super::proc_error(
+                        &self.proc_path,
+                        format!("Could not read from {}: {}", path.display(), err),
+                    ))
+                }
+            };
+            Self::collect_ports(&content, range, &mut ports)
+                .map_err(|x| Error(format!("Could not parse {}: {}", path.display(), x)))?;
+        }
+        Ok(vec![Ok(Self::percent(range, ports.len()))])
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("usage {}", data)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PROC_NET_TCP: &str = r#"  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 21371 1 0000000000000000 100 0 0 10 0
+   1: 0A00000A:9C40 0200000A:01BB 01 00000000:00000000 02:0009B5A3 00000000     0        0 41234 2 0000000000000000 20 4 29 10 -1
+   2: 0A00000A:9C40 0300000A:01BB 01 00000000:00000000 02:0009B5A3 00000000     0        0 41235 2 0000000000000000 20 4 29 10 -1
+   3: 0A00000A:9C41 0200000A:01BB 06 00000000:00000000 03:00000F5D 00000000     0        0 0 3 0000000000000000
+   4: 0100007F:9C42 0100007F:1F90 01 00000000:00000000 02:0009B5A3 00000000  1000        0 41236 2 0000000000000000 20 4 29 10 -1
+"#;
+
+    #[test]
+    fn test_parse_port_range() {
+        assert_eq!(
+            EphemeralPorts::parse_port_range("32768\t60999\n").unwrap(),
+            (32768, 60999)
+        );
+        assert!(EphemeralPorts::parse_port_range("32768").is_err());
+        assert!(EphemeralPorts::parse_port_range("60999\t32768").is_err());
+        assert!(EphemeralPorts::parse_port_range("32768\t70000").is_err());
+    }
+
+    #[test]
+    fn test_collect_ports() {
+        let mut ports = BTreeSet::new();
+        // 40000-40003
+        EphemeralPorts::collect_ports(PROC_NET_TCP, (40000, 40003), &mut ports).unwrap();
+        // the same port for two destinations only counts once, port 22 is out of range
+        assert_eq!(ports, BTreeSet::from([40000, 40001, 40002]));
+        assert_eq!(EphemeralPorts::percent((40000, 40003), ports.len()), 75);
+        assert!(EphemeralPorts::collect_ports(
+            "header\n   0: 00000000:XYZ 00000000:0000 01",
+            (40000, 40003),
+            &mut ports
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("sys/net/ipv4")).unwrap();
+        std::fs::create_dir_all(root.join("net")).unwrap();
+        std::fs::write(
+            root.join("sys/net/ipv4/ip_local_port_range"),
+            "40000\t40009\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("net/tcp"), PROC_NET_TCP).unwrap();
+        let ephemeral_ports = EphemeralPorts {
+            id: vec![String::from("Ephemeral")],
+            port_range_path: root.join("sys/net/ipv4/ip_local_port_range"),
+            proc_path: root.clone(),
+        };
+        let data = ephemeral_ports.get_data().await;
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(*data.unwrap()[0].as_ref().unwrap(), 30);
+    }
+}
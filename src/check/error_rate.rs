@@ -0,0 +1,119 @@
+use super::DataSource;
+use crate::Result;
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+pub struct ErrorRate {
+    id: Vec<String>,
+    error_count: Arc<AtomicU64>,
+    last_error_count: std::sync::Mutex<u64>,
+}
+
+impl ErrorRate {
+    // `error_count` is shared by all checks and actions, see `from_config`.
+    pub fn new(error_count: Arc<AtomicU64>) -> Self {
+        Self {
+            id: vec![String::from("MinMon")],
+            error_count,
+            last_error_count: std::sync::Mutex::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for ErrorRate {
+    type Item = u64;
+
+    // Returns the number of errors since the last cycle.
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let error_count = self.error_count.load(Ordering::Relaxed);
+        let mut last_error_count = self.last_error_count.lock().unwrap();
+        let errors = error_count - *last_error_count;
+        *last_error_count = error_count;
+        Ok(vec![Ok(errors)])
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("error count {}", data)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::factory_with_data_source;
+    use super::*;
+    use crate::{action, alarm, config, ActionMap};
+
+    fn error_count() -> Arc<AtomicU64> {
+        Arc::new(AtomicU64::new(0))
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let error_count = error_count();
+        let error_rate = ErrorRate::new(error_count.clone());
+        assert_eq!(
+            *error_rate.get_data().await.unwrap()[0].as_ref().unwrap(),
+            0
+        );
+        error_count.fetch_add(3, Ordering::Relaxed);
+        assert_eq!(
+            *error_rate.get_data().await.unwrap()[0].as_ref().unwrap(),
+            3
+        );
+        assert_eq!(
+            *error_rate.get_data().await.unwrap()[0].as_ref().unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_self_alarm() {
+        let config = config::Config::try_from(
+            r#"
+            [[checks]]
+            name = "test-check"
+            type = "ErrorRate"
+
+            [[checks.alarms]]
+            name = "test-alarm"
+            action = "test-action"
+            level = 10
+        "#,
+        )
+        .unwrap();
+        let mut mock_action = action::MockAction::new();
+        mock_action.expect_trigger().once().returning(|_| Ok(()));
+        let mut actions = ActionMap::new();
+        actions.insert(
+            String::from("test-action"),
+            std::sync::Arc::new(mock_action),
+        );
+        let error_count = error_count();
+        let mut check = factory_with_data_source::<ErrorRate, alarm::Level<u64>>(
+            ErrorRate::new(error_count.clone()),
+            &config.checks[0],
+            0,
+            &actions,
+            &super::super::ValueStore::default(),
+            &Default::default(),
+            &error_count,
+        )
+        .unwrap();
+        error_count.fetch_add(5, Ordering::Relaxed);
+        check.trigger().await;
+        for _ in 0..50 {
+            error_count.fetch_add(1, Ordering::Relaxed);
+        }
+        check.trigger().await;
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{Error, Result};
+
+// Minimal arithmetic expressions (+, -, *, / and parentheses) on numbers and variables.
+#[derive(PartialEq, Debug)]
+pub enum Expression {
+    Number(f64),
+    Variable(String),
+    Negate(Box<Expression>),
+    Binary(char, Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: Parser::tokenize(text)?,
+            position: 0,
+        };
+        let expression = parser.parse_sum()?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(expression),
+            Some(token) => Err(Error(format!("Unexpected token '{}'.", token))),
+        }
+    }
+
+    pub fn variables<'a>(&'a self, res: &mut Vec<&'a str>) {
+        match self {
+            Expression::Number(_) => {}
+            Expression::Variable(name) => res.push(name),
+            Expression::Negate(operand) => operand.variables(res),
+            Expression::Binary(_, left, right) => {
+                left.variables(res);
+                right.variables(res);
+            }
+        }
+    }
+
+    pub fn evaluate(&self, variables: &std::collections::HashMap<&str, f64>) -> Result<f64> {
+        match self {
+            Expression::Number(value) => Ok(*value),
+            Expression::Variable(name) => variables
+                .get(name.as_str())
+                .copied()
+                .ok_or_else(|| Error(format!("Unknown variable '{}'.", name))),
+            Expression::Negate(operand) => Ok(-operand.evaluate(variables)?),
+            Expression::Binary(operator, left, right) => {
+                let left = left.evaluate(variables)?;
+                let right = right.evaluate(variables)?;
+                match operator {
+                    '+' => Ok(left + right),
+                    '-' => Ok(left - right),
+                    '*' => Ok(left * right),
+                    _ if right == 0.0 => Err(Error(String::from("Division by zero."))),
+                    _ => Ok(left / right),
+                }
+            }
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    position: usize,
+}
+
+impl Parser {
+    fn tokenize(text: &str) -> Result<Vec<String>> {
+        let mut res = Vec::new();
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c.is_whitespace() {
+                continue;
+            }
+            let mut token = String::from(c);
+            if c.is_ascii_alphanumeric() || c == '_' || c == '.' {
+                while let Some(c) =
+                    chars.next_if(|x| x.is_ascii_alphanumeric() || *x == '_' || *x == '.')
+                {
+                    token.push(c);
+                }
+            } else if !"+-*/()".contains(c) {
+                return Err(Error(format!("Invalid character '{}'.", c)));
+            }
+            res.push(token);
+        }
+        Ok(res)
+    }
+
+    fn next_if(&mut self, tokens: &[&str]) -> Option<char> {
+        let token = self.tokens.get(self.position)?;
+        if tokens.contains(&token.as_str()) {
+            self.position += 1;
+            token.chars().next()
+        } else {
+            None
+        }
+    }
+
+    // sum := product (("+" | "-") product)*
+    fn parse_sum(&mut self) -> Result<Expression> {
+        let mut res = self.parse_product()?;
+        while let Some(operator) = self.next_if(&["+", "-"]) {
+            res = Expression::Binary(operator, Box::new(res), Box::new(self.parse_product()?));
+        }
+        Ok(res)
+    }
+
+    // product := factor (("*" | "/") factor)*
+    fn parse_product(&mut self) -> Result<Expression> {
+        let mut res = self.parse_factor()?;
+        while let Some(operator) = self.next_if(&["*", "/"]) {
+            res = Expression::Binary(operator, Box::new(res), Box::new(self.parse_factor()?));
+        }
+        Ok(res)
+    }
+
+    // factor := "-" factor | "(" sum ")" | number | variable
+    fn parse_factor(&mut self) -> Result<Expression> {
+        if self.next_if(&["-"]).is_some() {
+            return Ok(Expression::Negate(Box::new(self.parse_factor()?)));
+        }
+        if self.next_if(&["("]).is_some() {
+            let res = self.parse_sum()?;
+            return match self.next_if(&[")"]) {
+                Some(_) => Ok(res),
+                None => Err(Error(String::from("Missing closing parenthesis."))),
+            };
+        }
+        let token = self
+            .tokens
+            .get(self.position)
+            .ok_or_else(|| Error(String::from("Unexpected end of expression.")))?;
+        self.position += 1;
+        if token.starts_with(|x: char| x.is_ascii_digit() || x == '.') {
+            token
+                .parse()
+                .map(Expression::Number)
+                .map_err(|x| Error(format!("Invalid number '{}': {}", token, x)))
+        } else if token.starts_with(|x: char| x.is_ascii_alphabetic() || x == '_') {
+            Ok(Expression::Variable(token.clone()))
+        } else {
+            Err(Error(format!("Unexpected token '{}'.", token)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Expression::parse("1 + 2 * x").unwrap(),
+            Expression::Binary(
+                '+',
+                Box::new(Expression::Number(1.0)),
+                Box::new(Expression::Binary(
+                    '*',
+                    Box::new(Expression::Number(2.0)),
+                    Box::new(Expression::Variable(String::from("x")))
+                ))
+            )
+        );
+        assert!(Expression::parse("(1 + 2").is_err());
+        assert!(Expression::parse("1 +").is_err());
+        assert!(Expression::parse("1 2").is_err());
+        assert!(Expression::parse("1 % 2").is_err());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let variables = std::collections::HashMap::from([("a", 6.0), ("b", 4.0)]);
+        let evaluate = |x| Expression::parse(x).unwrap().evaluate(&variables);
+        assert_eq!(evaluate("a - b - 1").unwrap(), 1.0);
+        assert_eq!(evaluate("-(a + b) / 2").unwrap(), -5.0);
+        assert_eq!(evaluate("2.5 * b").unwrap(), 10.0);
+        assert!(evaluate("a / (b - 4)").is_err());
+    }
+}
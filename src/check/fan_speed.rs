@@ -0,0 +1,170 @@
+use super::temperature::{Sensor, Temperature};
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct FanRpm {
+    rpm: u64,
+    // label of the slowest matching fan
+    fan: String,
+}
+
+impl std::fmt::Display for FanRpm {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.rpm)
+    }
+}
+
+impl LevelItem for FanRpm {
+    fn level(&self) -> f64 {
+        self.rpm as f64
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("fan"), self.fan.clone());
+    }
+}
+
+pub struct FanSpeed {
+    id: Vec<String>,
+    hwmon_path: std::path::PathBuf,
+    sys_path: std::path::PathBuf,
+}
+
+impl FanSpeed {
+    // Unlike temperature sensors, a fan that cannot be read is an error, as some drivers fail to
+    // report a stalled fan.
+    async fn speed(pattern: &str, fans: &[Sensor]) -> Result<FanRpm> {
+        let mut res: Option<FanRpm> = None;
+        for fan in fans.iter().filter(|x| x.label.contains(pattern)) {
+            let rpm: u64 = Temperature::read_trimmed(&fan.input_path)
+                .await
+                .and_then(|x| x.parse().ok())
+                .ok_or_else(|| Error(format!("Could not read fan '{}'.", fan.label)))?;
+            if res.as_ref().is_none_or(|x| rpm < x.rpm) {
+                res = Some(FanRpm {
+                    rpm,
+                    fan: fan.label.clone(),
+                });
+            }
+        }
+        res.ok_or_else(|| Error(String::from("No matching fan found.")))
+    }
+}
+
+impl TryFrom<&config::Check> for FanSpeed {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::FanSpeed(fan_speed) = &check.type_ {
+            if fan_speed.fans.is_empty() {
+                Err(Error(String::from("'fans' cannot be empty.")))
+            } else if fan_speed.fans.iter().any(|x| x.is_empty()) {
+                Err(Error(String::from("'fans' cannot contain empty labels.")))
+            } else {
+                Ok(Self {
+                    id: fan_speed.fans.clone(),
+                    hwmon_path: check.sys_path.join("class/hwmon"),
+                    sys_path: check.sys_path.clone(),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for FanSpeed {
+    type Item = FanRpm;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let fans = Temperature::sensors(&self.hwmon_path, &self.sys_path, "fan").await?;
+        let mut res = Vec::new();
+        for pattern in self.id.iter() {
+            res.push(Self::speed(pattern, &fans).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("{} RPM", data.rpm)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.level())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        let hwmon_path = root.join("class/hwmon");
+        for (device, name, files) in [
+            (
+                "hwmon0",
+                "nct6775",
+                vec![
+                    ("fan1_label", "CPU Fan"),
+                    ("fan1_input", "1250"),
+                    ("fan2_input", "0"),
+                    ("temp1_input", "42000"),
+                ],
+            ),
+            ("hwmon1", "thinkpad", vec![("fan1_input", "")]),
+        ] {
+            std::fs::create_dir_all(hwmon_path.join(device)).unwrap();
+            std::fs::write(hwmon_path.join(device).join("name"), format!("{}\n", name)).unwrap();
+            for (file, content) in files {
+                std::fs::write(hwmon_path.join(device).join(file), content).unwrap();
+            }
+        }
+        let fan_speed = FanSpeed {
+            id: vec![
+                String::from("CPU Fan"),
+                String::from("nct6775"),
+                String::from("thinkpad"),
+                String::from("dell_smm"),
+            ],
+            hwmon_path,
+            sys_path: root.clone(),
+        };
+        let data = fan_speed.get_data().await.unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        let cpu_fan = data[0].as_ref().unwrap();
+        assert_eq!(FanSpeed::format_data(cpu_fan), "1250 RPM");
+        // the slowest matching fan is reported
+        let stalled = data[1].as_ref().unwrap();
+        assert_eq!(stalled.rpm, 0);
+        let mut placeholders = PlaceholderMap::new();
+        stalled.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("fan").unwrap(), "nct6775 fan2");
+        assert_eq!(
+            data[2].as_ref().unwrap_err().to_string(),
+            "Could not read fan 'thinkpad fan1'."
+        );
+        assert_eq!(
+            data[3].as_ref().unwrap_err().to_string(),
+            "No matching fan found."
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+pub struct FileAge {
+    id: Vec<String>,
+    paths: Vec<std::path::PathBuf>,
+}
+
+impl FileAge {
+    // Returns the time since the last modification in seconds.
+    async fn get_age(path: &std::path::Path) -> Result<u64> {
+        let metadata = tokio::fs::metadata(path)
+            .await
+            .map_err(|x| Error(format!("Could not stat {}: {}", path.display(), x)))?;
+        let modified = metadata.modified().map_err(|x| {
+            Error(format!(
+                "Could not get modification time of {}: {}",
+                path.display(),
+                x
+            ))
+        })?;
+        // Modification times in the future count as age 0.
+        Ok(modified.elapsed().map(|x| x.as_secs()).unwrap_or(0))
+    }
+}
+
+impl TryFrom<&config::Check> for FileAge {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::FileAge(file_age) = &check.type_ {
+            if file_age.paths.iter().any(|x| x.as_os_str().is_empty()) {
+                Err(Error(String::from("'paths' cannot contain empty paths.")))
+            } else {
+                Ok(Self {
+                    id: file_age
+                        .paths
+                        .iter()
+                        .map(|x| x.display().to_string())
+                        .collect(),
+                    paths: file_age.paths.clone(),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for FileAge {
+    type Item = u64;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mut res = Vec::new();
+        for path in self.paths.iter() {
+            res.push(Self::get_age(path).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("age {}s", data)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let now = std::time::SystemTime::now();
+        for (name, modified) in [
+            ("backup.tar", now - std::time::Duration::from_secs(7200)),
+            ("future", now + std::time::Duration::from_secs(7200)),
+        ] {
+            std::fs::File::create(root.join(name))
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+        let file_age = FileAge {
+            id: Vec::new(),
+            paths: vec![
+                root.join("backup.tar"),
+                root.join("missing"),
+                root.join("future"),
+            ],
+        };
+        let data = file_age.get_data().await;
+        std::fs::remove_dir_all(&root).unwrap();
+        let data = data.unwrap();
+        let age = *data[0].as_ref().unwrap();
+        assert!((7200..7300).contains(&age));
+        // a missing file is an error for its ID only
+        assert!(data[1].is_err());
+        assert_eq!(*data[2].as_ref().unwrap(), 0);
+    }
+}
@@ -0,0 +1,215 @@
+use super::process_presence::{Process, ProcessPresence};
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+pub struct FileDescriptors {
+    id: Vec<String>,
+    // empty for the system-wide usage
+    processes: Vec<String>,
+    proc_path: std::path::PathBuf,
+}
+
+impl FileDescriptors {
+    fn usage(open: u64, limit: u64) -> Result<u8> {
+        (open * 100)
+            .checked_div(limit)
+            .map(|x| x.min(100) as u8)
+            .ok_or_else(|| Error(String::from("File descriptor limit is 0.")))
+    }
+
+    // e.g. "2016\t0\t9223372036854775807", the second number is always 0 since Linux 2.6
+    fn parse_file_nr(content: &str) -> Result<u8> {
+        let error_message = "Could not parse file-nr";
+        let allocated: u64 = crate::get_number(error_message, content, 0)?;
+        let unused: u64 = crate::get_number(error_message, content, 1)?;
+        let max = crate::get_number(error_message, content, 2)?;
+        Self::usage(allocated.saturating_sub(unused), max)
+    }
+
+    // Returns the soft limit from e.g. "Max open files   1024   524288   files".
+    fn parse_limits(content: &str) -> Result<u64> {
+        let line = content
+            .lines()
+            .find(|x| x.starts_with("Max open files"))
+            .ok_or_else(|| Error(String::from("Max open files not found.")))?;
+        crate::get_number("Could not parse max open files", line, 3)
+    }
+
+    async fn system_usage(&self) -> Result<u8> {
+        let path = self.proc_path.join("sys/fs/file-nr");
+        let content = tokio::fs::read_to_string(&path).await.map_err(|x| {
+            super::proc_error(
+                &self.proc_path,
+                format!("Could not read from {}: {}", path.display(), x),
+            )
+        })?;
+        Self::parse_file_nr(&content)
+    }
+
+    // Returns None if the process exited in the meantime.
+    async fn process_usage(&self, pid: u32) -> Result<Option<u8>> {
+        let path = self.proc_path.join(pid.to_string());
+        let limits = match tokio::fs::read_to_string(path.join("limits")).await {
+            Ok(limits) => limits,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(Error(format!(
+                    "Could not read from {}: {}",
+                    path.join("limits").display(),
+                    err
+                )))
+            }
+        };
+        let fd_path = path.join("fd");
+        let mut entries = match tokio::fs::read_dir(&fd_path).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            // The fds of other users' processes can only be listed by root.
+            Err(err) => {
+                return Err(Error(format!(
+                    "Could not read directory {}: {}",
+                    fd_path.display(),
+                    err
+                )))
+            }
+        };
+        let mut open = 0;
+        while let Ok(Some(_)) = entries.next_entry().await {
+            open += 1;
+        }
+        Ok(Some(Self::usage(open, Self::parse_limits(&limits)?)?))
+    }
+
+    // If several processes match, the highest usage is reported.
+    async fn pattern_usage(&self, pattern: &str, processes: &[Process]) -> Result<u8> {
+        let mut res = None;
+        for process in processes
+            .iter()
+            .filter(|x| ProcessPresence::matches(Some(pattern), None, x))
+        {
+            if let Some(usage) = self.process_usage(process.pid).await? {
+                res = res.max(Some(usage));
+            }
+        }
+        res.ok_or_else(|| Error(String::from("No matching process found.")))
+    }
+}
+
+impl TryFrom<&config::Check> for FileDescriptors {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::FileDescriptors(file_descriptors) = &check.type_ {
+            if file_descriptors.processes.iter().any(|x| x.is_empty()) {
+                Err(Error(String::from(
+                    "'processes' cannot contain empty patterns.",
+                )))
+            } else {
+                Ok(Self {
+                    id: if file_descriptors.processes.is_empty() {
+                        vec![String::from("system")]
+                    } else {
+                        file_descriptors.processes.clone()
+                    },
+                    processes: file_descriptors.processes.clone(),
+                    proc_path: check.proc_path.clone(),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for FileDescriptors {
+    type Item = u8;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        if self.processes.is_empty() {
+            return Ok(vec![self.system_usage().await]);
+        }
+        let processes = ProcessPresence::processes(&self.proc_path, false).await?;
+        let mut res = Vec::new();
+        for pattern in self.processes.iter() {
+            res.push(self.pattern_usage(pattern, &processes).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("file descriptor usage level {}", data)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LIMITS: &str = "Limit                     Soft Limit           Hard Limit           Units
+Max cpu time              unlimited            unlimited            seconds
+Max open files            200                  524288               files
+Max locked memory         8388608              8388608              bytes
+";
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            FileDescriptors::parse_file_nr("2016\t0\t4032\n").unwrap(),
+            50
+        );
+        assert!(FileDescriptors::parse_file_nr("2016\t0\t0\n").is_err());
+        assert!(FileDescriptors::parse_file_nr("").is_err());
+        assert_eq!(FileDescriptors::parse_limits(LIMITS).unwrap(), 200);
+        assert!(FileDescriptors::parse_limits("Max cpu time unlimited\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("sys/fs")).unwrap();
+        std::fs::write(root.join("sys/fs/file-nr"), "1000\t0\t10000\n").unwrap();
+        for (pid, comm, open) in [
+            ("100", "nginx", 20),
+            ("101", "nginx", 50),
+            ("200", "sshd", 2),
+        ] {
+            std::fs::create_dir_all(root.join(pid).join("fd")).unwrap();
+            std::fs::write(root.join(pid).join("comm"), format!("{}\n", comm)).unwrap();
+            std::fs::write(root.join(pid).join("limits"), LIMITS).unwrap();
+            for fd in 0..open {
+                std::fs::write(root.join(pid).join("fd").join(fd.to_string()), "").unwrap();
+            }
+        }
+        let mut file_descriptors = FileDescriptors {
+            id: Vec::new(),
+            processes: Vec::new(),
+            proc_path: root.clone(),
+        };
+        let system = file_descriptors.get_data().await.unwrap();
+        file_descriptors.processes = vec![
+            String::from("nginx"),
+            String::from("ssh*"),
+            String::from("postgres"),
+        ];
+        let processes = file_descriptors.get_data().await.unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(*system[0].as_ref().unwrap(), 10);
+        assert_eq!(*processes[0].as_ref().unwrap(), 25);
+        assert_eq!(*processes[1].as_ref().unwrap(), 1);
+        assert_eq!(
+            processes[2].as_ref().unwrap_err().to_string(),
+            "No matching process found."
+        );
+    }
+}
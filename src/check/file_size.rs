@@ -0,0 +1,127 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+pub struct FileSize {
+    id: Vec<String>,
+    paths: Vec<std::path::PathBuf>,
+    follow_symlinks: bool,
+}
+
+impl FileSize {
+    async fn get_size(&self, path: &std::path::Path) -> Result<u64> {
+        let metadata = if self.follow_symlinks {
+            tokio::fs::metadata(path).await
+        } else {
+            tokio::fs::symlink_metadata(path).await
+        }
+        .map_err(|x| Error(format!("Could not stat {}: {}", path.display(), x)))?;
+        Ok(metadata.len())
+    }
+
+    // e.g. "1.3 GiB"
+    fn human_size(size: u64) -> String {
+        const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+        if size < 1024 {
+            return format!("{} B", size);
+        }
+        let mut value = size as f64 / 1024.0;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+impl TryFrom<&config::Check> for FileSize {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::FileSize(file_size) = &check.type_ {
+            if file_size.paths.iter().any(|x| x.as_os_str().is_empty()) {
+                Err(Error(String::from("'paths' cannot contain empty paths.")))
+            } else {
+                Ok(Self {
+                    id: file_size
+                        .paths
+                        .iter()
+                        .map(|x| x.display().to_string())
+                        .collect(),
+                    paths: file_size.paths.clone(),
+                    follow_symlinks: file_size.follow_symlinks,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for FileSize {
+    type Item = u64;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mut res = Vec::new();
+        for path in self.paths.iter() {
+            res.push(self.get_size(path).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("size {}", Self::human_size(*data))
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(FileSize::human_size(0), "0 B");
+        assert_eq!(FileSize::human_size(1023), "1023 B");
+        assert_eq!(FileSize::human_size(1536), "1.5 KiB");
+        assert_eq!(FileSize::human_size(1395864371), "1.3 GiB");
+        assert_eq!(FileSize::human_size(u64::MAX), "16.0 EiB");
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("export.csv"), "a,b\n1,2\n").unwrap();
+        std::os::unix::fs::symlink(root.join("export.csv"), root.join("latest.csv")).unwrap();
+        let paths = vec![root.join("latest.csv"), root.join("missing")];
+        let mut data = Vec::new();
+        for follow_symlinks in [true, false] {
+            let file_size = FileSize {
+                id: Vec::new(),
+                paths: paths.clone(),
+                follow_symlinks,
+            };
+            data.push(file_size.get_data().await.unwrap());
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(*data[0][0].as_ref().unwrap(), 8);
+        // the size of the link itself is the length of the target path
+        assert_eq!(
+            *data[1][0].as_ref().unwrap(),
+            root.join("export.csv").as_os_str().len() as u64
+        );
+        // a missing file is an error for its ID only
+        assert!(data[0][1].is_err());
+    }
+}
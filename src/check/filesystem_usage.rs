@@ -52,4 +52,8 @@ impl DataSource for FilesystemUsage {
     fn ids(&self) -> &[String] {
         &self.mountpoints[..]
     }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data as f64)
+    }
 }
@@ -0,0 +1,576 @@
+use super::DataSource;
+use crate::alarm::StatusItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ImageStatus {
+    image: String,
+    up_to_date: bool,
+    local_digest: String,
+    remote_digest: String,
+}
+
+impl StatusItem for ImageStatus {
+    fn is_ok(&self) -> bool {
+        self.up_to_date
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("image"), self.image.clone());
+        placeholders.insert(String::from("local_digest"), self.local_digest.clone());
+        placeholders.insert(String::from("remote_digest"), self.remote_digest.clone());
+    }
+}
+
+// Docker Hub images are normalized, e.g. "nginx" becomes "registry-1.docker.io/library/nginx".
+#[derive(PartialEq, Debug)]
+struct ImageReference {
+    registry: String,
+    repository: String,
+    tag: String,
+}
+
+impl ImageReference {
+    const DOCKER_HUB: &'static str = "registry-1.docker.io";
+
+    fn parse(image: &str) -> Result<Self> {
+        if image.contains('@') {
+            return Err(Error(format!(
+                "Image '{}' is pinned to a digest and cannot be updated.",
+                image
+            )));
+        }
+        let (registry, remainder) = match image.split_once('/') {
+            Some((first, remainder)) if first.contains(['.', ':']) || first == "localhost" => {
+                (String::from(first), remainder)
+            }
+            _ => (String::from(Self::DOCKER_HUB), image),
+        };
+        let (repository, tag) = match remainder.rsplit_once(':') {
+            Some((repository, tag)) if !tag.contains('/') => (repository, tag),
+            _ => (remainder, "latest"),
+        };
+        if repository.is_empty() || tag.is_empty() {
+            return Err(Error(format!("Invalid image reference '{}'.", image)));
+        }
+        let repository = if registry == Self::DOCKER_HUB && !repository.contains('/') {
+            format!("library/{}", repository)
+        } else {
+            String::from(repository)
+        };
+        Ok(Self {
+            registry,
+            repository,
+            tag: String::from(tag),
+        })
+    }
+
+    // e.g. "nginx@sha256:0d17b565c37bcbd895e9d92315a05c1c3c9a29f762b011a10c54a66cd53c9b31"
+    fn matches_repo_digest(&self, repo_digest: &str) -> Option<String> {
+        let (name, digest) = repo_digest.split_once('@')?;
+        let reference = Self::parse(name).ok()?;
+        if reference.registry == self.registry && reference.repository == self.repository {
+            Some(String::from(digest))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Container {
+    image: String,
+    config: ContainerConfig,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContainerConfig {
+    image: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Image {
+    #[serde(default)]
+    repo_digests: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct Token {
+    #[serde(default)]
+    token: Option<String>,
+    #[serde(default)]
+    access_token: Option<String>,
+}
+
+// Media types of single- and multi-platform manifests.
+const MANIFEST_TYPES: [&str; 4] = [
+    "application/vnd.oci.image.index.v1+json",
+    "application/vnd.docker.distribution.manifest.list.v2+json",
+    "application/vnd.oci.image.manifest.v1+json",
+    "application/vnd.docker.distribution.manifest.v2+json",
+];
+
+pub struct ImageUpdate {
+    id: Vec<String>,
+    docker_host: String,
+    insecure_registries: Vec<String>,
+    username: Option<String>,
+    password: Option<String>,
+    resolver: Option<std::sync::Arc<crate::resolver::Resolver>>,
+    capture: crate::capture::Capture,
+}
+
+impl ImageUpdate {
+    // The Docker API is either reached via its UNIX socket or via HTTP.
+    async fn docker_get(&self, client: &reqwest::Client, path: &str) -> Result<Vec<u8>> {
+        let (status, body) = if self.docker_host.starts_with("http://")
+            || self.docker_host.starts_with("https://")
+        {
+            let response = client
+                .get(format!("{}{}", self.docker_host, path))
+                .send()
+                .await
+                .map_err(|x| Error(format!("Docker API request failed: {}", x)))?;
+            let status = response.status().as_u16();
+            let body = self
+                .capture
+                .body(response)
+                .await
+                .map_err(|x| Error(format!("Could not read Docker API response: {}", x)))?;
+            (status, body)
+        } else {
+            Self::unix_socket_get(&self.docker_host, path).await?
+        };
+        if status != 200 {
+            return Err(Error(format!(
+                "Docker API returned HTTP status code {} for {}.",
+                status, path
+            )));
+        }
+        Ok(body)
+    }
+
+    // HTTP/1.0 is used so the response is neither chunked nor kept alive.
+    async fn unix_socket_get(socket_path: &str, path: &str) -> Result<(u16, Vec<u8>)> {
+        let mut stream = tokio::net::UnixStream::connect(socket_path)
+            .await
+            .map_err(|x| Error(format!("Could not connect to {}: {}", socket_path, x)))?;
+        stream
+            .write_all(format!("GET {} HTTP/1.0\r\nHost: docker\r\n\r\n", path).as_bytes())
+            .await
+            .map_err(|x| Error(format!("Could not write to {}: {}", socket_path, x)))?;
+        let mut response = Vec::new();
+        stream
+            .read_to_end(&mut response)
+            .await
+            .map_err(|x| Error(format!("Could not read from {}: {}", socket_path, x)))?;
+        Self::parse_response(&response)
+    }
+
+    fn parse_response(response: &[u8]) -> Result<(u16, Vec<u8>)> {
+        let separator = response
+            .windows(4)
+            .position(|x| x == b"\r\n\r\n")
+            .ok_or_else(|| Error(String::from("Invalid HTTP response.")))?;
+        let head = String::from_utf8_lossy(&response[..separator]);
+        let status = crate::get_number("Invalid HTTP status line", &head, 1)?;
+        Ok((status, response[separator + 4..].to_vec()))
+    }
+
+    async fn local_image(&self, client: &reqwest::Client, container: &str) -> Result<Container> {
+        let body = self
+            .docker_get(client, &format!("/containers/{}/json", container))
+            .await?;
+        serde_json::from_slice(&body)
+            .map_err(|x| Error(format!("Invalid container details: {}", x)))
+    }
+
+    async fn local_digests(
+        &self,
+        client: &reqwest::Client,
+        image_id: &str,
+        reference: &ImageReference,
+    ) -> Result<Vec<String>> {
+        let body = self
+            .docker_get(client, &format!("/images/{}/json", image_id))
+            .await?;
+        let image: Image = serde_json::from_slice(&body)
+            .map_err(|x| Error(format!("Invalid image details: {}", x)))?;
+        let digests: Vec<String> = image
+            .repo_digests
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|x| reference.matches_repo_digest(x))
+            .collect();
+        if digests.is_empty() {
+            Err(Error(String::from(
+                "Image has no digest for its repository (e.g. because it was built locally).",
+            )))
+        } else {
+            Ok(digests)
+        }
+    }
+
+    // e.g. 'Bearer realm="https://auth.docker.io/token",service="registry.docker.io"'
+    fn parse_challenge(header: &str) -> Option<Vec<(String, String)>> {
+        let mut chars = header.strip_prefix("Bearer ")?.chars().peekable();
+        let mut res = Vec::new();
+        loop {
+            while chars.next_if(|x| *x == ',' || x.is_whitespace()).is_some() {}
+            let key: String = std::iter::from_fn(|| chars.next_if(|x| *x != '=')).collect();
+            if key.is_empty() {
+                return Some(res);
+            }
+            chars.next()?;
+            let value: String = if chars.next_if_eq(&'"').is_some() {
+                let value = std::iter::from_fn(|| chars.next_if(|x| *x != '"')).collect();
+                chars.next()?;
+                value
+            } else {
+                std::iter::from_fn(|| chars.next_if(|x| *x != ',')).collect()
+            };
+            res.push((key, value));
+        }
+    }
+
+    async fn get_token(&self, client: &reqwest::Client, challenge: &str) -> Result<String> {
+        let mut parameters = Self::parse_challenge(challenge)
+            .ok_or_else(|| Error(format!("Unsupported authentication '{}'.", challenge)))?;
+        let realm = match parameters.iter().position(|(key, _)| key == "realm") {
+            Some(index) => parameters.remove(index).1,
+            None => return Err(Error(String::from("Missing realm in authentication."))),
+        };
+        let mut request = client.get(realm).query(&parameters);
+        if let Some(username) = &self.username {
+            request = request.basic_auth(username, self.password.as_ref());
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|x| Error(format!("Token request failed: {}", x)))?;
+        if !response.status().is_success() {
+            return Err(Error(format!(
+                "Token endpoint returned HTTP status code {}.",
+                response.status().as_u16()
+            )));
+        }
+        let body = self
+            .capture
+            .body(response)
+            .await
+            .map_err(|x| Error(format!("Invalid token response: {}", x)))?;
+        let token: Token = serde_json::from_slice(&body)
+            .map_err(|x| Error(format!("Invalid token response: {}", x)))?;
+        token
+            .token
+            .or(token.access_token)
+            .ok_or_else(|| Error(String::from("Token response does not contain a token.")))
+    }
+
+    async fn remote_digest(
+        &self,
+        client: &reqwest::Client,
+        reference: &ImageReference,
+    ) -> Result<String> {
+        let scheme = if self.insecure_registries.contains(&reference.registry) {
+            "http"
+        } else {
+            "https"
+        };
+        let url = format!(
+            "{}://{}/v2/{}/manifests/{}",
+            scheme, reference.registry, reference.repository, reference.tag
+        );
+        let mut token: Option<String> = None;
+        loop {
+            let mut request = client
+                .get(&url)
+                .header(reqwest::header::ACCEPT, MANIFEST_TYPES.join(", "));
+            if let Some(token) = &token {
+                request = request.bearer_auth(token);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|x| Error(format!("Registry request failed: {}", x)))?;
+            let status = response.status().as_u16();
+            let challenge = response
+                .headers()
+                .get(reqwest::header::WWW_AUTHENTICATE)
+                .and_then(|x| x.to_str().ok())
+                .map(String::from);
+            match (status, challenge) {
+                (401, Some(challenge)) if token.is_none() => {
+                    token = Some(self.get_token(client, &challenge).await?);
+                    continue;
+                }
+                (429, _) => return Err(Error(String::from("Registry rate limit exceeded."))),
+                (200, _) => {}
+                (status, _) => {
+                    return Err(Error(format!(
+                        "Registry returned HTTP status code {}.",
+                        status
+                    )))
+                }
+            }
+            // The digest is the hash of the manifest if the registry does not provide it.
+            if let Some(digest) = response
+                .headers()
+                .get("docker-content-digest")
+                .and_then(|x| x.to_str().ok())
+            {
+                return Ok(String::from(digest));
+            }
+            let body = self
+                .capture
+                .body(response)
+                .await
+                .map_err(|x| Error(format!("Could not read manifest: {}", x)))?;
+            return Ok(format!(
+                "sha256:{}",
+                openssl::sha::sha256(&body)
+                    .iter()
+                    .map(|x| format!("{:02x}", x))
+                    .collect::<String>()
+            ));
+        }
+    }
+
+    async fn get_status(&self, client: &reqwest::Client, container: &str) -> Result<ImageStatus> {
+        let container = self.local_image(client, container).await?;
+        let reference = ImageReference::parse(&container.config.image)?;
+        let local_digests = self
+            .local_digests(client, &container.image, &reference)
+            .await?;
+        let remote_digest = self.remote_digest(client, &reference).await?;
+        let up_to_date = local_digests.contains(&remote_digest);
+        Ok(ImageStatus {
+            image: container.config.image,
+            up_to_date,
+            local_digest: if up_to_date {
+                remote_digest.clone()
+            } else {
+                local_digests[0].clone()
+            },
+            remote_digest,
+        })
+    }
+}
+
+impl TryFrom<&config::Check> for ImageUpdate {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::ImageUpdate(image_update) = &check.type_ {
+            if image_update.containers.iter().any(|x| x.is_empty()) {
+                Err(Error(String::from(
+                    "'containers' cannot contain empty container names.",
+                )))
+            } else if image_update.docker_host.is_empty() {
+                Err(Error(String::from("'docker_host' cannot be empty.")))
+            } else if image_update.password.is_some() && image_update.username.is_none() {
+                Err(Error(String::from(
+                    "'password' cannot be set without 'username'.",
+                )))
+            } else {
+                Ok(Self {
+                    id: image_update.containers.clone(),
+                    docker_host: image_update.docker_host.clone(),
+                    insecure_registries: image_update.insecure_registries.clone(),
+                    username: image_update.username.clone(),
+                    password: image_update.password.clone(),
+                    resolver: crate::resolver::from_config(check.resolver.as_ref())?,
+                    capture: crate::capture::from_config(check.capture.as_ref())?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for ImageUpdate {
+    type Item = ImageStatus;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let client = crate::resolver::http_client_builder(self.resolver.as_ref())
+            .build()
+            .map_err(|x| Error(format!("Could not create HTTP client: {}", x)))?;
+        let mut res = Vec::new();
+        for container in self.id.iter() {
+            res.push(self.get_status(&client, container).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        if data.up_to_date {
+            format!("image {} is up to date", data.image)
+        } else {
+            format!("newer image available for {}", data.image)
+        }
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::mock_server;
+
+    const MANIFEST: &str = r#"{"schemaVersion": 2, "manifests": []}"#;
+
+    #[test]
+    fn test_parse_reference() {
+        let parse = |x| ImageReference::parse(x).unwrap();
+        assert_eq!(
+            parse("nginx"),
+            ImageReference {
+                registry: String::from("registry-1.docker.io"),
+                repository: String::from("library/nginx"),
+                tag: String::from("latest"),
+            }
+        );
+        assert_eq!(parse("grafana/grafana:10.2").repository, "grafana/grafana");
+        assert_eq!(parse("grafana/grafana:10.2").tag, "10.2");
+        let reference = parse("localhost:5000/team/app:stable");
+        assert_eq!(reference.registry, "localhost:5000");
+        assert_eq!(reference.repository, "team/app");
+        assert_eq!(reference.tag, "stable");
+        assert_eq!(parse("ghcr.io/foo/bar").tag, "latest");
+        assert!(ImageReference::parse("nginx@sha256:1234").is_err());
+        assert!(ImageReference::parse("nginx:").is_err());
+        assert_eq!(
+            parse("nginx:1.25").matches_repo_digest("nginx@sha256:1234"),
+            Some(String::from("sha256:1234"))
+        );
+        assert_eq!(
+            parse("nginx:1.25").matches_repo_digest("docker.io/foo/nginx@sha256:1234"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_challenge() {
+        assert_eq!(
+            ImageUpdate::parse_challenge(
+                r#"Bearer realm="https://auth.docker.io/token",service="registry.docker.io",scope="repository:library/nginx:pull,push""#
+            )
+            .unwrap(),
+            vec![
+                (
+                    String::from("realm"),
+                    String::from("https://auth.docker.io/token")
+                ),
+                (
+                    String::from("service"),
+                    String::from("registry.docker.io")
+                ),
+                (
+                    String::from("scope"),
+                    String::from("repository:library/nginx:pull,push")
+                ),
+            ]
+        );
+        assert!(ImageUpdate::parse_challenge("Basic realm=\"foo\"").is_none());
+    }
+
+    #[test]
+    fn test_parse_response() {
+        assert_eq!(
+            ImageUpdate::parse_response(b"HTTP/1.0 404 Not Found\r\nFoo: bar\r\n\r\n{}").unwrap(),
+            (404, b"{}".to_vec())
+        );
+        assert!(ImageUpdate::parse_response(b"HTTP/1.0 200 OK\r\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let host = std::sync::Arc::new(std::sync::Mutex::new(String::new()));
+        let host_clone = host.clone();
+        let url = mock_server(move |request| {
+            let host = host_clone.lock().unwrap().clone();
+            let path = request.split_whitespace().nth(1).unwrap();
+            let container = |image_id: &str, tag: &str| {
+                format!(
+                    r#"{{"Image": "{}", "Config": {{"Image": "{}/team/app:{}"}}}}"#,
+                    image_id, host, tag
+                )
+            };
+            let digest = format!(
+                "sha256:{}",
+                openssl::sha::sha256(MANIFEST.as_bytes())
+                    .iter()
+                    .map(|x| format!("{:02x}", x))
+                    .collect::<String>()
+            );
+            match path {
+                "/containers/current/json" => (200, container("sha256:1111", "stable")),
+                "/containers/outdated/json" => (200, container("sha256:2222", "stable")),
+                "/containers/limited/json" => (200, container("sha256:1111", "limited")),
+                "/images/sha256:1111/json" => (
+                    200,
+                    format!(
+                        r#"{{"RepoDigests": ["nginx@sha256:0000", "{}/team/app@{}"]}}"#,
+                        host, digest
+                    ),
+                ),
+                "/images/sha256:2222/json" => (
+                    200,
+                    format!(r#"{{"RepoDigests": ["{}/team/app@sha256:0000"]}}"#, host),
+                ),
+                "/v2/team/app/manifests/stable" => {
+                    assert!(request.contains("application/vnd.oci.image.index.v1+json"));
+                    (200, String::from(MANIFEST))
+                }
+                "/v2/team/app/manifests/limited" => (429, String::new()),
+                _ => (404, String::from(r#"{"message": "No such container"}"#)),
+            }
+        })
+        .await;
+        let registry = url.strip_prefix("http://").unwrap();
+        *host.lock().unwrap() = String::from(registry);
+        let image_update = ImageUpdate {
+            id: vec![
+                String::from("current"),
+                String::from("outdated"),
+                String::from("limited"),
+                String::from("missing"),
+            ],
+            docker_host: url.clone(),
+            insecure_registries: vec![String::from(registry)],
+            username: None,
+            password: None,
+            resolver: None,
+            capture: crate::capture::Capture::default(),
+        };
+        let data = image_update.get_data().await.unwrap();
+        let current = data[0].as_ref().unwrap();
+        assert!(current.is_ok());
+        assert_eq!(current.local_digest, current.remote_digest);
+        let outdated = data[1].as_ref().unwrap();
+        assert!(!outdated.is_ok());
+        assert_eq!(outdated.local_digest, "sha256:0000");
+        assert_eq!(outdated.remote_digest, current.remote_digest);
+        let mut placeholders = PlaceholderMap::new();
+        outdated.add_placeholders(&mut placeholders);
+        assert_eq!(
+            placeholders.get("image").unwrap(),
+            &format!("{}/team/app:stable", registry)
+        );
+        assert_eq!(placeholders.get("local_digest").unwrap(), "sha256:0000");
+        assert!(data[2].is_err());
+        assert!(data[3].is_err());
+    }
+}
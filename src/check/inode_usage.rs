@@ -0,0 +1,88 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+pub struct InodeUsage {
+    mountpoints: Vec<String>,
+}
+
+impl InodeUsage {
+    fn usage(files: u64, files_free: u64) -> Result<u8> {
+        // e.g. btrfs allocates inodes dynamically and reports 0
+        (files.saturating_sub(files_free) * 100)
+            .checked_div(files)
+            .map(|x| x as u8)
+            .ok_or_else(|| Error(String::from("Filesystem does not report an inode count.")))
+    }
+}
+
+impl TryFrom<&config::Check> for InodeUsage {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::InodeUsage(inode_usage) = &check.type_ {
+            if inode_usage.mountpoints.iter().any(|x| x.is_empty()) {
+                Err(Error(String::from(
+                    "'mountpoints' cannot contain empty paths.",
+                )))
+            } else {
+                Ok(Self {
+                    mountpoints: inode_usage.mountpoints.clone(),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for InodeUsage {
+    type Item = u8;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mut res = Vec::new();
+        for mountpoint in self.mountpoints.iter() {
+            res.push(match nix::sys::statvfs::statvfs(mountpoint.as_str()) {
+                Err(err) => Err(Error(format!("Call to 'statvfs' failed: {}", err))),
+                Ok(stat) => Self::usage(stat.files(), stat.files_free()),
+            })
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("inode usage level {}", data)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.mountpoints[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_usage() {
+        assert_eq!(InodeUsage::usage(1000, 250).unwrap(), 75);
+        assert_eq!(InodeUsage::usage(1000, 1000).unwrap(), 0);
+        assert!(InodeUsage::usage(0, 0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let inode_usage = InodeUsage {
+            mountpoints: vec![String::from("/"), String::from("/does/not/exist")],
+        };
+        let data = inode_usage.get_data().await.unwrap();
+        assert_eq!(data.len(), 2);
+        assert!(data[1].is_err());
+    }
+}
@@ -0,0 +1,553 @@
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+
+const SERVICE_ACCOUNT_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PodCount {
+    ready: u64,
+    total: u64,
+    not_ready_pods: Vec<String>,
+}
+
+impl std::fmt::Display for PodCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.ready)
+    }
+}
+
+impl LevelItem for PodCount {
+    fn level(&self) -> f64 {
+        self.ready as f64
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("total"), self.total.to_string());
+        placeholders.insert(
+            String::from("not_ready_pods"),
+            self.not_ready_pods.join(","),
+        );
+    }
+}
+
+// Only the fields needed for counting ready pods.
+#[derive(Deserialize)]
+struct PodList {
+    items: Vec<Pod>,
+}
+
+#[derive(Deserialize)]
+struct Pod {
+    metadata: PodMetadata,
+    #[serde(default)]
+    status: PodStatus,
+}
+
+#[derive(Deserialize)]
+struct PodMetadata {
+    name: String,
+}
+
+#[derive(Deserialize, Default)]
+struct PodStatus {
+    #[serde(default)]
+    conditions: Vec<PodCondition>,
+}
+
+#[derive(Deserialize)]
+struct PodCondition {
+    #[serde(rename = "type")]
+    type_: String,
+    status: String,
+}
+
+// Subset of the kubeconfig format, see
+// https://kubernetes.io/docs/concepts/configuration/organize-cluster-access-kubeconfig/
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Kubeconfig {
+    #[serde(default)]
+    current_context: String,
+    #[serde(default)]
+    contexts: Vec<Named<KubeconfigContext>>,
+    #[serde(default)]
+    clusters: Vec<Named<KubeconfigCluster>>,
+    #[serde(default)]
+    users: Vec<Named<KubeconfigUser>>,
+}
+
+#[derive(Deserialize)]
+struct Named<T> {
+    name: String,
+    #[serde(alias = "context", alias = "cluster", alias = "user")]
+    value: T,
+}
+
+#[derive(Deserialize)]
+struct KubeconfigContext {
+    cluster: String,
+    #[serde(default)]
+    user: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct KubeconfigCluster {
+    server: String,
+    certificate_authority: Option<std::path::PathBuf>,
+    certificate_authority_data: Option<String>,
+    #[serde(default)]
+    insecure_skip_tls_verify: bool,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+struct KubeconfigUser {
+    token: Option<String>,
+    token_file: Option<std::path::PathBuf>,
+    client_certificate: Option<std::path::PathBuf>,
+    client_certificate_data: Option<String>,
+    client_key: Option<std::path::PathBuf>,
+    client_key_data: Option<String>,
+    exec: Option<serde_yaml_ng::Value>,
+}
+
+#[derive(PartialEq, Debug, Default)]
+struct ApiConfig {
+    server: String,
+    token: Option<String>,
+    ca_certificate: Option<Vec<u8>>,
+    // certificate and key (PEM)
+    client_identity: Option<(Vec<u8>, Vec<u8>)>,
+    insecure: bool,
+}
+
+pub struct KubePods {
+    id: Vec<String>,
+    namespace: String,
+    kubeconfig: Option<std::path::PathBuf>,
+    context: Option<String>,
+    resolver: Option<std::sync::Arc<crate::resolver::Resolver>>,
+    capture: crate::capture::Capture,
+}
+
+impl KubePods {
+    fn read_file(path: &std::path::Path) -> Result<Vec<u8>> {
+        std::fs::read(path).map_err(|x| Error(format!("Could not read {}: {}", path.display(), x)))
+    }
+
+    // Inline data takes precedence over files. Relative paths are relative to the kubeconfig.
+    fn read_data(
+        data: &Option<String>,
+        path: &Option<std::path::PathBuf>,
+        base_path: &std::path::Path,
+    ) -> Result<Option<Vec<u8>>> {
+        match (data, path) {
+            (Some(data), _) => base64::decode(data.trim())
+                .map(Some)
+                .map_err(|x| Error(format!("Could not decode base64 data: {}", x))),
+            (None, Some(path)) => Self::read_file(&base_path.join(path)).map(Some),
+            (None, None) => Ok(None),
+        }
+    }
+
+    fn parse_kubeconfig(
+        content: &str,
+        base_path: &std::path::Path,
+        context: Option<&str>,
+    ) -> Result<ApiConfig> {
+        let kubeconfig: Kubeconfig = serde_yaml_ng::from_str(content)
+            .map_err(|x| Error(format!("Could not parse kubeconfig: {}", x)))?;
+        let context_name = context.unwrap_or(kubeconfig.current_context.as_str());
+        let context = &kubeconfig
+            .contexts
+            .iter()
+            .find(|x| x.name == context_name)
+            .ok_or_else(|| Error(format!("Context '{}' not found.", context_name)))?
+            .value;
+        let cluster = &kubeconfig
+            .clusters
+            .iter()
+            .find(|x| x.name == context.cluster)
+            .ok_or_else(|| Error(format!("Cluster '{}' not found.", context.cluster)))?
+            .value;
+        let default_user = KubeconfigUser::default();
+        let user = match kubeconfig.users.iter().find(|x| x.name == context.user) {
+            Some(user) => &user.value,
+            None if context.user.is_empty() => &default_user,
+            None => return Err(Error(format!("User '{}' not found.", context.user))),
+        };
+        if user.exec.is_some() {
+            return Err(Error(String::from(
+                "Credential plugins (exec) are not supported.",
+            )));
+        }
+        let token = match (&user.token, &user.token_file) {
+            (Some(token), _) => Some(token.clone()),
+            (None, Some(path)) => Some(
+                String::from_utf8_lossy(&Self::read_file(&base_path.join(path))?)
+                    .trim()
+                    .to_string(),
+            ),
+            (None, None) => None,
+        };
+        let client_certificate = Self::read_data(
+            &user.client_certificate_data,
+            &user.client_certificate,
+            base_path,
+        )?;
+        let client_key = Self::read_data(&user.client_key_data, &user.client_key, base_path)?;
+        Ok(ApiConfig {
+            server: cluster.server.trim_end_matches('/').to_string(),
+            token,
+            ca_certificate: Self::read_data(
+                &cluster.certificate_authority_data,
+                &cluster.certificate_authority,
+                base_path,
+            )?,
+            client_identity: match (client_certificate, client_key) {
+                (Some(certificate), Some(key)) => Some((certificate, key)),
+                (None, None) => None,
+                _ => {
+                    return Err(Error(String::from(
+                        "Client certificate and key need to be set together.",
+                    )))
+                }
+            },
+            insecure: cluster.insecure_skip_tls_verify,
+        })
+    }
+
+    fn in_cluster_config() -> Result<ApiConfig> {
+        let host = std::env::var("KUBERNETES_SERVICE_HOST").map_err(|_| {
+            Error(String::from(
+                "Not running in a cluster (KUBERNETES_SERVICE_HOST is not set) and no 'kubeconfig' configured.",
+            ))
+        })?;
+        let port = std::env::var("KUBERNETES_SERVICE_PORT").unwrap_or_else(|_| String::from("443"));
+        let path = std::path::Path::new(SERVICE_ACCOUNT_PATH);
+        Ok(ApiConfig {
+            server: if host.contains(':') {
+                format!("https://[{}]:{}", host, port)
+            } else {
+                format!("https://{}:{}", host, port)
+            },
+            // The token is re-read every time, because it's rotated.
+            token: Some(
+                String::from_utf8_lossy(&Self::read_file(&path.join("token"))?)
+                    .trim()
+                    .to_string(),
+            ),
+            ca_certificate: Some(Self::read_file(&path.join("ca.crt"))?),
+            client_identity: None,
+            insecure: false,
+        })
+    }
+
+    fn api_config(&self) -> Result<ApiConfig> {
+        match &self.kubeconfig {
+            Some(path) => Self::parse_kubeconfig(
+                &String::from_utf8_lossy(&Self::read_file(path)?),
+                path.parent().unwrap_or(std::path::Path::new("")),
+                self.context.as_deref(),
+            ),
+            None => Self::in_cluster_config(),
+        }
+    }
+
+    fn client(
+        api_config: &ApiConfig,
+        resolver: Option<&std::sync::Arc<crate::resolver::Resolver>>,
+    ) -> Result<reqwest::Client> {
+        let mut builder = crate::resolver::http_client_builder(resolver)
+            .user_agent(crate::user_agent())
+            .danger_accept_invalid_certs(api_config.insecure);
+        if let Some(ca_certificate) = &api_config.ca_certificate {
+            builder = builder.add_root_certificate(
+                reqwest::Certificate::from_pem(ca_certificate)
+                    .map_err(|x| Error(format!("Invalid CA certificate: {}", x)))?,
+            );
+        }
+        if let Some((certificate, key)) = &api_config.client_identity {
+            builder = builder.identity(
+                reqwest::Identity::from_pkcs8_pem(certificate, key)
+                    .map_err(|x| Error(format!("Invalid client certificate or key: {}", x)))?,
+            );
+        }
+        builder
+            .build()
+            .map_err(|x| Error(format!("Could not create HTTP client: {}", x)))
+    }
+
+    fn count_pods(pod_list: PodList) -> PodCount {
+        let mut ready = 0;
+        let mut not_ready_pods = Vec::new();
+        for pod in pod_list.items.iter() {
+            if pod
+                .status
+                .conditions
+                .iter()
+                .any(|x| x.type_ == "Ready" && x.status == "True")
+            {
+                ready += 1;
+            } else {
+                not_ready_pods.push(pod.metadata.name.clone());
+            }
+        }
+        PodCount {
+            ready,
+            total: pod_list.items.len() as u64,
+            not_ready_pods,
+        }
+    }
+
+    async fn get_pods(
+        &self,
+        client: &reqwest::Client,
+        api_config: &ApiConfig,
+        label_selector: &str,
+    ) -> Result<PodCount> {
+        let mut request = client
+            .get(format!(
+                "{}/api/v1/namespaces/{}/pods",
+                api_config.server, self.namespace
+            ))
+            .query(&[("labelSelector", label_selector)]);
+        if let Some(token) = &api_config.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|x| Error(format!("HTTP request failed: {}", x)))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error(format!(
+                "Kubernetes API returned HTTP status code {}.",
+                status.as_u16()
+            )));
+        }
+        let body = self
+            .capture
+            .body(response)
+            .await
+            .map_err(|x| Error(format!("Invalid pod list: {}", x)))?;
+        let pod_list: PodList =
+            serde_json::from_slice(&body).map_err(|x| Error(format!("Invalid pod list: {}", x)))?;
+        Ok(Self::count_pods(pod_list))
+    }
+}
+
+impl TryFrom<&config::Check> for KubePods {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::KubePods(kube_pods) = &check.type_ {
+            if kube_pods.namespace.is_empty() {
+                Err(Error(String::from("'namespace' cannot be empty.")))
+            } else if kube_pods.label_selectors.iter().any(|x| x.is_empty()) {
+                Err(Error(String::from(
+                    "'label_selectors' cannot contain empty selectors.",
+                )))
+            } else {
+                Ok(Self {
+                    id: kube_pods.label_selectors.clone(),
+                    namespace: kube_pods.namespace.clone(),
+                    kubeconfig: kube_pods.kubeconfig.clone(),
+                    context: kube_pods.context.clone(),
+                    resolver: crate::resolver::from_config(check.resolver.as_ref())?,
+                    capture: crate::capture::from_config(check.capture.as_ref())?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for KubePods {
+    type Item = PodCount;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let api_config = self.api_config()?;
+        let client = Self::client(&api_config, self.resolver.as_ref())?;
+        let mut res = Vec::new();
+        for label_selector in self.id.iter() {
+            res.push(self.get_pods(&client, &api_config, label_selector).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("{} of {} pods ready", data.ready, data.total)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.ready as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::mock_server;
+
+    fn pod(name: &str, ready: &str) -> String {
+        format!(
+            r#"{{"metadata": {{"name": "{}"}}, "status": {{"phase": "Running", "conditions": [{{"type": "Initialized", "status": "True"}}, {{"type": "Ready", "status": "{}"}}]}}}}"#,
+            name, ready
+        )
+    }
+
+    fn kubeconfig(server: &str) -> String {
+        format!(
+            r#"
+apiVersion: v1
+kind: Config
+current-context: test
+contexts:
+- name: test
+  context:
+    cluster: test-cluster
+    user: test-user
+- name: other
+  context:
+    cluster: other-cluster
+clusters:
+- name: test-cluster
+  cluster:
+    server: {}/
+- name: other-cluster
+  cluster:
+    server: https://other.example.com
+    certificate-authority-data: Zm9v
+    insecure-skip-tls-verify: true
+users:
+- name: test-user
+  user:
+    token: secret-token
+"#,
+            server
+        )
+    }
+
+    #[test]
+    fn test_parse_kubeconfig() {
+        let content = kubeconfig("https://k8s.example.com:6443");
+        let base_path = std::path::Path::new("/");
+        assert_eq!(
+            KubePods::parse_kubeconfig(&content, base_path, None).unwrap(),
+            ApiConfig {
+                server: String::from("https://k8s.example.com:6443"),
+                token: Some(String::from("secret-token")),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            KubePods::parse_kubeconfig(&content, base_path, Some("other")).unwrap(),
+            ApiConfig {
+                server: String::from("https://other.example.com"),
+                ca_certificate: Some(b"foo".to_vec()),
+                insecure: true,
+                ..Default::default()
+            }
+        );
+        assert!(KubePods::parse_kubeconfig(&content, base_path, Some("missing")).is_err());
+        assert!(KubePods::parse_kubeconfig("foo: [", base_path, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests_clone = requests.clone();
+        let url = mock_server(move |request| {
+            requests_clone.lock().unwrap().push(String::from(request));
+            if request.contains("labelSelector=app%3Dweb") {
+                (
+                    200,
+                    format!(
+                        r#"{{"kind": "PodList", "items": [{}, {}, {}]}}"#,
+                        pod("web-1", "True"),
+                        pod("web-2", "False"),
+                        pod("web-3", "True")
+                    ),
+                )
+            } else if request.contains("labelSelector=app%3Ddb") {
+                (200, String::from(r#"{"kind": "PodList", "items": []}"#))
+            } else {
+                (
+                    403,
+                    String::from(r#"{"kind": "Status", "reason": "Forbidden"}"#),
+                )
+            }
+        })
+        .await;
+        let path = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, kubeconfig(&url)).unwrap();
+        let kube_pods = KubePods {
+            id: vec![
+                String::from("app=web"),
+                String::from("app=db"),
+                String::from("app=secret"),
+            ],
+            namespace: String::from("prod"),
+            kubeconfig: Some(path.clone()),
+            context: None,
+            resolver: None,
+            capture: crate::capture::Capture::default(),
+        };
+        let data = kube_pods.get_data().await;
+        std::fs::remove_file(&path).unwrap();
+        let data = data.unwrap();
+        let pod_count = data[0].as_ref().unwrap();
+        assert_eq!(
+            pod_count,
+            &PodCount {
+                ready: 2,
+                total: 3,
+                not_ready_pods: vec![String::from("web-2")],
+            }
+        );
+        let mut placeholders = PlaceholderMap::new();
+        pod_count.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("total").unwrap(), "3");
+        assert_eq!(placeholders.get("not_ready_pods").unwrap(), "web-2");
+        assert_eq!(data[1].as_ref().unwrap().ready, 0);
+        assert!(data[2].is_err());
+        let requests = requests.lock().unwrap();
+        assert!(
+            requests[0].starts_with("GET /api/v1/namespaces/prod/pods?labelSelector=app%3Dweb ")
+        );
+        assert!(requests[0].contains("authorization: Bearer secret-token"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_kubeconfig() {
+        let kube_pods = KubePods {
+            id: vec![String::from("app=web")],
+            namespace: String::from("default"),
+            kubeconfig: Some(std::path::PathBuf::from("/does/not/exist")),
+            context: None,
+            resolver: None,
+            capture: crate::capture::Capture::default(),
+        };
+        assert!(kube_pods.get_data().await.is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Load(f64);
+
+impl std::fmt::Display for Load {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:.2}", self.0)
+    }
+}
+
+impl LevelItem for Load {
+    fn level(&self) -> f64 {
+        self.0
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub struct LoadAverage {
+    id: Vec<String>,
+    normalize: bool,
+    loadavg_path: std::path::PathBuf,
+    proc_path: std::path::PathBuf,
+}
+
+impl LoadAverage {
+    // e.g. "0.52 0.58 0.59 2/1024 12345"
+    fn parse_loadavg(content: &str) -> Result<[f64; 3]> {
+        let mut res = [0.0; 3];
+        for (index, load) in res.iter_mut().enumerate() {
+            *load = crate::get_number(&format!("Could not read column {}", index), content, index)?;
+        }
+        Ok(res)
+    }
+
+    fn cpu_count() -> Result<f64> {
+        match nix::unistd::sysconf(nix::unistd::SysconfVar::_NPROCESSORS_ONLN) {
+            Ok(Some(count)) if count > 0 => Ok(count as f64),
+            _ => Err(Error(String::from("Could not get the number of CPUs."))),
+        }
+    }
+}
+
+impl TryFrom<&config::Check> for LoadAverage {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::LoadAverage(load_average) = &check.type_ {
+            Ok(Self {
+                id: vec![String::from("1"), String::from("5"), String::from("15")],
+                normalize: load_average.normalize,
+                loadavg_path: check.proc_path.join("loadavg"),
+                proc_path: check.proc_path.clone(),
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for LoadAverage {
+    type Item = Load;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let content = tokio::fs::read_to_string(&self.loadavg_path)
+            .await
+            .map_err(|x| {
+                super::proc_error(
+                    &self.proc_path,
+                    format!("Could not read from {}: {}", self.loadavg_path.display(), x),
+                )
+            })?;
+        let loadavg = Self::parse_loadavg(&content)?;
+        let divisor = if self.normalize {
+            Self::cpu_count()?
+        } else {
+            1.0
+        };
+        Ok(loadavg.iter().map(|x| Ok(Load(x / divisor))).collect())
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("load average {:.2}", data.0)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_loadavg() {
+        assert_eq!(
+            LoadAverage::parse_loadavg("0.52 1.58 12.59 2/1024 12345\n").unwrap(),
+            [0.52, 1.58, 12.59]
+        );
+        assert!(LoadAverage::parse_loadavg("0.52 1.58").is_err());
+        assert!(LoadAverage::parse_loadavg("0.52 foo 12.59 2/1024 12345").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("loadavg"), "4.00 2.50 1.126 2/1024 12345\n").unwrap();
+        let mut load_average = LoadAverage {
+            id: Vec::new(),
+            normalize: false,
+            loadavg_path: root.join("loadavg"),
+            proc_path: root.clone(),
+        };
+        let data = load_average.get_data().await;
+        load_average.normalize = true;
+        let normalized = load_average.get_data().await;
+        std::fs::remove_dir_all(&root).unwrap();
+        let data: Vec<Load> = data.unwrap().into_iter().map(|x| x.unwrap()).collect();
+        assert_eq!(data, vec![Load(4.0), Load(2.5), Load(1.126)]);
+        assert_eq!(data[2].to_string(), "1.13");
+        assert_eq!(LoadAverage::format_data(&data[1]), "load average 2.50");
+        let cpu_count = LoadAverage::cpu_count().unwrap();
+        assert_eq!(normalized.unwrap()[0].as_ref().unwrap().0, 4.0 / cpu_count);
+    }
+}
@@ -0,0 +1,316 @@
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+use chrono::TimeZone;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct CertExpiry {
+    days: i64,
+    expiry: chrono::DateTime<chrono::Utc>,
+    path: std::path::PathBuf,
+    subject: String,
+    issuer: String,
+}
+
+impl std::fmt::Display for CertExpiry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.days)
+    }
+}
+
+impl LevelItem for CertExpiry {
+    fn level(&self) -> f64 {
+        self.days as f64
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(
+            String::from("expiry"),
+            crate::iso8601(std::time::SystemTime::from(self.expiry)),
+        );
+        placeholders.insert(String::from("path"), self.path.display().to_string());
+        placeholders.insert(String::from("subject"), self.subject.clone());
+        placeholders.insert(String::from("issuer"), self.issuer.clone());
+    }
+}
+
+pub struct Certificate {
+    pub expiry: chrono::DateTime<chrono::Utc>,
+    pub subject: String,
+    pub issuer: String,
+}
+
+pub struct LocalCertExpiry {
+    id: Vec<String>,
+    paths: Vec<std::path::PathBuf>,
+}
+
+impl LocalCertExpiry {
+    // e.g. "CN=example.com, O=Example"
+    fn format_name(name: &openssl::x509::X509NameRef) -> String {
+        name.entries()
+            .map(|x| {
+                format!(
+                    "{}={}",
+                    x.object().nid().short_name().unwrap_or("?"),
+                    x.data().to_string().unwrap_or_default()
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    // Only the first certificate is considered if the file contains a chain.
+    pub fn parse_certificate(content: &[u8]) -> Result<Certificate> {
+        let certificate = openssl::x509::X509::from_pem(content)
+            .or_else(|_| openssl::x509::X509::from_der(content))
+            .map_err(|_| Error(String::from("No PEM or DER encoded certificate found.")))?;
+        Self::read_certificate_info(&certificate)
+    }
+
+    pub fn read_certificate_info(certificate: &openssl::x509::X509Ref) -> Result<Certificate> {
+        let epoch = openssl::asn1::Asn1Time::from_unix(0)
+            .map_err(|x| Error(format!("Could not create timestamp: {}", x)))?;
+        let not_after = epoch
+            .diff(certificate.not_after())
+            .map_err(|x| Error(format!("Invalid notAfter date: {}", x)))?;
+        let timestamp = not_after.days as i64 * 86400 + not_after.secs as i64;
+        Ok(Certificate {
+            expiry: chrono::Utc
+                .timestamp_opt(timestamp, 0)
+                .single()
+                .ok_or_else(|| Error(format!("Invalid notAfter timestamp {}.", timestamp)))?,
+            subject: Self::format_name(certificate.subject_name()),
+            issuer: Self::format_name(certificate.issuer_name()),
+        })
+    }
+
+    fn cert_expiry(
+        path: &std::path::Path,
+        certificate: Certificate,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> CertExpiry {
+        CertExpiry {
+            days: certificate.expiry.signed_duration_since(now).num_days(),
+            expiry: certificate.expiry,
+            path: path.to_path_buf(),
+            subject: certificate.subject,
+            issuer: certificate.issuer,
+        }
+    }
+
+    async fn read_certificate(path: &std::path::Path) -> Result<Certificate> {
+        let content = tokio::fs::read(path)
+            .await
+            .map_err(|x| Error(format!("Could not read from {}: {}", path.display(), x)))?;
+        Self::parse_certificate(&content)
+            .map_err(|x| Error(format!("Could not parse {}: {}", path.display(), x)))
+    }
+
+    // Wildcards are only supported in the file name.
+    async fn expand(path: &std::path::Path) -> Result<Vec<std::path::PathBuf>> {
+        let pattern = match path.file_name().and_then(|x| x.to_str()) {
+            Some(pattern) if pattern.contains(['*', '?']) => pattern,
+            _ => return Ok(vec![path.to_path_buf()]),
+        };
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => std::path::Path::new("."),
+        };
+        let error = |x| {
+            Error(format!(
+                "Could not read directory {}: {}",
+                directory.display(),
+                x
+            ))
+        };
+        let mut entries = tokio::fs::read_dir(directory).await.map_err(error)?;
+        let mut paths = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(error)? {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if crate::wildcard_match(pattern, &file_name) {
+                paths.push(entry.path());
+            }
+        }
+        if paths.is_empty() {
+            return Err(Error(format!(
+                "No files matching {} found.",
+                path.display()
+            )));
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    // Returns the certificate expiring first if the path matches several files.
+    async fn get_expiry(path: &std::path::Path) -> Result<CertExpiry> {
+        let now = chrono::Utc::now();
+        let mut first: Option<CertExpiry> = None;
+        for path in Self::expand(path).await? {
+            let cert_expiry = Self::cert_expiry(&path, Self::read_certificate(&path).await?, now);
+            if first
+                .as_ref()
+                .map(|x| cert_expiry.expiry < x.expiry)
+                .unwrap_or(true)
+            {
+                first = Some(cert_expiry);
+            }
+        }
+        // expand() never returns an empty list
+        Ok(first.unwrap())
+    }
+}
+
+impl TryFrom<&config::Check> for LocalCertExpiry {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::LocalCertExpiry(local_cert_expiry) = &check.type_ {
+            if local_cert_expiry
+                .paths
+                .iter()
+                .any(|x| x.as_os_str().is_empty())
+            {
+                Err(Error(String::from("'paths' cannot contain empty paths.")))
+            } else {
+                Ok(Self {
+                    id: local_cert_expiry
+                        .paths
+                        .iter()
+                        .map(|x| x.display().to_string())
+                        .collect(),
+                    paths: local_cert_expiry.paths.clone(),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for LocalCertExpiry {
+    type Item = CertExpiry;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mut res = Vec::new();
+        for path in self.paths.iter() {
+            res.push(Self::get_expiry(path).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("{} days remaining", data.days)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.days as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    fn utc(text: &str) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::<chrono::Utc>::from_str(text).unwrap()
+    }
+
+    // self-signed, notAfter 2030-01-01T00:00:00Z
+    fn generate_certificate() -> openssl::x509::X509 {
+        let group =
+            openssl::ec::EcGroup::from_curve_name(openssl::nid::Nid::X9_62_PRIME256V1).unwrap();
+        let key = openssl::pkey::PKey::from_ec_key(openssl::ec::EcKey::generate(&group).unwrap())
+            .unwrap();
+        let mut name = openssl::x509::X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("O", "Example").unwrap();
+        name.append_entry_by_text("CN", "example.com").unwrap();
+        let name = name.build();
+        let mut builder = openssl::x509::X509Builder::new().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder
+            .set_not_before(&openssl::asn1::Asn1Time::from_unix(1577836800).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&openssl::asn1::Asn1Time::from_unix(1893456000).unwrap())
+            .unwrap();
+        builder
+            .sign(&key, openssl::hash::MessageDigest::sha256())
+            .unwrap();
+        builder.build()
+    }
+
+    #[test]
+    fn test_parse_certificate() {
+        let certificate = generate_certificate();
+        for content in [certificate.to_pem().unwrap(), certificate.to_der().unwrap()] {
+            let certificate = LocalCertExpiry::parse_certificate(&content).unwrap();
+            assert_eq!(certificate.expiry, utc("2030-01-01T00:00:00Z"));
+            assert_eq!(certificate.subject, "O=Example, CN=example.com");
+            assert_eq!(certificate.issuer, "O=Example, CN=example.com");
+        }
+        assert!(LocalCertExpiry::parse_certificate(b"foo").is_err());
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let certificate =
+            LocalCertExpiry::parse_certificate(&generate_certificate().to_pem().unwrap()).unwrap();
+        let cert_expiry = LocalCertExpiry::cert_expiry(
+            std::path::Path::new("/etc/foo/cert.pem"),
+            certificate,
+            utc("2029-12-01T12:00:00Z"),
+        );
+        assert_eq!(cert_expiry.days, 30);
+        let mut placeholders = PlaceholderMap::new();
+        cert_expiry.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("expiry").unwrap(), "2030-01-01T00:00:00Z");
+        assert_eq!(placeholders.get("path").unwrap(), "/etc/foo/cert.pem");
+        assert_eq!(
+            placeholders.get("subject").unwrap(),
+            "O=Example, CN=example.com"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let certificate = generate_certificate();
+        std::fs::write(root.join("a.pem"), certificate.to_pem().unwrap()).unwrap();
+        std::fs::write(root.join("b.der"), certificate.to_der().unwrap()).unwrap();
+        std::fs::write(root.join("invalid.pem"), "foo").unwrap();
+        let local_cert_expiry = LocalCertExpiry {
+            id: Vec::new(),
+            paths: vec![
+                root.join("a.pem"),
+                root.join("*.der"),
+                root.join("invalid.pem"),
+                root.join("missing.pem"),
+                root.join("*.crt"),
+            ],
+        };
+        let data = local_cert_expiry.get_data().await;
+        std::fs::remove_dir_all(&root).unwrap();
+        let data = data.unwrap();
+        assert_eq!(
+            data[0].as_ref().unwrap().expiry,
+            utc("2030-01-01T00:00:00Z")
+        );
+        assert_eq!(data[1].as_ref().unwrap().path, root.join("b.der"));
+        assert!(data[2].is_err());
+        assert!(data[3].is_err());
+        assert!(data[4].is_err());
+    }
+}
@@ -0,0 +1,308 @@
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct LogGrowth {
+    // share of the free space that fills up within the horizon at the current rate in percent
+    fill: f64,
+    bytes_per_second: f64,
+    // seconds until the filesystem is full, None if the directory doesn't grow
+    time_until_full: Option<f64>,
+    fastest_file: String,
+}
+
+impl std::fmt::Display for LogGrowth {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:.1}", self.fill)
+    }
+}
+
+impl LevelItem for LogGrowth {
+    fn level(&self) -> f64 {
+        self.fill
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(
+            String::from("bytes_per_second"),
+            format!("{:.0}", self.bytes_per_second),
+        );
+        if let Some(time_until_full) = self.time_until_full {
+            placeholders.insert(
+                String::from("hours_until_full"),
+                format!("{:.1}", time_until_full / 3600.0),
+            );
+        }
+        placeholders.insert(String::from("fastest_file"), self.fastest_file.clone());
+    }
+}
+
+// sizes of the files in the directory (including subdirectories)
+type Sizes = HashMap<std::path::PathBuf, u64>;
+
+pub struct LogGrowthRate {
+    id: Vec<String>,
+    paths: Vec<std::path::PathBuf>,
+    horizon: std::time::Duration,
+    samples: std::sync::Mutex<HashMap<std::path::PathBuf, (std::time::Instant, Sizes)>>,
+}
+
+impl LogGrowthRate {
+    // Files and subdirectories that vanish while walking (e.g. due to log rotation) are skipped.
+    async fn sizes(path: &std::path::Path) -> Result<Sizes> {
+        let mut res = Sizes::new();
+        let mut directories = vec![path.to_path_buf()];
+        while let Some(directory) = directories.pop() {
+            let mut entries = match tokio::fs::read_dir(&directory).await {
+                Ok(entries) => entries,
+                Err(err) if directory == path => {
+                    return Err(Error(format!(
+                        "Could not read directory {}: {}",
+                        path.display(),
+                        err
+                    )))
+                }
+                Err(_) => continue,
+            };
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                match entry.metadata().await {
+                    Ok(metadata) if metadata.is_dir() => directories.push(entry.path()),
+                    Ok(metadata) if metadata.is_file() => {
+                        res.insert(entry.path(), metadata.len());
+                    }
+                    _ => continue,
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    fn available_bytes(path: &std::path::Path) -> Result<u64> {
+        let stat = nix::sys::statvfs::statvfs(path)
+            .map_err(|x| Error(format!("Call to 'statvfs' failed: {}", x)))?;
+        Ok(stat.blocks_available() as u64 * stat.fragment_size() as u64)
+    }
+
+    // Shrinking files (e.g. truncated or rotated ones) don't count against the growth.
+    fn compute(
+        &self,
+        previous: &Sizes,
+        current: &Sizes,
+        elapsed: std::time::Duration,
+        available_bytes: u64,
+    ) -> Result<LogGrowth> {
+        let elapsed_s = elapsed.as_secs_f64();
+        if elapsed_s == 0.0 {
+            return Err(Error(String::from("No time elapsed since last cycle.")));
+        }
+        let total = |sizes: &Sizes| sizes.values().sum::<u64>();
+        let growth = total(current).saturating_sub(total(previous));
+        let fastest_file = current
+            .iter()
+            .map(|(path, size)| {
+                let growth = size.saturating_sub(previous.get(path).copied().unwrap_or(0));
+                (growth, path)
+            })
+            .filter(|(growth, _)| *growth > 0)
+            .max()
+            .map(|(_, path)| path.display().to_string())
+            .unwrap_or_default();
+        let bytes_per_second = growth as f64 / elapsed_s;
+        let fill = if available_bytes == 0 {
+            if growth > 0 {
+                f64::INFINITY
+            } else {
+                0.0
+            }
+        } else {
+            bytes_per_second * self.horizon.as_secs_f64() * 100.0 / available_bytes as f64
+        };
+        Ok(LogGrowth {
+            fill,
+            bytes_per_second,
+            time_until_full: (growth > 0).then(|| available_bytes as f64 / bytes_per_second),
+            fastest_file,
+        })
+    }
+
+    async fn sample(&self, path: &std::path::Path) -> Result<LogGrowth> {
+        let current = Self::sizes(path).await?;
+        let now = std::time::Instant::now();
+        let available_bytes = Self::available_bytes(path)?;
+        let previous = self
+            .samples
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), (now, current.clone()));
+        match previous {
+            Some((timestamp, previous)) => self.compute(
+                &previous,
+                &current,
+                now.saturating_duration_since(timestamp),
+                available_bytes,
+            ),
+            None => Err(Error(String::from(
+                "No previous sample available yet (first cycle).",
+            ))),
+        }
+    }
+}
+
+impl TryFrom<&config::Check> for LogGrowthRate {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::LogGrowthRate(log_growth_rate) = &check.type_ {
+            if log_growth_rate.paths.is_empty() {
+                Err(Error(String::from("'paths' cannot be empty.")))
+            } else if log_growth_rate
+                .paths
+                .iter()
+                .any(|x| x.as_os_str().is_empty())
+            {
+                Err(Error(String::from("'paths' cannot contain empty paths.")))
+            } else if log_growth_rate.horizon == 0 {
+                Err(Error(String::from("'horizon' cannot be 0.")))
+            } else {
+                Ok(Self {
+                    id: log_growth_rate
+                        .paths
+                        .iter()
+                        .map(|x| x.display().to_string())
+                        .collect(),
+                    paths: log_growth_rate.paths.clone(),
+                    horizon: std::time::Duration::from_secs(
+                        u64::from(log_growth_rate.horizon) * 3600,
+                    ),
+                    samples: std::sync::Mutex::new(HashMap::new()),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for LogGrowthRate {
+    type Item = LogGrowth;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mut res = Vec::new();
+        for path in self.paths.iter() {
+            res.push(self.sample(path).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!(
+            "growing {:.0}B/s, {:.1}% of the free space within the horizon",
+            data.bytes_per_second, data.fill
+        )
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.level())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sample() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("nginx")).unwrap();
+        std::fs::write(root.join("syslog"), vec![0; 1000]).unwrap();
+        std::fs::write(root.join("nginx/access.log"), vec![0; 500]).unwrap();
+        let log_growth_rate = LogGrowthRate {
+            id: vec![root.display().to_string()],
+            paths: vec![root.clone()],
+            horizon: std::time::Duration::from_secs(3600),
+            samples: std::sync::Mutex::new(HashMap::new()),
+        };
+        let first = log_growth_rate.sample(&root).await;
+        std::fs::write(root.join("syslog"), vec![0; 1100]).unwrap();
+        std::fs::write(root.join("nginx/access.log"), vec![0; 2500]).unwrap();
+        let second = log_growth_rate.sample(&root).await;
+        let missing = log_growth_rate.sample(&root.join("missing")).await;
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            first.unwrap_err().to_string(),
+            "No previous sample available yet (first cycle)."
+        );
+        let second = second.unwrap();
+        assert!(second.bytes_per_second > 0.0);
+        assert!(second.time_until_full.is_some());
+        assert_eq!(
+            second.fastest_file,
+            root.join("nginx/access.log").display().to_string()
+        );
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_compute() {
+        let log_growth_rate = LogGrowthRate {
+            id: Vec::new(),
+            paths: Vec::new(),
+            horizon: std::time::Duration::from_secs(3600),
+            samples: std::sync::Mutex::new(HashMap::new()),
+        };
+        let sizes = |x: &[(&str, u64)]| -> Sizes {
+            x.iter()
+                .map(|(path, size)| (std::path::PathBuf::from(path), *size))
+                .collect()
+        };
+        let previous = sizes(&[("/var/log/syslog", 1000), ("/var/log/app.log", 5000)]);
+        // 36000 bytes in 10 seconds fill 3600000 bytes within an hour
+        let current = sizes(&[
+            ("/var/log/syslog", 2000),
+            ("/var/log/app.log", 40000),
+            ("/var/log/new.log", 0),
+        ]);
+        let elapsed = std::time::Duration::from_secs(10);
+        let growth = log_growth_rate
+            .compute(&previous, &current, elapsed, 7200000)
+            .unwrap();
+        assert_eq!(growth.bytes_per_second, 3600.0);
+        assert_eq!(growth.fill, 180.0);
+        assert_eq!(growth.to_string(), "180.0");
+        let mut placeholders = PlaceholderMap::new();
+        growth.add_placeholders(&mut placeholders);
+        assert_eq!(
+            placeholders.get("fastest_file").unwrap(),
+            "/var/log/app.log"
+        );
+        assert_eq!(placeholders.get("hours_until_full").unwrap(), "0.6");
+        // rotation
+        let rotated = sizes(&[("/var/log/syslog", 0), ("/var/log/app.log", 5000)]);
+        let growth = log_growth_rate
+            .compute(&previous, &rotated, elapsed, 7200000)
+            .unwrap();
+        assert_eq!(growth.fill, 0.0);
+        assert_eq!(growth.time_until_full, None);
+        assert_eq!(growth.fastest_file, "");
+        assert!(log_growth_rate
+            .compute(&previous, &current, std::time::Duration::ZERO, 1)
+            .is_err());
+    }
+}
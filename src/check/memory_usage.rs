@@ -6,22 +6,74 @@ use crate::{Error, Result};
 use async_trait::async_trait;
 use tokio::io::AsyncReadExt;
 
-const MEMINFO_PATH: &str = "/proc/meminfo";
-
 pub struct MemoryUsage {
     id: Vec<String>,
     memory: bool,
     swap: bool,
+    combined: bool,
+    meminfo_path: std::path::PathBuf,
+    proc_path: std::path::PathBuf,
+}
+
+#[derive(Default)]
+struct Meminfo {
+    mem_total: Option<usize>,
+    mem_available: Option<usize>,
+    swap_total: Option<usize>,
+    swap_free: Option<usize>,
 }
 
 impl MemoryUsage {
-    fn get_number(id: &str, line: &str) -> Result<usize> {
+    fn get_number(&self, id: &str, line: &str) -> Result<usize> {
         crate::get_number(
-            &format!("Could not read {} from {}", id, MEMINFO_PATH),
+            &format!("Could not read {} from {}", id, self.meminfo_path.display()),
             line,
             1,
         )
     }
+
+    fn parse_meminfo(&self, buffer: &[u8]) -> Result<Meminfo> {
+        let mut meminfo = Meminfo::default();
+        for line in buffer.lines() {
+            let line = line.map_err(|x| Error(format!("Error reading line: {}", x)))?;
+            if line.starts_with("MemTotal") {
+                meminfo.mem_total = Some(self.get_number("MemTotal", &line)?);
+            } else if line.starts_with("MemAvailable") {
+                meminfo.mem_available = Some(self.get_number("MemAvailable", &line)?);
+            } else if line.starts_with("SwapTotal") {
+                meminfo.swap_total = Some(self.get_number("SwapTotal", &line)?);
+            } else if line.starts_with("SwapFree") {
+                meminfo.swap_free = Some(self.get_number("SwapFree", &line)?);
+            }
+        }
+        Ok(meminfo)
+    }
+
+    fn usage(total: usize, available: usize) -> Option<u8> {
+        if total != 0 && available <= total {
+            Some(((total - available) * 100 / total) as u8)
+        } else {
+            None
+        }
+    }
+}
+
+impl Meminfo {
+    fn mem_usage(&self) -> Option<u8> {
+        MemoryUsage::usage(self.mem_total?, self.mem_available?)
+    }
+
+    fn swap_usage(&self) -> Option<u8> {
+        MemoryUsage::usage(self.swap_total?, self.swap_free?)
+    }
+
+    // RAM and swap taken together, i.e. how close the system is to running out of memory.
+    fn combined_usage(&self) -> Option<u8> {
+        MemoryUsage::usage(
+            self.mem_total? + self.swap_total?,
+            self.mem_available? + self.swap_free?,
+        )
+    }
 }
 
 impl TryFrom<&config::Check> for MemoryUsage {
@@ -29,9 +81,9 @@ impl TryFrom<&config::Check> for MemoryUsage {
 
     fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
         if let config::CheckType::MemoryUsage(memory_usage) = &check.type_ {
-            if !memory_usage.memory && !memory_usage.swap {
+            if !memory_usage.memory && !memory_usage.swap && !memory_usage.combined {
                 Err(Error(String::from(
-                    "Either 'memory' or 'swap' or both need to be enabled.",
+                    "At least one of 'memory', 'swap' and 'combined' needs to be enabled.",
                 )))
             } else {
                 let mut id = Vec::new();
@@ -41,10 +93,16 @@ impl TryFrom<&config::Check> for MemoryUsage {
                 if memory_usage.swap {
                     id.push(String::from("Swap"));
                 }
+                if memory_usage.combined {
+                    id.push(String::from("Combined"));
+                }
                 Ok(Self {
                     id,
                     memory: memory_usage.memory,
                     swap: memory_usage.swap,
+                    combined: memory_usage.combined,
+                    meminfo_path: check.proc_path.join("meminfo"),
+                    proc_path: check.proc_path.clone(),
                 })
             }
         } else {
@@ -58,55 +116,48 @@ impl DataSource for MemoryUsage {
     type Item = u8;
 
     async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
-        let mut file = tokio::fs::File::open(MEMINFO_PATH).await.map_err(|x| {
+        let mut file = tokio::fs::File::open(&self.meminfo_path)
+            .await
+            .map_err(|x| {
+                super::proc_error(
+                    &self.proc_path,
+                    format!(
+                        "Could not open {} for reading: {}",
+                        self.meminfo_path.display(),
+                        x
+                    ),
+                )
+            })?;
+        let mut buffer = Vec::new();
+        file.read_to_end(&mut buffer).await.map_err(|x| {
             Error(format!(
-                "Could not open {} for reading: {}",
-                MEMINFO_PATH, x
+                "Could not read from {}: {}",
+                self.meminfo_path.display(),
+                x
             ))
         })?;
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)
-            .await
-            .map_err(|x| Error(format!("Could not read from {}: {}", MEMINFO_PATH, x)))?;
-        let mut mem_total: Option<usize> = None;
-        let mut mem_available: Option<usize> = None;
-        let mut mem_usage: Option<u8> = None;
-        let mut swap_total: Option<usize> = None;
-        let mut swap_free: Option<usize> = None;
-        let mut swap_usage: Option<u8> = None;
-        for line in buffer.lines() {
-            let line = line.map_err(|x| Error(format!("Error reading line: {}", x)))?;
-            if self.memory {
-                if line.starts_with("MemTotal") {
-                    mem_total = Some(Self::get_number("MemTotal", &line)?);
-                } else if line.starts_with("MemAvailable") {
-                    mem_available = Some(Self::get_number("MemAvailable", &line)?);
-                }
-            }
-            if self.swap {
-                if line.starts_with("SwapTotal") {
-                    swap_total = Some(Self::get_number("SwapTotal", &line)?);
-                } else if line.starts_with("SwapFree") {
-                    swap_free = Some(Self::get_number("SwapFree", &line)?);
-                }
-            }
-        }
-        if let (Some(mem_total), Some(mem_available)) = (mem_total, mem_available) {
-            if mem_total != 0 {
-                mem_usage = Some(((mem_total - mem_available) * 100 / mem_total) as u8);
-            }
-        }
-        if let (Some(swap_total), Some(swap_free)) = (swap_total, swap_free) {
-            if swap_total != 0 {
-                swap_usage = Some(((swap_total - swap_free) * 100 / swap_total) as u8);
-            }
-        }
+        let meminfo = self.parse_meminfo(&buffer)?;
         let mut res = Vec::new();
         if self.memory {
-            res.push(mem_usage.ok_or_else(|| Error(String::from("Could not read memory usage."))));
+            res.push(
+                meminfo
+                    .mem_usage()
+                    .ok_or_else(|| Error(String::from("Could not read memory usage."))),
+            );
         }
         if self.swap {
-            res.push(swap_usage.ok_or_else(|| Error(String::from("Could not read swap usage."))));
+            res.push(
+                meminfo
+                    .swap_usage()
+                    .ok_or_else(|| Error(String::from("Could not read swap usage."))),
+            );
+        }
+        if self.combined {
+            res.push(
+                meminfo
+                    .combined_usage()
+                    .ok_or_else(|| Error(String::from("Could not read combined usage."))),
+            );
         }
         Ok(res)
     }
@@ -118,4 +169,83 @@ impl DataSource for MemoryUsage {
     fn ids(&self) -> &[String] {
         &self.id[..]
     }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MEMINFO: &str = "MemTotal:        8000000 kB
+MemFree:          500000 kB
+MemAvailable:    2000000 kB
+Buffers:          100000 kB
+Cached:          1500000 kB
+SwapCached:        10000 kB
+SwapTotal:       2000000 kB
+SwapFree:         500000 kB
+";
+
+    fn memory_usage(proc_path: std::path::PathBuf) -> MemoryUsage {
+        MemoryUsage {
+            id: vec![
+                String::from("Memory"),
+                String::from("Swap"),
+                String::from("Combined"),
+            ],
+            memory: true,
+            swap: true,
+            combined: true,
+            meminfo_path: proc_path.join("meminfo"),
+            proc_path,
+        }
+    }
+
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = memory_usage(std::path::PathBuf::from("/proc"))
+            .parse_meminfo(MEMINFO.as_bytes())
+            .unwrap();
+        assert_eq!(meminfo.mem_usage(), Some(75));
+        assert_eq!(meminfo.swap_usage(), Some(75));
+        // (8000000 + 2000000 - 2000000 - 500000) * 100 / (8000000 + 2000000)
+        assert_eq!(meminfo.combined_usage(), Some(75));
+    }
+
+    #[test]
+    fn test_combined_usage_without_swap() {
+        let meminfo = Meminfo {
+            mem_total: Some(4000000),
+            mem_available: Some(1000000),
+            swap_total: Some(0),
+            swap_free: Some(0),
+        };
+        assert_eq!(meminfo.swap_usage(), None);
+        assert_eq!(meminfo.combined_usage(), Some(75));
+        let meminfo = Meminfo {
+            mem_total: Some(4000000),
+            mem_available: Some(1000000),
+            swap_total: Some(4000000),
+            swap_free: Some(4000000),
+        };
+        assert_eq!(meminfo.combined_usage(), Some(37));
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("meminfo"),
+            MEMINFO.replace("SwapFree:         500000", "SwapFree:        2000000"),
+        )
+        .unwrap();
+        let data = memory_usage(root.clone()).get_data().await;
+        std::fs::remove_dir_all(&root).unwrap();
+        let data: Vec<u8> = data.unwrap().into_iter().map(|x| x.unwrap()).collect();
+        assert_eq!(data, vec![75, 0, 60]);
+    }
 }
@@ -2,18 +2,81 @@ use crate::action;
 use crate::alarm;
 use crate::alarm::{Alarm, AlarmBase, DataSink};
 use crate::config;
+use crate::redaction::Redaction;
 use crate::ActionMap;
 use crate::{Error, PlaceholderMap, Result};
 use async_trait::async_trait;
 
+mod acme_renewal;
+mod boot_state;
+mod connection_count;
+mod consumer_lag;
+mod cpu_throttle;
+mod cpu_usage;
+mod cron_job_status;
+mod derived;
+mod dir_entry_count;
+mod disk_io;
+mod disk_latency;
+mod disk_temperature;
+mod ephemeral_ports;
+mod error_rate;
+mod expiring_credential;
+mod expression;
+mod fan_speed;
+mod file_age;
+mod file_descriptors;
+mod file_size;
 mod filesystem_usage;
+mod http_status;
+mod image_update;
+mod inode_usage;
+mod kube_pods;
+mod load_average;
+mod local_cert_expiry;
+mod log_growth_rate;
 mod memory_usage;
+mod mount_present;
+mod numa_memory;
+mod oldest_file_age;
+mod pid_file;
+mod ping;
+mod process_memory;
+mod process_presence;
+mod raid_bbu;
+mod rtc_drift;
+#[cfg(feature = "script")]
+mod script;
+mod smart_health;
+mod smoothing;
+mod swap_usage;
+mod synthetic_login;
+mod systemd_failed_units;
+mod systemd_unit;
+mod tcp_connect;
+mod temperature;
+mod time_wait_count;
+mod tls_cert_expiry;
+mod transform;
+mod uptime;
+mod wireguard_peer;
+mod zombie_processes;
 
 #[async_trait]
 pub trait Check: Send + Sync {
     async fn trigger(&mut self);
     fn interval(&self) -> std::time::Duration;
+    fn skip_overlapping(&self) -> bool;
     fn name(&self) -> &str;
+
+    fn disabled(&self) -> bool {
+        false
+    }
+
+    // Whether the last cycle got no data at all.
+    fn failing(&self) -> bool {
+        false
+    }
 }
 
 #[async_trait]
@@ -23,18 +86,69 @@ pub trait DataSource: Send + Sync {
     async fn get_data(&self) -> Result<Vec<Result<Self::Item>>>;
     fn format_data(data: &Self::Item) -> String;
     fn ids(&self) -> &[String];
+
+    // Numeric representation of the data that can be used as input of derived checks.
+    fn value(_data: &Self::Item) -> Option<f64> {
+        None
+    }
+
+    // Additional placeholders describing the data errors of the last cycle.
+    fn add_error_placeholders(&self, _placeholders: &mut PlaceholderMap) {}
 }
 
+pub struct Value {
+    value: f64,
+    timestamp: tokio::time::Instant,
+    interval: std::time::Duration,
+}
+
+impl Value {
+    pub fn new(value: f64, interval: std::time::Duration) -> Self {
+        Self {
+            value,
+            timestamp: tokio::time::Instant::now(),
+            interval,
+        }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    // Allow the check to miss one cycle before its value is considered stale.
+    pub fn is_stale(&self) -> bool {
+        self.timestamp.elapsed() > self.interval * 2
+    }
+}
+
+// Latest values of all checks by check name and ID.
+pub type ValueStore =
+    std::sync::Arc<std::sync::RwLock<std::collections::HashMap<(String, String), Value>>>;
+
 pub struct CheckBase<T, U>
 where
     T: DataSource,
     U: Alarm,
 {
     interval: u32,
+    skip_overlapping: bool,
     name: String,
     placeholders: PlaceholderMap,
     data_source: T,
     alarms: Vec<Vec<U>>,
+    values: ValueStore,
+    auto_disable_cycles: Option<u32>,
+    auto_disable_action: Option<std::sync::Arc<dyn action::Action>>,
+    failed_cycles: u32,
+    disabled: bool,
+    failing: bool,
+    warmup_cycles: u32,
+    history_size: usize,
+    // recent samples per ID, oldest first
+    history: Vec<std::collections::VecDeque<String>>,
+    redaction: Option<std::sync::Arc<Redaction>>,
+    // data errors count towards the ErrorRate check
+    error_count: Option<std::sync::Arc<std::sync::atomic::AtomicU64>>,
 }
 
 impl<T, U> CheckBase<T, U>
@@ -44,10 +158,12 @@ where
 {
     fn new(
         interval: u32,
+        skip_overlapping: bool,
         name: String,
         placeholders: PlaceholderMap,
         data_source: T,
         alarms: Vec<Vec<U>>,
+        values: ValueStore,
     ) -> Result<Self> {
         if interval == 0 {
             Err(Error(String::from("'interval' cannot be 0.")))
@@ -56,13 +172,129 @@ where
         } else {
             Ok(Self {
                 interval,
+                skip_overlapping,
                 name,
                 placeholders,
                 data_source,
                 alarms,
+                values,
+                auto_disable_cycles: None,
+                auto_disable_action: None,
+                failed_cycles: 0,
+                disabled: false,
+                failing: false,
+                warmup_cycles: 0,
+                history_size: 0,
+                history: Vec::new(),
+                redaction: None,
+                error_count: None,
             })
         }
     }
+
+    fn set_auto_disable(
+        &mut self,
+        cycles: u32,
+        action: Option<std::sync::Arc<dyn action::Action>>,
+    ) -> Result<()> {
+        if cycles == 0 {
+            return Err(Error(String::from("'auto_disable_cycles' cannot be 0.")));
+        }
+        self.auto_disable_cycles = Some(cycles);
+        self.auto_disable_action = action;
+        Ok(())
+    }
+
+    // The alarms are held inactive during the first cycles, e.g. until delta-based checks have
+    // enough samples.
+    fn set_warmup(&mut self, cycles: u32) {
+        self.warmup_cycles = cycles;
+    }
+
+    fn set_history(&mut self, size: usize) {
+        self.history_size = size;
+        self.history = vec![std::collections::VecDeque::new(); self.data_source.ids().len()];
+    }
+
+    fn set_error_count(&mut self, error_count: std::sync::Arc<std::sync::atomic::AtomicU64>) {
+        self.error_count = Some(error_count);
+    }
+
+    // The alarms share the redaction of their check, see `AlarmBase::set_redaction`.
+    fn set_redaction(&mut self, redaction: std::sync::Arc<Redaction>) {
+        self.redaction = Some(redaction);
+    }
+
+    // The formatted data as it's logged and kept in the history.
+    fn format_data(redaction: Option<&Redaction>, data: &T::Item) -> String {
+        redact(redaction, T::format_data(data))
+    }
+
+    // Counts the consecutive cycles in which no data at all could be retrieved.
+    async fn update_failed_cycles(
+        &mut self,
+        data_vec: &[Result<T::Item>],
+        placeholders: &PlaceholderMap,
+    ) {
+        let cycles = match self.auto_disable_cycles {
+            Some(cycles) => cycles,
+            None => return,
+        };
+        let last_error = match data_vec.last() {
+            Some(Err(err)) if data_vec.iter().all(|x| x.is_err()) => err.clone(),
+            _ => {
+                self.failed_cycles = 0;
+                return;
+            }
+        };
+        self.failed_cycles += 1;
+        if self.failed_cycles < cycles {
+            return;
+        }
+        self.disabled = true;
+        log::error!(
+            "Check '{}' got no data for {} consecutive cycles and is disabled until MinMon is restarted.",
+            self.name,
+            self.failed_cycles
+        );
+        if let Some(action) = &self.auto_disable_action {
+            let mut placeholders = placeholders.clone();
+            placeholders.insert(String::from("alarm_name"), String::from("AutoDisable"));
+            placeholders.insert(String::from("alarm_id"), String::from("auto_disable"));
+            placeholders.insert(String::from("alarm_state"), String::from("Error"));
+            placeholders.insert(String::from("alarm_uuid"), uuid::Uuid::new_v4().to_string());
+            placeholders.insert(
+                String::from("alarm_timestamp"),
+                crate::iso8601(std::time::SystemTime::now()),
+            );
+            placeholders.insert(String::from("check_error"), last_error.to_string());
+            if let Err(err) = action.trigger(placeholders).await {
+                log::error!(
+                    "Auto-disable action of check '{}' had an error: {}",
+                    self.name,
+                    err
+                );
+            }
+        }
+    }
+
+    fn update_values(&self, data_vec: &[Result<T::Item>]) {
+        let mut values = self.values.write().unwrap();
+        for (id, data) in self.data_source.ids().iter().zip(data_vec.iter()) {
+            let key = (self.name.clone(), id.clone());
+            match data.as_ref().ok().and_then(T::value) {
+                Some(value) => {
+                    values.insert(
+                        key,
+                        Value::new(value, std::time::Duration::from_secs(self.interval.into())),
+                    );
+                }
+                None => {
+                    values.remove(&key);
+                }
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -72,6 +304,9 @@ where
     U: Alarm<Item = T::Item>,
 {
     async fn trigger(&mut self) {
+        if self.disabled {
+            return;
+        }
         let mut placeholders = crate::global_placeholders();
         crate::merge_placeholders(&mut placeholders, &self.placeholders);
         placeholders.insert(String::from("check_name"), self.name.clone());
@@ -83,27 +318,80 @@ where
             }
             res
         });
+        // Errors often quote the data source's output, so they are redacted right away.
+        let data_vec: Vec<Result<T::Item>> = match &self.redaction {
+            Some(redaction) => data_vec
+                .into_iter()
+                .map(|x| x.map_err(|x| Error(redaction.redact(&x.0))))
+                .collect(),
+            None => data_vec,
+        };
+        self.update_values(&data_vec);
+        self.failing = !data_vec.is_empty() && data_vec.iter().all(|x| x.is_err());
+        if self.history_size > 0 {
+            let timestamp = crate::iso8601(std::time::SystemTime::now());
+            let samples: Vec<String> = data_vec
+                .iter()
+                .map(|data| match data {
+                    Ok(data) => format!(
+                        "{} {}",
+                        timestamp,
+                        Self::format_data(self.redaction.as_deref(), data)
+                    ),
+                    Err(err) => format!("{} error: {}", timestamp, err),
+                })
+                .collect();
+            for (history, sample) in self.history.iter_mut().zip(samples) {
+                if history.len() == self.history_size {
+                    history.pop_front();
+                }
+                history.push_back(sample);
+            }
+        }
+        if self.warmup_cycles > 0 {
+            self.warmup_cycles -= 1;
+            log::debug!(
+                "Check '{}' is warming up, alarms are inactive for {} more cycle(s).",
+                self.name,
+                self.warmup_cycles
+            );
+            // A check that gets no data at all is still disabled in time.
+            self.update_failed_cycles(&data_vec, &placeholders).await;
+            return;
+        }
         for ((i, data), alarms) in data_vec.iter().enumerate().zip(self.alarms.iter_mut()) {
             match data {
                 Ok(data) => log::debug!(
                     "Check '{}' got {} for id '{}'.",
                     self.name,
-                    T::format_data(data),
-                    ids[i]
-                ),
-                Err(err) => log::warn!(
-                    "Check '{}' got no data for id '{}': {}",
-                    self.name,
-                    ids[i],
-                    err
+                    Self::format_data(self.redaction.as_deref(), data),
+                    redact(self.redaction.as_deref(), ids[i].clone())
                 ),
+                Err(err) => {
+                    log::warn!(
+                        "Check '{}' got no data for id '{}': {}",
+                        self.name,
+                        redact(self.redaction.as_deref(), ids[i].clone()),
+                        err
+                    );
+                    if let Some(error_count) = &self.error_count {
+                        error_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    }
+                }
             }
             for alarm in alarms.iter_mut() {
                 let mut placeholders = placeholders.clone();
+                if let Some(history) = self.history.get(i) {
+                    placeholders.insert(
+                        String::from("check_history"),
+                        history.iter().cloned().collect::<Vec<String>>().join("\n"),
+                    );
+                }
                 let result = match data {
                     Ok(data) => alarm.put_data(data, placeholders).await,
                     Err(err) => {
                         placeholders.insert(String::from("check_error"), err.to_string());
+                        self.data_source.add_error_placeholders(&mut placeholders);
                         alarm.put_error(err, placeholders).await
                     }
                 };
@@ -112,30 +400,191 @@ where
                 }
             }
         }
+        self.update_failed_cycles(&data_vec, &placeholders).await;
     }
 
     fn interval(&self) -> std::time::Duration {
         std::time::Duration::from_secs(self.interval.into())
     }
 
+    fn skip_overlapping(&self) -> bool {
+        self.skip_overlapping
+    }
+
     fn name(&self) -> &str {
         self.name.as_str()
     }
+
+    fn disabled(&self) -> bool {
+        self.disabled
+    }
+
+    fn failing(&self) -> bool {
+        self.failing
+    }
+}
+
+// Reading from procfs or sysfs fails confusingly if they are not mounted, e.g. in a restricted
+// container. A hint is added to the error message then.
+fn proc_error(proc_path: &std::path::Path, message: String) -> Error {
+    if proc_path.join("stat").is_file() {
+        Error(message)
+    } else {
+        Error(format!(
+            "{} {} does not look like a procfs mount. When running in a container, mount the host's /proc (e.g. at /host/proc) and set 'proc_path'.",
+            message,
+            proc_path.display()
+        ))
+    }
+}
+
+fn sys_error(sys_path: &std::path::Path, message: String) -> Error {
+    if sys_path.join("kernel").is_dir() {
+        Error(message)
+    } else {
+        Error(format!(
+            "{} {} does not look like a sysfs mount. When running in a container, mount the host's /sys (e.g. at /host/sys) and set 'sys_path'.",
+            message,
+            sys_path.display()
+        ))
+    }
 }
 
-fn factory<'a, T, U>(check_config: &'a config::Check, actions: &ActionMap) -> Result<Box<dyn Check>>
+// IDs can contain secrets as well, e.g. a token in a URL.
+fn redact(redaction: Option<&Redaction>, text: String) -> String {
+    match redaction {
+        Some(redaction) => redaction.redact(&text),
+        None => text,
+    }
+}
+
+// Fetches the data of the IDs of a network check with up to `concurrency` requests at a time, so
+// the timeouts of many slow targets don't add up. The results keep the order of the IDs.
+async fn fetch_concurrently<I, F, Fut, T>(ids: I, concurrency: usize, fetch: F) -> Vec<Result<T>>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    use futures::StreamExt;
+    // The futures are created up front, a lazily mapped stream isn't `Send` in `get_data`.
+    let fetches: Vec<Fut> = ids.into_iter().map(fetch).collect();
+    futures::stream::iter(fetches)
+        .buffered(concurrency)
+        .collect()
+        .await
+}
+
+fn concurrency(concurrency: u32) -> Result<usize> {
+    if concurrency == 0 {
+        Err(Error(String::from("'concurrency' cannot be 0.")))
+    } else {
+        Ok(concurrency as usize)
+    }
+}
+
+fn alarm_placeholders(alarm_config: &config::Alarm) -> PlaceholderMap {
+    let mut placeholders = PlaceholderMap::new();
+    if let Some(description) = &alarm_config.description {
+        placeholders.insert(String::from("alarm_description"), description.clone());
+    }
+    if let Some(runbook_url) = &alarm_config.runbook_url {
+        placeholders.insert(String::from("alarm_runbook_url"), runbook_url.clone());
+    }
+    crate::merge_placeholders(&mut placeholders, &alarm_config.placeholders);
+    placeholders
+}
+
+fn factory<'a, T, U>(
+    check_config: &'a config::Check,
+    index: usize,
+    actions: &ActionMap,
+    values: &ValueStore,
+    incidents: &std::sync::Arc<alarm::Incidents>,
+    error_count: &std::sync::Arc<std::sync::atomic::AtomicU64>,
+) -> Result<Box<dyn Check>>
 where
     T: DataSource + TryFrom<&'a config::Check, Error = Error> + 'static,
     U: DataSink<Item = T::Item> + TryFrom<&'a config::Alarm, Error = Error> + 'static,
 {
     let data_source = T::try_from(check_config)?;
+    factory_with_data_source::<T, U>(
+        data_source,
+        check_config,
+        index,
+        actions,
+        values,
+        incidents,
+        error_count,
+    )
+}
+
+fn factory_with_data_source<'a, T, U>(
+    data_source: T,
+    check_config: &'a config::Check,
+    index: usize,
+    actions: &ActionMap,
+    values: &ValueStore,
+    incidents: &std::sync::Arc<alarm::Incidents>,
+    error_count: &std::sync::Arc<std::sync::atomic::AtomicU64>,
+) -> Result<Box<dyn Check>>
+where
+    T: DataSource + 'static,
+    U: DataSink<Item = T::Item> + TryFrom<&'a config::Alarm, Error = Error> + 'static,
+{
+    // Transformed data is always compared against the level as a floating point number.
+    match &check_config.transform {
+        Some(_) if !U::LEVEL => Err(Error(String::from(
+            "'transform' is only supported by checks with level alarms.",
+        ))),
+        Some(transform) => build_check::<transform::Transform<T>, alarm::Level<f64>>(
+            transform::Transform::new(data_source, transform)?,
+            check_config,
+            index,
+            actions,
+            values,
+            incidents,
+            error_count,
+        ),
+        None => build_check::<T, U>(
+            data_source,
+            check_config,
+            index,
+            actions,
+            values,
+            incidents,
+            error_count,
+        ),
+    }
+}
+
+fn build_check<'a, T, U>(
+    data_source: T,
+    check_config: &'a config::Check,
+    index: usize,
+    actions: &ActionMap,
+    values: &ValueStore,
+    incidents: &std::sync::Arc<alarm::Incidents>,
+    error_count: &std::sync::Arc<std::sync::atomic::AtomicU64>,
+) -> Result<Box<dyn Check>>
+where
+    T: DataSource + 'static,
+    U: DataSink<Item = T::Item> + TryFrom<&'a config::Alarm, Error = Error> + 'static,
+{
+    let redaction = if check_config.redact.is_empty() {
+        None
+    } else {
+        Some(std::sync::Arc::new(Redaction::new(&check_config.redact)?))
+    };
     let mut all_alarms: Vec<Vec<AlarmBase<U>>> = Vec::new();
     for (i, id) in data_source.ids().iter().enumerate() {
         let mut alarms: Vec<AlarmBase<U>> = Vec::new();
-        for alarm_config in check_config.alarms.iter() {
+        for (j, alarm_config) in check_config.alarms.iter().enumerate() {
             let alarm_log_id = format!(
                 "Alarm '{}', id '{}' from check '{}'",
-                alarm_config.name, id, check_config.name
+                alarm_config.name,
+                redact(redaction.as_deref(), id.clone()),
+                check_config.name
             );
             if alarm_config.disable {
                 log::info!("{} is disabled.", alarm_log_id);
@@ -157,11 +606,11 @@ where
                 alarm_config.error_repeat_cycles,
                 alarm_log_id.clone(),
             )?;
-            let alarm = alarm::AlarmBase::new(
+            let mut alarm = alarm::AlarmBase::new(
                 alarm_config.name.clone(),
                 id.clone(),
                 action::get_action(&alarm_config.action, actions)?,
-                alarm_config.placeholders.clone(),
+                alarm_placeholders(alarm_config),
                 match &alarm_config.recover_action {
                     Some(action) => Some(action::get_action(action, actions)?),
                     None => None,
@@ -175,33 +624,432 @@ where
                 alarm_config.invert,
                 alarm_state_machine,
                 data_sink,
-                alarm_log_id,
+                alarm_log_id.clone(),
             )?;
+            alarm.set_stale_cycles(alarm_config.stale_cycles, alarm_config.cycles)?;
+            alarm.set_heartbeat_cycles(alarm_config.heartbeat_cycles);
+            if let Some(recover_confirmation) = &alarm_config.recover_confirmation {
+                alarm.set_recover_confirmation(alarm::RecoverConfirmation::new(
+                    recover_confirmation,
+                    values.clone(),
+                )?);
+            }
+            if let Some(incident) = &alarm_config.incident {
+                alarm.set_incident(alarm::Incident::new(
+                    incidents.clone(),
+                    incident,
+                    (index, j, id.clone()),
+                    &alarm_log_id,
+                )?);
+            }
+            if let Some(dedup) = &alarm_config.dedup {
+                alarm.set_dedup(alarm::Dedup::new(dedup)?);
+            }
+            if let Some(redaction) = &redaction {
+                alarm.set_redaction(redaction.clone());
+            }
             alarms.push(alarm);
         }
         all_alarms.push(alarms);
     }
-    Ok(Box::new(CheckBase::new(
+    let mut check = CheckBase::new(
         check_config.interval,
+        check_config.skip_overlapping,
         check_config.name.clone(),
         check_config.placeholders.clone(),
         data_source,
         all_alarms,
-    )?))
+        values.clone(),
+    )?;
+    check.set_warmup(check_config.warmup);
+    check.set_history(check_config.history as usize);
+    check.set_error_count(error_count.clone());
+    if let Some(redaction) = redaction {
+        check.set_redaction(redaction);
+    }
+    if let Some(cycles) = check_config.auto_disable_cycles {
+        check.set_auto_disable(
+            cycles,
+            match &check_config.auto_disable_action {
+                Some(action) => Some(action::get_action(action, actions)?),
+                None => None,
+            },
+        )?;
+    }
+    Ok(Box::new(check))
 }
 
+// `index` identifies the check within the incidents.
 pub fn from_check_config(
     check_config: &config::Check,
+    index: usize,
     actions: &ActionMap,
+    values: &ValueStore,
+    incidents: &std::sync::Arc<alarm::Incidents>,
+    error_count: &std::sync::Arc<std::sync::atomic::AtomicU64>,
 ) -> Result<Box<dyn Check>> {
     match &check_config.type_ {
         // NOTE Add mapping here when implementing new data source / alarms.
-        config::CheckType::FilesystemUsage(_) => {
-            factory::<filesystem_usage::FilesystemUsage, alarm::Level>(check_config, actions)
+        config::CheckType::AcmeRenewal(_) => factory::<
+            acme_renewal::AcmeRenewal,
+            alarm::Status<acme_renewal::RenewalState>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::BootState(_) => factory::<
+            boot_state::BootState,
+            alarm::Status<boot_state::SystemState>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::ConnectionCount(_) => factory::<
+            connection_count::ConnectionCount,
+            alarm::Level<u64>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::ConsumerLag(_) => {
+            factory::<consumer_lag::ConsumerLag, alarm::Level<u64>>(
+                check_config,
+                index,
+                actions,
+                values,
+                incidents,
+                error_count,
+            )
+        }
+        config::CheckType::CpuThrottle(_) => factory::<
+            cpu_throttle::CpuThrottle,
+            alarm::Level<cpu_throttle::Throttling>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::CpuUsage(_) => factory::<cpu_usage::CpuUsage, alarm::Level<u8>>(
+            check_config,
+            index,
+            actions,
+            values,
+            incidents,
+            error_count,
+        ),
+        config::CheckType::CronJobStatus(_) => factory::<
+            cron_job_status::CronJobStatus,
+            alarm::Status<cron_job_status::JobStatus>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::Derived(_) => derived::Derived::new(check_config, values.clone())
+            .and_then(|x| {
+                factory_with_data_source::<derived::Derived, alarm::Level<f64>>(
+                    x,
+                    check_config,
+                    index,
+                    actions,
+                    values,
+                    incidents,
+                    error_count,
+                )
+            }),
+        config::CheckType::DirEntryCount(_) => factory::<
+            dir_entry_count::DirEntryCount,
+            alarm::Level<u64>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::DiskIo(_) => factory::<disk_io::DiskIo, alarm::Level<disk_io::IoRate>>(
+            check_config,
+            index,
+            actions,
+            values,
+            incidents,
+            error_count,
+        ),
+        config::CheckType::DiskLatency(_) => factory::<
+            disk_latency::DiskLatency,
+            alarm::Level<disk_latency::IoLatency>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::DiskTemperature(_) => factory::<
+            disk_temperature::DiskTemperature,
+            alarm::Level<i16>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::EphemeralPorts(_) => factory::<
+            ephemeral_ports::EphemeralPorts,
+            alarm::Level<u8>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::ErrorRate(_) => {
+            factory_with_data_source::<error_rate::ErrorRate, alarm::Level<u64>>(
+                error_rate::ErrorRate::new(error_count.clone()),
+                check_config,
+                index,
+                actions,
+                values,
+                incidents,
+                error_count,
+            )
+        }
+        config::CheckType::ExpiringCredential(_) => {
+            factory::<
+                expiring_credential::ExpiringCredential,
+                alarm::Level<expiring_credential::DaysRemaining>,
+            >(check_config, index, actions, values, incidents, error_count)
         }
+        config::CheckType::FanSpeed(_) => {
+            factory::<fan_speed::FanSpeed, alarm::Level<fan_speed::FanRpm>>(
+                check_config,
+                index,
+                actions,
+                values,
+                incidents,
+                error_count,
+            )
+        }
+        config::CheckType::FileAge(_) => factory::<file_age::FileAge, alarm::Level<u64>>(
+            check_config,
+            index,
+            actions,
+            values,
+            incidents,
+            error_count,
+        ),
+        config::CheckType::FileDescriptors(_) => factory::<
+            file_descriptors::FileDescriptors,
+            alarm::Level<u8>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::FileSize(_) => factory::<file_size::FileSize, alarm::Level<u64>>(
+            check_config,
+            index,
+            actions,
+            values,
+            incidents,
+            error_count,
+        ),
+        config::CheckType::FilesystemUsage(_) => factory::<
+            filesystem_usage::FilesystemUsage,
+            alarm::Level<u8>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::HttpStatus(_) => factory::<
+            http_status::HttpStatus,
+            alarm::StatusCode<http_status::HttpResponse>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::ImageUpdate(_) => factory::<
+            image_update::ImageUpdate,
+            alarm::Status<image_update::ImageStatus>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::InodeUsage(_) => factory::<inode_usage::InodeUsage, alarm::Level<u8>>(
+            check_config,
+            index,
+            actions,
+            values,
+            incidents,
+            error_count,
+        ),
+        config::CheckType::KubePods(_) => {
+            factory::<kube_pods::KubePods, alarm::Level<kube_pods::PodCount>>(
+                check_config,
+                index,
+                actions,
+                values,
+                incidents,
+                error_count,
+            )
+        }
+        config::CheckType::LoadAverage(_) => factory::<
+            load_average::LoadAverage,
+            alarm::Level<load_average::Load>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::LocalCertExpiry(_) => factory::<
+            local_cert_expiry::LocalCertExpiry,
+            alarm::Level<local_cert_expiry::CertExpiry>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::LogGrowthRate(_) => factory::<
+            log_growth_rate::LogGrowthRate,
+            alarm::Level<log_growth_rate::LogGrowth>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
         config::CheckType::MemoryUsage(_) => {
-            factory::<memory_usage::MemoryUsage, alarm::Level>(check_config, actions)
+            factory::<memory_usage::MemoryUsage, alarm::Level<u8>>(
+                check_config,
+                index,
+                actions,
+                values,
+                incidents,
+                error_count,
+            )
+        }
+        config::CheckType::MountPresent(_) => factory::<
+            mount_present::MountPresent,
+            alarm::Status<mount_present::MountStatus>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::NumaMemory(_) => factory::<numa_memory::NumaMemory, alarm::Level<u8>>(
+            check_config,
+            index,
+            actions,
+            values,
+            incidents,
+            error_count,
+        ),
+        config::CheckType::OldestFileAge(_) => factory::<
+            oldest_file_age::OldestFileAge,
+            alarm::Level<oldest_file_age::FileAge>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::PidFile(_) => {
+            factory::<pid_file::PidFile, alarm::Status<pid_file::PidFileStatus>>(
+                check_config,
+                index,
+                actions,
+                values,
+                incidents,
+                error_count,
+            )
         }
+        config::CheckType::Ping(_) => factory::<ping::Ping, alarm::Level<ping::RoundTrip>>(
+            check_config,
+            index,
+            actions,
+            values,
+            incidents,
+            error_count,
+        ),
+        config::CheckType::ProcessMemory(_) => factory::<
+            process_memory::ProcessMemory,
+            alarm::Level<process_memory::ProcessRss>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::ProcessPresence(_) => {
+            factory::<
+                process_presence::ProcessPresence,
+                alarm::Status<process_presence::ProcessCount>,
+            >(check_config, index, actions, values, incidents, error_count)
+        }
+        config::CheckType::RaidBbu(_) => {
+            factory::<raid_bbu::RaidBbu, alarm::Status<raid_bbu::BbuStatus>>(
+                check_config,
+                index,
+                actions,
+                values,
+                incidents,
+                error_count,
+            )
+        }
+        config::CheckType::RtcDrift(_) => {
+            factory::<rtc_drift::RtcDrift, alarm::Level<rtc_drift::ClockOffset>>(
+                check_config,
+                index,
+                actions,
+                values,
+                incidents,
+                error_count,
+            )
+        }
+        #[cfg(feature = "script")]
+        config::CheckType::Script(_) => factory::<script::Script, alarm::Level<f64>>(
+            check_config,
+            index,
+            actions,
+            values,
+            incidents,
+            error_count,
+        ),
+        config::CheckType::SmartHealth(_) => factory::<
+            smart_health::SmartHealth,
+            alarm::Level<smart_health::DiskHealth>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::SwapUsage(_) => factory::<swap_usage::SwapUsage, alarm::Level<u64>>(
+            check_config,
+            index,
+            actions,
+            values,
+            incidents,
+            error_count,
+        ),
+        config::CheckType::SyntheticLogin(_) => factory::<
+            synthetic_login::SyntheticLogin,
+            alarm::Level<synthetic_login::LoginTime>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::SystemdFailedUnits(_) => {
+            factory::<
+                systemd_failed_units::SystemdFailedUnits,
+                alarm::Level<systemd_failed_units::FailedUnits>,
+            >(check_config, index, actions, values, incidents, error_count)
+        }
+        config::CheckType::SystemdUnit(_) => factory::<
+            systemd_unit::SystemdUnit,
+            alarm::Status<systemd_unit::UnitStatus>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::TcpConnect(_) => factory::<
+            tcp_connect::TcpConnect,
+            alarm::Level<tcp_connect::ConnectTime>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::Temperature(_) => factory::<
+            temperature::Temperature,
+            alarm::Level<temperature::SensorTemperature>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::TimeWaitCount(_) => factory::<
+            time_wait_count::TimeWaitCount,
+            alarm::Level<u64>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::TlsCertExpiry(_) => factory::<
+            tls_cert_expiry::TlsCertExpiry,
+            alarm::Level<tls_cert_expiry::TlsExpiry>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::Uptime(_) => factory::<uptime::Uptime, alarm::Level<u64>>(
+            check_config,
+            index,
+            actions,
+            values,
+            incidents,
+            error_count,
+        ),
+        config::CheckType::WireguardPeer(_) => factory::<
+            wireguard_peer::WireguardPeer,
+            alarm::Level<wireguard_peer::PeerHandshake>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
+        config::CheckType::ZombieProcesses(_) => factory::<
+            zombie_processes::ZombieProcesses,
+            alarm::Level<zombie_processes::ZombieCount>,
+        >(
+            check_config, index, actions, values, incidents, error_count
+        ),
     }
     .map_err(|x| {
         Error(format!(
@@ -210,3 +1058,596 @@ pub fn from_check_config(
         ))
     })
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use mockall::predicate::*;
+
+    #[tokio::test]
+    async fn test_alarm_description_placeholders() {
+        let config = config::Config::try_from(
+            r#"
+            [[checks]]
+            name = "test-check"
+            type = "FilesystemUsage"
+            mountpoints = ["/"]
+
+            [[checks.alarms]]
+            name = "test-alarm"
+            description = "Root filesystem is full."
+            runbook_url = "https://example.com/runbook"
+            action = "test-action"
+            level = 100
+            invert = true
+        "#,
+        )
+        .unwrap();
+        let mut mock_action = action::MockAction::new();
+        mock_action
+            .expect_trigger()
+            .once()
+            .with(function(|placeholders: &PlaceholderMap| {
+                assert_eq!(
+                    placeholders.get("alarm_description").unwrap(),
+                    "Root filesystem is full."
+                );
+                assert_eq!(
+                    placeholders.get("alarm_runbook_url").unwrap(),
+                    "https://example.com/runbook"
+                );
+                true
+            }))
+            .returning(|_| Ok(()));
+        let mut actions = ActionMap::new();
+        actions.insert(
+            String::from("test-action"),
+            std::sync::Arc::new(mock_action),
+        );
+        let mut check = from_check_config(
+            &config.checks[0],
+            0,
+            &actions,
+            &ValueStore::default(),
+            &Default::default(),
+            &Default::default(),
+        )
+        .unwrap();
+        check.trigger().await;
+    }
+
+    #[test]
+    fn test_yaml_merge_keys() {
+        let config = config::Config::from_str_with_format(
+            r#"
+checks:
+  - &defaults
+    name: test-check
+    type: FilesystemUsage
+    interval: 42
+    mountpoints: ["/"]
+  - <<: *defaults
+    name: test-check-2
+"#,
+            config::ConfigFormat::Yaml,
+        )
+        .unwrap();
+        let check = from_check_config(
+            &config.checks[1],
+            0,
+            &ActionMap::new(),
+            &ValueStore::default(),
+            &Default::default(),
+            &Default::default(),
+        )
+        .unwrap();
+        assert_eq!(check.name(), "test-check-2");
+        assert_eq!(check.interval(), std::time::Duration::from_secs(42));
+    }
+
+    #[test]
+    fn test_alarm_placeholders_without_description() {
+        let config = config::Config::try_from(
+            r#"
+            [[checks]]
+            name = "test-check"
+            type = "FilesystemUsage"
+            mountpoints = ["/"]
+
+            [[checks.alarms]]
+            name = "test-alarm"
+            action = "test-action"
+            level = 100
+            placeholders = {"Foo" = "Bar"}
+        "#,
+        )
+        .unwrap();
+        let placeholders = alarm_placeholders(&config.checks[0].alarms[0]);
+        assert_eq!(
+            placeholders,
+            PlaceholderMap::from([(String::from("Foo"), String::from("Bar"))])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_proc_path() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("net")).unwrap();
+        std::fs::write(root.join("stat"), "cpu 1 2 3 4\n").unwrap();
+        std::fs::write(
+            root.join("meminfo"),
+            "MemTotal: 1000 kB\nMemAvailable: 250 kB\nSwapTotal: 0 kB\nSwapFree: 0 kB\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("net/sockstat"),
+            "TCP: inuse 1 orphan 0 tw 42 alloc 1 mem 1\n",
+        )
+        .unwrap();
+        let config = config::Config::try_from(
+            format!(
+                r#"
+                proc_path = "{}"
+
+                [[checks]]
+                name = "memory"
+                type = "MemoryUsage"
+
+                [[checks]]
+                name = "time-wait"
+                type = "TimeWaitCount"
+                "#,
+                root.display()
+            )
+            .as_str(),
+        )
+        .unwrap();
+        let memory_usage = memory_usage::MemoryUsage::try_from(&config.checks[0])
+            .unwrap()
+            .get_data()
+            .await;
+        let time_wait_count = time_wait_count::TimeWaitCount::try_from(&config.checks[1])
+            .unwrap()
+            .get_data()
+            .await;
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(*memory_usage.unwrap()[0].as_ref().unwrap(), 75);
+        assert_eq!(*time_wait_count.unwrap()[0].as_ref().unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn test_proc_path_not_mounted() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        let config = config::Config::try_from(
+            format!(
+                r#"
+                proc_path = "{}"
+
+                [[checks]]
+                name = "memory"
+                type = "MemoryUsage"
+                "#,
+                root.display()
+            )
+            .as_str(),
+        )
+        .unwrap();
+        let error = memory_usage::MemoryUsage::try_from(&config.checks[0])
+            .unwrap()
+            .get_data()
+            .await
+            .err()
+            .unwrap();
+        assert!(error
+            .to_string()
+            .ends_with("does not look like a procfs mount. When running in a container, mount the host's /proc (e.g. at /host/proc) and set 'proc_path'."));
+    }
+
+    #[tokio::test]
+    async fn test_auto_disable() {
+        let config = config::Config::try_from(
+            r#"
+            [[checks]]
+            name = "test-check"
+            type = "FilesystemUsage"
+            mountpoints = ["/does/not/exist/1", "/does/not/exist/2"]
+            auto_disable_cycles = 3
+            auto_disable_action = "meta-action"
+
+            [[checks.alarms]]
+            name = "test-alarm"
+            action = "test-action"
+            level = 100
+        "#,
+        )
+        .unwrap();
+        let mut meta_action = action::MockAction::new();
+        meta_action
+            .expect_trigger()
+            .once()
+            .with(function(|placeholders: &PlaceholderMap| {
+                placeholders.get("check_name").unwrap() == "test-check"
+                    && placeholders.get("alarm_name").unwrap() == "AutoDisable"
+                    && placeholders.contains_key("check_error")
+            }))
+            .returning(|_| Ok(()));
+        let mut actions = ActionMap::new();
+        actions.insert(
+            String::from("test-action"),
+            std::sync::Arc::new(action::MockAction::new()),
+        );
+        actions.insert(
+            String::from("meta-action"),
+            std::sync::Arc::new(meta_action),
+        );
+        let mut check = from_check_config(
+            &config.checks[0],
+            0,
+            &actions,
+            &ValueStore::default(),
+            &Default::default(),
+            &Default::default(),
+        )
+        .unwrap();
+        for _ in 0..2 {
+            check.trigger().await;
+            assert!(!check.disabled());
+        }
+        check.trigger().await;
+        assert!(check.disabled());
+        // no further data retrieval or actions
+        check.trigger().await;
+        assert!(check.disabled());
+    }
+
+    #[tokio::test]
+    async fn test_auto_disable_partial_failure() {
+        let config = config::Config::try_from(
+            r#"
+            [[checks]]
+            name = "test-check"
+            type = "FilesystemUsage"
+            mountpoints = ["/", "/does/not/exist"]
+            auto_disable_cycles = 1
+        "#,
+        )
+        .unwrap();
+        let mut check = from_check_config(
+            &config.checks[0],
+            0,
+            &ActionMap::new(),
+            &ValueStore::default(),
+            &Default::default(),
+            &Default::default(),
+        )
+        .unwrap();
+        check.trigger().await;
+        assert!(!check.disabled());
+    }
+
+    #[test]
+    fn test_auto_disable_zero_cycles() {
+        let config = config::Config::try_from(
+            r#"
+            [[checks]]
+            name = "test-check"
+            type = "FilesystemUsage"
+            mountpoints = ["/"]
+            auto_disable_cycles = 0
+        "#,
+        )
+        .unwrap();
+        assert!(from_check_config(
+            &config.checks[0],
+            0,
+            &ActionMap::new(),
+            &ValueStore::default(),
+            &Default::default(),
+            &Default::default()
+        )
+        .is_err());
+    }
+
+    // Returns the next of the given values every cycle, like a delta-based check whose first
+    // values are off.
+    struct SequenceDataSource {
+        ids: Vec<String>,
+        values: std::sync::Mutex<std::collections::VecDeque<u8>>,
+    }
+
+    #[async_trait]
+    impl DataSource for SequenceDataSource {
+        type Item = u8;
+
+        async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+            let value = self.values.lock().unwrap().pop_front().unwrap();
+            Ok(vec![Ok(value)])
+        }
+
+        fn format_data(data: &Self::Item) -> String {
+            data.to_string()
+        }
+
+        fn ids(&self) -> &[String] {
+            &self.ids[..]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_warmup() {
+        let config = config::Config::try_from(
+            r#"
+            [[checks]]
+            name = "test-check"
+            type = "MemoryUsage"
+            warmup = 2
+
+            [[checks.alarms]]
+            name = "test-alarm"
+            action = "test-action"
+            level = 50
+        "#,
+        )
+        .unwrap();
+        let triggered = std::sync::Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let triggered_clone = triggered.clone();
+        let mut mock_action = action::MockAction::new();
+        mock_action.expect_trigger().returning(move |_| {
+            triggered_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        });
+        let mut actions = ActionMap::new();
+        actions.insert(
+            String::from("test-action"),
+            std::sync::Arc::new(mock_action),
+        );
+        let mut check = factory_with_data_source::<SequenceDataSource, alarm::Level<u8>>(
+            SequenceDataSource {
+                ids: vec![String::from("id")],
+                values: std::sync::Mutex::new(std::collections::VecDeque::from([100, 90, 10, 100])),
+            },
+            &config.checks[0],
+            0,
+            &actions,
+            &ValueStore::default(),
+            &Default::default(),
+            &Default::default(),
+        )
+        .unwrap();
+        // the bad values of the first two cycles are discarded
+        for _ in 0..3 {
+            check.trigger().await;
+            assert_eq!(triggered.load(std::sync::atomic::Ordering::SeqCst), 0);
+        }
+        check.trigger().await;
+        assert_eq!(triggered.load(std::sync::atomic::Ordering::SeqCst), 1);
+        // cycles without data count towards the auto-disable during the warmup as well
+        let mut check = CheckBase::new(
+            1,
+            false,
+            String::from("test-check"),
+            PlaceholderMap::new(),
+            ErrorDataSource {
+                ids: vec![String::from("id")],
+            },
+            vec![vec![NullAlarm {}]],
+            ValueStore::default(),
+        )
+        .unwrap();
+        check.set_warmup(3);
+        check.set_auto_disable(2, None).unwrap();
+        check.trigger().await;
+        assert!(!check.disabled());
+        check.trigger().await;
+        assert!(check.disabled());
+    }
+
+    struct ErrorDataSource {
+        ids: Vec<String>,
+    }
+
+    #[async_trait]
+    impl DataSource for ErrorDataSource {
+        type Item = u8;
+
+        async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+            Ok(vec![Err(Error(String::from("Error")))])
+        }
+
+        fn format_data(data: &Self::Item) -> String {
+            data.to_string()
+        }
+
+        fn ids(&self) -> &[String] {
+            &self.ids[..]
+        }
+    }
+
+    struct NullAlarm {}
+
+    #[async_trait]
+    impl Alarm for NullAlarm {
+        type Item = u8;
+
+        fn log_id(&self) -> &str {
+            "NullAlarm"
+        }
+
+        async fn put_data(&mut self, _data: &u8, _placeholders: PlaceholderMap) -> Result<()> {
+            Ok(())
+        }
+
+        async fn put_error(&mut self, _error: &Error, _placeholders: PlaceholderMap) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_concurrently() {
+        let delays = [300, 200, 100, 0];
+        let start = std::time::Instant::now();
+        let data = fetch_concurrently(delays.iter(), 2, |delay| async move {
+            tokio::time::sleep(std::time::Duration::from_millis(*delay)).await;
+            Ok(*delay)
+        })
+        .await;
+        // the results keep the order of the IDs, not of their completion
+        let data: Vec<u64> = data.into_iter().map(|x| x.unwrap()).collect();
+        assert_eq!(data, delays);
+        // 300ms and 200ms are fetched first, 100ms and 0ms follow as soon as slots free up
+        assert!(start.elapsed() < std::time::Duration::from_millis(550));
+        assert!(concurrency(0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_error_count() {
+        for alarm_count in [0, 2] {
+            let alarms = (0..alarm_count).map(|_| NullAlarm {}).collect();
+            let mut check = CheckBase::new(
+                1,
+                false,
+                String::from("test-check"),
+                PlaceholderMap::new(),
+                ErrorDataSource {
+                    ids: vec![String::from("id")],
+                },
+                vec![alarms],
+                ValueStore::default(),
+            )
+            .unwrap();
+            let error_count = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
+            check.set_error_count(error_count.clone());
+            check.trigger().await;
+            assert_eq!(error_count.load(std::sync::atomic::Ordering::Relaxed), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_history() {
+        let config = config::Config::try_from(
+            r#"
+            [[checks]]
+            name = "test-check"
+            type = "FilesystemUsage"
+            mountpoints = ["/"]
+            history = 2
+
+            [[checks.alarms]]
+            name = "test-alarm"
+            action = "test-action"
+            level = 100
+            invert = true
+            repeat_cycles = 1
+        "#,
+        )
+        .unwrap();
+        let histories = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let histories_clone = histories.clone();
+        let mut mock_action = action::MockAction::new();
+        mock_action.expect_trigger().returning(move |placeholders| {
+            histories_clone
+                .lock()
+                .unwrap()
+                .push(placeholders.get("check_history").unwrap().clone());
+            Ok(())
+        });
+        let mut actions = ActionMap::new();
+        actions.insert(
+            String::from("test-action"),
+            std::sync::Arc::new(mock_action),
+        );
+        let mut check = from_check_config(
+            &config.checks[0],
+            0,
+            &actions,
+            &ValueStore::default(),
+            &Default::default(),
+            &Default::default(),
+        )
+        .unwrap();
+        for _ in 0..3 {
+            check.trigger().await;
+        }
+        let histories = histories.lock().unwrap();
+        assert_eq!(histories.len(), 3);
+        let lines = |x: &String| x.lines().map(String::from).collect::<Vec<String>>();
+        assert_eq!(lines(&histories[0]).len(), 1);
+        let last = lines(&histories[2]);
+        assert_eq!(last.len(), 2);
+        for line in last {
+            assert!(line.contains("Z usage level "));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redact() {
+        let config = config::Config::try_from(
+            r#"
+            [[checks]]
+            name = "test-redact-check"
+            type = "FileAge"
+            paths = ["/nonexistent/token=s3cr3t"]
+            history = 1
+            redact = ['token=\S+']
+            placeholders = {"endpoint" = "https://example.com/?token=s3cr3t"}
+
+            [[checks.alarms]]
+            name = "test-alarm"
+            action = "test-action"
+            error_action = "test-action"
+            level = 100
+        "#,
+        )
+        .unwrap();
+        let placeholders = std::sync::Arc::new(std::sync::Mutex::new(PlaceholderMap::new()));
+        let placeholders_clone = placeholders.clone();
+        let mut mock_action = action::MockAction::new();
+        mock_action
+            .expect_trigger()
+            .once()
+            .returning(move |placeholders| {
+                *placeholders_clone.lock().unwrap() = placeholders;
+                Ok(())
+            });
+        let mut actions = ActionMap::new();
+        actions.insert(
+            String::from("test-action"),
+            std::sync::Arc::new(mock_action),
+        );
+        crate::test_util::capture_logs();
+        let mut check = from_check_config(
+            &config.checks[0],
+            0,
+            &actions,
+            &ValueStore::default(),
+            &Default::default(),
+            &Default::default(),
+        )
+        .unwrap();
+        check.trigger().await;
+        let logs = crate::test_util::captured_logs("test-redact-check");
+        assert!(logs.contains(&String::from(
+            "Alarm 'test-alarm', id '/nonexistent/***' from check 'test-redact-check' will be \
+             triggered after 1 bad cycles and recover after 1 good cycles."
+        )));
+        assert!(logs.iter().any(|x| x.starts_with(
+            "Check 'test-redact-check' got no data for id '/nonexistent/***': Could not stat \
+             /nonexistent/*** "
+        )));
+        assert!(logs.iter().all(|x| !x.contains("s3cr3t")));
+        let placeholders = placeholders.lock().unwrap();
+        assert!(placeholders
+            .get("check_error")
+            .unwrap()
+            .starts_with("Could not stat /nonexistent/*** "));
+        assert!(placeholders
+            .get("check_history")
+            .unwrap()
+            .contains("error: Could not stat /nonexistent/*** "));
+        assert_eq!(
+            placeholders.get("endpoint").unwrap(),
+            "https://example.com/?***"
+        );
+        assert!(placeholders.values().all(|x| !x.contains("s3cr3t")));
+    }
+}
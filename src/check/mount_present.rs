@@ -0,0 +1,251 @@
+use super::DataSource;
+use crate::alarm::StatusItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MountState {
+    Mounted,
+    WrongType,
+    Missing,
+}
+
+impl std::fmt::Display for MountState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            MountState::Mounted => write!(f, "Mounted"),
+            MountState::WrongType => write!(f, "WrongType"),
+            MountState::Missing => write!(f, "Missing"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct MountStatus {
+    state: MountState,
+    // source and filesystem type of the mount, if any
+    source: Option<String>,
+    fstype: Option<String>,
+}
+
+impl StatusItem for MountStatus {
+    fn is_ok(&self) -> bool {
+        self.state == MountState::Mounted
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("mount_state"), self.state.to_string());
+        if let Some(source) = &self.source {
+            placeholders.insert(String::from("source"), source.clone());
+        }
+        if let Some(fstype) = &self.fstype {
+            placeholders.insert(String::from("fstype"), fstype.clone());
+        }
+    }
+}
+
+pub struct MountPresent {
+    id: Vec<String>,
+    fstypes: Vec<Option<String>>,
+    mounts_path: std::path::PathBuf,
+    proc_path: std::path::PathBuf,
+}
+
+impl MountPresent {
+    // Spaces, tabs, newlines and backslashes are octal escaped in /proc/mounts, e.g. "\040".
+    fn unescape(field: &str) -> String {
+        let mut res = String::new();
+        let mut rest = field;
+        while let Some(index) = rest.find('\\') {
+            res.push_str(&rest[..index]);
+            match rest
+                .get(index + 1..index + 4)
+                .and_then(|x| u8::from_str_radix(x, 8).ok())
+            {
+                Some(byte) => {
+                    res.push(byte as char);
+                    rest = &rest[index + 4..];
+                }
+                None => {
+                    res.push('\\');
+                    rest = &rest[index + 1..];
+                }
+            }
+        }
+        res.push_str(rest);
+        res
+    }
+
+    // e.g. "nas:/export/backup /mnt/backup nfs4 rw,relatime,vers=4.2 0 0"
+    // If mounts are stacked on the same mount point, the last one is visible.
+    fn get_status(mounts: &str, path: &str, fstype: Option<&str>) -> MountStatus {
+        let mount = mounts
+            .lines()
+            .rev()
+            .filter_map(|x| {
+                let mut columns = x.split_whitespace();
+                Some((
+                    Self::unescape(columns.next()?),
+                    Self::unescape(columns.next()?),
+                    Self::unescape(columns.next()?),
+                ))
+            })
+            .find(|(_, mount_point, _)| mount_point == path);
+        match mount {
+            Some((source, _, mount_fstype)) => MountStatus {
+                state: if fstype.is_none_or(|x| x == mount_fstype) {
+                    MountState::Mounted
+                } else {
+                    MountState::WrongType
+                },
+                source: Some(source),
+                fstype: Some(mount_fstype),
+            },
+            None => MountStatus {
+                state: MountState::Missing,
+                source: None,
+                fstype: None,
+            },
+        }
+    }
+}
+
+impl TryFrom<&config::Check> for MountPresent {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::MountPresent(mount_present) = &check.type_ {
+            if mount_present.mounts.is_empty() {
+                return Err(Error(String::from("'mounts' cannot be empty.")));
+            }
+            let mut id = Vec::new();
+            for mount in mount_present.mounts.iter() {
+                let path = mount.path.display().to_string();
+                if !path.starts_with('/') {
+                    return Err(Error(format!("Path '{}' must be absolute.", path)));
+                } else if mount.fstype.as_ref().is_some_and(|x| x.is_empty()) {
+                    return Err(Error(format!(
+                        "'fstype' cannot be empty for path '{}'.",
+                        path
+                    )));
+                }
+                // /proc/mounts lists the mount points without trailing slashes.
+                let trimmed = path.trim_end_matches('/');
+                id.push(String::from(if trimmed.is_empty() { "/" } else { trimmed }));
+            }
+            Ok(Self {
+                id,
+                fstypes: mount_present
+                    .mounts
+                    .iter()
+                    .map(|x| x.fstype.clone())
+                    .collect(),
+                mounts_path: check.proc_path.join("mounts"),
+                proc_path: check.proc_path.clone(),
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for MountPresent {
+    type Item = MountStatus;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mounts = tokio::fs::read_to_string(&self.mounts_path)
+            .await
+            .map_err(|x| {
+                super::proc_error(
+                    &self.proc_path,
+                    format!("Could not read from {}: {}", self.mounts_path.display(), x),
+                )
+            })?;
+        Ok(self
+            .id
+            .iter()
+            .zip(self.fstypes.iter())
+            .map(|(path, fstype)| Ok(Self::get_status(&mounts, path, fstype.as_deref())))
+            .collect())
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        match (&data.state, &data.fstype) {
+            (MountState::Missing, _) => String::from("not mounted"),
+            (_, Some(fstype)) => format!("mounted ({})", fstype),
+            (_, None) => String::from("mounted"),
+        }
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MOUNTS: &str = "sysfs /sys sysfs rw,nosuid,nodev,noexec,relatime 0 0
+/dev/nvme0n1p2 / ext4 rw,relatime 0 0
+nas:/export/backup /mnt/backup nfs4 rw,relatime,vers=4.2 0 0
+/dev/sdb1 /media/USB\\040Disk vfat rw,relatime 0 0
+/dev/sdc1 /srv/data ext4 rw,relatime 0 0
+tmpfs /srv/data tmpfs rw,relatime 0 0
+";
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("mounts"), MOUNTS).unwrap();
+        let mount_present = MountPresent {
+            id: vec![
+                String::from("/mnt/backup"),
+                String::from("/media/USB Disk"),
+                String::from("/srv/data"),
+                String::from("/mnt/archive"),
+                String::from("/"),
+            ],
+            fstypes: vec![
+                Some(String::from("nfs4")),
+                None,
+                Some(String::from("ext4")),
+                None,
+                None,
+            ],
+            mounts_path: root.join("mounts"),
+            proc_path: root.clone(),
+        };
+        let data = mount_present.get_data().await.unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        let backup = data[0].as_ref().unwrap();
+        assert!(backup.is_ok());
+        let mut placeholders = PlaceholderMap::new();
+        backup.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("mount_state").unwrap(), "Mounted");
+        assert_eq!(placeholders.get("source").unwrap(), "nas:/export/backup");
+        assert_eq!(placeholders.get("fstype").unwrap(), "nfs4");
+        assert!(data[1].as_ref().unwrap().is_ok());
+        // covered by a tmpfs
+        let data_mount = data[2].as_ref().unwrap();
+        assert_eq!(data_mount.state, MountState::WrongType);
+        assert_eq!(MountPresent::format_data(data_mount), "mounted (tmpfs)");
+        let archive = data[3].as_ref().unwrap();
+        assert_eq!(archive.state, MountState::Missing);
+        assert_eq!(MountPresent::format_data(archive), "not mounted");
+        assert!(data[4].as_ref().unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(
+            MountPresent::unescape("/media/USB\\040Disk"),
+            "/media/USB Disk"
+        );
+        assert_eq!(MountPresent::unescape("a\\134b"), "a\\b");
+        assert_eq!(MountPresent::unescape("a\\b"), "a\\b");
+    }
+}
@@ -0,0 +1,192 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+pub struct NumaMemory {
+    id: Vec<String>,
+    nodes: Vec<u32>,
+    node_path: std::path::PathBuf,
+    meminfo_path: std::path::PathBuf,
+    proc_path: std::path::PathBuf,
+    sys_path: std::path::PathBuf,
+}
+
+impl NumaMemory {
+    fn get_number(id: &str, value: Option<&str>) -> Result<u64> {
+        value
+            .ok_or_else(|| Error(format!("Could not read {}: Column not found.", id)))?
+            .parse()
+            .map_err(|x| Error(format!("Could not read {}: {}", id, x)))
+    }
+
+    // Handles both the per-node format ("Node 0 MemTotal:  16318460 kB") and the format of
+    // /proc/meminfo ("MemTotal:  16318460 kB").
+    fn parse_meminfo(content: &str) -> Result<u8> {
+        let mut mem_total: Option<u64> = None;
+        let mut mem_free: Option<u64> = None;
+        for line in content.lines() {
+            let mut columns = line.split_whitespace().peekable();
+            if columns.peek() == Some(&"Node") {
+                columns.nth(1);
+            }
+            match columns.next() {
+                Some("MemTotal:") => {
+                    mem_total = Some(Self::get_number("MemTotal", columns.next())?)
+                }
+                Some("MemFree:") => mem_free = Some(Self::get_number("MemFree", columns.next())?),
+                _ => {}
+            }
+        }
+        match (mem_total, mem_free) {
+            (Some(mem_total), Some(mem_free)) if mem_total != 0 && mem_free <= mem_total => {
+                Ok(((mem_total - mem_free) * 100 / mem_total) as u8)
+            }
+            _ => Err(Error(String::from("Could not read memory usage."))),
+        }
+    }
+
+    async fn get_usage(&self, node: u32) -> Result<u8> {
+        let path = self.node_path.join(format!("node{}/meminfo", node));
+        let content = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => content,
+            // Kernels without NUMA support don't have the node directory. All memory is local then,
+            // i.e. it's a single node.
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound && node == 0 => {
+                return self.get_system_usage().await
+            }
+            Err(err) => {
+                return Err(super::sys_error(
+                    &self.sys_path,
+                    format!("Could not read from {}: {}", path.display(), err),
+                ))
+            }
+        };
+        Self::parse_meminfo(&content)
+            .map_err(|x| Error(format!("Could not parse {}: {}", path.display(), x)))
+    }
+
+    async fn get_system_usage(&self) -> Result<u8> {
+        let content = tokio::fs::read_to_string(&self.meminfo_path)
+            .await
+            .map_err(|x| {
+                super::proc_error(
+                    &self.proc_path,
+                    format!("Could not read from {}: {}", self.meminfo_path.display(), x),
+                )
+            })?;
+        Self::parse_meminfo(&content).map_err(|x| {
+            Error(format!(
+                "Could not parse {}: {}",
+                self.meminfo_path.display(),
+                x
+            ))
+        })
+    }
+}
+
+impl TryFrom<&config::Check> for NumaMemory {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::NumaMemory(numa_memory) = &check.type_ {
+            if numa_memory.nodes.is_empty() {
+                Err(Error(String::from("'nodes' cannot be empty.")))
+            } else {
+                Ok(Self {
+                    id: numa_memory
+                        .nodes
+                        .iter()
+                        .map(|x| format!("node{}", x))
+                        .collect(),
+                    nodes: numa_memory.nodes.clone(),
+                    node_path: check.sys_path.join("devices/system/node"),
+                    meminfo_path: check.proc_path.join("meminfo"),
+                    proc_path: check.proc_path.clone(),
+                    sys_path: check.sys_path.clone(),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for NumaMemory {
+    type Item = u8;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mut res = Vec::new();
+        for node in self.nodes.iter() {
+            res.push(self.get_usage(*node).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("usage level {}", data)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NODE_MEMINFO: &str = "Node 1 MemTotal:       16318460 kB
+Node 1 MemFree:         4079615 kB
+Node 1 MemUsed:        12238845 kB
+Node 1 Active:          6391960 kB
+";
+
+    const PROC_MEMINFO: &str = "MemTotal:        8000000 kB
+MemFree:         6000000 kB
+MemAvailable:    7000000 kB
+";
+
+    #[test]
+    fn test_parse_node_meminfo() {
+        assert_eq!(NumaMemory::parse_meminfo(NODE_MEMINFO).unwrap(), 75);
+    }
+
+    #[test]
+    fn test_parse_proc_meminfo() {
+        assert_eq!(NumaMemory::parse_meminfo(PROC_MEMINFO).unwrap(), 25);
+    }
+
+    #[test]
+    fn test_parse_meminfo_invalid() {
+        assert!(NumaMemory::parse_meminfo("Node 0 MemTotal: 0 kB\nNode 0 MemFree: 0 kB").is_err());
+        assert!(
+            NumaMemory::parse_meminfo("Node 0 MemTotal: foo kB\nNode 0 MemFree: 0 kB").is_err()
+        );
+        assert!(NumaMemory::parse_meminfo("Node 0 Active: 1 kB").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_non_numa_fallback() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("meminfo"), PROC_MEMINFO).unwrap();
+        let numa_memory = NumaMemory {
+            id: vec![String::from("node0"), String::from("node1")],
+            nodes: vec![0, 1],
+            node_path: root.join("node"),
+            meminfo_path: root.join("meminfo"),
+            proc_path: root.clone(),
+            sys_path: root.clone(),
+        };
+        let data = numa_memory.get_data().await.unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(*data[0].as_ref().unwrap(), 25);
+        assert!(data[1].is_err());
+    }
+}
@@ -0,0 +1,236 @@
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct FileAge {
+    // age in seconds
+    age: u64,
+    file_name: Option<String>,
+}
+
+impl std::fmt::Display for FileAge {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.age)
+    }
+}
+
+impl LevelItem for FileAge {
+    fn level(&self) -> f64 {
+        self.age as f64
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(
+            String::from("oldest_file"),
+            self.file_name.clone().unwrap_or_default(),
+        );
+    }
+}
+
+pub struct OldestFileAge {
+    id: Vec<String>,
+    paths: Vec<std::path::PathBuf>,
+    pattern: Option<String>,
+    empty_is_error: bool,
+}
+
+impl OldestFileAge {
+    fn is_match(&self, name: &str) -> bool {
+        match &self.pattern {
+            Some(pattern) => crate::wildcard_match(pattern, name),
+            None => true,
+        }
+    }
+
+    async fn get_oldest(&self, path: &std::path::Path) -> Result<FileAge> {
+        let error = |x| {
+            Error(format!(
+                "Could not read directory {}: {}",
+                path.display(),
+                x
+            ))
+        };
+        let mut entries = tokio::fs::read_dir(path).await.map_err(error)?;
+        let mut oldest: Option<(std::time::SystemTime, String)> = None;
+        while let Some(entry) = entries.next_entry().await.map_err(error)? {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if !self.is_match(&file_name) {
+                continue;
+            }
+            // The file might have been processed in the meantime.
+            let metadata = match entry.metadata().await {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let modified = metadata.modified().map_err(|x| {
+                Error(format!(
+                    "Could not get modification time of {}: {}",
+                    entry.path().display(),
+                    x
+                ))
+            })?;
+            if oldest.as_ref().map(|(x, _)| modified < *x).unwrap_or(true) {
+                oldest = Some((modified, file_name));
+            }
+        }
+        match oldest {
+            Some((modified, file_name)) => Ok(FileAge {
+                // Modification times in the future count as age 0.
+                age: modified.elapsed().map(|x| x.as_secs()).unwrap_or(0),
+                file_name: Some(file_name),
+            }),
+            None if self.empty_is_error => Err(Error(format!(
+                "No matching files found in {}.",
+                path.display()
+            ))),
+            None => Ok(FileAge {
+                age: 0,
+                file_name: None,
+            }),
+        }
+    }
+}
+
+impl TryFrom<&config::Check> for OldestFileAge {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::OldestFileAge(oldest_file_age) = &check.type_ {
+            if oldest_file_age
+                .paths
+                .iter()
+                .any(|x| x.as_os_str().is_empty())
+            {
+                Err(Error(String::from("'paths' cannot contain empty paths.")))
+            } else if oldest_file_age.pattern.as_deref() == Some("") {
+                Err(Error(String::from("'pattern' cannot be empty.")))
+            } else {
+                Ok(Self {
+                    id: oldest_file_age
+                        .paths
+                        .iter()
+                        .map(|x| x.display().to_string())
+                        .collect(),
+                    paths: oldest_file_age.paths.clone(),
+                    pattern: oldest_file_age.pattern.clone(),
+                    empty_is_error: oldest_file_age.empty_is_error,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for OldestFileAge {
+    type Item = FileAge;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mut res = Vec::new();
+        for path in self.paths.iter() {
+            res.push(self.get_oldest(path).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("oldest file age {}s", data.age)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.age as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn oldest_file_age(
+        path: &std::path::Path,
+        pattern: Option<&str>,
+        empty_is_error: bool,
+    ) -> OldestFileAge {
+        OldestFileAge {
+            id: vec![path.display().to_string()],
+            paths: vec![path.to_path_buf()],
+            pattern: pattern.map(String::from),
+            empty_is_error,
+        }
+    }
+
+    fn create_file(path: &std::path::Path, age: u64) {
+        let file = std::fs::File::create(path).unwrap();
+        file.set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(age))
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_oldest() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("subdirectory")).unwrap();
+        create_file(&root.join("new.job"), 10);
+        create_file(&root.join("stuck.job"), 3600);
+        create_file(&root.join("older.tmp"), 7200);
+        create_file(&root.join("medium.job"), 600);
+        let all = oldest_file_age(&root, None, false).get_oldest(&root).await;
+        let jobs = oldest_file_age(&root, Some("*.job"), false)
+            .get_oldest(&root)
+            .await;
+        std::fs::remove_dir_all(&root).unwrap();
+        let all = all.unwrap();
+        assert_eq!(all.file_name.as_deref(), Some("older.tmp"));
+        assert!((7200..7205).contains(&all.age));
+        let jobs = jobs.unwrap();
+        assert_eq!(jobs.file_name.as_deref(), Some("stuck.job"));
+        assert!((3600..3605).contains(&jobs.age));
+        let mut placeholders = PlaceholderMap::new();
+        jobs.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("oldest_file").unwrap(), "stuck.job");
+    }
+
+    #[tokio::test]
+    async fn test_empty_directory() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        create_file(&root.join("foo.tmp"), 60);
+        let zero = oldest_file_age(&root, Some("*.job"), false)
+            .get_oldest(&root)
+            .await;
+        let error = oldest_file_age(&root, Some("*.job"), true)
+            .get_oldest(&root)
+            .await;
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            zero.unwrap(),
+            FileAge {
+                age: 0,
+                file_name: None
+            }
+        );
+        assert!(error.is_err());
+        assert!(oldest_file_age(&root, None, false)
+            .get_oldest(&root)
+            .await
+            .is_err());
+    }
+}
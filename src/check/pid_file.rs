@@ -0,0 +1,239 @@
+use super::DataSource;
+use crate::alarm::StatusItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PidFileState {
+    Alive,
+    Stale,
+    Missing,
+}
+
+impl std::fmt::Display for PidFileState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            PidFileState::Alive => write!(f, "Alive"),
+            PidFileState::Stale => write!(f, "Stale"),
+            PidFileState::Missing => write!(f, "Missing"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PidFileStatus {
+    state: PidFileState,
+    pid: Option<u32>,
+    process_name: Option<String>,
+}
+
+impl StatusItem for PidFileStatus {
+    fn is_ok(&self) -> bool {
+        self.state == PidFileState::Alive
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("pid_file_state"), self.state.to_string());
+        if let Some(pid) = self.pid {
+            placeholders.insert(String::from("pid"), pid.to_string());
+        }
+        if let Some(process_name) = &self.process_name {
+            placeholders.insert(String::from("process_name"), process_name.clone());
+        }
+    }
+}
+
+pub struct PidFile {
+    id: Vec<String>,
+    paths: Vec<std::path::PathBuf>,
+    proc_path: std::path::PathBuf,
+}
+
+impl PidFile {
+    async fn get_status(&self, path: &std::path::Path) -> Result<PidFileStatus> {
+        let content = match tokio::fs::read_to_string(path).await {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(PidFileStatus {
+                    state: PidFileState::Missing,
+                    pid: None,
+                    process_name: None,
+                })
+            }
+            Err(err) => {
+                return Err(Error(format!(
+                    "Could not read from {}: {}",
+                    path.display(),
+                    err
+                )))
+            }
+        };
+        let pid: u32 = content
+            .trim()
+            .parse()
+            .map_err(|x| Error(format!("Invalid PID in {}: {}", path.display(), x)))?;
+        let process_path = self.proc_path.join(pid.to_string());
+        if !process_path.is_dir() {
+            // Without procfs, all processes would look dead.
+            if !self.proc_path.join("stat").is_file() {
+                return Err(super::proc_error(
+                    &self.proc_path,
+                    format!("Could not check if process {} is alive.", pid),
+                ));
+            }
+            return Ok(PidFileStatus {
+                state: PidFileState::Stale,
+                pid: Some(pid),
+                process_name: None,
+            });
+        }
+        let process_name = tokio::fs::read_to_string(process_path.join("comm"))
+            .await
+            .ok()
+            .map(|x| String::from(x.trim_end()));
+        Ok(PidFileStatus {
+            state: PidFileState::Alive,
+            pid: Some(pid),
+            process_name,
+        })
+    }
+}
+
+impl TryFrom<&config::Check> for PidFile {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::PidFile(pid_file) = &check.type_ {
+            if pid_file.paths.iter().any(|x| x.as_os_str().is_empty()) {
+                Err(Error(String::from("'paths' cannot contain empty paths.")))
+            } else {
+                Ok(Self {
+                    id: pid_file
+                        .paths
+                        .iter()
+                        .map(|x| x.display().to_string())
+                        .collect(),
+                    paths: pid_file.paths.clone(),
+                    proc_path: check.proc_path.clone(),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for PidFile {
+    type Item = PidFileStatus;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mut res = Vec::new();
+        for path in self.paths.iter() {
+            res.push(self.get_status(path).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        match data.pid {
+            Some(pid) => format!("state {} (PID {})", data.state, pid),
+            None => format!("state {}", data.state),
+        }
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct FakeTree {
+        root: std::path::PathBuf,
+    }
+
+    impl FakeTree {
+        fn new() -> Self {
+            let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+            std::fs::create_dir_all(root.join("proc/1234")).unwrap();
+            std::fs::write(root.join("proc/1234/comm"), "foobard\n").unwrap();
+            std::fs::write(root.join("proc/stat"), "cpu 1 2 3 4\n").unwrap();
+            std::fs::write(root.join("alive.pid"), "1234\n").unwrap();
+            std::fs::write(root.join("stale.pid"), "4321\n").unwrap();
+            std::fs::write(root.join("invalid.pid"), "foo\n").unwrap();
+            Self { root }
+        }
+
+        fn pid_file(&self, names: &[&str]) -> PidFile {
+            PidFile {
+                id: names.iter().map(|x| String::from(*x)).collect(),
+                paths: names.iter().map(|x| self.root.join(x)).collect(),
+                proc_path: self.root.join("proc"),
+            }
+        }
+    }
+
+    impl Drop for FakeTree {
+        fn drop(&mut self) {
+            std::fs::remove_dir_all(&self.root).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let fake_tree = FakeTree::new();
+        let pid_file = fake_tree.pid_file(&["alive.pid", "stale.pid", "missing.pid"]);
+        let data = pid_file.get_data().await.unwrap();
+        assert_eq!(
+            data[0].as_ref().unwrap(),
+            &PidFileStatus {
+                state: PidFileState::Alive,
+                pid: Some(1234),
+                process_name: Some(String::from("foobard")),
+            }
+        );
+        assert_eq!(
+            data[1].as_ref().unwrap(),
+            &PidFileStatus {
+                state: PidFileState::Stale,
+                pid: Some(4321),
+                process_name: None,
+            }
+        );
+        assert_eq!(
+            data[2].as_ref().unwrap(),
+            &PidFileStatus {
+                state: PidFileState::Missing,
+                pid: None,
+                process_name: None,
+            }
+        );
+        assert!(data.iter().filter(|x| x.as_ref().unwrap().is_ok()).count() == 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_data_invalid() {
+        let fake_tree = FakeTree::new();
+        let pid_file = fake_tree.pid_file(&["invalid.pid"]);
+        let data = pid_file.get_data().await.unwrap();
+        assert!(data[0].is_err());
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let mut placeholders = PlaceholderMap::new();
+        PidFileStatus {
+            state: PidFileState::Alive,
+            pid: Some(1234),
+            process_name: Some(String::from("foobard")),
+        }
+        .add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("pid_file_state").unwrap(), "Alive");
+        assert_eq!(placeholders.get("pid").unwrap(), "1234");
+        assert_eq!(placeholders.get("process_name").unwrap(), "foobard");
+    }
+}
@@ -0,0 +1,465 @@
+use super::smoothing::Smoothing;
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+use std::io::Read;
+use std::net::IpAddr;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct RoundTrip {
+    // in milliseconds
+    average: f64,
+    minimum: f64,
+    maximum: f64,
+    // average round-trip time smoothed over the cycles, if configured
+    smoothed: Option<f64>,
+}
+
+impl std::fmt::Display for RoundTrip {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:.3}", self.level())
+    }
+}
+
+impl LevelItem for RoundTrip {
+    fn level(&self) -> f64 {
+        self.smoothed.unwrap_or(self.average)
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("rtt_avg"), format!("{:.3}", self.average));
+        if let Some(smoothed) = self.smoothed {
+            placeholders.insert(String::from("rtt_avg_smoothed"), format!("{:.3}", smoothed));
+        }
+        placeholders.insert(String::from("rtt_min"), format!("{:.3}", self.minimum));
+        placeholders.insert(String::from("rtt_max"), format!("{:.3}", self.maximum));
+    }
+}
+
+impl RoundTrip {
+    fn from_durations(durations: &[f64]) -> Self {
+        Self {
+            average: durations.iter().sum::<f64>() / durations.len() as f64,
+            minimum: durations.iter().copied().fold(f64::INFINITY, f64::min),
+            maximum: durations.iter().copied().fold(0.0, f64::max),
+            smoothed: None,
+        }
+    }
+}
+
+fn packet_loss(transmitted: u32, received: u32) -> Result<()> {
+    if received == 0 {
+        Err(Error(format!(
+            "Host unreachable: all {} packets lost.",
+            transmitted
+        )))
+    } else if received < transmitted {
+        Err(Error(format!(
+            "Packet loss: {} of {} packets lost.",
+            transmitted - received,
+            transmitted
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+const ICMP_ECHO_REQUEST: u8 = 8;
+const ICMP_ECHO_REPLY: u8 = 0;
+const ICMPV6_ECHO_REQUEST: u8 = 128;
+const ICMPV6_ECHO_REPLY: u8 = 129;
+const PAYLOAD: &[u8] = b"minmon ping";
+
+struct IcmpSocket {
+    socket: tokio::io::unix::AsyncFd<socket2::Socket>,
+    // Raw sockets receive every ICMP packet (IPv4 ones including the IP header), datagram
+    // sockets only the replies to their own requests.
+    raw: bool,
+    v6: bool,
+}
+
+impl IcmpSocket {
+    // Datagram ICMP sockets can be opened without CAP_NET_RAW if the group of the process is
+    // within net.ipv4.ping_group_range, so they are tried first.
+    fn open(address: &IpAddr) -> std::io::Result<Self> {
+        let (domain, protocol) = match address {
+            IpAddr::V4(_) => (socket2::Domain::IPV4, socket2::Protocol::ICMPV4),
+            IpAddr::V6(_) => (socket2::Domain::IPV6, socket2::Protocol::ICMPV6),
+        };
+        let (socket, raw) = match socket2::Socket::new(domain, socket2::Type::DGRAM, Some(protocol))
+        {
+            Ok(socket) => (socket, false),
+            Err(_) => (
+                socket2::Socket::new(domain, socket2::Type::RAW, Some(protocol))?,
+                true,
+            ),
+        };
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket: tokio::io::unix::AsyncFd::new(socket)?,
+            raw,
+            v6: address.is_ipv6(),
+        })
+    }
+
+    async fn echo(&self, address: &IpAddr, identifier: u16, sequence: u16) -> Result<f64> {
+        let packet = echo_request(self.v6, identifier, sequence);
+        let address = socket2::SockAddr::from(std::net::SocketAddr::new(*address, 0));
+        let start = std::time::Instant::now();
+        self.socket
+            .get_ref()
+            .send_to(&packet, &address)
+            .map_err(|x| Error(format!("Could not send echo request: {}", x)))?;
+        let mut buffer = [0; 1024];
+        loop {
+            let mut guard = self
+                .socket
+                .readable()
+                .await
+                .map_err(|x| Error(format!("Could not receive echo reply: {}", x)))?;
+            let size = match guard.try_io(|x| x.get_ref().read(&mut buffer)) {
+                Ok(result) => {
+                    result.map_err(|x| Error(format!("Could not receive echo reply: {}", x)))?
+                }
+                Err(_) => continue,
+            };
+            // The kernel sets the identifier of datagram sockets.
+            if let Some((reply_identifier, reply_sequence)) =
+                parse_echo_reply(&buffer[..size], self.v6, self.raw)
+            {
+                if reply_sequence == sequence && (!self.raw || reply_identifier == identifier) {
+                    return Ok(start.elapsed().as_secs_f64() * 1000.0);
+                }
+            }
+        }
+    }
+}
+
+// The kernel calculates the checksum of ICMPv6 packets.
+fn echo_request(v6: bool, identifier: u16, sequence: u16) -> Vec<u8> {
+    let mut res = vec![
+        if v6 {
+            ICMPV6_ECHO_REQUEST
+        } else {
+            ICMP_ECHO_REQUEST
+        },
+        0,
+        0,
+        0,
+    ];
+    res.extend_from_slice(&identifier.to_be_bytes());
+    res.extend_from_slice(&sequence.to_be_bytes());
+    res.extend_from_slice(PAYLOAD);
+    if !v6 {
+        let checksum = checksum(&res);
+        res[2..4].copy_from_slice(&checksum.to_be_bytes());
+    }
+    res
+}
+
+// Internet checksum (RFC 1071).
+fn checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|x| u16::from_be_bytes([x[0], x.get(1).copied().unwrap_or(0)]) as u32)
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+// Returns the identifier and sequence number of an echo reply.
+fn parse_echo_reply(packet: &[u8], v6: bool, raw: bool) -> Option<(u16, u16)> {
+    let packet = if raw && !v6 {
+        let header_length = (*packet.first()? & 0x0f) as usize * 4;
+        packet.get(header_length..)?
+    } else {
+        packet
+    };
+    let reply = if v6 {
+        ICMPV6_ECHO_REPLY
+    } else {
+        ICMP_ECHO_REPLY
+    };
+    if packet.len() < 8 || packet[0] != reply {
+        return None;
+    }
+    Some((
+        u16::from_be_bytes([packet[4], packet[5]]),
+        u16::from_be_bytes([packet[6], packet[7]]),
+    ))
+}
+
+// Parses the summary of iputils' and BusyBox' ping, e.g.
+// 3 packets transmitted, 3 received, 0% packet loss, time 2003ms
+// rtt min/avg/max/mdev = 0.031/0.042/0.051/0.008 ms
+fn parse_ping_output(output: &str) -> Result<RoundTrip> {
+    let statistics = output
+        .lines()
+        .find(|x| x.contains("packets transmitted"))
+        .ok_or_else(|| Error(String::from("Could not find packet statistics.")))?;
+    let count = |part: Option<&str>| -> Result<u32> {
+        part.and_then(|x| x.split_whitespace().next())
+            .and_then(|x| x.parse().ok())
+            .ok_or_else(|| Error(format!("Could not parse packet statistics: {}", statistics)))
+    };
+    let mut parts = statistics.split(',');
+    let transmitted = count(parts.next())?;
+    let received = count(parts.next())?;
+    packet_loss(transmitted, received)?;
+    let round_trip = output
+        .lines()
+        .find(|x| x.contains("min/avg/max"))
+        .and_then(|x| x.split_once('='))
+        .ok_or_else(|| Error(String::from("Could not find round-trip times.")))?
+        .1;
+    let times: Vec<f64> = round_trip
+        .trim()
+        .trim_end_matches("ms")
+        .trim()
+        .split('/')
+        .map(|x| x.parse())
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|x| Error(format!("Could not parse round-trip times: {}", x)))?;
+    if times.len() < 3 {
+        return Err(Error(format!(
+            "Could not parse round-trip times: {}",
+            round_trip.trim()
+        )));
+    }
+    Ok(RoundTrip {
+        average: times[1],
+        minimum: times[0],
+        maximum: times[2],
+        smoothed: None,
+    })
+}
+
+pub struct Ping {
+    id: Vec<String>,
+    count: u32,
+    timeout: std::time::Duration,
+    binary: Option<std::path::PathBuf>,
+    resolver: Option<std::sync::Arc<crate::resolver::Resolver>>,
+    capture: crate::capture::Capture,
+    smoothing: Option<Smoothing>,
+    concurrency: usize,
+}
+
+impl Ping {
+    async fn resolve(&self, host: &str) -> Result<IpAddr> {
+        if let Ok(address) = host.parse() {
+            return Ok(address);
+        }
+        crate::resolver::lookup_host(self.resolver.as_deref(), &format!("{}:0", host))
+            .await?
+            .first()
+            .map(|x| x.ip())
+            .ok_or_else(|| Error(format!("Could not resolve {}.", host)))
+    }
+
+    async fn ping_socket(&self, socket: IcmpSocket, address: &IpAddr) -> Result<RoundTrip> {
+        let identifier =
+            u16::from_be_bytes(*uuid::Uuid::new_v4().as_bytes().first_chunk().unwrap());
+        let mut durations = Vec::new();
+        for sequence in 0..self.count {
+            if let Ok(duration) = tokio::time::timeout(
+                self.timeout,
+                socket.echo(address, identifier, sequence as u16),
+            )
+            .await
+            {
+                durations.push(duration?);
+            }
+        }
+        packet_loss(self.count, durations.len() as u32)?;
+        Ok(RoundTrip::from_durations(&durations))
+    }
+
+    async fn ping_binary(&self, binary: &std::path::Path, host: &str) -> Result<RoundTrip> {
+        let output = self
+            .capture
+            .output(tokio::process::Command::new(binary).args([
+                "-n",
+                "-c",
+                &self.count.to_string(),
+                "-W",
+                &self.timeout.as_secs().to_string(),
+                host,
+            ]))
+            .await
+            .map_err(|x| Error(format!("Failed to run {}: {}", binary.display(), x)))?;
+        let stdout = String::from_utf8(output.stdout).map_err(|x| {
+            Error(format!(
+                "Output of {} is not valid UTF-8: {}",
+                binary.display(),
+                x
+            ))
+        })?;
+        // ping exits with a non-zero status on packet loss too, but prints the statistics
+        if !output.status.success() && !stdout.contains("packets transmitted") {
+            return Err(Error(format!(
+                "{} failed: {}",
+                binary.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        parse_ping_output(&stdout)
+    }
+
+    async fn ping(&self, host: &str) -> Result<RoundTrip> {
+        let address = self.resolve(host).await?;
+        match IcmpSocket::open(&address) {
+            Ok(socket) => self.ping_socket(socket, &address).await,
+            Err(_) if self.binary.is_some() => {
+                self.ping_binary(self.binary.as_ref().unwrap(), host).await
+            }
+            Err(err) => Err(Error(format!(
+                "Could not open ICMP socket (requires CAP_NET_RAW or a matching net.ipv4.ping_group_range): {}",
+                err
+            ))),
+        }
+    }
+}
+
+impl TryFrom<&config::Check> for Ping {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::Ping(ping) = &check.type_ {
+            if ping.hosts.is_empty() {
+                Err(Error(String::from("'hosts' cannot be empty.")))
+            } else if ping.hosts.iter().any(|x| x.is_empty()) {
+                Err(Error(String::from("'hosts' cannot contain empty hosts.")))
+            } else if ping.count == 0 || ping.count > u16::MAX as u32 {
+                Err(Error(format!(
+                    "'count' must be between 1 and {}.",
+                    u16::MAX
+                )))
+            } else if ping.timeout == 0 {
+                Err(Error(String::from("'timeout' cannot be 0.")))
+            } else if ping.binary.as_ref().is_some_and(|x| !x.is_file()) {
+                Err(Error(format!(
+                    "'binary' is not a file: {}.",
+                    ping.binary.as_ref().unwrap().display()
+                )))
+            } else {
+                Ok(Self {
+                    id: ping.hosts.clone(),
+                    count: ping.count,
+                    timeout: std::time::Duration::from_secs(ping.timeout.into()),
+                    binary: ping.binary.clone(),
+                    resolver: crate::resolver::from_config(check.resolver.as_ref())?,
+                    capture: crate::capture::from_config(check.capture.as_ref())?,
+                    smoothing: ping.smoothing.map(Smoothing::new).transpose()?,
+                    concurrency: super::concurrency(ping.concurrency)?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for Ping {
+    type Item = RoundTrip;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        Ok(
+            super::fetch_concurrently(self.id.iter(), self.concurrency, |host| async {
+                self.ping(host).await.map(|mut x| {
+                    x.smoothed = self.smoothing.as_ref().map(|s| s.update(host, x.average));
+                    x
+                })
+            })
+            .await,
+        )
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        match data.smoothed {
+            Some(smoothed) => format!(
+                "round-trip time {:.3}ms (smoothed {:.3}ms)",
+                data.average, smoothed
+            ),
+            None => format!("round-trip time {:.3}ms", data.average),
+        }
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.level())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_echo_packet() {
+        let request = echo_request(false, 0x1234, 7);
+        assert_eq!(request[0], ICMP_ECHO_REQUEST);
+        assert_eq!(&request[4..8], &[0x12, 0x34, 0, 7]);
+        // the checksum over a packet including its checksum is 0
+        assert_eq!(checksum(&request), 0);
+        assert_eq!(checksum(&[0x45, 0x00, 0x00]), !0x4500);
+        let mut reply = request.clone();
+        reply[0] = ICMP_ECHO_REPLY;
+        assert_eq!(parse_echo_reply(&reply, false, false), Some((0x1234, 7)));
+        assert_eq!(parse_echo_reply(&request, false, false), None);
+        // raw IPv4 sockets receive the IP header too
+        let mut raw = vec![0x45];
+        raw.extend_from_slice(&[0; 19]);
+        raw.extend_from_slice(&reply);
+        assert_eq!(parse_echo_reply(&raw, false, true), Some((0x1234, 7)));
+        let mut reply = echo_request(true, 1, 2);
+        reply[0] = ICMPV6_ECHO_REPLY;
+        assert_eq!(parse_echo_reply(&reply, true, true), Some((1, 2)));
+        assert_eq!(parse_echo_reply(&reply[..6], true, true), None);
+    }
+
+    #[test]
+    fn test_parse_ping_output() {
+        let iputils = "PING 192.0.2.1 (192.0.2.1) 56(84) bytes of data.
+64 bytes from 192.0.2.1: icmp_seq=1 ttl=64 time=0.031 ms
+
+--- 192.0.2.1 ping statistics ---
+3 packets transmitted, 3 received, 0% packet loss, time 2003ms
+rtt min/avg/max/mdev = 0.031/0.042/0.051/0.008 ms
+";
+        let round_trip = parse_ping_output(iputils).unwrap();
+        assert_eq!(round_trip.average, 0.042);
+        let mut placeholders = PlaceholderMap::new();
+        round_trip.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("rtt_min").unwrap(), "0.031");
+        assert_eq!(placeholders.get("rtt_max").unwrap(), "0.051");
+        assert_eq!(placeholders.get("rtt_avg").unwrap(), "0.042");
+        assert!(!placeholders.contains_key("rtt_avg_smoothed"));
+        let busybox = "--- 192.0.2.1 ping statistics ---
+3 packets transmitted, 3 packets received, 0% packet loss
+round-trip min/avg/max = 1.120/1.503/2.011 ms
+";
+        assert_eq!(parse_ping_output(busybox).unwrap().maximum, 2.011);
+        let loss = "3 packets transmitted, 2 received, 33.3333% packet loss, time 2010ms
+rtt min/avg/max/mdev = 0.031/0.042/0.051/0.008 ms
+";
+        assert_eq!(
+            parse_ping_output(loss).unwrap_err().to_string(),
+            "Packet loss: 1 of 3 packets lost."
+        );
+        let unreachable = "3 packets transmitted, 0 received, +3 errors, 100% packet loss";
+        assert_eq!(
+            parse_ping_output(unreachable).unwrap_err().to_string(),
+            "Host unreachable: all 3 packets lost."
+        );
+        assert!(parse_ping_output("").is_err());
+    }
+}
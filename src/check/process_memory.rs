@@ -0,0 +1,236 @@
+use super::process_presence::{Process, ProcessPresence};
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ProcessRss {
+    // in MiB
+    rss: u64,
+    pids: Vec<u32>,
+}
+
+impl std::fmt::Display for ProcessRss {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.rss)
+    }
+}
+
+impl LevelItem for ProcessRss {
+    fn level(&self) -> f64 {
+        self.rss as f64
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("process_count"), self.pids.len().to_string());
+        placeholders.insert(
+            String::from("pids"),
+            self.pids
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+        );
+    }
+}
+
+pub struct ProcessMemory {
+    id: Vec<String>,
+    patterns: Vec<config::ProcessMemoryPattern>,
+    zero_if_missing: bool,
+    read_cmdline: bool,
+    proc_path: std::path::PathBuf,
+}
+
+impl ProcessMemory {
+    fn page_size() -> Result<u64> {
+        match nix::unistd::sysconf(nix::unistd::SysconfVar::PAGE_SIZE) {
+            Ok(Some(size)) if size > 0 => Ok(size as u64),
+            _ => Err(Error(String::from("Could not get the page size."))),
+        }
+    }
+
+    // Returns the resident set size in pages.
+    async fn read_rss(&self, pid: u32) -> Result<u64> {
+        let path = self.proc_path.join(pid.to_string()).join("statm");
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|x| Error(format!("Could not read from {}: {}", path.display(), x)))?;
+        crate::get_number(
+            &format!("Could not read RSS from {}", path.display()),
+            &content,
+            1,
+        )
+    }
+
+    async fn rss(
+        &self,
+        pattern: &config::ProcessMemoryPattern,
+        processes: &[Process],
+        page_size: u64,
+    ) -> Result<ProcessRss> {
+        let mut pages = 0;
+        let mut pids = Vec::new();
+        for process in processes.iter().filter(|x| {
+            ProcessPresence::matches(pattern.comm.as_deref(), pattern.cmdline.as_deref(), x)
+        }) {
+            // The process might have exited in the meantime.
+            if let Ok(rss) = self.read_rss(process.pid).await {
+                pages += rss;
+                pids.push(process.pid);
+            }
+        }
+        if pids.is_empty() && !self.zero_if_missing {
+            return Err(Error(String::from("No matching process found.")));
+        }
+        pids.sort_unstable();
+        Ok(ProcessRss {
+            rss: pages * page_size / 1024 / 1024,
+            pids,
+        })
+    }
+}
+
+impl TryFrom<&config::Check> for ProcessMemory {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::ProcessMemory(process_memory) = &check.type_ {
+            for pattern in process_memory.processes.iter() {
+                if pattern.name.is_empty() {
+                    return Err(Error(String::from("'name' cannot be empty.")));
+                } else if pattern.comm.is_some() == pattern.cmdline.is_some() {
+                    return Err(Error(format!(
+                        "Either 'comm' or 'cmdline' must be set for process '{}'.",
+                        pattern.name
+                    )));
+                }
+            }
+            Ok(Self {
+                id: process_memory
+                    .processes
+                    .iter()
+                    .map(|x| x.name.clone())
+                    .collect(),
+                patterns: process_memory.processes.clone(),
+                zero_if_missing: process_memory.zero_if_missing,
+                read_cmdline: process_memory.processes.iter().any(|x| x.cmdline.is_some()),
+                proc_path: check.proc_path.clone(),
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for ProcessMemory {
+    type Item = ProcessRss;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let page_size = Self::page_size()?;
+        let processes = ProcessPresence::processes(&self.proc_path, self.read_cmdline).await?;
+        let mut res = Vec::new();
+        for pattern in self.patterns.iter() {
+            res.push(self.rss(pattern, &processes, page_size).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("RSS {} MiB", data.rss)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.level())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pattern(
+        name: &str,
+        comm: Option<&str>,
+        cmdline: Option<&str>,
+    ) -> config::ProcessMemoryPattern {
+        config::ProcessMemoryPattern {
+            name: String::from(name),
+            comm: comm.map(String::from),
+            cmdline: cmdline.map(String::from),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        let page_size = ProcessMemory::page_size().unwrap();
+        // resident memory in MiB
+        for (pid, comm, cmdline, rss) in [
+            ("100", "nginx", "nginx: master process\0", 100),
+            ("101", "nginx", "nginx: worker process\0", 50),
+            (
+                "200",
+                "python3",
+                "/usr/bin/python3\0/opt/app/worker.py\0",
+                10,
+            ),
+        ] {
+            std::fs::create_dir_all(root.join(pid)).unwrap();
+            std::fs::write(root.join(pid).join("comm"), format!("{}\n", comm)).unwrap();
+            std::fs::write(root.join(pid).join("cmdline"), cmdline).unwrap();
+            std::fs::write(
+                root.join(pid).join("statm"),
+                format!(
+                    "500000 {} 1000 200 0 3000 0\n",
+                    rss * 1024 * 1024 / page_size
+                ),
+            )
+            .unwrap();
+        }
+        let mut process_memory = ProcessMemory {
+            id: Vec::new(),
+            patterns: vec![
+                pattern("nginx", Some("nginx"), None),
+                pattern("worker", None, Some("*/worker.py")),
+                pattern("postgres", Some("postgres*"), None),
+            ],
+            zero_if_missing: false,
+            read_cmdline: true,
+            proc_path: root.clone(),
+        };
+        let data = process_memory.get_data().await.unwrap();
+        process_memory.zero_if_missing = true;
+        let data_zero = process_memory.get_data().await.unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        let nginx = data[0].as_ref().unwrap();
+        assert_eq!(nginx.rss, 150);
+        assert_eq!(nginx.pids, vec![100, 101]);
+        assert_eq!(data[1].as_ref().unwrap().rss, 10);
+        assert_eq!(
+            data[2].as_ref().unwrap_err().to_string(),
+            "No matching process found."
+        );
+        let postgres = data_zero[2].as_ref().unwrap();
+        assert_eq!(postgres.rss, 0);
+        let mut placeholders = PlaceholderMap::new();
+        nginx.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("process_count").unwrap(), "2");
+        assert_eq!(placeholders.get("pids").unwrap(), "100,101");
+    }
+}
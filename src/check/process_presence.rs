@@ -0,0 +1,266 @@
+use super::DataSource;
+use crate::alarm::StatusItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ProcessCount {
+    count: u32,
+    min: u32,
+    max: Option<u32>,
+    pids: Vec<u32>,
+}
+
+impl StatusItem for ProcessCount {
+    fn is_ok(&self) -> bool {
+        self.count >= self.min && self.max.is_none_or(|max| self.count <= max)
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("process_count"), self.count.to_string());
+        placeholders.insert(
+            String::from("pids"),
+            self.pids
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+        );
+    }
+}
+
+pub struct Process {
+    pub pid: u32,
+    pub comm: String,
+    // arguments separated by spaces
+    pub cmdline: String,
+}
+
+pub struct ProcessPresence {
+    id: Vec<String>,
+    patterns: Vec<config::ProcessPresencePattern>,
+    read_cmdline: bool,
+    proc_path: std::path::PathBuf,
+}
+
+impl ProcessPresence {
+    pub fn matches(comm: Option<&str>, cmdline: Option<&str>, process: &Process) -> bool {
+        match (comm, cmdline) {
+            (Some(comm), _) => crate::wildcard_match(comm, &process.comm),
+            (None, Some(cmdline)) => crate::wildcard_match(cmdline, &process.cmdline),
+            (None, None) => false,
+        }
+    }
+
+    fn count(pattern: &config::ProcessPresencePattern, processes: &[Process]) -> ProcessCount {
+        let mut pids: Vec<u32> = processes
+            .iter()
+            .filter(|x| Self::matches(pattern.comm.as_deref(), pattern.cmdline.as_deref(), x))
+            .map(|x| x.pid)
+            .collect();
+        pids.sort_unstable();
+        ProcessCount {
+            count: pids.len() as u32,
+            min: pattern.min,
+            max: pattern.max,
+            pids,
+        }
+    }
+
+    // The command lines are only read if needed.
+    pub async fn processes(
+        proc_path: &std::path::Path,
+        read_cmdline: bool,
+    ) -> Result<Vec<Process>> {
+        let mut entries = tokio::fs::read_dir(proc_path).await.map_err(|x| {
+            super::proc_error(
+                proc_path,
+                format!("Could not read directory {}: {}", proc_path.display(), x),
+            )
+        })?;
+        let mut res = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(|x| {
+            Error(format!(
+                "Could not read directory {}: {}",
+                proc_path.display(),
+                x
+            ))
+        })? {
+            // only the numeric entries are processes
+            let pid = match entry
+                .file_name()
+                .to_str()
+                .and_then(|x| x.parse::<u32>().ok())
+            {
+                Some(pid) => pid,
+                None => continue,
+            };
+            // The process might have exited in the meantime.
+            let comm = match tokio::fs::read_to_string(entry.path().join("comm")).await {
+                Ok(comm) => String::from(comm.trim_end_matches('\n')),
+                Err(_) => continue,
+            };
+            let cmdline = if read_cmdline {
+                match tokio::fs::read(entry.path().join("cmdline")).await {
+                    Ok(cmdline) => String::from_utf8_lossy(&cmdline)
+                        .split('\0')
+                        .filter(|x| !x.is_empty())
+                        .collect::<Vec<&str>>()
+                        .join(" "),
+                    Err(_) => continue,
+                }
+            } else {
+                String::new()
+            };
+            res.push(Process { pid, comm, cmdline });
+        }
+        Ok(res)
+    }
+}
+
+impl TryFrom<&config::Check> for ProcessPresence {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::ProcessPresence(process_presence) = &check.type_ {
+            for pattern in process_presence.processes.iter() {
+                if pattern.name.is_empty() {
+                    return Err(Error(String::from("'name' cannot be empty.")));
+                } else if pattern.comm.is_some() == pattern.cmdline.is_some() {
+                    return Err(Error(format!(
+                        "Either 'comm' or 'cmdline' must be set for process '{}'.",
+                        pattern.name
+                    )));
+                } else if pattern.max.is_some_and(|max| max < pattern.min) {
+                    return Err(Error(format!(
+                        "'max' cannot be less than 'min' for process '{}'.",
+                        pattern.name
+                    )));
+                }
+            }
+            Ok(Self {
+                id: process_presence
+                    .processes
+                    .iter()
+                    .map(|x| x.name.clone())
+                    .collect(),
+                patterns: process_presence.processes.clone(),
+                read_cmdline: process_presence
+                    .processes
+                    .iter()
+                    .any(|x| x.cmdline.is_some()),
+                proc_path: check.proc_path.clone(),
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for ProcessPresence {
+    type Item = ProcessCount;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let processes = Self::processes(&self.proc_path, self.read_cmdline).await?;
+        Ok(self
+            .patterns
+            .iter()
+            .map(|x| Ok(Self::count(x, &processes)))
+            .collect())
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("process count {}", data.count)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.count as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pattern(
+        name: &str,
+        comm: Option<&str>,
+        cmdline: Option<&str>,
+        max: Option<u32>,
+    ) -> config::ProcessPresencePattern {
+        config::ProcessPresencePattern {
+            name: String::from(name),
+            comm: comm.map(String::from),
+            cmdline: cmdline.map(String::from),
+            min: 1,
+            max,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        for (pid, comm, cmdline) in [
+            ("1", "systemd", "/sbin/init\0splash\0"),
+            ("100", "nginx", "nginx: master process /usr/sbin/nginx\0"),
+            ("101", "nginx", "nginx: worker process\0"),
+            ("102", "nginx", "nginx: worker process\0"),
+            ("200", "python3", "/usr/bin/python3\0/opt/app/worker.py\0"),
+        ] {
+            std::fs::create_dir_all(root.join(pid)).unwrap();
+            std::fs::write(root.join(pid).join("comm"), format!("{}\n", comm)).unwrap();
+            std::fs::write(root.join(pid).join("cmdline"), cmdline).unwrap();
+        }
+        std::fs::create_dir_all(root.join("self")).unwrap();
+        let process_presence = ProcessPresence {
+            id: Vec::new(),
+            patterns: vec![
+                pattern("nginx", Some("nginx"), None, Some(2)),
+                pattern("worker", None, Some("*/python3 */worker.py"), None),
+                pattern("postgres", Some("postgres*"), None, None),
+            ],
+            read_cmdline: true,
+            proc_path: root.clone(),
+        };
+        let data = process_presence.get_data().await;
+        std::fs::remove_dir_all(&root).unwrap();
+        let data: Vec<ProcessCount> = data.unwrap().into_iter().map(|x| x.unwrap()).collect();
+        // too many copies
+        assert_eq!(data[0].pids, vec![100, 101, 102]);
+        assert!(!data[0].is_ok());
+        assert_eq!(data[1].pids, vec![200]);
+        assert!(data[1].is_ok());
+        // not running
+        assert_eq!(data[2].count, 0);
+        assert!(!data[2].is_ok());
+        let mut placeholders = PlaceholderMap::new();
+        data[0].add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("process_count").unwrap(), "3");
+        assert_eq!(placeholders.get("pids").unwrap(), "100,101,102");
+    }
+
+    #[test]
+    fn test_matches() {
+        let process = Process {
+            pid: 200,
+            comm: String::from("python3"),
+            cmdline: String::from("/usr/bin/python3 /opt/app/worker.py"),
+        };
+        let matches = |pattern: config::ProcessPresencePattern| {
+            ProcessPresence::matches(
+                pattern.comm.as_deref(),
+                pattern.cmdline.as_deref(),
+                &process,
+            )
+        };
+        assert!(matches(pattern("worker", None, Some("*/worker.py"), None)));
+        assert!(matches(pattern("python", Some("python?"), None, None)));
+        assert!(!matches(pattern("python", Some("python"), None, None)));
+    }
+}
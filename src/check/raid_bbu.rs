@@ -0,0 +1,316 @@
+use super::DataSource;
+use crate::alarm::StatusItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct BbuStatus {
+    // e.g. "Optimal", "Learning" or "Failed"
+    state: String,
+    // relative state of charge in percent
+    charge: Option<u8>,
+    // Why the BBU is not usable, empty if it is.
+    problem: String,
+}
+
+impl StatusItem for BbuStatus {
+    fn is_ok(&self) -> bool {
+        self.problem.is_empty()
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("bbu_state"), self.state.clone());
+        placeholders.insert(
+            String::from("bbu_charge"),
+            self.charge.map(|x| x.to_string()).unwrap_or_default(),
+        );
+        placeholders.insert(String::from("bbu_problem"), self.problem.clone());
+    }
+}
+
+pub struct RaidBbu {
+    id: Vec<String>,
+    tool: config::RaidBbuTool,
+    binary: String,
+    controller: u32,
+    capture: crate::capture::Capture,
+}
+
+impl RaidBbu {
+    // Both tools print one property per line, MegaCli as "Name: Value" and storcli as
+    // "Name    Value". The names are converted to lower case.
+    fn parse_properties(tool: config::RaidBbuTool, output: &str) -> HashMap<String, String> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let (name, value) = match tool {
+                    config::RaidBbuTool::MegaCli => line.split_once(':')?,
+                    config::RaidBbuTool::Storcli => {
+                        let line = line.trim();
+                        let index = line.find("  ")?;
+                        (&line[..index], &line[index..])
+                    }
+                };
+                Some((name.trim().to_lowercase(), String::from(value.trim())))
+            })
+            .collect()
+    }
+
+    fn bbu_status(tool: config::RaidBbuTool, output: &str) -> Result<BbuStatus> {
+        let properties = Self::parse_properties(tool, output);
+        let state = properties
+            .get("battery state")
+            .ok_or_else(|| Error(String::from("Battery state not found.")))?;
+        let yes = |name: &str| properties.get(name).is_some_and(|x| x == "Yes");
+        let problem = if yes("battery pack missing") {
+            String::from("Battery pack missing")
+        } else if yes("replacement required") || yes("battery replacement required") {
+            String::from("Battery replacement required")
+        } else if yes("learn cycle active") || state == "Learning" {
+            String::from("Learn cycle active, write-back cache disabled")
+        } else if state != "Optimal" {
+            format!("Battery state {}", state)
+        } else {
+            String::new()
+        };
+        Ok(BbuStatus {
+            state: state.clone(),
+            charge: properties
+                .get("relative state of charge")
+                .and_then(|x| x.trim_end_matches('%').trim().parse().ok()),
+            problem,
+        })
+    }
+
+    async fn read_status(&self) -> Result<BbuStatus> {
+        let args = match self.tool {
+            config::RaidBbuTool::MegaCli => vec![
+                String::from("-AdpBbuCmd"),
+                String::from("-GetBbuStatus"),
+                format!("-a{}", self.controller),
+                String::from("-NoLog"),
+            ],
+            config::RaidBbuTool::Storcli => vec![
+                format!("/c{}/bbu", self.controller),
+                String::from("show"),
+                String::from("status"),
+            ],
+        };
+        let output = self
+            .capture
+            .output(tokio::process::Command::new(&self.binary).args(args))
+            .await
+            .map_err(|x| match x.kind() {
+                std::io::ErrorKind::NotFound => Error(format!(
+                    "{} not found, is the RAID controller's CLI installed?",
+                    self.binary
+                )),
+                _ => Error(format!("Failed to run {}: {}", self.binary, x)),
+            })?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() {
+            return Err(Error(format!(
+                "{} failed: {}",
+                self.binary,
+                // Both tools report errors on stdout.
+                format!("{}{}", stdout, String::from_utf8_lossy(&output.stderr)).trim()
+            )));
+        }
+        Self::bbu_status(self.tool, &stdout)
+    }
+}
+
+impl TryFrom<&config::Check> for RaidBbu {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::RaidBbu(raid_bbu) = &check.type_ {
+            let binary = match &raid_bbu.binary {
+                Some(binary) if binary.is_empty() => {
+                    return Err(Error(String::from("'binary' cannot be empty.")))
+                }
+                Some(binary) => binary.clone(),
+                None => String::from(match raid_bbu.tool {
+                    config::RaidBbuTool::MegaCli => "MegaCli64",
+                    config::RaidBbuTool::Storcli => "storcli64",
+                }),
+            };
+            Ok(Self {
+                id: vec![format!("Controller {}", raid_bbu.controller)],
+                tool: raid_bbu.tool,
+                binary,
+                controller: raid_bbu.controller,
+                capture: crate::capture::from_config(check.capture.as_ref())?,
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for RaidBbu {
+    type Item = BbuStatus;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        Ok(vec![self.read_status().await])
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("battery state {}", data.state)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        data.charge.map(f64::from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MEGACLI_OPTIMAL: &str = "
+BBU status for Adapter: 0
+
+BatteryType: iBBU
+Voltage: 4063 mV
+Current: 0 mA
+Temperature: 29 C
+Battery State: Optimal
+BBU Firmware Status:
+
+  Charging Status              : None
+  Voltage                                 : OK
+  Temperature                             : OK
+  Learn Cycle Requested\t                  : No
+  Learn Cycle Active                      : No
+  Learn Cycle Status                      : OK
+  Battery Pack Missing                    : No
+  Battery Replacement required            : No
+  Remaining Capacity Low                  : No
+
+GasGuageStatus:
+  Fully Discharged        : No
+  Fully Charged           : Yes
+  Relative State of Charge: 98 %
+
+Exit Code: 0x00
+";
+
+    const STORCLI_FAILED: &str = "
+CLI Version = 007.1017.0000.0000 May 10, 2019
+Operating system = Linux 5.15.0
+Controller = 0
+Status = Success
+Description = None
+
+
+BBU_Info :
+========
+
+----------------------------------------
+Property                  Value
+----------------------------------------
+Type                      iBBU
+Voltage                   3512 mV
+Current                   0 mA
+Temperature               31 C
+Battery State             Failed
+----------------------------------------
+
+
+BBU_Firmware_Status :
+===================
+
+---------------------------------------------
+Property                                Value
+---------------------------------------------
+Charging Status                         None
+Voltage                                 Low
+Temperature                             OK
+Learn Cycle Requested                   No
+Learn Cycle Active                      No
+Learn Cycle Status                      OK
+Battery Pack Missing                    No
+Replacement required                    Yes
+---------------------------------------------
+
+
+GasGaugeStatus :
+==============
+
+------------------------------------
+Property                      Value
+------------------------------------
+Fully Discharged              No
+Fully Charged                 No
+Relative State of Charge      12%
+------------------------------------
+";
+
+    fn placeholders(status: &BbuStatus) -> PlaceholderMap {
+        let mut placeholders = PlaceholderMap::new();
+        status.add_placeholders(&mut placeholders);
+        placeholders
+    }
+
+    #[test]
+    fn test_healthy() {
+        let status = RaidBbu::bbu_status(config::RaidBbuTool::MegaCli, MEGACLI_OPTIMAL).unwrap();
+        assert!(status.is_ok());
+        let placeholders = placeholders(&status);
+        assert_eq!(placeholders.get("bbu_state").unwrap(), "Optimal");
+        assert_eq!(placeholders.get("bbu_charge").unwrap(), "98");
+        assert_eq!(placeholders.get("bbu_problem").unwrap(), "");
+    }
+
+    #[test]
+    fn test_failed() {
+        let status = RaidBbu::bbu_status(config::RaidBbuTool::Storcli, STORCLI_FAILED).unwrap();
+        assert!(!status.is_ok());
+        let placeholders = placeholders(&status);
+        assert_eq!(placeholders.get("bbu_state").unwrap(), "Failed");
+        assert_eq!(placeholders.get("bbu_charge").unwrap(), "12");
+        assert_eq!(
+            placeholders.get("bbu_problem").unwrap(),
+            "Battery replacement required"
+        );
+    }
+
+    #[test]
+    fn test_learning() {
+        let output = MEGACLI_OPTIMAL.replace(
+            "Learn Cycle Active                      : No",
+            "Learn Cycle Active                      : Yes",
+        );
+        let status = RaidBbu::bbu_status(config::RaidBbuTool::MegaCli, &output).unwrap();
+        assert!(!status.is_ok());
+        assert_eq!(
+            status.problem,
+            "Learn cycle active, write-back cache disabled"
+        );
+        assert!(RaidBbu::bbu_status(config::RaidBbuTool::Storcli, "No BBU found").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_missing_tool() {
+        let raid_bbu = RaidBbu {
+            id: vec![String::from("Controller 0")],
+            tool: config::RaidBbuTool::Storcli,
+            binary: String::from("/nonexistent/storcli64"),
+            controller: 0,
+            capture: crate::capture::Capture::default(),
+        };
+        let data = raid_bbu.get_data().await.unwrap();
+        assert_eq!(
+            data[0].as_ref().unwrap_err().to_string(),
+            "/nonexistent/storcli64 not found, is the RAID controller's CLI installed?"
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ClockOffset {
+    // positive if the system clock is ahead of the RTC
+    seconds: f64,
+}
+
+impl std::fmt::Display for ClockOffset {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:.3}", self.seconds)
+    }
+}
+
+impl LevelItem for ClockOffset {
+    fn level(&self) -> f64 {
+        self.seconds.abs()
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub struct RtcDrift {
+    id: Vec<String>,
+    device: String,
+    capture: crate::capture::Capture,
+}
+
+impl RtcDrift {
+    // e.g. "2023-01-05 12:34:56.789012+01:00"
+    fn parse_hwclock(output: &str) -> Result<chrono::DateTime<chrono::FixedOffset>> {
+        let output = output.trim();
+        chrono::DateTime::parse_from_str(output, "%Y-%m-%d %H:%M:%S%.f%:z")
+            .map_err(|x| Error(format!("Invalid hwclock output '{}': {}", output, x)))
+    }
+
+    fn offset(
+        system_time: chrono::DateTime<chrono::Utc>,
+        rtc_time: chrono::DateTime<chrono::FixedOffset>,
+    ) -> ClockOffset {
+        let difference = system_time.signed_duration_since(rtc_time);
+        ClockOffset {
+            seconds: difference.num_milliseconds() as f64 / 1000.0,
+        }
+    }
+
+    async fn get_offset(&self) -> Result<ClockOffset> {
+        // hwclock reports the time of the RTC at the moment it was invoked.
+        let system_time = chrono::Utc::now();
+        let output = self
+            .capture
+            .output(tokio::process::Command::new("hwclock").args([
+                "--get",
+                "--rtc",
+                self.device.as_str(),
+            ]))
+            .await
+            .map_err(|x| Error(format!("Failed to run hwclock: {}", x)))?;
+        if !output.status.success() {
+            return Err(Error(format!(
+                "Could not read the RTC: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        let output = String::from_utf8(output.stdout)
+            .map_err(|x| Error(format!("Output of hwclock is not valid UTF-8: {}", x)))?;
+        Ok(Self::offset(system_time, Self::parse_hwclock(&output)?))
+    }
+}
+
+impl TryFrom<&config::Check> for RtcDrift {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::RtcDrift(rtc_drift) = &check.type_ {
+            if rtc_drift.device.is_empty() {
+                Err(Error(String::from("'device' cannot be empty.")))
+            } else {
+                Ok(Self {
+                    id: vec![rtc_drift.device.clone()],
+                    device: rtc_drift.device.clone(),
+                    capture: crate::capture::from_config(check.capture.as_ref())?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for RtcDrift {
+    type Item = ClockOffset;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        Ok(vec![self.get_offset().await])
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("offset {}s", data)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.seconds)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_parse_hwclock() {
+        let rtc_time = RtcDrift::parse_hwclock("2023-01-05 12:34:56.789012+01:00\n").unwrap();
+        assert_eq!(
+            rtc_time,
+            chrono::DateTime::<chrono::FixedOffset>::from_str("2023-01-05T11:34:56.789012Z")
+                .unwrap()
+        );
+        assert!(RtcDrift::parse_hwclock("hwclock: Cannot access the Hardware Clock").is_err());
+    }
+
+    #[test]
+    fn test_offset() {
+        let system_time =
+            chrono::DateTime::<chrono::Utc>::from_str("2023-01-05T11:35:00Z").unwrap();
+        let rtc_time = RtcDrift::parse_hwclock("2023-01-05 12:34:56.750000+01:00").unwrap();
+        let offset = RtcDrift::offset(system_time, rtc_time);
+        assert_eq!(offset.seconds, 3.25);
+        assert_eq!(offset.level(), 3.25);
+        let rtc_time = RtcDrift::parse_hwclock("2023-01-05 11:35:10.500000+00:00").unwrap();
+        let offset = RtcDrift::offset(system_time, rtc_time);
+        assert_eq!(offset.seconds, -10.5);
+        assert_eq!(offset.level(), 10.5);
+        assert_eq!(offset.to_string(), "-10.500");
+    }
+}
@@ -0,0 +1,204 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+pub struct Script {
+    id: Vec<String>,
+    ast: std::sync::Arc<rhai::AST>,
+    timeout: std::time::Duration,
+    resolver: Option<std::sync::Arc<crate::resolver::Resolver>>,
+    capture: crate::capture::Capture,
+}
+
+impl Script {
+    // The engine has no access to the outside world except for the helper functions below.
+    fn engine(
+        timeout: std::time::Duration,
+        handle: tokio::runtime::Handle,
+        resolver: Option<std::sync::Arc<crate::resolver::Resolver>>,
+        capture: crate::capture::Capture,
+    ) -> rhai::Engine {
+        let mut engine = rhai::Engine::new();
+        let start = std::time::Instant::now();
+        engine.on_progress(move |_| {
+            if start.elapsed() > timeout {
+                Some(rhai::Dynamic::UNIT)
+            } else {
+                None
+            }
+        });
+        engine.register_fn(
+            "read_file",
+            |path: &str| -> std::result::Result<String, Box<rhai::EvalAltResult>> {
+                std::fs::read_to_string(path)
+                    .map_err(|x| format!("Could not read from {}: {}", path, x).into())
+            },
+        );
+        engine.register_fn(
+            "http_get",
+            move |url: &str| -> std::result::Result<String, Box<rhai::EvalAltResult>> {
+                handle
+                    .block_on(async {
+                        let request = async {
+                            crate::resolver::http_client_builder(resolver.as_ref())
+                                .timeout(timeout)
+                                .user_agent(crate::user_agent())
+                                .build()?
+                                .get(url)
+                                .send()
+                                .await?
+                                .error_for_status()
+                        };
+                        let response = request.await.map_err(|x| Error(x.to_string()))?;
+                        capture.text(response).await
+                    })
+                    .map_err(|x| format!("HTTP request failed: {}", x).into())
+            },
+        );
+        engine
+    }
+
+    fn to_number(result: rhai::Dynamic) -> Result<f64> {
+        if let Ok(value) = result.as_int() {
+            Ok(value as f64)
+        } else if let Ok(value) = result.as_float() {
+            Ok(value)
+        } else if let Ok(value) = result.as_bool() {
+            Ok(if value { 1.0 } else { 0.0 })
+        } else {
+            Err(Error(format!(
+                "Script returned {} instead of a number or boolean.",
+                result.type_name()
+            )))
+        }
+    }
+
+    async fn run(&self) -> Result<f64> {
+        let ast = self.ast.clone();
+        let timeout = self.timeout;
+        let handle = tokio::runtime::Handle::current();
+        let resolver = self.resolver.clone();
+        let capture = self.capture.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            Self::engine(timeout, handle, resolver, capture).eval_ast::<rhai::Dynamic>(&ast)
+        })
+        .await
+        .map_err(|x| Error(format!("Script did not finish: {}", x)))?;
+        match result {
+            Ok(result) => Self::to_number(result),
+            Err(err) => match *err {
+                rhai::EvalAltResult::ErrorTerminated(..) => Err(Error(format!(
+                    "Script timed out after {} seconds.",
+                    timeout.as_secs_f64()
+                ))),
+                err => Err(Error(format!("Script failed: {}", err))),
+            },
+        }
+    }
+}
+
+impl TryFrom<&config::Check> for Script {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::Script(script) = &check.type_ {
+            if script.script.is_empty() {
+                Err(Error(String::from("'script' cannot be empty.")))
+            } else if script.timeout == 0 {
+                Err(Error(String::from("'timeout' cannot be 0.")))
+            } else {
+                let ast = rhai::Engine::new()
+                    .compile(&script.script)
+                    .map_err(|x| Error(format!("Invalid 'script': {}", x)))?;
+                Ok(Self {
+                    id: vec![check.name.clone()],
+                    ast: std::sync::Arc::new(ast),
+                    timeout: std::time::Duration::from_secs(script.timeout.into()),
+                    resolver: crate::resolver::from_config(check.resolver.as_ref())?,
+                    capture: crate::capture::from_config(check.capture.as_ref())?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for Script {
+    type Item = f64;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        Ok(vec![self.run().await])
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("value {}", data)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn script(text: &str) -> Result<Script> {
+        let config = config::Config::try_from(
+            format!(
+                r#"
+                [[checks]]
+                name = "test-check"
+                type = "Script"
+                script = '''{}'''
+                timeout = 1
+                "#,
+                text
+            )
+            .as_str(),
+        )
+        .unwrap();
+        Script::try_from(&config.checks[0])
+    }
+
+    async fn run(text: &str) -> Result<f64> {
+        script(text)?.get_data().await?.remove(0)
+    }
+
+    #[tokio::test]
+    async fn test_computed_value() {
+        assert_eq!(run("40 + 2").await.unwrap(), 42.0);
+        assert_eq!(run("let x = 3.0; x / 2").await.unwrap(), 1.5);
+        assert_eq!(run("[1, 2, 3].len() > 2").await.unwrap(), 1.0);
+        assert!(run(r#""foo""#).await.is_err());
+        assert!(run("throw \"foo\"").await.is_err());
+        assert!(script("let x = ;").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_file() {
+        let path = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "queue: 17\n").unwrap();
+        let result = run(&format!(
+            r#"let value = read_file("{}").split(":")[1]; value.trim(); parse_int(value) * 2"#,
+            path.display()
+        ))
+        .await;
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(result.unwrap(), 34.0);
+        assert!(run(r#"read_file("/nonexistent/file")"#).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let error = run("loop {}").await.unwrap_err();
+        assert_eq!(error.to_string(), "Script timed out after 1 seconds.");
+    }
+}
@@ -0,0 +1,328 @@
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct SmartctlOutput {
+    #[serde(default)]
+    smartctl: Option<SmartctlInfo>,
+    #[serde(default)]
+    model_name: Option<String>,
+    #[serde(default)]
+    smart_status: Option<SmartStatus>,
+    #[serde(default)]
+    ata_smart_attributes: Option<AtaAttributes>,
+    #[serde(default)]
+    nvme_smart_health_information_log: Option<std::collections::HashMap<String, serde_json::Value>>,
+    #[serde(default)]
+    power_on_time: Option<PowerOnTime>,
+    #[serde(default)]
+    temperature: Option<CurrentTemperature>,
+}
+
+#[derive(Deserialize)]
+struct SmartctlInfo {
+    #[serde(default)]
+    messages: Vec<SmartctlMessage>,
+}
+
+#[derive(Deserialize)]
+struct SmartctlMessage {
+    string: String,
+    #[serde(default)]
+    severity: String,
+}
+
+#[derive(Deserialize)]
+struct SmartStatus {
+    passed: bool,
+}
+
+#[derive(Deserialize)]
+struct AtaAttributes {
+    table: Vec<AtaAttribute>,
+}
+
+#[derive(Deserialize)]
+struct AtaAttribute {
+    name: String,
+    raw: AtaRawValue,
+}
+
+#[derive(Deserialize)]
+struct AtaRawValue {
+    value: u64,
+}
+
+#[derive(Deserialize)]
+struct PowerOnTime {
+    hours: u64,
+}
+
+#[derive(Deserialize)]
+struct CurrentTemperature {
+    current: i64,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct DiskHealth {
+    passed: bool,
+    // raw value of the selected attribute
+    attribute: Option<u64>,
+    model: String,
+    power_on_hours: Option<u64>,
+    temperature: Option<i64>,
+}
+
+impl std::fmt::Display for DiskHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if !self.passed {
+            write!(f, "FAILED")
+        } else {
+            write!(f, "{}", self.attribute.unwrap_or(0))
+        }
+    }
+}
+
+// A failed self-assessment exceeds any level.
+impl LevelItem for DiskHealth {
+    fn level(&self) -> f64 {
+        if self.passed {
+            self.attribute.unwrap_or(0) as f64
+        } else {
+            f64::INFINITY
+        }
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(
+            String::from("smart_status"),
+            String::from(if self.passed { "PASSED" } else { "FAILED" }),
+        );
+        placeholders.insert(String::from("model"), self.model.clone());
+        if let Some(power_on_hours) = self.power_on_hours {
+            placeholders.insert(String::from("power_on_hours"), power_on_hours.to_string());
+        }
+        if let Some(temperature) = self.temperature {
+            placeholders.insert(String::from("temperature"), temperature.to_string());
+        }
+    }
+}
+
+pub struct SmartHealth {
+    id: Vec<String>,
+    attribute: Option<String>,
+    capture: crate::capture::Capture,
+}
+
+impl SmartHealth {
+    // The attribute is looked up by name in the ATA attribute table (raw value) or the NVMe
+    // health information log.
+    fn parse(output: &str, attribute: Option<&str>) -> Result<DiskHealth> {
+        let output: SmartctlOutput = serde_json::from_str(output)
+            .map_err(|x| Error(format!("Could not parse output of smartctl: {}", x)))?;
+        let smart_status = match output.smart_status {
+            Some(smart_status) => smart_status,
+            None => {
+                let messages: Vec<String> = output
+                    .smartctl
+                    .map(|x| x.messages)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|x| x.severity == "error")
+                    .map(|x| x.string)
+                    .collect();
+                return Err(Error(if messages.is_empty() {
+                    String::from("smartctl did not report a SMART status.")
+                } else {
+                    format!("smartctl failed: {}", messages.join(" "))
+                }));
+            }
+        };
+        let attribute = match attribute {
+            Some(name) => Some(
+                output
+                    .ata_smart_attributes
+                    .and_then(|x| x.table.into_iter().find(|x| x.name == name))
+                    .map(|x| x.raw.value)
+                    .or_else(|| {
+                        output
+                            .nvme_smart_health_information_log
+                            .as_ref()
+                            .and_then(|x| x.get(name))
+                            .and_then(|x| x.as_u64())
+                    })
+                    .ok_or_else(|| Error(format!("Attribute '{}' not found.", name)))?,
+            ),
+            None => None,
+        };
+        Ok(DiskHealth {
+            passed: smart_status.passed,
+            attribute,
+            model: output.model_name.unwrap_or_default(),
+            power_on_hours: output.power_on_time.map(|x| x.hours),
+            temperature: output.temperature.map(|x| x.current),
+        })
+    }
+
+    // smartctl uses the exit code as a bit mask (e.g. 8 for a failing disk), so the output is
+    // parsed regardless of it.
+    async fn get_health(&self, device: &str) -> Result<DiskHealth> {
+        let output = self
+            .capture
+            .output(tokio::process::Command::new("smartctl").args([
+                "--json",
+                "--info",
+                "--health",
+                "--attributes",
+                device,
+            ]))
+            .await
+            .map_err(|x| match x.kind() {
+                std::io::ErrorKind::NotFound => Error(String::from(
+                    "smartctl not found, is smartmontools installed?",
+                )),
+                _ => Error(format!("Failed to run smartctl: {}", x)),
+            })?;
+        let stdout = String::from_utf8(output.stdout)
+            .map_err(|x| Error(format!("Output of smartctl is not valid UTF-8: {}", x)))?;
+        Self::parse(&stdout, self.attribute.as_deref())
+    }
+}
+
+impl TryFrom<&config::Check> for SmartHealth {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::SmartHealth(smart_health) = &check.type_ {
+            if smart_health.devices.is_empty() {
+                Err(Error(String::from("'devices' cannot be empty.")))
+            } else if smart_health.devices.iter().any(|x| x.is_empty()) {
+                Err(Error(String::from(
+                    "'devices' cannot contain empty devices.",
+                )))
+            } else if smart_health
+                .attribute
+                .as_ref()
+                .is_some_and(|x| x.is_empty())
+            {
+                Err(Error(String::from("'attribute' cannot be empty.")))
+            } else {
+                Ok(Self {
+                    id: smart_health.devices.clone(),
+                    attribute: smart_health.attribute.clone(),
+                    capture: crate::capture::from_config(check.capture.as_ref())?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for SmartHealth {
+    type Item = DiskHealth;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mut res = Vec::new();
+        for device in self.id.iter() {
+            res.push(self.get_health(device).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        match (data.passed, data.attribute) {
+            (false, _) => String::from("SMART self-assessment failed"),
+            (true, Some(attribute)) => {
+                format!("SMART self-assessment passed, attribute {}", attribute)
+            }
+            (true, None) => String::from("SMART self-assessment passed"),
+        }
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        data.passed.then(|| data.attribute.unwrap_or(0) as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ATA: &str = r#"{
+        "smartctl": {"version": [7, 3], "exit_status": 0},
+        "device": {"name": "/dev/sda", "type": "sat"},
+        "model_name": "WDC WD40EFRX-68N32N0",
+        "smart_status": {"passed": true},
+        "ata_smart_attributes": {"revision": 16, "table": [
+            {"id": 5, "name": "Reallocated_Sector_Ct", "value": 200, "worst": 200, "thresh": 140,
+             "raw": {"value": 12, "string": "12"}},
+            {"id": 197, "name": "Current_Pending_Sector", "value": 200, "worst": 200, "thresh": 0,
+             "raw": {"value": 0, "string": "0"}}
+        ]},
+        "power_on_time": {"hours": 31234},
+        "temperature": {"current": 36}
+    }"#;
+
+    const NVME: &str = r#"{
+        "smartctl": {"exit_status": 8},
+        "model_name": "Samsung SSD 970 EVO Plus 1TB",
+        "smart_status": {"passed": false, "nvme": {"value": 4}},
+        "nvme_smart_health_information_log": {"critical_warning": 4, "media_errors": 3,
+            "percentage_used": 102},
+        "temperature": {"current": 41}
+    }"#;
+
+    const MISSING: &str = r#"{
+        "smartctl": {"exit_status": 2, "messages": [
+            {"string": "Smartctl open device: /dev/sdz failed: No such device", "severity": "error"}
+        ]}
+    }"#;
+
+    #[test]
+    fn test_parse() {
+        let ata = SmartHealth::parse(ATA, Some("Reallocated_Sector_Ct")).unwrap();
+        assert!(ata.passed);
+        assert_eq!(ata.level(), 12.0);
+        let mut placeholders = PlaceholderMap::new();
+        ata.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("smart_status").unwrap(), "PASSED");
+        assert_eq!(placeholders.get("model").unwrap(), "WDC WD40EFRX-68N32N0");
+        assert_eq!(placeholders.get("power_on_hours").unwrap(), "31234");
+        assert_eq!(placeholders.get("temperature").unwrap(), "36");
+        assert_eq!(SmartHealth::parse(ATA, None).unwrap().level(), 0.0);
+        assert_eq!(
+            SmartHealth::parse(ATA, Some("media_errors"))
+                .unwrap_err()
+                .to_string(),
+            "Attribute 'media_errors' not found."
+        );
+        let nvme = SmartHealth::parse(NVME, Some("media_errors")).unwrap();
+        assert!(!nvme.passed);
+        assert_eq!(nvme.attribute, Some(3));
+        assert_eq!(nvme.level(), f64::INFINITY);
+        assert_eq!(SmartHealth::value(&nvme), None);
+        assert_eq!(
+            SmartHealth::parse(MISSING, None).unwrap_err().to_string(),
+            "smartctl failed: Smartctl open device: /dev/sdz failed: No such device"
+        );
+        assert!(SmartHealth::parse("", None).is_err());
+    }
+}
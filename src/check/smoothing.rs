@@ -0,0 +1,63 @@
+use crate::{Error, Result};
+use std::collections::HashMap;
+
+// Exponentially weighted moving average (EWMA) of the latency measured by the network checks, so
+// occasional spikes don't dominate while lasting regressions still show.
+pub struct Smoothing {
+    // weight of the newest value
+    factor: f64,
+    averages: std::sync::Mutex<HashMap<String, f64>>,
+}
+
+impl Smoothing {
+    pub fn new(factor: f64) -> Result<Self> {
+        if factor > 0.0 && factor <= 1.0 {
+            Ok(Self {
+                factor,
+                averages: std::sync::Mutex::new(HashMap::new()),
+            })
+        } else {
+            Err(Error(String::from(
+                "'smoothing' must be greater than 0 and at most 1.",
+            )))
+        }
+    }
+
+    // Returns the average including `value`. The first value of an ID starts the average.
+    pub fn update(&self, id: &str, value: f64) -> f64 {
+        let mut averages = self.averages.lock().unwrap();
+        let average = averages
+            .entry(String::from(id))
+            .and_modify(|x| *x += self.factor * (value - *x))
+            .or_insert(value);
+        *average
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_smoothing() {
+        let smoothing = Smoothing::new(0.1).unwrap();
+        let latencies = [10.0, 12.0, 500.0, 11.0, 9.0, 480.0, 10.0, 10.0];
+        let smoothed: Vec<f64> = latencies
+            .iter()
+            .map(|x| smoothing.update("A", *x))
+            .collect();
+        assert_eq!(smoothed[0], 10.0);
+        // the spikes are damped ...
+        assert!(smoothed.iter().all(|x| *x < 100.0));
+        // ... while a lasting regression still shows
+        for _ in 0..40 {
+            smoothing.update("A", 200.0);
+        }
+        assert!(smoothing.update("A", 200.0) > 190.0);
+        // the IDs are smoothed independently
+        assert_eq!(smoothing.update("B", 42.0), 42.0);
+        assert!(Smoothing::new(0.0).is_err());
+        assert!(Smoothing::new(1.5).is_err());
+        assert!(Smoothing::new(1.0).is_ok());
+    }
+}
@@ -0,0 +1,149 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+#[derive(PartialEq, Debug)]
+struct Swap {
+    // in kB
+    total: u64,
+    free: u64,
+}
+
+pub struct SwapUsage {
+    id: Vec<String>,
+    used_mib: bool,
+    meminfo_path: std::path::PathBuf,
+    proc_path: std::path::PathBuf,
+}
+
+impl SwapUsage {
+    // e.g. "SwapTotal:       2000000 kB"
+    fn parse_meminfo(content: &str) -> Result<Swap> {
+        let get = |name: &str| -> Result<u64> {
+            let line = content
+                .lines()
+                .find(|x| x.split(':').next() == Some(name))
+                .ok_or_else(|| Error(format!("{} not found.", name)))?;
+            crate::get_number(&format!("Could not read {}", name), line, 1)
+        };
+        Ok(Swap {
+            total: get("SwapTotal")?,
+            free: get("SwapFree")?,
+        })
+    }
+
+    fn percent(swap: &Swap) -> Result<u64> {
+        (swap.total.saturating_sub(swap.free) * 100)
+            .checked_div(swap.total)
+            .ok_or_else(|| Error(String::from("Swap is disabled (SwapTotal is 0).")))
+    }
+
+    fn used_mib(swap: &Swap) -> u64 {
+        swap.total.saturating_sub(swap.free) / 1024
+    }
+}
+
+impl TryFrom<&config::Check> for SwapUsage {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::SwapUsage(swap_usage) = &check.type_ {
+            let mut id = vec![String::from("Percent")];
+            if swap_usage.used_mib {
+                id.push(String::from("UsedMiB"));
+            }
+            Ok(Self {
+                id,
+                used_mib: swap_usage.used_mib,
+                meminfo_path: check.proc_path.join("meminfo"),
+                proc_path: check.proc_path.clone(),
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for SwapUsage {
+    type Item = u64;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let content = tokio::fs::read_to_string(&self.meminfo_path)
+            .await
+            .map_err(|x| {
+                super::proc_error(
+                    &self.proc_path,
+                    format!("Could not read from {}: {}", self.meminfo_path.display(), x),
+                )
+            })?;
+        let swap = Self::parse_meminfo(&content)?;
+        let mut res = vec![Self::percent(&swap)];
+        if self.used_mib {
+            res.push(Ok(Self::used_mib(&swap)));
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("usage {}", data)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MEMINFO: &str = "MemTotal:        8000000 kB
+MemAvailable:    2000000 kB
+SwapCached:        10000 kB
+SwapTotal:       2048000 kB
+SwapFree:         512000 kB
+";
+
+    #[test]
+    fn test_parse_meminfo() {
+        let swap = SwapUsage::parse_meminfo(MEMINFO).unwrap();
+        assert_eq!(
+            swap,
+            Swap {
+                total: 2048000,
+                free: 512000
+            }
+        );
+        assert_eq!(SwapUsage::percent(&swap).unwrap(), 75);
+        assert_eq!(SwapUsage::used_mib(&swap), 1500);
+        assert!(SwapUsage::parse_meminfo("MemTotal:        8000000 kB").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_swap_disabled() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("meminfo"),
+            "SwapTotal:             0 kB\nSwapFree:              0 kB\n",
+        )
+        .unwrap();
+        let swap_usage = SwapUsage {
+            id: vec![String::from("Percent"), String::from("UsedMiB")],
+            used_mib: true,
+            meminfo_path: root.join("meminfo"),
+            proc_path: root.clone(),
+        };
+        let data = swap_usage.get_data().await;
+        std::fs::remove_dir_all(&root).unwrap();
+        let data = data.unwrap();
+        assert!(data[0].is_err());
+        assert_eq!(*data[1].as_ref().unwrap(), 0);
+    }
+}
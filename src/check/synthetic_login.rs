@@ -0,0 +1,360 @@
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct LoginTime {
+    // in milliseconds
+    total: u64,
+    steps: Vec<u64>,
+}
+
+impl std::fmt::Display for LoginTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.total)
+    }
+}
+
+impl LevelItem for LoginTime {
+    fn level(&self) -> f64 {
+        self.total as f64
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(
+            String::from("step_times"),
+            self.steps
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+        );
+    }
+}
+
+struct Step {
+    url: String,
+    method: reqwest::Method,
+    headers: HashMap<String, String>,
+    body: String,
+    status: Option<u16>,
+    contains: Option<String>,
+    extract: HashMap<String, config::SyntheticLoginExtract>,
+}
+
+impl From<&config::SyntheticLoginStep> for Step {
+    fn from(step: &config::SyntheticLoginStep) -> Self {
+        Self {
+            url: step.url.clone(),
+            method: reqwest::Method::from(step.method),
+            headers: step.headers.clone(),
+            body: step.body.clone(),
+            status: step.status,
+            contains: step.contains.clone(),
+            extract: step.extract.clone(),
+        }
+    }
+}
+
+pub struct SyntheticLogin {
+    id: Vec<String>,
+    steps: Vec<Step>,
+    client: reqwest::Client,
+    // 1-based index of the step that failed in the last cycle
+    failed_step: std::sync::Mutex<Option<usize>>,
+    capture: crate::capture::Capture,
+}
+
+impl SyntheticLogin {
+    // Returns the text between the first occurrence of `after` and the next occurrence of
+    // `before`, e.g. the value of a hidden CSRF token input field.
+    fn extract(body: &str, extract: &config::SyntheticLoginExtract) -> Option<String> {
+        let start = body.find(&extract.after)? + extract.after.len();
+        let length = body[start..].find(&extract.before)?;
+        Some(String::from(&body[start..start + length]))
+    }
+
+    // Cookies are kept for the whole transaction, independent of their domain and path.
+    fn store_cookies(response: &reqwest::Response, cookies: &mut BTreeMap<String, String>) {
+        for value in response
+            .headers()
+            .get_all(reqwest::header::SET_COOKIE)
+            .iter()
+            .filter_map(|x| x.to_str().ok())
+        {
+            let pair = value.split(';').next().unwrap_or_default();
+            if let Some((name, value)) = pair.split_once('=') {
+                cookies.insert(String::from(name.trim()), String::from(value.trim()));
+            }
+        }
+    }
+
+    async fn run_step(
+        &self,
+        step: &Step,
+        variables: &mut PlaceholderMap,
+        cookies: &mut BTreeMap<String, String>,
+    ) -> Result<()> {
+        let url = crate::fill_placeholders(&step.url, variables);
+        let mut request = self
+            .client
+            .request(step.method.clone(), &url)
+            .body(crate::fill_placeholders(&step.body, variables));
+        for (name, value) in step.headers.iter() {
+            request = request.header(name, crate::fill_placeholders(value, variables));
+        }
+        if !cookies.is_empty() {
+            request = request.header(
+                reqwest::header::COOKIE,
+                cookies
+                    .iter()
+                    .map(|(name, value)| format!("{}={}", name, value))
+                    .collect::<Vec<String>>()
+                    .join("; "),
+            );
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|x| Error(format!("HTTP request failed: {}", x)))?;
+        Self::store_cookies(&response, cookies);
+        let status = response.status().as_u16();
+        let status_ok = match step.status {
+            Some(expected) => status == expected,
+            None => status < 400,
+        };
+        if !status_ok {
+            return Err(Error(format!("Unexpected HTTP status code {}.", status)));
+        }
+        let body = self
+            .capture
+            .text(response)
+            .await
+            .map_err(|x| Error(format!("Could not read response body: {}", x)))?;
+        if let Some(contains) = &step.contains {
+            if !body.contains(contains.as_str()) {
+                return Err(Error(format!("Response does not contain '{}'.", contains)));
+            }
+        }
+        for (name, extract) in step.extract.iter() {
+            let value = Self::extract(&body, extract)
+                .ok_or_else(|| Error(format!("Could not extract '{}'.", name)))?;
+            variables.insert(name.clone(), value);
+        }
+        Ok(())
+    }
+
+    async fn login(&self) -> Result<LoginTime> {
+        let mut variables = PlaceholderMap::new();
+        let mut cookies = BTreeMap::new();
+        let mut steps = Vec::new();
+        for (index, step) in self.steps.iter().enumerate() {
+            let start = std::time::Instant::now();
+            if let Err(err) = self.run_step(step, &mut variables, &mut cookies).await {
+                *self.failed_step.lock().unwrap() = Some(index + 1);
+                return Err(Error(format!("Step {} failed: {}", index + 1, err)));
+            }
+            steps.push(start.elapsed().as_millis() as u64);
+        }
+        *self.failed_step.lock().unwrap() = None;
+        Ok(LoginTime {
+            total: steps.iter().sum(),
+            steps,
+        })
+    }
+}
+
+impl TryFrom<&config::Check> for SyntheticLogin {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::SyntheticLogin(synthetic_login) = &check.type_ {
+            if synthetic_login.steps.is_empty() {
+                Err(Error(String::from("'steps' cannot be empty.")))
+            } else if synthetic_login.steps.iter().any(|x| x.url.is_empty()) {
+                Err(Error(String::from("'url' cannot be empty.")))
+            } else if synthetic_login.steps.iter().any(|x| {
+                x.extract
+                    .values()
+                    .any(|x| x.after.is_empty() || x.before.is_empty())
+            }) {
+                Err(Error(String::from(
+                    "'after' and 'before' of 'extract' cannot be empty.",
+                )))
+            } else if synthetic_login.timeout == 0 {
+                Err(Error(String::from("'timeout' cannot be 0.")))
+            } else {
+                Ok(Self {
+                    id: vec![String::from("Login")],
+                    steps: synthetic_login.steps.iter().map(Step::from).collect(),
+                    // Redirects are not followed, so cookies set along the way are not lost.
+                    client: crate::http_client_builder(check.resolver.as_ref())?
+                        .user_agent(crate::user_agent())
+                        .redirect(reqwest::redirect::Policy::none())
+                        .timeout(std::time::Duration::from_secs(
+                            synthetic_login.timeout.into(),
+                        ))
+                        .danger_accept_invalid_certs(synthetic_login.tls_insecure)
+                        .build()
+                        .map_err(|x| Error(format!("Could not create HTTP client: {}", x)))?,
+                    failed_step: std::sync::Mutex::new(None),
+                    capture: crate::capture::from_config(check.capture.as_ref())?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for SyntheticLogin {
+    type Item = LoginTime;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        Ok(vec![self.login().await])
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("login time {}ms", data.total)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.level())
+    }
+
+    fn add_error_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        if let Some(step) = *self.failed_step.lock().unwrap() {
+            placeholders.insert(String::from("failed_step"), step.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::mock_server_with_headers;
+
+    fn synthetic_login(url: &str, password: &str) -> SyntheticLogin {
+        let config = config::Config::try_from(
+            format!(
+                r#"
+                [[checks]]
+                name = "Login"
+                type = "SyntheticLogin"
+                [[checks.steps]]
+                url = "{url}/login"
+                status = 200
+                extract = {{csrf = {{after = 'name="csrf" value="', before = '"'}}}}
+                [[checks.steps]]
+                url = "{url}/login"
+                method = "POST"
+                headers = {{"Content-Type" = "application/x-www-form-urlencoded"}}
+                body = "user=monitor&password={password}&csrf={{{{csrf}}}}"
+                status = 302
+                [[checks.steps]]
+                url = "{url}/home"
+                contains = "Welcome, monitor"
+                "#,
+            )
+            .as_str(),
+        )
+        .unwrap();
+        SyntheticLogin::try_from(&config.checks[0]).unwrap()
+    }
+
+    async fn server() -> String {
+        mock_server_with_headers(|request| {
+            let header = |name: &str, value: &str| vec![(String::from(name), String::from(value))];
+            if request.starts_with("GET /login ") {
+                (
+                    200,
+                    header("Set-Cookie", "session=abc; Path=/; HttpOnly"),
+                    String::from(r#"<form><input name="csrf" value="t0k3n"></form>"#),
+                )
+            } else if request.starts_with("POST /login ") {
+                if request.contains("cookie: session=abc")
+                    && request.ends_with("user=monitor&password=secret&csrf=t0k3n")
+                {
+                    (302, header("Set-Cookie", "auth=xyz; Path=/"), String::new())
+                } else {
+                    (403, Vec::new(), String::from("Forbidden"))
+                }
+            } else if request.starts_with("GET /home ")
+                && request.contains("cookie: auth=xyz; session=abc")
+            {
+                (200, Vec::new(), String::from("<h1>Welcome, monitor</h1>"))
+            } else {
+                (200, Vec::new(), String::from("<h1>Please log in</h1>"))
+            }
+        })
+        .await
+    }
+
+    #[test]
+    fn test_extract() {
+        let extract = config::SyntheticLoginExtract {
+            after: String::from("value=\""),
+            before: String::from("\""),
+        };
+        assert_eq!(
+            SyntheticLogin::extract(r#"<input value="abc">"#, &extract).unwrap(),
+            "abc"
+        );
+        assert!(SyntheticLogin::extract("<input>", &extract).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_login() {
+        let url = server().await;
+        let synthetic_login = synthetic_login(&url, "secret");
+        let data = synthetic_login.get_data().await.unwrap();
+        let login_time = data[0].as_ref().unwrap();
+        assert_eq!(login_time.steps.len(), 3);
+        let mut placeholders = PlaceholderMap::new();
+        synthetic_login.add_error_placeholders(&mut placeholders);
+        assert!(placeholders.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failed_step() {
+        let url = server().await;
+        let synthetic_login = synthetic_login(&url, "wrong");
+        let data = synthetic_login.get_data().await.unwrap();
+        assert_eq!(
+            data[0].as_ref().unwrap_err().to_string(),
+            "Step 2 failed: Unexpected HTTP status code 403."
+        );
+        let mut placeholders = PlaceholderMap::new();
+        synthetic_login.add_error_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("failed_step").unwrap(), "2");
+    }
+
+    #[tokio::test]
+    async fn test_missing_marker() {
+        let url = server().await;
+        let mut synthetic_login = synthetic_login(&url, "secret");
+        // without the POST step, the session is not authenticated
+        synthetic_login.steps.remove(1);
+        let data = synthetic_login.get_data().await.unwrap();
+        assert_eq!(
+            data[0].as_ref().unwrap_err().to_string(),
+            "Step 2 failed: Response does not contain 'Welcome, monitor'."
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct FailedUnits {
+    pub units: Vec<String>,
+}
+
+impl std::fmt::Display for FailedUnits {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.units.len())
+    }
+}
+
+impl LevelItem for FailedUnits {
+    fn level(&self) -> f64 {
+        self.units.len() as f64
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("failed_units"), self.units.join(","));
+    }
+}
+
+pub struct SystemdFailedUnits {
+    id: Vec<String>,
+    capture: crate::capture::Capture,
+}
+
+impl SystemdFailedUnits {
+    // e.g. "foo.service loaded failed failed Foo daemon"
+    pub fn parse_units(output: &str) -> FailedUnits {
+        FailedUnits {
+            units: output
+                .lines()
+                .filter_map(|x| {
+                    x.split_whitespace()
+                        .find(|x| *x != "●" && *x != "*")
+                        .map(String::from)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<&config::Check> for SystemdFailedUnits {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::SystemdFailedUnits(_) = &check.type_ {
+            Ok(Self {
+                id: vec![String::from("FailedUnits")],
+                capture: crate::capture::from_config(check.capture.as_ref())?,
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for SystemdFailedUnits {
+    type Item = FailedUnits;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let output = self
+            .capture
+            .output(tokio::process::Command::new("systemctl").args([
+                "list-units",
+                "--state=failed",
+                "--no-legend",
+                "--plain",
+            ]))
+            .await
+            .map_err(|x| Error(format!("Failed to run systemctl: {}", x)))?;
+        if !output.status.success() {
+            return Err(Error(format!(
+                "systemctl failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        let output = String::from_utf8(output.stdout)
+            .map_err(|x| Error(format!("Output of systemctl is not valid UTF-8: {}", x)))?;
+        Ok(vec![Ok(Self::parse_units(&output))])
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("failed unit count {}", data.units.len())
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.units.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_units_none() {
+        let failed_units = SystemdFailedUnits::parse_units("");
+        assert_eq!(failed_units.level(), 0.0);
+        let mut placeholders = PlaceholderMap::new();
+        failed_units.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("failed_units").unwrap(), "");
+    }
+
+    #[test]
+    fn test_parse_units() {
+        let output = "foo.service        loaded failed failed Foo daemon
+bar-baz.mount      loaded failed failed /bar/baz
+● qux@1.service   loaded failed failed Qux instance 1
+";
+        let failed_units = SystemdFailedUnits::parse_units(output);
+        assert_eq!(failed_units.level(), 3.0);
+        let mut placeholders = PlaceholderMap::new();
+        failed_units.add_placeholders(&mut placeholders);
+        assert_eq!(
+            placeholders.get("failed_units").unwrap(),
+            "foo.service,bar-baz.mount,qux@1.service"
+        );
+    }
+}
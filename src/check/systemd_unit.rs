@@ -0,0 +1,233 @@
+use super::DataSource;
+use crate::alarm::StatusItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct UnitStatus {
+    // e.g. "active", "inactive" or "failed"
+    active_state: String,
+    // e.g. "success" or "exit-code", empty for unit types without a result
+    result: String,
+}
+
+impl StatusItem for UnitStatus {
+    fn is_ok(&self) -> bool {
+        matches!(self.active_state.as_str(), "active" | "reloading")
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("active_state"), self.active_state.clone());
+        placeholders.insert(String::from("unit_result"), self.result.clone());
+    }
+}
+
+pub struct SystemdUnit {
+    id: Vec<String>,
+    method: config::SystemdUnitMethod,
+    capture: crate::capture::Capture,
+}
+
+impl SystemdUnit {
+    async fn run(&self, program: &str, args: &[&str]) -> Result<String> {
+        let output = self
+            .capture
+            .output(tokio::process::Command::new(program).args(args))
+            .await
+            .map_err(|x| Error(format!("Failed to run {}: {}", program, x)))?;
+        if !output.status.success() {
+            return Err(Error(format!(
+                "{} failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        String::from_utf8(output.stdout)
+            .map_err(|x| Error(format!("Output of {} is not valid UTF-8: {}", program, x)))
+    }
+
+    // e.g. "ActiveState=failed\nResult=exit-code"
+    fn parse_show(output: &str) -> Result<UnitStatus> {
+        let get = |name: &str| {
+            output
+                .lines()
+                .find_map(|x| x.strip_prefix(name)?.strip_prefix('='))
+        };
+        Ok(UnitStatus {
+            active_state: String::from(
+                get("ActiveState").ok_or_else(|| Error(String::from("ActiveState not found.")))?,
+            ),
+            result: String::from(get("Result").unwrap_or_default()),
+        })
+    }
+
+    // Escapes the unit name like sd_bus_path_encode(), e.g. "foo-bar.service" becomes
+    // "/org/freedesktop/systemd1/unit/foo_2dbar_2eservice".
+    fn object_path(unit: &str) -> String {
+        let mut res = String::from("/org/freedesktop/systemd1/unit/");
+        for (i, byte) in unit.bytes().enumerate() {
+            if byte.is_ascii_alphabetic() || (byte.is_ascii_digit() && i > 0) {
+                res.push(byte as char);
+            } else {
+                res.push_str(&format!("_{:02x}", byte));
+            }
+        }
+        res
+    }
+
+    // Only some unit types have a result, each on its own D-Bus interface.
+    fn result_interface(unit: &str) -> Option<&'static str> {
+        match unit.rsplit_once('.')?.1 {
+            "service" => Some("org.freedesktop.systemd1.Service"),
+            "socket" => Some("org.freedesktop.systemd1.Socket"),
+            "mount" => Some("org.freedesktop.systemd1.Mount"),
+            "automount" => Some("org.freedesktop.systemd1.Automount"),
+            "swap" => Some("org.freedesktop.systemd1.Swap"),
+            "timer" => Some("org.freedesktop.systemd1.Timer"),
+            "path" => Some("org.freedesktop.systemd1.Path"),
+            "scope" => Some("org.freedesktop.systemd1.Scope"),
+            _ => None,
+        }
+    }
+
+    // e.g. 's "active"'
+    fn parse_property(output: &str) -> Result<String> {
+        output
+            .trim()
+            .strip_prefix("s \"")
+            .and_then(|x| x.strip_suffix('"'))
+            .map(String::from)
+            .ok_or_else(|| Error(format!("Unexpected output of busctl: {}", output.trim())))
+    }
+
+    async fn get_property(&self, path: &str, interface: &str, property: &str) -> Result<String> {
+        let output = self
+            .run(
+                "busctl",
+                &[
+                    "get-property",
+                    "org.freedesktop.systemd1",
+                    path,
+                    interface,
+                    property,
+                ],
+            )
+            .await?;
+        Self::parse_property(&output)
+    }
+
+    async fn unit_status(&self, unit: &str) -> Result<UnitStatus> {
+        match self.method {
+            config::SystemdUnitMethod::Systemctl => {
+                let output = self
+                    .run(
+                        "systemctl",
+                        &["show", "--property=ActiveState,Result", unit],
+                    )
+                    .await?;
+                Self::parse_show(&output)
+            }
+            config::SystemdUnitMethod::Dbus => {
+                let path = Self::object_path(unit);
+                let active_state = self
+                    .get_property(&path, "org.freedesktop.systemd1.Unit", "ActiveState")
+                    .await?;
+                let result = match Self::result_interface(unit) {
+                    Some(interface) => self.get_property(&path, interface, "Result").await?,
+                    None => String::new(),
+                };
+                Ok(UnitStatus {
+                    active_state,
+                    result,
+                })
+            }
+        }
+    }
+}
+
+impl TryFrom<&config::Check> for SystemdUnit {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::SystemdUnit(systemd_unit) = &check.type_ {
+            if systemd_unit.units.iter().any(|x| x.is_empty()) {
+                Err(Error(String::from("'units' cannot contain empty names.")))
+            } else {
+                Ok(Self {
+                    id: systemd_unit.units.clone(),
+                    method: systemd_unit.method,
+                    capture: crate::capture::from_config(check.capture.as_ref())?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for SystemdUnit {
+    type Item = UnitStatus;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mut res = Vec::new();
+        for unit in self.id.iter() {
+            res.push(self.unit_status(unit).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("active state {}", data.active_state)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_show() {
+        let failed = SystemdUnit::parse_show("ActiveState=failed\nResult=exit-code\n").unwrap();
+        assert!(!failed.is_ok());
+        let mut placeholders = PlaceholderMap::new();
+        failed.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("active_state").unwrap(), "failed");
+        assert_eq!(placeholders.get("unit_result").unwrap(), "exit-code");
+        let target = SystemdUnit::parse_show("ActiveState=active\n").unwrap();
+        assert!(target.is_ok());
+        assert_eq!(target.result, "");
+        assert!(
+            !SystemdUnit::parse_show("ActiveState=inactive\nResult=success\n")
+                .unwrap()
+                .is_ok()
+        );
+        assert!(SystemdUnit::parse_show("").is_err());
+    }
+
+    #[test]
+    fn test_object_path() {
+        assert_eq!(
+            SystemdUnit::object_path("dbus-broker.service"),
+            "/org/freedesktop/systemd1/unit/dbus_2dbroker_2eservice"
+        );
+        assert_eq!(
+            SystemdUnit::object_path("0ad@1.timer"),
+            "/org/freedesktop/systemd1/unit/_30ad_401_2etimer"
+        );
+    }
+
+    #[test]
+    fn test_parse_property() {
+        assert_eq!(
+            SystemdUnit::parse_property("s \"active\"\n").unwrap(),
+            "active"
+        );
+        assert!(SystemdUnit::parse_property("u 3\n").is_err());
+    }
+}
@@ -0,0 +1,178 @@
+use super::smoothing::Smoothing;
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ConnectTime {
+    // in milliseconds
+    connect_time: u64,
+    // connect time smoothed over the cycles, if configured
+    smoothed: Option<f64>,
+}
+
+impl std::fmt::Display for ConnectTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.smoothed {
+            Some(smoothed) => write!(f, "{:.1}", smoothed),
+            None => write!(f, "{}", self.connect_time),
+        }
+    }
+}
+
+impl LevelItem for ConnectTime {
+    fn level(&self) -> f64 {
+        self.smoothed.unwrap_or(self.connect_time as f64)
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("connect_time"), self.connect_time.to_string());
+        if let Some(smoothed) = self.smoothed {
+            placeholders.insert(
+                String::from("connect_time_smoothed"),
+                format!("{:.1}", smoothed),
+            );
+        }
+    }
+}
+
+pub struct TcpConnect {
+    targets: Vec<String>,
+    timeout: std::time::Duration,
+    resolver: Option<std::sync::Arc<crate::resolver::Resolver>>,
+    smoothing: Option<Smoothing>,
+    concurrency: usize,
+}
+
+impl TcpConnect {
+    // Returns the time it took to establish the connection (including the name resolution) in
+    // milliseconds.
+    async fn connect(&self, target: &str) -> Result<u64> {
+        let start = std::time::Instant::now();
+        let connect = async {
+            let addresses = crate::resolver::lookup_host(self.resolver.as_deref(), target).await?;
+            tokio::net::TcpStream::connect(&addresses[..])
+                .await
+                .map_err(|x| Error(format!("Could not connect: {}", x)))
+        };
+        match tokio::time::timeout(self.timeout, connect).await {
+            Ok(Ok(_)) => Ok(start.elapsed().as_millis() as u64),
+            Ok(Err(err)) => Err(err),
+            Err(_) => Err(Error(format!(
+                "Connection timed out after {} seconds.",
+                self.timeout.as_secs()
+            ))),
+        }
+    }
+}
+
+impl TryFrom<&config::Check> for TcpConnect {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::TcpConnect(tcp_connect) = &check.type_ {
+            if tcp_connect.targets.iter().any(|x| x.is_empty()) {
+                Err(Error(String::from(
+                    "'targets' cannot contain empty targets.",
+                )))
+            } else if let Some(target) = tcp_connect.targets.iter().find(|x| !x.contains(':')) {
+                Err(Error(format!(
+                    "Target '{}' must be given as '<host>:<port>'.",
+                    target
+                )))
+            } else if tcp_connect.timeout == 0 {
+                Err(Error(String::from("'timeout' cannot be 0.")))
+            } else {
+                Ok(Self {
+                    targets: tcp_connect.targets.clone(),
+                    timeout: std::time::Duration::from_secs(tcp_connect.timeout.into()),
+                    resolver: crate::resolver::from_config(check.resolver.as_ref())?,
+                    smoothing: tcp_connect.smoothing.map(Smoothing::new).transpose()?,
+                    concurrency: super::concurrency(tcp_connect.concurrency)?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for TcpConnect {
+    type Item = ConnectTime;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        Ok(
+            super::fetch_concurrently(self.targets.iter(), self.concurrency, |target| async {
+                self.connect(target).await.map(|x| ConnectTime {
+                    connect_time: x,
+                    smoothed: self.smoothing.as_ref().map(|s| s.update(target, x as f64)),
+                })
+            })
+            .await,
+        )
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        match data.smoothed {
+            Some(smoothed) => format!(
+                "connect time {}ms (smoothed {:.1}ms)",
+                data.connect_time, smoothed
+            ),
+            None => format!("connect time {}ms", data.connect_time),
+        }
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.targets[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.level())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let open = listener.local_addr().unwrap().to_string();
+        // The port of a dropped listener is closed.
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+        let tcp_connect = TcpConnect {
+            targets: vec![open, closed, String::from("127.0.0.1")],
+            timeout: std::time::Duration::from_secs(1),
+            resolver: None,
+            smoothing: Some(Smoothing::new(0.5).unwrap()),
+            concurrency: 2,
+        };
+        let data = tcp_connect.get_data().await.unwrap();
+        let mut placeholders = PlaceholderMap::new();
+        data[0]
+            .as_ref()
+            .unwrap()
+            .add_placeholders(&mut placeholders);
+        assert!(placeholders.contains_key("connect_time"));
+        assert!(placeholders.contains_key("connect_time_smoothed"));
+        assert!(data[1].is_err());
+        assert!(data[2].is_err());
+    }
+}
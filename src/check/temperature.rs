@@ -0,0 +1,238 @@
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SensorTemperature {
+    // in degrees Celsius
+    temperature: f64,
+    // label of the hottest matching sensor
+    sensor: String,
+}
+
+impl std::fmt::Display for SensorTemperature {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:.1}", self.temperature)
+    }
+}
+
+impl LevelItem for SensorTemperature {
+    fn level(&self) -> f64 {
+        self.temperature
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("sensor"), self.sensor.clone());
+    }
+}
+
+pub struct Sensor {
+    // e.g. "coretemp Package id 0" or "nvme Composite"
+    pub label: String,
+    pub input_path: std::path::PathBuf,
+}
+
+pub struct Temperature {
+    id: Vec<String>,
+    hwmon_path: std::path::PathBuf,
+    sys_path: std::path::PathBuf,
+}
+
+impl Temperature {
+    pub async fn read_trimmed(path: &std::path::Path) -> Option<String> {
+        tokio::fs::read_to_string(path)
+            .await
+            .ok()
+            .map(|x| String::from(x.trim()))
+    }
+
+    // Lists the hwmon sensors of the given kind (e.g. "temp" or "fan"). The label consists of the
+    // name of the hwmon device and the sensor's label or, if it has none, its file name (e.g.
+    // "temp1").
+    pub async fn sensors(
+        hwmon_path: &std::path::Path,
+        sys_path: &std::path::Path,
+        kind: &str,
+    ) -> Result<Vec<Sensor>> {
+        let mut devices = tokio::fs::read_dir(hwmon_path).await.map_err(|x| {
+            super::sys_error(
+                sys_path,
+                format!("Could not read directory {}: {}", hwmon_path.display(), x),
+            )
+        })?;
+        let mut res = Vec::new();
+        while let Ok(Some(device)) = devices.next_entry().await {
+            let device_path = device.path();
+            let name = Self::read_trimmed(&device_path.join("name"))
+                .await
+                .unwrap_or_else(|| device.file_name().to_string_lossy().into_owned());
+            let mut files = match tokio::fs::read_dir(&device_path).await {
+                Ok(files) => files,
+                Err(_) => continue,
+            };
+            while let Ok(Some(file)) = files.next_entry().await {
+                let file_name = file.file_name().to_string_lossy().into_owned();
+                let sensor = match file_name
+                    .strip_prefix(kind)
+                    .and_then(|x| x.strip_suffix("_input"))
+                {
+                    Some(number) => format!("{}{}", kind, number),
+                    None => continue,
+                };
+                let label = Self::read_trimmed(&device_path.join(format!("{}_label", sensor)))
+                    .await
+                    .unwrap_or(sensor);
+                res.push(Sensor {
+                    label: format!("{} {}", name, label),
+                    input_path: file.path(),
+                });
+            }
+        }
+        res.sort_by(|a, b| a.label.cmp(&b.label));
+        Ok(res)
+    }
+
+    // e.g. "62000" in millidegrees Celsius
+    async fn read_temperature(sensor: &Sensor) -> Option<f64> {
+        let millidegrees: i64 = Self::read_trimmed(&sensor.input_path).await?.parse().ok()?;
+        Some(millidegrees as f64 / 1000.0)
+    }
+
+    // Sensors that cannot be read (e.g. of a device in power saving mode) are skipped.
+    async fn temperature(pattern: &str, sensors: &[Sensor]) -> Result<SensorTemperature> {
+        let mut res: Option<SensorTemperature> = None;
+        let mut found = false;
+        for sensor in sensors.iter().filter(|x| x.label.contains(pattern)) {
+            found = true;
+            if let Some(temperature) = Self::read_temperature(sensor).await {
+                if res.as_ref().is_none_or(|x| temperature > x.temperature) {
+                    res = Some(SensorTemperature {
+                        temperature,
+                        sensor: sensor.label.clone(),
+                    });
+                }
+            }
+        }
+        match res {
+            Some(res) => Ok(res),
+            None if found => Err(Error(String::from(
+                "None of the matching sensors could be read.",
+            ))),
+            None => Err(Error(String::from("No matching sensor found."))),
+        }
+    }
+}
+
+impl TryFrom<&config::Check> for Temperature {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::Temperature(temperature) = &check.type_ {
+            if temperature.sensors.is_empty() {
+                Err(Error(String::from("'sensors' cannot be empty.")))
+            } else if temperature.sensors.iter().any(|x| x.is_empty()) {
+                Err(Error(String::from(
+                    "'sensors' cannot contain empty labels.",
+                )))
+            } else {
+                Ok(Self {
+                    id: temperature.sensors.clone(),
+                    hwmon_path: check.sys_path.join("class/hwmon"),
+                    sys_path: check.sys_path.clone(),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for Temperature {
+    type Item = SensorTemperature;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let sensors = Self::sensors(&self.hwmon_path, &self.sys_path, "temp").await?;
+        let mut res = Vec::new();
+        for pattern in self.id.iter() {
+            res.push(Self::temperature(pattern, &sensors).await);
+        }
+        Ok(res)
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("{:.1} °C", data.temperature)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.level())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        let hwmon_path = root.join("class/hwmon");
+        for (device, name, files) in [
+            (
+                "hwmon0",
+                "coretemp",
+                vec![
+                    ("temp1_label", "Package id 0"),
+                    ("temp1_input", "62000"),
+                    ("temp2_label", "Core 0"),
+                    ("temp2_input", "58500"),
+                ],
+            ),
+            (
+                "hwmon1",
+                "nvme",
+                vec![("temp1_label", "Composite"), ("temp1_input", "41850")],
+            ),
+            // reading the input fails
+            ("hwmon2", "drivetemp", vec![("temp1_input", "")]),
+        ] {
+            std::fs::create_dir_all(hwmon_path.join(device)).unwrap();
+            std::fs::write(hwmon_path.join(device).join("name"), format!("{}\n", name)).unwrap();
+            for (file, content) in files {
+                std::fs::write(hwmon_path.join(device).join(file), content).unwrap();
+            }
+        }
+        let temperature = Temperature {
+            id: vec![
+                String::from("coretemp"),
+                String::from("nvme"),
+                String::from("drivetemp"),
+                String::from("acpitz"),
+            ],
+            hwmon_path,
+            sys_path: root.clone(),
+        };
+        let data = temperature.get_data().await.unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        let coretemp = data[0].as_ref().unwrap();
+        assert_eq!(Temperature::format_data(coretemp), "62.0 °C");
+        let mut placeholders = PlaceholderMap::new();
+        coretemp.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("sensor").unwrap(), "coretemp Package id 0");
+        assert_eq!(data[1].as_ref().unwrap().temperature, 41.85);
+        assert_eq!(
+            data[2].as_ref().unwrap_err().to_string(),
+            "None of the matching sensors could be read."
+        );
+        assert_eq!(
+            data[3].as_ref().unwrap_err().to_string(),
+            "No matching sensor found."
+        );
+    }
+}
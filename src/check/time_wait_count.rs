@@ -0,0 +1,128 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+pub struct TimeWaitCount {
+    id: Vec<String>,
+    sockstat_path: std::path::PathBuf,
+    proc_path: std::path::PathBuf,
+}
+
+impl TimeWaitCount {
+    // e.g. "TCP: inuse 5 orphan 0 tw 2 alloc 7 mem 1"
+    // The TIME_WAIT count covers IPv4 and IPv6 sockets.
+    fn parse_sockstat(content: &str) -> Result<u64> {
+        let line = content
+            .lines()
+            .find(|x| x.starts_with("TCP:"))
+            .ok_or_else(|| Error(String::from("TCP line not found.")))?;
+        let mut columns = line.split_whitespace().skip(1);
+        while let Some(key) = columns.next() {
+            let value = columns.next();
+            if key == "tw" {
+                let value =
+                    value.ok_or_else(|| Error(String::from("TIME_WAIT count not found.")))?;
+                return value
+                    .parse()
+                    .map_err(|x| Error(format!("Invalid TIME_WAIT count '{}': {}", value, x)));
+            }
+        }
+        Err(Error(String::from("TIME_WAIT count not found.")))
+    }
+}
+
+impl TryFrom<&config::Check> for TimeWaitCount {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::TimeWaitCount(_) = &check.type_ {
+            Ok(Self {
+                id: vec![String::from("TimeWait")],
+                sockstat_path: check.proc_path.join("net/sockstat"),
+                proc_path: check.proc_path.clone(),
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for TimeWaitCount {
+    type Item = u64;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let content = tokio::fs::read_to_string(&self.sockstat_path)
+            .await
+            .map_err(|x| {
+                super::proc_error(
+                    &self.proc_path,
+                    format!(
+                        "Could not read from {}: {}",
+                        self.sockstat_path.display(),
+                        x
+                    ),
+                )
+            })?;
+        Ok(vec![Self::parse_sockstat(&content).map_err(|x| {
+            Error(format!(
+                "Could not parse {}: {}",
+                self.sockstat_path.display(),
+                x
+            ))
+        })])
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("TIME_WAIT count {}", data)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SOCKSTAT: &str = "sockets: used 1234
+TCP: inuse 52 orphan 1 tw 4711 alloc 80 mem 23
+UDP: inuse 8 mem 4
+UDPLITE: inuse 0
+RAW: inuse 0
+FRAG: inuse 0 memory 0
+";
+
+    #[test]
+    fn test_parse_sockstat() {
+        assert_eq!(TimeWaitCount::parse_sockstat(SOCKSTAT).unwrap(), 4711);
+        assert_eq!(
+            TimeWaitCount::parse_sockstat("TCP: inuse 0 orphan 0 tw 0 alloc 0 mem 0").unwrap(),
+            0
+        );
+        assert!(TimeWaitCount::parse_sockstat("UDP: inuse 8 mem 4").is_err());
+        assert!(TimeWaitCount::parse_sockstat("TCP: inuse 52 orphan 1").is_err());
+        assert!(TimeWaitCount::parse_sockstat("TCP: inuse 52 orphan 1 tw").is_err());
+        assert!(TimeWaitCount::parse_sockstat("TCP: inuse 52 orphan 1 tw x").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let path = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, SOCKSTAT).unwrap();
+        let time_wait_count = TimeWaitCount {
+            id: vec![String::from("TimeWait")],
+            sockstat_path: path.clone(),
+            proc_path: std::env::temp_dir(),
+        };
+        let data = time_wait_count.get_data().await;
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(*data.unwrap()[0].as_ref().unwrap(), 4711);
+    }
+}
@@ -0,0 +1,351 @@
+use super::local_cert_expiry::LocalCertExpiry;
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TlsExpiry {
+    days: i64,
+    expiry: chrono::DateTime<chrono::Utc>,
+    subject: String,
+    issuer: String,
+}
+
+impl std::fmt::Display for TlsExpiry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.days)
+    }
+}
+
+impl LevelItem for TlsExpiry {
+    fn level(&self) -> f64 {
+        self.days as f64
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(
+            String::from("expiry"),
+            crate::iso8601(std::time::SystemTime::from(self.expiry)),
+        );
+        placeholders.insert(String::from("subject"), self.subject.clone());
+        placeholders.insert(String::from("issuer"), self.issuer.clone());
+    }
+}
+
+#[derive(Clone)]
+struct Target {
+    address: String,
+    server_name: String,
+    tls_insecure: bool,
+}
+
+pub struct TlsCertExpiry {
+    id: Vec<String>,
+    targets: Vec<Target>,
+    timeout: std::time::Duration,
+    resolver: Option<std::sync::Arc<crate::resolver::Resolver>>,
+    concurrency: usize,
+}
+
+impl TlsCertExpiry {
+    fn connect(
+        addresses: &[std::net::SocketAddr],
+        timeout: std::time::Duration,
+    ) -> Result<std::net::TcpStream> {
+        let mut last_error = None;
+        for address in addresses {
+            match std::net::TcpStream::connect_timeout(address, timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(err) => last_error = Some(err),
+            }
+        }
+        Err(match last_error {
+            Some(err) => Error(format!("Could not connect: {}", err)),
+            None => Error(String::from("Address did not resolve.")),
+        })
+    }
+
+    // The handshake is blocking, so it is run on a separate thread.
+    fn handshake(
+        target: &Target,
+        addresses: &[std::net::SocketAddr],
+        timeout: std::time::Duration,
+    ) -> Result<openssl::x509::X509> {
+        let stream = Self::connect(addresses, timeout)?;
+        stream
+            .set_read_timeout(Some(timeout))
+            .and_then(|_| stream.set_write_timeout(Some(timeout)))
+            .map_err(|x| Error(format!("Could not set timeout: {}", x)))?;
+        let mut builder = openssl::ssl::SslConnector::builder(openssl::ssl::SslMethod::tls())
+            .map_err(|x| Error(format!("Could not create TLS connector: {}", x)))?;
+        if target.tls_insecure {
+            builder.set_verify(openssl::ssl::SslVerifyMode::NONE);
+        }
+        let mut configuration = builder
+            .build()
+            .configure()
+            .map_err(|x| Error(format!("Could not create TLS connector: {}", x)))?;
+        configuration.set_verify_hostname(!target.tls_insecure);
+        // The server name is sent via SNI and used to verify the certificate.
+        let stream = configuration
+            .connect(&target.server_name, stream)
+            .map_err(|x| Error(format!("TLS handshake failed: {}", x)))?;
+        stream
+            .ssl()
+            .peer_certificate()
+            .ok_or_else(|| Error(String::from("Server did not send a certificate.")))
+    }
+
+    async fn get_expiry(&self, target: &Target) -> Result<TlsExpiry> {
+        let target_clone = target.clone();
+        let timeout = self.timeout;
+        let addresses = tokio::time::timeout(
+            timeout,
+            crate::resolver::lookup_host(self.resolver.as_deref(), &target.address),
+        )
+        .await
+        .map_err(|_| Error(String::from("Name resolution timed out.")))??;
+        let certificate = tokio::task::spawn_blocking(move || {
+            Self::handshake(&target_clone, &addresses, timeout)
+        })
+        .await
+        .map_err(|x| Error(format!("Could not run TLS handshake: {}", x)))??;
+        let certificate = LocalCertExpiry::read_certificate_info(&certificate)?;
+        Ok(TlsExpiry {
+            days: certificate
+                .expiry
+                .signed_duration_since(chrono::Utc::now())
+                .num_days(),
+            expiry: certificate.expiry,
+            subject: certificate.subject,
+            issuer: certificate.issuer,
+        })
+    }
+}
+
+impl TryFrom<&config::Check> for TlsCertExpiry {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::TlsCertExpiry(tls_cert_expiry) = &check.type_ {
+            let mut targets = Vec::new();
+            for target in tls_cert_expiry.targets.iter() {
+                let host = match target.address.rsplit_once(':') {
+                    Some((host, _)) if !host.is_empty() => host,
+                    _ => {
+                        return Err(Error(format!(
+                            "Address '{}' must be given as '<host>:<port>'.",
+                            target.address
+                        )))
+                    }
+                };
+                let server_name = match &target.server_name {
+                    Some(server_name) if server_name.is_empty() => {
+                        return Err(Error(String::from("'server_name' cannot be empty.")))
+                    }
+                    Some(server_name) => server_name.clone(),
+                    None => String::from(host.trim_start_matches('[').trim_end_matches(']')),
+                };
+                targets.push(Target {
+                    address: target.address.clone(),
+                    server_name,
+                    tls_insecure: target.tls_insecure,
+                });
+            }
+            if tls_cert_expiry.timeout == 0 {
+                Err(Error(String::from("'timeout' cannot be 0.")))
+            } else {
+                Ok(Self {
+                    id: tls_cert_expiry
+                        .targets
+                        .iter()
+                        .map(|x| x.address.clone())
+                        .collect(),
+                    targets,
+                    timeout: std::time::Duration::from_secs(tls_cert_expiry.timeout.into()),
+                    resolver: crate::resolver::from_config(check.resolver.as_ref())?,
+                    concurrency: super::concurrency(tls_cert_expiry.concurrency)?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for TlsCertExpiry {
+    type Item = TlsExpiry;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        Ok(
+            super::fetch_concurrently(self.targets.iter(), self.concurrency, |target| {
+                self.get_expiry(target)
+            })
+            .await,
+        )
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("{} days remaining", data.days)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.days as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // self-signed for "localhost", notAfter 2099-01-01T00:00:00Z
+    fn generate_certificate() -> (
+        openssl::x509::X509,
+        openssl::pkey::PKey<openssl::pkey::Private>,
+    ) {
+        let group =
+            openssl::ec::EcGroup::from_curve_name(openssl::nid::Nid::X9_62_PRIME256V1).unwrap();
+        let key = openssl::pkey::PKey::from_ec_key(openssl::ec::EcKey::generate(&group).unwrap())
+            .unwrap();
+        let mut name = openssl::x509::X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", "localhost").unwrap();
+        let name = name.build();
+        let mut builder = openssl::x509::X509Builder::new().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder
+            .set_not_before(&openssl::asn1::Asn1Time::from_unix(1577836800).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&openssl::asn1::Asn1Time::from_unix(4070908800).unwrap())
+            .unwrap();
+        builder
+            .sign(&key, openssl::hash::MessageDigest::sha256())
+            .unwrap();
+        (builder.build(), key)
+    }
+
+    // Accepts TLS connections and records the server names sent by the clients.
+    fn server(server_names: std::sync::Arc<std::sync::Mutex<Vec<String>>>) -> String {
+        let (certificate, key) = generate_certificate();
+        let mut acceptor =
+            openssl::ssl::SslAcceptor::mozilla_intermediate_v5(openssl::ssl::SslMethod::tls())
+                .unwrap();
+        acceptor.set_certificate(&certificate).unwrap();
+        acceptor.set_private_key(&key).unwrap();
+        acceptor.set_servername_callback(move |ssl, _| {
+            if let Some(server_name) = ssl.servername(openssl::ssl::NameType::HOST_NAME) {
+                server_names.lock().unwrap().push(String::from(server_name));
+            }
+            Ok(())
+        });
+        let acceptor = acceptor.build();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // the handshake of a rejecting client fails
+                let _ = acceptor.accept(stream);
+            }
+        });
+        address
+    }
+
+    fn target(address: &str, server_name: Option<&str>, tls_insecure: bool) -> Target {
+        let check = config::Config::try_from(
+            format!(
+                r#"
+                [[checks]]
+                name = "TLS"
+                type = "TlsCertExpiry"
+                timeout = 5
+                [[checks.targets]]
+                address = "{}"
+                {}
+                tls_insecure = {}
+                "#,
+                address,
+                server_name
+                    .map(|x| format!("server_name = \"{}\"", x))
+                    .unwrap_or_default(),
+                tls_insecure
+            )
+            .as_str(),
+        )
+        .unwrap();
+        TlsCertExpiry::try_from(&check.checks[0])
+            .unwrap()
+            .targets
+            .remove(0)
+    }
+
+    #[tokio::test]
+    async fn test_get_expiry() {
+        let server_names = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let address = server(server_names.clone());
+        let check = TlsCertExpiry {
+            id: Vec::new(),
+            targets: vec![
+                target(&address, Some("localhost"), true),
+                // self-signed certificates are rejected by default
+                target(&address, Some("localhost"), false),
+                target("127.0.0.1:1", None, true),
+            ],
+            timeout: std::time::Duration::from_secs(5),
+            resolver: None,
+            concurrency: 3,
+        };
+        let data = check.get_data().await.unwrap();
+        let tls_expiry = data[0].as_ref().unwrap();
+        assert!(tls_expiry.days > 25000);
+        assert_eq!(tls_expiry.subject, "CN=localhost");
+        assert!(data[1]
+            .as_ref()
+            .unwrap_err()
+            .to_string()
+            .starts_with("TLS handshake failed:"));
+        assert!(data[2]
+            .as_ref()
+            .unwrap_err()
+            .to_string()
+            .starts_with("Could not connect:"));
+        assert_eq!(server_names.lock().unwrap()[0], "localhost");
+    }
+
+    #[test]
+    fn test_server_name() {
+        assert_eq!(
+            target("example.com:443", None, false).server_name,
+            "example.com"
+        );
+        assert_eq!(target("[::1]:443", None, false).server_name, "::1");
+        assert_eq!(
+            target("10.0.0.1:443", Some("example.com"), false).server_name,
+            "example.com"
+        );
+    }
+
+    #[test]
+    fn test_invalid_address() {
+        let check = config::Config::try_from(
+            r#"
+            [[checks]]
+            name = "TLS"
+            type = "TlsCertExpiry"
+            [[checks.targets]]
+            address = "example.com"
+            "#,
+        )
+        .unwrap();
+        assert!(TlsCertExpiry::try_from(&check.checks[0]).is_err());
+    }
+}
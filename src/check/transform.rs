@@ -0,0 +1,216 @@
+use super::expression::Expression;
+use super::DataSource;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+// Applies the `transform` expression to the numeric value of another data source.
+pub struct Transform<T>
+where
+    T: DataSource,
+{
+    data_source: T,
+    expression: Expression,
+}
+
+impl<T> Transform<T>
+where
+    T: DataSource,
+{
+    pub fn new(data_source: T, transform: &str) -> Result<Self> {
+        if transform.is_empty() {
+            return Err(Error(String::from("'transform' cannot be empty.")));
+        }
+        let expression = Expression::parse(transform)
+            .map_err(|x| Error(format!("Invalid 'transform': {}", x)))?;
+        let mut variables = Vec::new();
+        expression.variables(&mut variables);
+        if let Some(variable) = variables.iter().find(|x| **x != "value") {
+            return Err(Error(format!(
+                "Invalid 'transform': Unknown variable '{}', only 'value' is allowed.",
+                variable
+            )));
+        }
+        Ok(Self {
+            data_source,
+            expression,
+        })
+    }
+
+    fn transform(&self, data: &T::Item) -> Result<f64> {
+        let value = T::value(data).ok_or_else(|| {
+            Error(String::from(
+                "Data is not numeric and cannot be transformed.",
+            ))
+        })?;
+        self.expression
+            .evaluate(&std::collections::HashMap::from([("value", value)]))
+            .map_err(|x| Error(format!("Could not transform value {}: {}", value, x)))
+    }
+}
+
+#[async_trait]
+impl<T> DataSource for Transform<T>
+where
+    T: DataSource,
+{
+    type Item = f64;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        Ok(self
+            .data_source
+            .get_data()
+            .await?
+            .into_iter()
+            .map(|x| x.and_then(|x| self.transform(&x)))
+            .collect())
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("transformed value {}", data)
+    }
+
+    fn ids(&self) -> &[String] {
+        self.data_source.ids()
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{factory_with_data_source, from_check_config, ValueStore};
+    use super::*;
+    use crate::{action, alarm, config, ActionMap, PlaceholderMap};
+    use mockall::predicate::*;
+
+    struct Constant {
+        id: Vec<String>,
+        data: Vec<u64>,
+    }
+
+    #[async_trait]
+    impl DataSource for Constant {
+        type Item = u64;
+
+        async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+            Ok(self.data.iter().map(|x| Ok(*x)).collect())
+        }
+
+        fn format_data(data: &Self::Item) -> String {
+            data.to_string()
+        }
+
+        fn ids(&self) -> &[String] {
+            &self.id[..]
+        }
+
+        fn value(data: &Self::Item) -> Option<f64> {
+            Some(*data as f64)
+        }
+    }
+
+    async fn transform(data: u64, transform: &str) -> Result<f64> {
+        let transform = Transform::new(
+            Constant {
+                id: vec![String::from("A")],
+                data: vec![data],
+            },
+            transform,
+        )?;
+        transform.get_data().await?.remove(0)
+    }
+
+    #[tokio::test]
+    async fn test_transform() {
+        assert_eq!(transform(42, "value").await.unwrap(), 42.0);
+        assert_eq!(transform(3, "value * 100").await.unwrap(), 300.0);
+        assert_eq!(transform(2097152, "value / 1048576").await.unwrap(), 2.0);
+        assert_eq!(transform(75, "100 - value").await.unwrap(), 25.0);
+        assert_eq!(transform(4, "(value + 1) * -2").await.unwrap(), -10.0);
+        assert!(transform(0, "1 / value").await.is_err());
+        assert!(transform(1, "value * x").await.is_err());
+        assert!(transform(1, "value ^ 2").await.is_err());
+        assert!(transform(1, "").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_transformed_value_reaches_alarm() {
+        let config = config::Config::try_from(
+            r#"
+            [[checks]]
+            name = "test-check"
+            type = "MemoryUsage"
+            transform = "100 - value"
+
+            [[checks.alarms]]
+            name = "test-alarm"
+            action = "test-action"
+            level = 50
+        "#,
+        )
+        .unwrap();
+        let mut mock_action = action::MockAction::new();
+        mock_action
+            .expect_trigger()
+            .once()
+            .with(function(|placeholders: &PlaceholderMap| {
+                placeholders.get("level").unwrap() == "70"
+            }))
+            .returning(|_| Ok(()));
+        let mut actions = ActionMap::new();
+        actions.insert(
+            String::from("test-action"),
+            std::sync::Arc::new(mock_action),
+        );
+        let mut check = factory_with_data_source::<Constant, alarm::Level<u64>>(
+            Constant {
+                id: vec![String::from("A"), String::from("B")],
+                data: vec![30, 90],
+            },
+            &config.checks[0],
+            0,
+            &actions,
+            &ValueStore::default(),
+            &Default::default(),
+            &Default::default(),
+        )
+        .unwrap();
+        check.trigger().await;
+    }
+
+    #[test]
+    fn test_transform_status_check() {
+        let config = config::Config::try_from(
+            r#"
+            [[checks]]
+            name = "test-check"
+            type = "PidFile"
+            paths = ["/run/foo.pid"]
+            transform = "value * 2"
+
+            [[checks.alarms]]
+            name = "test-alarm"
+            action = "test-action"
+        "#,
+        )
+        .unwrap();
+        let mut actions = ActionMap::new();
+        actions.insert(
+            String::from("test-action"),
+            std::sync::Arc::new(action::MockAction::new()),
+        );
+        match from_check_config(
+            &config.checks[0],
+            0,
+            &actions,
+            &ValueStore::default(),
+            &Default::default(),
+            &Default::default(),
+        ) {
+            Err(err) => assert!(err.0.contains("level alarms")),
+            Ok(_) => panic!(),
+        }
+    }
+}
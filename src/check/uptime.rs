@@ -0,0 +1,84 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+pub struct Uptime {
+    id: Vec<String>,
+    uptime_path: std::path::PathBuf,
+    proc_path: std::path::PathBuf,
+}
+
+impl TryFrom<&config::Check> for Uptime {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::Uptime(_) = &check.type_ {
+            Ok(Self {
+                id: vec![String::from("uptime")],
+                uptime_path: check.proc_path.join("uptime"),
+                proc_path: check.proc_path.clone(),
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for Uptime {
+    type Item = u64;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let content = tokio::fs::read_to_string(&self.uptime_path)
+            .await
+            .map_err(|x| {
+                super::proc_error(
+                    &self.proc_path,
+                    format!("Could not read from {}: {}", self.uptime_path.display(), x),
+                )
+            })?;
+        let uptime: Result<f64> = crate::get_number(
+            &format!("Could not read uptime from {}", self.uptime_path.display()),
+            &content,
+            0,
+        );
+        Ok(vec![uptime.map(|x| x as u64)])
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("uptime {}d {}h", data / 86400, data % 86400 / 3600)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(*data as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("uptime"), "277384.52 2154963.28\n").unwrap();
+        let uptime = Uptime {
+            id: vec![String::from("uptime")],
+            uptime_path: root.join("uptime"),
+            proc_path: root.clone(),
+        };
+        let data = uptime.get_data().await.unwrap();
+        std::fs::write(root.join("uptime"), "\n").unwrap();
+        let invalid = uptime.get_data().await.unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(*data[0].as_ref().unwrap(), 277384);
+        assert_eq!(Uptime::format_data(&277384), "uptime 3d 5h");
+        assert!(invalid[0].is_err());
+    }
+}
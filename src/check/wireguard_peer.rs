@@ -0,0 +1,215 @@
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct PeerHandshake {
+    // seconds since the latest handshake, None if there was none yet
+    age: Option<u64>,
+    endpoint: String,
+    // in bytes
+    transfer_rx: u64,
+    transfer_tx: u64,
+}
+
+impl std::fmt::Display for PeerHandshake {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.age {
+            Some(age) => write!(f, "{}", age),
+            None => write!(f, "never"),
+        }
+    }
+}
+
+impl LevelItem for PeerHandshake {
+    fn level(&self) -> f64 {
+        self.age.map(|x| x as f64).unwrap_or(f64::INFINITY)
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("endpoint"), self.endpoint.clone());
+        placeholders.insert(String::from("transfer_rx"), self.transfer_rx.to_string());
+        placeholders.insert(String::from("transfer_tx"), self.transfer_tx.to_string());
+    }
+}
+
+pub struct WireguardPeer {
+    id: Vec<String>,
+    interface: String,
+    public_keys: Vec<String>,
+    capture: crate::capture::Capture,
+}
+
+impl WireguardPeer {
+    // Output of `wg show <interface> dump`: The first line describes the interface, the others
+    // the peers (tab separated): public key, preshared key, endpoint, allowed IPs, latest
+    // handshake (UNIX timestamp, 0 if none), bytes received, bytes sent, persistent keepalive.
+    fn parse_dump(output: &str, public_key: &str, now: u64) -> Result<PeerHandshake> {
+        let columns: Vec<&str> = output
+            .lines()
+            .skip(1)
+            .map(|x| x.split('\t').collect::<Vec<&str>>())
+            .find(|x| x.first() == Some(&public_key))
+            .ok_or_else(|| Error(String::from("Peer not found.")))?;
+        if columns.len() < 8 {
+            return Err(Error(format!(
+                "Could not parse peer: Expected 8 columns, got {}.",
+                columns.len()
+            )));
+        }
+        let number = |index: usize| -> Result<u64> {
+            columns[index]
+                .parse()
+                .map_err(|x| Error(format!("Could not parse peer: {}", x)))
+        };
+        let latest_handshake = number(4)?;
+        Ok(PeerHandshake {
+            age: (latest_handshake != 0).then(|| now.saturating_sub(latest_handshake)),
+            endpoint: match columns[2] {
+                "(none)" => String::new(),
+                endpoint => String::from(endpoint),
+            },
+            transfer_rx: number(5)?,
+            transfer_tx: number(6)?,
+        })
+    }
+
+    async fn dump(&self) -> Result<String> {
+        let output = self
+            .capture
+            .output(tokio::process::Command::new("wg").args(["show", &self.interface, "dump"]))
+            .await
+            .map_err(|x| Error(format!("Failed to run wg: {}", x)))?;
+        if !output.status.success() {
+            return Err(Error(format!(
+                "wg failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        String::from_utf8(output.stdout)
+            .map_err(|x| Error(format!("Output of wg is not valid UTF-8: {}", x)))
+    }
+}
+
+impl TryFrom<&config::Check> for WireguardPeer {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::WireguardPeer(wireguard_peer) = &check.type_ {
+            if wireguard_peer.interface.is_empty() {
+                return Err(Error(String::from("'interface' cannot be empty.")));
+            }
+            for peer in wireguard_peer.peers.iter() {
+                if peer.name.is_empty() {
+                    return Err(Error(String::from("'name' cannot be empty.")));
+                } else if peer.public_key.is_empty() {
+                    return Err(Error(format!(
+                        "'public_key' cannot be empty for peer '{}'.",
+                        peer.name
+                    )));
+                }
+            }
+            Ok(Self {
+                id: wireguard_peer
+                    .peers
+                    .iter()
+                    .map(|x| x.name.clone())
+                    .collect(),
+                interface: wireguard_peer.interface.clone(),
+                public_keys: wireguard_peer
+                    .peers
+                    .iter()
+                    .map(|x| x.public_key.clone())
+                    .collect(),
+                capture: crate::capture::from_config(check.capture.as_ref())?,
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for WireguardPeer {
+    type Item = PeerHandshake;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let output = self.dump().await?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|x| Error(format!("Invalid system time: {}", x)))?
+            .as_secs();
+        Ok(self
+            .public_keys
+            .iter()
+            .map(|x| Self::parse_dump(&output, x, now))
+            .collect())
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        match data.age {
+            Some(age) => format!("latest handshake {} seconds ago", age),
+            None => String::from("no handshake yet"),
+        }
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        data.age.map(|x| x as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DUMP: &str = "yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=\tHIgo9xNzJMWLKASShiTqIybxZ0U3wGLiUeJ1PKf8ykw=\t51820\toff
+xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=\t(none)\t192.95.5.67:1234\t10.192.122.3/32\t1700000000\t5930248\t1020136\toff
+TrMvSoP4jYQlY6RIzBgbssQqY3vxI2Pi+y71lOWWXX0=\t(none)\t192.95.5.70:51820\t10.192.122.4/32\t1699990000\t12345\t67890\t25
+gN65BkIKy1eCE9pP1wdc8ROUtkHLF2PfAqYdyYBz6EA=\t(none)\t(none)\t10.10.10.230/32\t0\t0\t0\toff
+";
+
+    #[test]
+    fn test_parse_dump() {
+        let now = 1700000060;
+        let active =
+            WireguardPeer::parse_dump(DUMP, "xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=", now)
+                .unwrap();
+        assert_eq!(active.age, Some(60));
+        let mut placeholders = PlaceholderMap::new();
+        active.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("endpoint").unwrap(), "192.95.5.67:1234");
+        assert_eq!(placeholders.get("transfer_rx").unwrap(), "5930248");
+        assert_eq!(placeholders.get("transfer_tx").unwrap(), "1020136");
+        let stale =
+            WireguardPeer::parse_dump(DUMP, "TrMvSoP4jYQlY6RIzBgbssQqY3vxI2Pi+y71lOWWXX0=", now)
+                .unwrap();
+        assert_eq!(stale.age, Some(10060));
+        let never =
+            WireguardPeer::parse_dump(DUMP, "gN65BkIKy1eCE9pP1wdc8ROUtkHLF2PfAqYdyYBz6EA=", now)
+                .unwrap();
+        assert_eq!(never.age, None);
+        assert_eq!(never.level(), f64::INFINITY);
+        assert_eq!(never.endpoint, "");
+        // the interface line is not a peer
+        assert!(WireguardPeer::parse_dump(
+            DUMP,
+            "yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=",
+            now
+        )
+        .is_err());
+    }
+}
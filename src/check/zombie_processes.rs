@@ -0,0 +1,209 @@
+use super::DataSource;
+use crate::alarm::LevelItem;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ZombieCount {
+    count: u64,
+    parent_pids: Vec<u32>,
+}
+
+impl std::fmt::Display for ZombieCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.count)
+    }
+}
+
+impl LevelItem for ZombieCount {
+    fn level(&self) -> f64 {
+        self.count as f64
+    }
+
+    fn validate_level(level: f64) -> Result<()> {
+        if level < 0.0 {
+            Err(Error(String::from("'level' cannot be negative.")))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(
+            String::from("parent_pids"),
+            self.parent_pids
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+        );
+    }
+}
+
+pub struct ZombieProcesses {
+    id: Vec<String>,
+    proc_path: std::path::PathBuf,
+}
+
+impl ZombieProcesses {
+    // Returns the parent PID if the process is a zombie.
+    fn parse_stat(content: &str) -> Result<Option<u32>> {
+        // The process name is in parentheses and may contain spaces and parentheses itself.
+        let (_, tail) = content
+            .rsplit_once(')')
+            .ok_or_else(|| Error(String::from("Process name not found.")))?;
+        let mut columns = tail.split_whitespace();
+        let state = columns
+            .next()
+            .ok_or_else(|| Error(String::from("State column not found.")))?;
+        if state != "Z" {
+            return Ok(None);
+        }
+        let parent_pid = columns
+            .next()
+            .ok_or_else(|| Error(String::from("Parent PID column not found.")))?;
+        parent_pid
+            .parse()
+            .map(Some)
+            .map_err(|x| Error(format!("Invalid parent PID '{}': {}", parent_pid, x)))
+    }
+}
+
+impl TryFrom<&config::Check> for ZombieProcesses {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, self::Error> {
+        if let config::CheckType::ZombieProcesses(_) = &check.type_ {
+            Ok(Self {
+                id: vec![String::from("Zombies")],
+                proc_path: check.proc_path.clone(),
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for ZombieProcesses {
+    type Item = ZombieCount;
+
+    async fn get_data(&self) -> Result<Vec<Result<Self::Item>>> {
+        let mut entries = tokio::fs::read_dir(&self.proc_path).await.map_err(|x| {
+            super::proc_error(
+                &self.proc_path,
+                format!(
+                    "Could not read directory {}: {}",
+                    self.proc_path.display(),
+                    x
+                ),
+            )
+        })?;
+        let mut count = 0;
+        let mut parent_pids = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(|x| {
+            Error(format!(
+                "Could not read directory {}: {}",
+                self.proc_path.display(),
+                x
+            ))
+        })? {
+            // only the numeric entries are processes
+            if entry
+                .file_name()
+                .to_str()
+                .and_then(|x| x.parse::<u32>().ok())
+                .is_none()
+            {
+                continue;
+            }
+            let path = entry.path().join("stat");
+            // The process might have exited in the meantime.
+            let content = match tokio::fs::read_to_string(&path).await {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            if let Some(parent_pid) = Self::parse_stat(&content)
+                .map_err(|x| Error(format!("Could not parse {}: {}", path.display(), x)))?
+            {
+                count += 1;
+                if !parent_pids.contains(&parent_pid) {
+                    parent_pids.push(parent_pid);
+                }
+            }
+        }
+        parent_pids.sort_unstable();
+        Ok(vec![Ok(ZombieCount { count, parent_pids })])
+    }
+
+    fn format_data(data: &Self::Item) -> String {
+        format!("zombie count {}", data.count)
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+
+    fn value(data: &Self::Item) -> Option<f64> {
+        Some(data.count as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_stat() {
+        assert_eq!(
+            ZombieProcesses::parse_stat("1234 (foo) S 1 1234 1234 0 -1 4194560").unwrap(),
+            None
+        );
+        assert_eq!(
+            ZombieProcesses::parse_stat("1234 (foo) Z 42 1234 1234 0 -1 4194564").unwrap(),
+            Some(42)
+        );
+        assert_eq!(
+            ZombieProcesses::parse_stat("1234 (a) Z (b) Z 43 1234 1234 0 -1").unwrap(),
+            Some(43)
+        );
+        assert!(ZombieProcesses::parse_stat("1234 foo Z 42").is_err());
+        assert!(ZombieProcesses::parse_stat("1234 (foo) Z").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_data() {
+        let root = std::env::temp_dir().join(format!("minmon-{}", uuid::Uuid::new_v4()));
+        for (pid, stat) in [
+            ("1", "1 (init) S 0 1 1 0 -1 4194560"),
+            ("100", "100 (worker) Z 50 100 100 0 -1 4194564"),
+            ("101", "101 (worker) Z 50 101 101 0 -1 4194564"),
+            ("102", "102 (other worker) Z 60 102 102 0 -1 4194564"),
+            ("103", "103 (bash) R 1 103 103 0 -1 4194304"),
+        ] {
+            std::fs::create_dir_all(root.join(pid)).unwrap();
+            std::fs::write(root.join(pid).join("stat"), stat).unwrap();
+        }
+        std::fs::create_dir_all(root.join("self")).unwrap();
+        std::fs::write(root.join("uptime"), "1 2").unwrap();
+        let zombie_processes = ZombieProcesses {
+            id: vec![String::from("Zombies")],
+            proc_path: root.clone(),
+        };
+        let data = zombie_processes.get_data().await;
+        std::fs::remove_dir_all(&root).unwrap();
+        let data = data.unwrap();
+        let zombie_count = data[0].as_ref().unwrap();
+        assert_eq!(
+            zombie_count,
+            &ZombieCount {
+                count: 3,
+                parent_pids: vec![50, 60]
+            }
+        );
+        let mut placeholders = PlaceholderMap::new();
+        zombie_count.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("parent_pids").unwrap(), "50,60");
+    }
+}
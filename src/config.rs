@@ -7,7 +7,7 @@ trait Validate {
     fn validate(&self) -> bool;
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, PartialEq, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
     #[serde(default)]
@@ -18,9 +18,71 @@ pub struct Config {
     pub actions: Vec<Action>,
     #[serde(default)]
     pub checks: Vec<Check>,
+    #[serde(default)]
+    pub canaries: Vec<Canary>,
+    #[serde(default)]
+    pub snippets: HashMap<String, String>,
+    #[serde(default = "default::proc_path")]
+    pub proc_path: std::path::PathBuf,
+    #[serde(default = "default::sys_path")]
+    pub sys_path: std::path::PathBuf,
+    #[serde(default)]
+    pub duplicate_names: DuplicateNames,
+    #[serde(default)]
+    pub degraded_mode: Option<DegradedMode>,
+    #[serde(default)]
+    pub resolver: Option<Resolver>,
+    #[serde(default)]
+    pub capture: Option<Capture>,
+    #[serde(default)]
+    pub action_concurrency: HashMap<String, u32>,
+}
+
+#[derive(Default, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum DuplicateNames {
+    #[default]
+    Error,
+    Warn,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DegradedMode {
+    #[serde(default = "default::degraded_mode_failing_percent")]
+    pub failing_percent: u8,
+    #[serde(default = "default::degraded_mode_min_checks")]
+    pub min_checks: u32,
+    #[serde(default = "default::degraded_mode_backoff")]
+    pub backoff: u32,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Capture {
+    pub max_size: u64,
+    #[serde(default)]
+    pub policy: CapturePolicy,
+}
+
+#[derive(Default, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum CapturePolicy {
+    #[default]
+    Truncate,
+    Error,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Resolver {
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub doh_url: Option<String>,
+    #[serde(default = "default::resolver_timeout")]
+    pub timeout: u32,
 }
 
-#[derive(Default, Deserialize)]
+#[derive(Default, Deserialize, PartialEq, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct Log {
     #[serde(default)]
@@ -63,7 +125,7 @@ pub enum LogTarget {
     Journal,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, PartialEq, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct Report {
     #[serde(default)]
@@ -98,7 +160,20 @@ pub struct ReportEvent {
     pub placeholders: PlaceholderMap,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Canary {
+    #[serde(default)]
+    pub disable: bool,
+    pub name: String,
+    #[serde(default = "default::canary_interval")]
+    pub interval: u32,
+    pub action: String,
+    #[serde(default)]
+    pub placeholders: PlaceholderMap,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
 pub struct Action {
     #[serde(default)]
     pub disable: bool,
@@ -107,31 +182,332 @@ pub struct Action {
     pub timeout: u32,
     #[serde(default)]
     pub placeholders: PlaceholderMap,
+    #[serde(default)]
+    pub templates: HashMap<AlarmState, PlaceholderMap>,
+    #[serde(default)]
+    pub template_files: HashMap<String, TemplateFile>,
+    #[serde(default)]
+    pub condition: Option<String>,
+    #[serde(default)]
+    pub critical: bool,
     #[serde(flatten)]
     pub type_: ActionType,
+    // copied from the global options
+    #[serde(skip)]
+    pub resolver: Option<Resolver>,
 }
 
-#[derive(Deserialize, PartialEq, Debug)]
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateFile {
+    pub path: std::path::PathBuf,
+    #[serde(default)]
+    pub format: TemplateFormat,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+pub enum TemplateFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl Action {
+    // Returns a copy of the action with the templates for the given alarm state applied.
+    pub fn with_templates(&self, state: AlarmState) -> Result<Action, Error> {
+        let mut res = self.clone();
+        res.templates.clear();
+        for (name, template) in self.templates.get(&state).into_iter().flatten() {
+            *res.type_.template_mut(name).ok_or_else(|| {
+                Error(format!(
+                    "Action type {} has no template '{}'.",
+                    self.type_, name
+                ))
+            })? = template.clone();
+        }
+        Ok(res)
+    }
+}
+
+// Deserialized from a string, because it's also used as map key.
+#[derive(Deserialize, PartialEq, Eq, Hash, Debug, Clone, Copy)]
+#[serde(try_from = "String")]
+pub enum AlarmState {
+    Good,
+    Bad,
+    Error,
+}
+
+impl TryFrom<String> for AlarmState {
+    type Error = String;
+
+    fn try_from(state: String) -> Result<Self, String> {
+        match state.as_str() {
+            "Good" => Ok(AlarmState::Good),
+            "Bad" => Ok(AlarmState::Bad),
+            "Error" => Ok(AlarmState::Error),
+            _ => Err(format!(
+                "unknown alarm state '{}', expected one of Good, Bad, Error",
+                state
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for AlarmState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            AlarmState::Good => write!(f, "Good"),
+            AlarmState::Bad => write!(f, "Bad"),
+            AlarmState::Error => write!(f, "Error"),
+        }
+    }
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
 #[serde(tag = "type")]
 pub enum ActionType {
+    AwsSns(ActionAwsSns),
+    DesktopNotification(ActionDesktopNotification),
+    Discord(ActionDiscord),
     Email(ActionEmail),
+    Healthchecks(ActionHealthchecks),
     Log(ActionLog),
+    Matrix(ActionMatrix),
+    Nagios(ActionNagios),
+    Ntfy(ActionNtfy),
+    PagerDuty(ActionPagerDuty),
     Process(ActionProcess),
+    Pushover(ActionPushover),
+    Slack(ActionSlack),
     Webhook(ActionWebhook),
 }
 
 impl std::fmt::Display for ActionType {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match *self {
+            ActionType::AwsSns(_) => write!(f, "AwsSns"),
+            ActionType::DesktopNotification(_) => write!(f, "DesktopNotification"),
+            ActionType::Discord(_) => write!(f, "Discord"),
             ActionType::Email(_) => write!(f, "Email"),
+            ActionType::Healthchecks(_) => write!(f, "Healthchecks"),
             ActionType::Log(_) => write!(f, "Log"),
+            ActionType::Matrix(_) => write!(f, "Matrix"),
+            ActionType::Nagios(_) => write!(f, "Nagios"),
+            ActionType::Ntfy(_) => write!(f, "Ntfy"),
+            ActionType::PagerDuty(_) => write!(f, "PagerDuty"),
             ActionType::Process(_) => write!(f, "Process"),
+            ActionType::Pushover(_) => write!(f, "Pushover"),
+            ActionType::Slack(_) => write!(f, "Slack"),
             ActionType::Webhook(_) => write!(f, "Webhook"),
         }
     }
 }
 
-#[derive(Deserialize, PartialEq, Debug)]
+impl ActionType {
+    pub const NAMES: &'static [&'static str] = &[
+        "AwsSns",
+        "DesktopNotification",
+        "Discord",
+        "Email",
+        "Healthchecks",
+        "Log",
+        "Matrix",
+        "Nagios",
+        "Ntfy",
+        "PagerDuty",
+        "Process",
+        "Pushover",
+        "Slack",
+        "Webhook",
+    ];
+
+    // All templates of the action by name. Nested templates are named by their path, e.g.
+    // "embeds.0.title" or "message_attributes.<name>".
+    fn named_templates_mut(&mut self) -> Vec<(String, &mut String)> {
+        fn named<'a>(name: &str, template: &'a mut String) -> (String, &'a mut String) {
+            (String::from(name), template)
+        }
+        match self {
+            ActionType::AwsSns(aws_sns) => aws_sns
+                .subject
+                .iter_mut()
+                .map(|x| named("subject", x))
+                .chain([named("message", &mut aws_sns.message)])
+                .chain(
+                    aws_sns
+                        .message_attributes
+                        .iter_mut()
+                        .map(|(name, x)| (format!("message_attributes.{}", name), x)),
+                )
+                .collect(),
+            ActionType::DesktopNotification(desktop_notification) => vec![
+                named("summary", &mut desktop_notification.summary),
+                named("body", &mut desktop_notification.body),
+            ],
+            ActionType::Discord(discord) => [named("content", &mut discord.content)]
+                .into_iter()
+                .chain(discord.embeds.iter_mut().enumerate().flat_map(|(i, x)| {
+                    [
+                        (format!("embeds.{}.title", i), &mut x.title),
+                        (format!("embeds.{}.description", i), &mut x.description),
+                    ]
+                }))
+                .collect(),
+            ActionType::Email(email) => vec![
+                named("subject", &mut email.subject),
+                named("body", &mut email.body),
+            ],
+            ActionType::Healthchecks(healthchecks) => vec![named("body", &mut healthchecks.body)],
+            ActionType::Log(log) => vec![named("template", &mut log.template)],
+            ActionType::Matrix(matrix) => vec![
+                named("body", &mut matrix.body),
+                named("formatted_body", &mut matrix.formatted_body),
+            ],
+            ActionType::Nagios(nagios) => vec![
+                named("host", &mut nagios.host),
+                named("service", &mut nagios.service),
+                named("output", &mut nagios.output),
+            ],
+            ActionType::Ntfy(ntfy) => [
+                named("title", &mut ntfy.title),
+                named("message", &mut ntfy.message),
+                named("priority", &mut ntfy.priority),
+            ]
+            .into_iter()
+            .chain(
+                ntfy.tags
+                    .iter_mut()
+                    .enumerate()
+                    .map(|(i, x)| (format!("tags.{}", i), x)),
+            )
+            .collect(),
+            ActionType::PagerDuty(pager_duty) => pager_duty
+                .source
+                .iter_mut()
+                .map(|x| named("source", x))
+                .chain([
+                    named("summary", &mut pager_duty.summary),
+                    named("dedup_key", &mut pager_duty.dedup_key),
+                ])
+                .collect(),
+            ActionType::Process(process) => process
+                .arguments
+                .iter_mut()
+                .enumerate()
+                .map(|(i, x)| (format!("arguments.{}", i), x))
+                .chain(
+                    process
+                        .environment_variables
+                        .iter_mut()
+                        .map(|(name, x)| (format!("environment_variables.{}", name), x)),
+                )
+                .collect(),
+            ActionType::Pushover(pushover) => vec![
+                named("title", &mut pushover.title),
+                named("message", &mut pushover.message),
+            ],
+            ActionType::Slack(slack) => vec![named("message", &mut slack.message)],
+            ActionType::Webhook(webhook) => vec![
+                named("url", &mut webhook.url),
+                named("body", &mut webhook.body),
+            ],
+        }
+    }
+
+    fn templates_mut(&mut self) -> Vec<&mut String> {
+        self.named_templates_mut()
+            .into_iter()
+            .map(|(_, template)| template)
+            .collect()
+    }
+
+    fn template_mut(&mut self, name: &str) -> Option<&mut String> {
+        self.named_templates_mut()
+            .into_iter()
+            .find(|(template_name, _)| template_name == name)
+            .map(|(_, template)| template)
+    }
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ActionAwsSns {
+    pub topic_arn: String,
+    pub region: String,
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub subject: Option<String>,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub message_attributes: HashMap<String, String>,
+    #[serde(default)]
+    pub access_key_id: Option<String>,
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+    #[serde(default)]
+    pub session_token: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ActionDesktopNotification {
+    pub summary: String,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default)]
+    pub urgency: Option<Urgency>,
+    #[serde(default = "default::action_desktop_notification_app_name")]
+    pub app_name: String,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default = "default::action_desktop_notification_notify_send")]
+    pub notify_send: std::path::PathBuf,
+}
+
+#[derive(Default, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum Urgency {
+    Low,
+    #[default]
+    Normal,
+    Critical,
+}
+
+impl std::fmt::Display for Urgency {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Urgency::Low => write!(f, "low"),
+            Urgency::Normal => write!(f, "normal"),
+            Urgency::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ActionDiscord {
+    pub url: String,
+    pub content: String,
+    #[serde(default)]
+    pub embeds: Vec<DiscordEmbed>,
+    #[serde(default)]
+    pub username: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DiscordEmbed {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub color: Option<u32>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct ActionEmail {
     pub from: String,
@@ -139,6 +515,7 @@ pub struct ActionEmail {
     #[serde(default)]
     pub reply_to: Option<String>,
     pub subject: String,
+    #[serde(default)]
     pub body: String,
     pub smtp_server: String,
     #[serde(default)]
@@ -149,6 +526,25 @@ pub struct ActionEmail {
     pub password: String,
 }
 
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ActionHealthchecks {
+    #[serde(default = "default::action_healthchecks_url")]
+    pub url: String,
+    pub uuid: String,
+    #[serde(default)]
+    pub endpoint: Option<HealthchecksEndpoint>,
+    #[serde(default)]
+    pub body: String,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum HealthchecksEndpoint {
+    Ping,
+    Start,
+    Fail,
+}
+
 #[derive(Deserialize, PartialEq, Debug, Clone, Copy, Default)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum SmtpSecurity {
@@ -158,15 +554,109 @@ pub enum SmtpSecurity {
     Plain,
 }
 
-#[derive(Deserialize, PartialEq, Debug)]
+#[derive(Deserialize, PartialEq, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct ActionLog {
     #[serde(default)]
     pub level: LogLevel,
+    #[serde(default)]
     pub template: String,
 }
 
-#[derive(Deserialize, PartialEq, Debug)]
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ActionMatrix {
+    pub homeserver: String,
+    pub room_id: String,
+    pub access_token: String,
+    pub body: String,
+    #[serde(default)]
+    pub formatted_body: String,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ActionNagios {
+    #[serde(default)]
+    pub command_file: Option<std::path::PathBuf>,
+    #[serde(default)]
+    pub icinga2_url: Option<String>,
+    pub host: String,
+    pub service: String,
+    #[serde(default)]
+    pub output: String,
+    #[serde(default)]
+    pub state_mapping: HashMap<AlarmState, NagiosState>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub tls_insecure: bool,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum NagiosState {
+    OK,
+    WARNING,
+    CRITICAL,
+    UNKNOWN,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ActionNtfy {
+    #[serde(default = "default::action_ntfy_url")]
+    pub url: String,
+    pub topic: String,
+    #[serde(default)]
+    pub title: String,
+    pub message: String,
+    #[serde(default)]
+    pub priority: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ActionPagerDuty {
+    #[serde(default = "default::action_pager_duty_url")]
+    pub url: String,
+    pub routing_key: String,
+    pub summary: String,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default = "default::action_pager_duty_dedup_key")]
+    pub dedup_key: String,
+    #[serde(default)]
+    pub severity: Option<PagerDutySeverity>,
+}
+
+#[derive(Default, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum PagerDutySeverity {
+    Critical,
+    #[default]
+    Error,
+    Warning,
+    Info,
+}
+
+impl std::fmt::Display for PagerDutySeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            PagerDutySeverity::Critical => write!(f, "critical"),
+            PagerDutySeverity::Error => write!(f, "error"),
+            PagerDutySeverity::Warning => write!(f, "warning"),
+            PagerDutySeverity::Info => write!(f, "info"),
+        }
+    }
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct ActionProcess {
     pub path: std::path::PathBuf,
@@ -182,7 +672,36 @@ pub struct ActionProcess {
     pub gid: Option<u32>,
 }
 
-#[derive(Deserialize, PartialEq, Debug)]
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ActionPushover {
+    #[serde(default = "default::action_pushover_url")]
+    pub url: String,
+    pub token: String,
+    pub user: String,
+    #[serde(default)]
+    pub title: String,
+    pub message: String,
+    #[serde(default)]
+    pub priority: Option<i8>,
+    #[serde(default)]
+    pub retry: Option<u32>,
+    #[serde(default)]
+    pub expire: Option<u32>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ActionSlack {
+    pub url: String,
+    pub message: String,
+    #[serde(default)]
+    pub channel: Option<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct ActionWebhook {
     pub url: String,
@@ -192,6 +711,28 @@ pub struct ActionWebhook {
     pub headers: HashMap<String, String>,
     #[serde(default)]
     pub body: String,
+    #[serde(default)]
+    pub escape: WebhookEscape,
+    #[serde(default)]
+    pub oauth2: Option<OAuth2>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct OAuth2 {
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+pub enum WebhookEscape {
+    #[default]
+    Auto,
+    Json,
+    Off,
 }
 
 #[derive(Deserialize, PartialEq, Debug, Clone, Copy, Default)]
@@ -205,110 +746,947 @@ pub enum HttpMethod {
     PATCH,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, PartialEq, Debug)]
 pub struct Check {
     #[serde(default)]
     pub disable: bool,
     #[serde(default = "default::check_interval")]
     pub interval: u32,
+    #[serde(default)]
+    pub skip_overlapping: bool,
     pub name: String,
     #[serde(default)]
+    pub transform: Option<String>,
+    #[serde(default)]
+    pub warmup: u32,
+    #[serde(default)]
+    pub history: u32,
+    #[serde(default)]
+    pub auto_disable_cycles: Option<u32>,
+    #[serde(default)]
+    pub auto_disable_action: Option<String>,
+    #[serde(default)]
+    pub redact: Vec<String>,
+    #[serde(default)]
     pub placeholders: PlaceholderMap,
     #[serde(flatten)]
     pub type_: CheckType,
     #[serde(default)]
     pub alarms: Vec<Alarm>,
+    // copied from the global options
+    #[serde(skip)]
+    pub proc_path: std::path::PathBuf,
+    #[serde(skip)]
+    pub sys_path: std::path::PathBuf,
+    #[serde(skip)]
+    pub resolver: Option<Resolver>,
+    #[serde(skip)]
+    pub capture: Option<Capture>,
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(tag = "type")]
 pub enum CheckType {
+    AcmeRenewal(CheckAcmeRenewal),
+    BootState(CheckBootState),
+    ConnectionCount(CheckConnectionCount),
+    ConsumerLag(CheckConsumerLag),
+    CpuThrottle(CheckCpuThrottle),
+    CpuUsage(CheckCpuUsage),
+    CronJobStatus(CheckCronJobStatus),
+    Derived(CheckDerived),
+    DirEntryCount(CheckDirEntryCount),
+    DiskIo(CheckDiskIo),
+    DiskLatency(CheckDiskLatency),
+    DiskTemperature(CheckDiskTemperature),
+    EphemeralPorts(CheckEphemeralPorts),
+    ErrorRate(CheckErrorRate),
+    ExpiringCredential(CheckExpiringCredential),
+    FanSpeed(CheckFanSpeed),
+    FileAge(CheckFileAge),
+    FileDescriptors(CheckFileDescriptors),
+    FileSize(CheckFileSize),
     FilesystemUsage(CheckFilesystemUsage),
+    HttpStatus(CheckHttpStatus),
+    ImageUpdate(CheckImageUpdate),
+    InodeUsage(CheckInodeUsage),
+    KubePods(CheckKubePods),
+    LoadAverage(CheckLoadAverage),
+    LocalCertExpiry(CheckLocalCertExpiry),
+    LogGrowthRate(CheckLogGrowthRate),
     MemoryUsage(CheckMemoryUsage),
+    MountPresent(CheckMountPresent),
+    NumaMemory(CheckNumaMemory),
+    OldestFileAge(CheckOldestFileAge),
+    PidFile(CheckPidFile),
+    Ping(CheckPing),
+    ProcessMemory(CheckProcessMemory),
+    ProcessPresence(CheckProcessPresence),
+    RaidBbu(CheckRaidBbu),
+    RtcDrift(CheckRtcDrift),
+    #[cfg(feature = "script")]
+    Script(CheckScript),
+    SmartHealth(CheckSmartHealth),
+    SwapUsage(CheckSwapUsage),
+    SyntheticLogin(CheckSyntheticLogin),
+    SystemdFailedUnits(CheckSystemdFailedUnits),
+    SystemdUnit(CheckSystemdUnit),
+    TcpConnect(CheckTcpConnect),
+    Temperature(CheckTemperature),
+    TimeWaitCount(CheckTimeWaitCount),
+    TlsCertExpiry(CheckTlsCertExpiry),
+    Uptime(CheckUptime),
+    WireguardPeer(CheckWireguardPeer),
+    ZombieProcesses(CheckZombieProcesses),
 }
 
 impl std::fmt::Display for CheckType {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match *self {
+            CheckType::AcmeRenewal(_) => write!(f, "AcmeRenewal"),
+            CheckType::BootState(_) => write!(f, "BootState"),
+            CheckType::ConnectionCount(_) => write!(f, "ConnectionCount"),
+            CheckType::ConsumerLag(_) => write!(f, "ConsumerLag"),
+            CheckType::CpuThrottle(_) => write!(f, "CpuThrottle"),
+            CheckType::CpuUsage(_) => write!(f, "CpuUsage"),
+            CheckType::CronJobStatus(_) => write!(f, "CronJobStatus"),
+            CheckType::Derived(_) => write!(f, "Derived"),
+            CheckType::DirEntryCount(_) => write!(f, "DirEntryCount"),
+            CheckType::DiskIo(_) => write!(f, "DiskIo"),
+            CheckType::DiskLatency(_) => write!(f, "DiskLatency"),
+            CheckType::DiskTemperature(_) => write!(f, "DiskTemperature"),
+            CheckType::EphemeralPorts(_) => write!(f, "EphemeralPorts"),
+            CheckType::ErrorRate(_) => write!(f, "ErrorRate"),
+            CheckType::ExpiringCredential(_) => write!(f, "ExpiringCredential"),
+            CheckType::FanSpeed(_) => write!(f, "FanSpeed"),
+            CheckType::FileAge(_) => write!(f, "FileAge"),
+            CheckType::FileDescriptors(_) => write!(f, "FileDescriptors"),
+            CheckType::FileSize(_) => write!(f, "FileSize"),
             CheckType::FilesystemUsage(_) => write!(f, "FilesystemUsage"),
+            CheckType::HttpStatus(_) => write!(f, "HttpStatus"),
+            CheckType::ImageUpdate(_) => write!(f, "ImageUpdate"),
+            CheckType::InodeUsage(_) => write!(f, "InodeUsage"),
+            CheckType::KubePods(_) => write!(f, "KubePods"),
+            CheckType::LoadAverage(_) => write!(f, "LoadAverage"),
+            CheckType::LocalCertExpiry(_) => write!(f, "LocalCertExpiry"),
+            CheckType::LogGrowthRate(_) => write!(f, "LogGrowthRate"),
             CheckType::MemoryUsage(_) => write!(f, "MemoryUsage"),
+            CheckType::MountPresent(_) => write!(f, "MountPresent"),
+            CheckType::NumaMemory(_) => write!(f, "NumaMemory"),
+            CheckType::OldestFileAge(_) => write!(f, "OldestFileAge"),
+            CheckType::PidFile(_) => write!(f, "PidFile"),
+            CheckType::Ping(_) => write!(f, "Ping"),
+            CheckType::ProcessMemory(_) => write!(f, "ProcessMemory"),
+            CheckType::ProcessPresence(_) => write!(f, "ProcessPresence"),
+            CheckType::RaidBbu(_) => write!(f, "RaidBbu"),
+            CheckType::RtcDrift(_) => write!(f, "RtcDrift"),
+            #[cfg(feature = "script")]
+            CheckType::Script(_) => write!(f, "Script"),
+            CheckType::SmartHealth(_) => write!(f, "SmartHealth"),
+            CheckType::SwapUsage(_) => write!(f, "SwapUsage"),
+            CheckType::SyntheticLogin(_) => write!(f, "SyntheticLogin"),
+            CheckType::SystemdFailedUnits(_) => write!(f, "SystemdFailedUnits"),
+            CheckType::SystemdUnit(_) => write!(f, "SystemdUnit"),
+            CheckType::TcpConnect(_) => write!(f, "TcpConnect"),
+            CheckType::Temperature(_) => write!(f, "Temperature"),
+            CheckType::TimeWaitCount(_) => write!(f, "TimeWaitCount"),
+            CheckType::TlsCertExpiry(_) => write!(f, "TlsCertExpiry"),
+            CheckType::Uptime(_) => write!(f, "Uptime"),
+            CheckType::WireguardPeer(_) => write!(f, "WireguardPeer"),
+            CheckType::ZombieProcesses(_) => write!(f, "ZombieProcesses"),
         }
     }
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(deny_unknown_fields)]
-pub struct CheckFilesystemUsage {
-    pub mountpoints: Vec<String>,
+pub struct CheckAcmeRenewal {
+    pub domains: Vec<String>,
+    #[serde(default = "default::check_acme_renewal_renewal_dir")]
+    pub renewal_dir: std::path::PathBuf,
+    #[serde(default = "default::check_acme_renewal_grace_days")]
+    pub grace_days: u32,
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(deny_unknown_fields)]
-pub struct CheckMemoryUsage {
-    #[serde(default = "default::check_memory_usage_memory")]
-    pub memory: bool,
-    #[serde(default = "default::check_memory_usage_swap")]
-    pub swap: bool,
+pub struct CheckBootState {
+    #[serde(default = "default::check_boot_state_startup_timeout")]
+    pub startup_timeout: u32,
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
-pub struct Alarm {
-    #[serde(default)]
-    pub disable: bool,
-    pub name: String,
-    pub action: String,
-    #[serde(default)]
-    pub placeholders: PlaceholderMap,
-    #[serde(default = "default::check_alarm_cycles")]
-    pub cycles: u32,
-    #[serde(default)]
-    pub repeat_cycles: u32,
+#[serde(deny_unknown_fields)]
+pub struct CheckConnectionCount {
+    pub ports: Vec<u16>,
+    #[serde(default = "default::check_connection_count_states")]
+    pub states: Vec<TcpState>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum TcpState {
+    Established,
+    SynSent,
+    SynRecv,
+    FinWait1,
+    FinWait2,
+    TimeWait,
+    Close,
+    CloseWait,
+    LastAck,
+    Listen,
+    Closing,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckConsumerLag {
+    pub broker: ConsumerLagBroker,
+    pub url: String,
+    pub queues: Vec<String>,
+    #[serde(default = "default::check_consumer_lag_vhost")]
+    pub vhost: String,
     #[serde(default)]
-    pub recover_action: Option<String>,
+    pub consumer_group: Option<String>,
     #[serde(default)]
-    pub recover_placeholders: PlaceholderMap,
-    #[serde(default = "default::check_alarm_recover_cycles")]
-    pub recover_cycles: u32,
+    pub cluster_id: Option<String>,
     #[serde(default)]
-    pub error_action: Option<String>,
+    pub username: Option<String>,
     #[serde(default)]
-    pub error_placeholders: PlaceholderMap,
+    pub password: Option<String>,
     #[serde(default)]
-    pub error_repeat_cycles: u32,
+    pub ca_certificate: Option<std::path::PathBuf>,
     #[serde(default)]
-    pub invert: bool,
-    #[serde(flatten)]
-    pub type_: AlarmType,
+    pub tls_insecure: bool,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum ConsumerLagBroker {
+    Kafka,
+    Nats,
+    RabbitMq,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckCpuThrottle {
+    #[serde(default = "default::check_cpu_throttle_total")]
+    pub total: bool,
+    #[serde(default)]
+    pub cores: Vec<u32>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckCpuUsage {
+    #[serde(default = "default::check_cpu_usage_total")]
+    pub total: bool,
+    #[serde(default)]
+    pub cores: Vec<u32>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckCronJobStatus {
+    pub jobs: Vec<CronJob>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CronJob {
+    pub name: String,
+    pub marker_file: std::path::PathBuf,
+    pub max_age: u32,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckDerived {
+    pub expression: String,
+    pub inputs: HashMap<String, DerivedInput>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DerivedInput {
+    pub check: String,
+    pub id: String,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckDirEntryCount {
+    pub paths: Vec<std::path::PathBuf>,
+    #[serde(default)]
+    pub pattern: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckDiskIo {
+    pub devices: Vec<String>,
+    #[serde(default = "default::check_disk_io_metric")]
+    pub metric: DiskIoMetric,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum DiskIoMetric {
+    ReadBytes,
+    WriteBytes,
+    Utilization,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckDiskLatency {
+    pub devices: Vec<String>,
+    #[serde(default = "default::check_disk_latency_metric")]
+    pub metric: DiskLatencyMetric,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum DiskLatencyMetric {
+    Await,
+    QueueDepth,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckDiskTemperature {
+    pub devices: Vec<String>,
+    #[serde(default)]
+    pub tool: DiskTemperatureTool,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+pub enum DiskTemperatureTool {
+    #[default]
+    Auto,
+    Smartctl,
+    Nvme,
+    Hddtemp,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckEphemeralPorts {}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckErrorRate {}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckExpiringCredential {
+    pub paths: Vec<std::path::PathBuf>,
+    pub format: CredentialFormat,
+    #[serde(default = "default::check_expiring_credential_timestamp_format")]
+    pub timestamp_format: String,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum CredentialFormat {
+    Timestamp,
+    Jwt,
+    Gpg,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckFanSpeed {
+    pub fans: Vec<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckFileAge {
+    pub paths: Vec<std::path::PathBuf>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckFileDescriptors {
+    #[serde(default)]
+    pub processes: Vec<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckFileSize {
+    pub paths: Vec<std::path::PathBuf>,
+    #[serde(default = "default::check_file_size_follow_symlinks")]
+    pub follow_symlinks: bool,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckFilesystemUsage {
+    pub mountpoints: Vec<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckHttpStatus {
+    pub targets: Vec<HttpStatusTarget>,
+    #[serde(default)]
+    pub smoothing: Option<f64>,
+    #[serde(default)]
+    pub freshness: Option<HttpFreshness>,
+    #[serde(default = "default::check_concurrency")]
+    pub concurrency: u32,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct HttpFreshness {
+    #[serde(default)]
+    pub json_field: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct HttpStatusTarget {
+    pub url: String,
+    #[serde(default)]
+    pub method: HttpStatusMethod,
+    #[serde(default = "default::check_http_status_timeout")]
+    pub timeout: u32,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub body_contains: Option<String>,
+    #[serde(default)]
+    pub body_regex: Option<String>,
+    #[serde(default)]
+    pub expected_headers: HashMap<String, String>,
+    #[serde(default)]
+    pub max_size: Option<u64>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum HttpStatusMethod {
+    #[default]
+    GET,
+    HEAD,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckImageUpdate {
+    pub containers: Vec<String>,
+    #[serde(default = "default::check_image_update_docker_host")]
+    pub docker_host: String,
+    #[serde(default)]
+    pub insecure_registries: Vec<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckInodeUsage {
+    pub mountpoints: Vec<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckKubePods {
+    pub label_selectors: Vec<String>,
+    #[serde(default = "default::check_kube_pods_namespace")]
+    pub namespace: String,
+    #[serde(default)]
+    pub kubeconfig: Option<std::path::PathBuf>,
+    #[serde(default)]
+    pub context: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckLoadAverage {
+    #[serde(default)]
+    pub normalize: bool,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckLocalCertExpiry {
+    pub paths: Vec<std::path::PathBuf>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckLogGrowthRate {
+    pub paths: Vec<std::path::PathBuf>,
+    #[serde(default = "default::check_log_growth_rate_horizon")]
+    pub horizon: u32,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckMemoryUsage {
+    #[serde(default = "default::check_memory_usage_memory")]
+    pub memory: bool,
+    #[serde(default = "default::check_memory_usage_swap")]
+    pub swap: bool,
+    #[serde(default = "default::check_memory_usage_combined")]
+    pub combined: bool,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckMountPresent {
+    pub mounts: Vec<ExpectedMount>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedMount {
+    pub path: std::path::PathBuf,
+    #[serde(default)]
+    pub fstype: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckNumaMemory {
+    #[serde(default = "default::check_numa_memory_nodes")]
+    pub nodes: Vec<u32>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckOldestFileAge {
+    pub paths: Vec<std::path::PathBuf>,
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub empty_is_error: bool,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckPidFile {
+    pub paths: Vec<std::path::PathBuf>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckPing {
+    pub hosts: Vec<String>,
+    #[serde(default = "default::check_ping_count")]
+    pub count: u32,
+    #[serde(default = "default::check_ping_timeout")]
+    pub timeout: u32,
+    #[serde(default)]
+    pub binary: Option<std::path::PathBuf>,
+    #[serde(default)]
+    pub smoothing: Option<f64>,
+    #[serde(default = "default::check_concurrency")]
+    pub concurrency: u32,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckProcessMemory {
+    pub processes: Vec<ProcessMemoryPattern>,
+    #[serde(default)]
+    pub zero_if_missing: bool,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ProcessMemoryPattern {
+    pub name: String,
+    #[serde(default)]
+    pub comm: Option<String>,
+    #[serde(default)]
+    pub cmdline: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckProcessPresence {
+    pub processes: Vec<ProcessPresencePattern>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ProcessPresencePattern {
+    pub name: String,
+    #[serde(default)]
+    pub comm: Option<String>,
+    #[serde(default)]
+    pub cmdline: Option<String>,
+    #[serde(default = "default::check_process_presence_min")]
+    pub min: u32,
+    #[serde(default)]
+    pub max: Option<u32>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckRaidBbu {
+    #[serde(default)]
+    pub tool: RaidBbuTool,
+    #[serde(default)]
+    pub binary: Option<String>,
+    #[serde(default)]
+    pub controller: u32,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+pub enum RaidBbuTool {
+    #[default]
+    Storcli,
+    MegaCli,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckRtcDrift {
+    #[serde(default = "default::check_rtc_drift_device")]
+    pub device: String,
+}
+
+#[cfg(feature = "script")]
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckScript {
+    pub script: String,
+    #[serde(default = "default::check_script_timeout")]
+    pub timeout: u32,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckSmartHealth {
+    pub devices: Vec<String>,
+    #[serde(default)]
+    pub attribute: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckSwapUsage {
+    #[serde(default)]
+    pub used_mib: bool,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckSyntheticLogin {
+    pub steps: Vec<SyntheticLoginStep>,
+    #[serde(default = "default::check_synthetic_login_timeout")]
+    pub timeout: u32,
+    #[serde(default)]
+    pub tls_insecure: bool,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SyntheticLoginStep {
+    pub url: String,
+    #[serde(default = "default::check_synthetic_login_method")]
+    pub method: HttpMethod,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default)]
+    pub status: Option<u16>,
+    #[serde(default)]
+    pub contains: Option<String>,
+    #[serde(default)]
+    pub extract: HashMap<String, SyntheticLoginExtract>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SyntheticLoginExtract {
+    pub after: String,
+    pub before: String,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckSystemdFailedUnits {}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckSystemdUnit {
+    pub units: Vec<String>,
+    #[serde(default)]
+    pub method: SystemdUnitMethod,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+pub enum SystemdUnitMethod {
+    #[default]
+    Systemctl,
+    Dbus,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckTcpConnect {
+    pub targets: Vec<String>,
+    #[serde(default = "default::check_tcp_connect_timeout")]
+    pub timeout: u32,
+    #[serde(default)]
+    pub smoothing: Option<f64>,
+    #[serde(default = "default::check_concurrency")]
+    pub concurrency: u32,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckTemperature {
+    pub sensors: Vec<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckTimeWaitCount {}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckTlsCertExpiry {
+    pub targets: Vec<TlsCertExpiryTarget>,
+    #[serde(default = "default::check_tls_cert_expiry_timeout")]
+    pub timeout: u32,
+    #[serde(default = "default::check_concurrency")]
+    pub concurrency: u32,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TlsCertExpiryTarget {
+    pub address: String,
+    #[serde(default)]
+    pub server_name: Option<String>,
+    #[serde(default)]
+    pub tls_insecure: bool,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckUptime {}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckWireguardPeer {
+    pub interface: String,
+    pub peers: Vec<WireguardPeer>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct WireguardPeer {
+    pub name: String,
+    pub public_key: String,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckZombieProcesses {}
+
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct Alarm {
+    #[serde(default)]
+    pub disable: bool,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub runbook_url: Option<String>,
+    pub action: String,
+    #[serde(default)]
+    pub placeholders: PlaceholderMap,
+    #[serde(default = "default::check_alarm_cycles")]
+    pub cycles: u32,
+    #[serde(default)]
+    pub repeat_cycles: u32,
+    #[serde(default)]
+    pub recover_action: Option<String>,
+    #[serde(default)]
+    pub recover_placeholders: PlaceholderMap,
+    #[serde(default = "default::check_alarm_recover_cycles")]
+    pub recover_cycles: u32,
+    #[serde(default)]
+    pub recover_confirmation: Option<RecoverConfirmation>,
+    #[serde(default)]
+    pub incident: Option<String>,
+    #[serde(default)]
+    pub dedup: Option<Dedup>,
+    #[serde(default)]
+    pub error_action: Option<String>,
+    #[serde(default)]
+    pub error_placeholders: PlaceholderMap,
+    #[serde(default)]
+    pub error_repeat_cycles: u32,
+    #[serde(default)]
+    pub invert: bool,
+    #[serde(default)]
+    pub stale_cycles: u32,
+    #[serde(default)]
+    pub heartbeat_cycles: u32,
+    #[serde(flatten)]
+    pub type_: AlarmType,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RecoverConfirmation {
+    pub check: String,
+    pub id: String,
+    pub level: f64,
+    #[serde(default)]
+    pub invert: bool,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Dedup {
+    pub buckets: Vec<f64>,
+    pub window: u32,
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(untagged)]
 pub enum AlarmType {
     Level(AlarmLevel),
+    Anomaly(AlarmAnomaly),
+    StatusCode(AlarmStatusCode),
+    Status(AlarmStatus),
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct AlarmLevel {
-    pub level: u8,
+    pub level: f64,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmAnomaly {
+    pub deviations: f64,
+    #[serde(default = "default::check_alarm_anomaly_warmup")]
+    pub warmup: u32,
+    #[serde(default = "default::check_alarm_anomaly_window")]
+    pub window: u32,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmStatusCode {
+    pub status_codes: Vec<u16>,
 }
 
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmStatus {}
+
 mod default {
+    pub const PROC_PATH: &str = "/proc";
+    pub fn proc_path() -> std::path::PathBuf {
+        std::path::PathBuf::from(PROC_PATH)
+    }
+
+    pub const SYS_PATH: &str = "/sys";
+    pub fn sys_path() -> std::path::PathBuf {
+        std::path::PathBuf::from(SYS_PATH)
+    }
+
     pub const REPORT_INTERVAL: u32 = 604800;
     pub fn report_interval() -> u32 {
         REPORT_INTERVAL
     }
 
+    pub const CANARY_INTERVAL: u32 = 86400;
+    pub fn canary_interval() -> u32 {
+        CANARY_INTERVAL
+    }
+
+    pub const DEGRADED_MODE_FAILING_PERCENT: u8 = 50;
+    pub fn degraded_mode_failing_percent() -> u8 {
+        DEGRADED_MODE_FAILING_PERCENT
+    }
+
+    pub const DEGRADED_MODE_MIN_CHECKS: u32 = 3;
+    pub fn degraded_mode_min_checks() -> u32 {
+        DEGRADED_MODE_MIN_CHECKS
+    }
+
+    pub const DEGRADED_MODE_BACKOFF: u32 = 4;
+    pub fn degraded_mode_backoff() -> u32 {
+        DEGRADED_MODE_BACKOFF
+    }
+
+    pub const RESOLVER_TIMEOUT: u32 = 5;
+    pub fn resolver_timeout() -> u32 {
+        RESOLVER_TIMEOUT
+    }
+
     pub const ACTION_TIMEOUT: u32 = 10;
     pub fn action_timeout() -> u32 {
         ACTION_TIMEOUT
     }
 
+    pub const ACTION_DESKTOP_NOTIFICATION_APP_NAME: &str = "MinMon";
+    pub fn action_desktop_notification_app_name() -> String {
+        String::from(ACTION_DESKTOP_NOTIFICATION_APP_NAME)
+    }
+
+    pub const ACTION_DESKTOP_NOTIFICATION_NOTIFY_SEND: &str = "notify-send";
+    pub fn action_desktop_notification_notify_send() -> std::path::PathBuf {
+        std::path::PathBuf::from(ACTION_DESKTOP_NOTIFICATION_NOTIFY_SEND)
+    }
+
+    pub const ACTION_HEALTHCHECKS_URL: &str = "https://hc-ping.com";
+    pub fn action_healthchecks_url() -> String {
+        String::from(ACTION_HEALTHCHECKS_URL)
+    }
+
+    pub const ACTION_NTFY_URL: &str = "https://ntfy.sh";
+    pub fn action_ntfy_url() -> String {
+        String::from(ACTION_NTFY_URL)
+    }
+
+    pub const ACTION_PAGER_DUTY_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+    pub fn action_pager_duty_url() -> String {
+        String::from(ACTION_PAGER_DUTY_URL)
+    }
+
+    pub const ACTION_PAGER_DUTY_DEDUP_KEY: &str = "{{check_name}}/{{alarm_name}}/{{alarm_id}}";
+    pub fn action_pager_duty_dedup_key() -> String {
+        String::from(ACTION_PAGER_DUTY_DEDUP_KEY)
+    }
+
+    pub const ACTION_PUSHOVER_URL: &str = "https://api.pushover.net/1/messages.json";
+    pub fn action_pushover_url() -> String {
+        String::from(ACTION_PUSHOVER_URL)
+    }
+
     pub const CHECK_INTERVAL: u32 = 300;
     pub fn check_interval() -> u32 {
         CHECK_INTERVAL
     }
 
+    pub const CHECK_ALARM_ANOMALY_WARMUP: u32 = 30;
+    pub fn check_alarm_anomaly_warmup() -> u32 {
+        CHECK_ALARM_ANOMALY_WARMUP
+    }
+
+    pub const CHECK_ALARM_ANOMALY_WINDOW: u32 = 60;
+    pub fn check_alarm_anomaly_window() -> u32 {
+        CHECK_ALARM_ANOMALY_WINDOW
+    }
+
     pub const CHECK_ALARM_CYCLES: u32 = 1;
     pub fn check_alarm_cycles() -> u32 {
         CHECK_ALARM_CYCLES
@@ -319,6 +1697,80 @@ mod default {
         CHECK_ALARM_RECOVER_CYCLES
     }
 
+    pub const CHECK_ACME_RENEWAL_RENEWAL_DIR: &str = "/etc/letsencrypt/renewal";
+    pub fn check_acme_renewal_renewal_dir() -> std::path::PathBuf {
+        std::path::PathBuf::from(CHECK_ACME_RENEWAL_RENEWAL_DIR)
+    }
+
+    pub const CHECK_ACME_RENEWAL_GRACE_DAYS: u32 = 2;
+    pub fn check_acme_renewal_grace_days() -> u32 {
+        CHECK_ACME_RENEWAL_GRACE_DAYS
+    }
+
+    pub const CHECK_BOOT_STATE_STARTUP_TIMEOUT: u32 = 600;
+    pub fn check_boot_state_startup_timeout() -> u32 {
+        CHECK_BOOT_STATE_STARTUP_TIMEOUT
+    }
+
+    pub fn check_connection_count_states() -> Vec<super::TcpState> {
+        vec![super::TcpState::Established]
+    }
+
+    pub const CHECK_CONSUMER_LAG_VHOST: &str = "/";
+    pub fn check_consumer_lag_vhost() -> String {
+        String::from(CHECK_CONSUMER_LAG_VHOST)
+    }
+
+    pub const CHECK_CPU_THROTTLE_TOTAL: bool = true;
+    pub fn check_cpu_throttle_total() -> bool {
+        CHECK_CPU_THROTTLE_TOTAL
+    }
+
+    pub const CHECK_CPU_USAGE_TOTAL: bool = true;
+    pub fn check_cpu_usage_total() -> bool {
+        CHECK_CPU_USAGE_TOTAL
+    }
+
+    pub const CHECK_DISK_IO_METRIC: super::DiskIoMetric = super::DiskIoMetric::Utilization;
+    pub fn check_disk_io_metric() -> super::DiskIoMetric {
+        CHECK_DISK_IO_METRIC
+    }
+
+    pub const CHECK_DISK_LATENCY_METRIC: super::DiskLatencyMetric = super::DiskLatencyMetric::Await;
+    pub fn check_disk_latency_metric() -> super::DiskLatencyMetric {
+        CHECK_DISK_LATENCY_METRIC
+    }
+
+    pub const CHECK_EXPIRING_CREDENTIAL_TIMESTAMP_FORMAT: &str = "%+";
+    pub fn check_expiring_credential_timestamp_format() -> String {
+        String::from(CHECK_EXPIRING_CREDENTIAL_TIMESTAMP_FORMAT)
+    }
+
+    pub const CHECK_FILE_SIZE_FOLLOW_SYMLINKS: bool = true;
+    pub fn check_file_size_follow_symlinks() -> bool {
+        CHECK_FILE_SIZE_FOLLOW_SYMLINKS
+    }
+
+    pub const CHECK_HTTP_STATUS_TIMEOUT: u32 = 10;
+    pub fn check_http_status_timeout() -> u32 {
+        CHECK_HTTP_STATUS_TIMEOUT
+    }
+
+    pub const CHECK_IMAGE_UPDATE_DOCKER_HOST: &str = "/var/run/docker.sock";
+    pub fn check_image_update_docker_host() -> String {
+        String::from(CHECK_IMAGE_UPDATE_DOCKER_HOST)
+    }
+
+    pub const CHECK_KUBE_PODS_NAMESPACE: &str = "default";
+    pub fn check_kube_pods_namespace() -> String {
+        String::from(CHECK_KUBE_PODS_NAMESPACE)
+    }
+
+    pub const CHECK_LOG_GROWTH_RATE_HORIZON: u32 = 24;
+    pub fn check_log_growth_rate_horizon() -> u32 {
+        CHECK_LOG_GROWTH_RATE_HORIZON
+    }
+
     pub const CHECK_MEMORY_USAGE_MEMORY: bool = true;
     pub fn check_memory_usage_memory() -> bool {
         CHECK_MEMORY_USAGE_MEMORY
@@ -328,13 +1780,227 @@ mod default {
     pub fn check_memory_usage_swap() -> bool {
         CHECK_MEMORY_USAGE_SWAP
     }
+
+    pub const CHECK_MEMORY_USAGE_COMBINED: bool = false;
+    pub fn check_memory_usage_combined() -> bool {
+        CHECK_MEMORY_USAGE_COMBINED
+    }
+
+    #[cfg(feature = "script")]
+    pub const CHECK_SCRIPT_TIMEOUT: u32 = 5;
+    #[cfg(feature = "script")]
+    pub fn check_script_timeout() -> u32 {
+        CHECK_SCRIPT_TIMEOUT
+    }
+
+    pub fn check_numa_memory_nodes() -> Vec<u32> {
+        vec![0]
+    }
+
+    pub const CHECK_PING_COUNT: u32 = 3;
+    pub fn check_ping_count() -> u32 {
+        CHECK_PING_COUNT
+    }
+
+    pub const CHECK_PING_TIMEOUT: u32 = 2;
+    pub fn check_ping_timeout() -> u32 {
+        CHECK_PING_TIMEOUT
+    }
+
+    pub const CHECK_PROCESS_PRESENCE_MIN: u32 = 1;
+    pub fn check_process_presence_min() -> u32 {
+        CHECK_PROCESS_PRESENCE_MIN
+    }
+
+    pub const CHECK_RTC_DRIFT_DEVICE: &str = "/dev/rtc0";
+    pub fn check_rtc_drift_device() -> String {
+        String::from(CHECK_RTC_DRIFT_DEVICE)
+    }
+
+    pub const CHECK_SYNTHETIC_LOGIN_TIMEOUT: u32 = 10;
+    pub fn check_synthetic_login_timeout() -> u32 {
+        CHECK_SYNTHETIC_LOGIN_TIMEOUT
+    }
+
+    pub const CHECK_SYNTHETIC_LOGIN_METHOD: super::HttpMethod = super::HttpMethod::GET;
+    pub fn check_synthetic_login_method() -> super::HttpMethod {
+        CHECK_SYNTHETIC_LOGIN_METHOD
+    }
+
+    pub const CHECK_CONCURRENCY: u32 = 10;
+    pub fn check_concurrency() -> u32 {
+        CHECK_CONCURRENCY
+    }
+
+    pub const CHECK_TCP_CONNECT_TIMEOUT: u32 = 5;
+    pub fn check_tcp_connect_timeout() -> u32 {
+        CHECK_TCP_CONNECT_TIMEOUT
+    }
+
+    pub const CHECK_TLS_CERT_EXPIRY_TIMEOUT: u32 = 10;
+    pub fn check_tls_cert_expiry_timeout() -> u32 {
+        CHECK_TLS_CERT_EXPIRY_TIMEOUT
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    fn from_path(path: &std::path::Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "toml" => Some(ConfigFormat::Toml),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            "json" => Some(ConfigFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            ConfigFormat::Toml => write!(f, "TOML"),
+            ConfigFormat::Yaml => write!(f, "YAML"),
+            ConfigFormat::Json => write!(f, "JSON"),
+        }
+    }
+}
+
+impl Config {
+    // Merge keys (`<<: *defaults`) are expanded before deserializing, otherwise they would be
+    // rejected as unknown fields.
+    fn from_yaml_str(text: &str) -> Result<Self, serde_yaml_ng::Error> {
+        let mut value: serde_yaml_ng::Value = serde_yaml_ng::from_str(text)?;
+        value.apply_merge()?;
+        serde_yaml_ng::from_value(value)
+    }
+
+    pub fn from_str_with_format(text: &str, format: ConfigFormat) -> Result<Self, Error> {
+        let mut config: Self = match format {
+            ConfigFormat::Toml => toml::from_str(text).map_err(|x| x.to_string()),
+            ConfigFormat::Yaml => Self::from_yaml_str(text).map_err(|x| x.to_string()),
+            ConfigFormat::Json => serde_json::from_str(text).map_err(|x| x.to_string()),
+        }
+        .map_err(|x| Error(format!("Could not parse {} config: {}", format, x)))?;
+        config.read_template_files()?;
+        config.expand_snippets()?;
+        config.validate_template_files()?;
+        for check in config.checks.iter_mut() {
+            check.proc_path = config.proc_path.clone();
+            check.sys_path = config.sys_path.clone();
+            check.resolver = config.resolver.clone();
+            check.capture = config.capture.clone();
+        }
+        for action in config.actions.iter_mut() {
+            action.resolver = config.resolver.clone();
+        }
+        Ok(config)
+    }
+
+    fn expand_snippets(&mut self) -> Result<(), Error> {
+        for action in self.actions.iter_mut() {
+            let templates = action.templates.values_mut().flat_map(|x| x.values_mut());
+            for template in action.type_.templates_mut().into_iter().chain(templates) {
+                *template = crate::expand_snippets(template, &self.snippets).map_err(|x| {
+                    Error(format!(
+                        "Failed to expand snippets of action '{}': {}",
+                        action.name, x
+                    ))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    // The template files are read whenever the config is loaded, so they can include snippets.
+    fn read_template_files(&mut self) -> Result<(), Error> {
+        for action in self.actions.iter_mut() {
+            for (name, template_file) in action.template_files.iter() {
+                let content = std::fs::read_to_string(&template_file.path).map_err(|x| {
+                    Error(format!(
+                        "Could not read template file {} of action '{}': {}",
+                        template_file.path.display(),
+                        action.name,
+                        x
+                    ))
+                })?;
+                let type_name = action.type_.to_string();
+                *action.type_.template_mut(name).ok_or_else(|| {
+                    Error(format!(
+                        "Action type {} has no template '{}'.",
+                        type_name, name
+                    ))
+                })? = content;
+            }
+        }
+        Ok(())
+    }
+
+    // JSON templates must be valid JSON with a neutral value in place of each placeholder, so
+    // they work inside strings as well as for numbers.
+    fn validate_template_files(&mut self) -> Result<(), Error> {
+        for action in self.actions.iter_mut() {
+            for (name, template_file) in action.template_files.iter() {
+                if template_file.format != TemplateFormat::Json {
+                    continue;
+                }
+                let template = action.type_.template_mut(name).unwrap();
+                let mut placeholders = PlaceholderMap::new();
+                let mut rest = template.as_str();
+                while let Some(start) = rest.find("{{") {
+                    let end = match rest[start..].find("}}") {
+                        Some(end) => start + end,
+                        None => break,
+                    };
+                    placeholders
+                        .insert(String::from(rest[start + 2..end].trim()), String::from("0"));
+                    rest = &rest[end + 2..];
+                }
+                let rendered = crate::fill_placeholders(template, &placeholders);
+                serde_json::from_str::<serde_json::Value>(&rendered).map_err(|x| {
+                    Error(format!(
+                        "Template file {} of action '{}' is not valid JSON: {}",
+                        template_file.path.display(),
+                        action.name,
+                        x
+                    ))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn from_str_detect_format(text: &str) -> Result<Self, Error> {
+        // JSON is also valid YAML, so it has to be tried first.
+        let formats = if text.trim_start().starts_with('{') {
+            [ConfigFormat::Json, ConfigFormat::Toml, ConfigFormat::Yaml]
+        } else {
+            [ConfigFormat::Toml, ConfigFormat::Yaml, ConfigFormat::Json]
+        };
+        let mut errors = Vec::new();
+        for format in formats {
+            match Self::from_str_with_format(text, format) {
+                Ok(config) => return Ok(config),
+                Err(err) => errors.push(err.to_string()),
+            }
+        }
+        Err(Error(format!(
+            "Could not detect config file format: {}",
+            errors.join(" ")
+        )))
+    }
 }
 
 impl TryFrom<&str> for Config {
     type Error = Error;
 
     fn try_from(text: &str) -> Result<Self, Self::Error> {
-        toml::from_str(text).map_err(|x| Error(x.to_string()))
+        Config::from_str_with_format(text, ConfigFormat::Toml)
     }
 }
 
@@ -347,7 +2013,10 @@ impl TryFrom<&std::path::Path> for Config {
         let mut content = String::new();
         file.read_to_string(&mut content)
             .map_err(|x| Error(x.to_string()))?;
-        Config::try_from(content.as_str())
+        match ConfigFormat::from_path(path) {
+            Some(format) => Config::from_str_with_format(&content, format),
+            None => Config::from_str_detect_format(&content),
+        }
     }
 }
 
@@ -367,6 +2036,10 @@ mod test {
         assert_eq!(config.report.events.len(), 0);
         assert_eq!(config.actions.len(), 0);
         assert_eq!(config.checks.len(), 0);
+        assert_eq!(config.canaries.len(), 0);
+        assert_eq!(config.snippets.len(), 0);
+        assert_eq!(config.proc_path, std::path::PathBuf::from("/proc"));
+        assert_eq!(config.sys_path, std::path::PathBuf::from("/sys"));
     }
 
     #[test]
@@ -438,6 +2111,8 @@ mod test {
                     String::from("application/json")
                 )]),
                 body: String::from(r#"{"name": "{{ name }}"}"#),
+                escape: WebhookEscape::Auto,
+                oauth2: None,
             })
         );
 
@@ -456,11 +2131,342 @@ mod test {
         let alarm = check.alarms.first().unwrap();
         assert!(alarm.disable);
         assert_eq!(alarm.name, "test-alarm");
-        assert_eq!(alarm.type_, AlarmType::Level(AlarmLevel { level: 75 }));
+        assert_eq!(alarm.type_, AlarmType::Level(AlarmLevel { level: 75.0 }));
         assert_eq!(alarm.cycles, 3);
         assert_eq!(alarm.repeat_cycles, 600);
         assert_eq!(alarm.action, "test-action");
         assert_eq!(alarm.recover_cycles, 4);
         assert_eq!(alarm.recover_action, Some(String::from("test-action")));
     }
+
+    #[test]
+    fn test_from_str_alarm_types() {
+        let text = r#"
+            [[checks]]
+            name = "test-check"
+            type = "PidFile"
+            paths = ["/run/foo.pid"]
+
+            [[checks.alarms]]
+            name = "status-alarm"
+            action = "test-action"
+
+            [[checks.alarms]]
+            name = "level-alarm"
+            action = "test-action"
+            level = 1
+        "#;
+        let config = Config::try_from(text).unwrap();
+        let check = config.checks.first().unwrap();
+        assert_eq!(
+            check.type_,
+            CheckType::PidFile(CheckPidFile {
+                paths: vec![std::path::PathBuf::from("/run/foo.pid")]
+            })
+        );
+        assert_eq!(check.alarms[0].type_, AlarmType::Status(AlarmStatus {}));
+        assert_eq!(
+            check.alarms[1].type_,
+            AlarmType::Level(AlarmLevel { level: 1.0 })
+        );
+    }
+
+    const FORMAT_TOML: &str = r#"
+        [[actions]]
+        name = "test-action"
+        type = "Log"
+        template = "{{check_name}}"
+
+        [[checks]]
+        name = "test-check"
+        type = "FilesystemUsage"
+        mountpoints = ["/"]
+
+        [[checks.alarms]]
+        name = "test-alarm"
+        level = 75
+        action = "test-action"
+    "#;
+
+    const FORMAT_YAML: &str = r#"
+actions:
+  - name: test-action
+    type: Log
+    template: "{{check_name}}"
+checks:
+  - name: test-check
+    type: FilesystemUsage
+    mountpoints: ["/"]
+    alarms:
+      - name: test-alarm
+        level: 75
+        action: test-action
+"#;
+
+    const FORMAT_JSON: &str = r#"{
+        "actions": [
+            {"name": "test-action", "type": "Log", "template": "{{check_name}}"}
+        ],
+        "checks": [
+            {
+                "name": "test-check",
+                "type": "FilesystemUsage",
+                "mountpoints": ["/"],
+                "alarms": [{"name": "test-alarm", "level": 75, "action": "test-action"}]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_formats_equivalent() {
+        let toml = Config::from_str_with_format(FORMAT_TOML, ConfigFormat::Toml).unwrap();
+        let yaml = Config::from_str_with_format(FORMAT_YAML, ConfigFormat::Yaml).unwrap();
+        let json = Config::from_str_with_format(FORMAT_JSON, ConfigFormat::Json).unwrap();
+        assert_eq!(toml, yaml);
+        assert_eq!(toml, json);
+    }
+
+    #[test]
+    fn test_yaml_merge_keys() {
+        let yaml = Config::from_str_with_format(
+            r#"
+actions:
+  - name: test-action
+    type: Log
+    template: "{{check_name}}"
+checks:
+  - &defaults
+    name: test-check
+    type: FilesystemUsage
+    mountpoints: ["/"]
+    alarms:
+      - name: test-alarm
+        level: 75
+        action: test-action
+  - <<: *defaults
+    name: test-check-2
+"#,
+            ConfigFormat::Yaml,
+        )
+        .unwrap();
+        assert_eq!(yaml.checks.len(), 2);
+        assert_eq!(yaml.checks[1].name, "test-check-2");
+        assert_eq!(yaml.checks[1].type_, yaml.checks[0].type_);
+        assert_eq!(yaml.checks[1].alarms, yaml.checks[0].alarms);
+    }
+
+    #[test]
+    fn test_detect_format() {
+        let toml = Config::from_str_detect_format(FORMAT_TOML).unwrap();
+        assert_eq!(toml, Config::from_str_detect_format(FORMAT_YAML).unwrap());
+        assert_eq!(toml, Config::from_str_detect_format(FORMAT_JSON).unwrap());
+        let err = Config::from_str_detect_format("checks = [").unwrap_err();
+        assert!(err.0.starts_with("Could not detect config file format:"));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        use std::path::Path;
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("/etc/minmon.toml")),
+            Some(ConfigFormat::Toml)
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("/etc/minmon.yml")),
+            Some(ConfigFormat::Yaml)
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("/etc/minmon.YAML")),
+            Some(ConfigFormat::Yaml)
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("/etc/minmon.json")),
+            Some(ConfigFormat::Json)
+        );
+        assert_eq!(ConfigFormat::from_path(Path::new("/etc/minmon")), None);
+    }
+
+    #[test]
+    fn test_snippets() {
+        let text = r#"
+            [snippets]
+            header = "[{{hostname}}]"
+
+            [[actions]]
+            name = "test-action"
+            type = "Log"
+            template = "{{>header}} {{alarm_name}}"
+        "#;
+        let config = Config::try_from(text).unwrap();
+        assert_eq!(
+            config.actions[0].type_,
+            ActionType::Log(ActionLog {
+                level: LogLevel::Info,
+                template: String::from("[{{hostname}}] {{alarm_name}}")
+            })
+        );
+        let text = r#"
+            [snippets]
+            a = "{{>b}}"
+            b = "{{>a}}"
+
+            [[actions]]
+            name = "test-action"
+            type = "Log"
+            template = "{{>a}}"
+        "#;
+        assert!(Config::try_from(text).is_err());
+    }
+
+    #[test]
+    fn test_action_templates() {
+        let text = r#"
+            [snippets]
+            header = "[{{hostname}}]"
+
+            [[actions]]
+            name = "test-action"
+            type = "Log"
+            template = "Alarm {{alarm_name}}"
+            templates.Good = {template = "{{>header}} Recovered {{alarm_name}}"}
+        "#;
+        let config = Config::try_from(text).unwrap();
+        let template = |action: Action| match action.type_ {
+            ActionType::Log(log) => log.template,
+            _ => panic!(),
+        };
+        let action = &config.actions[0];
+        assert_eq!(
+            template(action.with_templates(AlarmState::Good).unwrap()),
+            "[{{hostname}}] Recovered {{alarm_name}}"
+        );
+        assert_eq!(
+            template(action.with_templates(AlarmState::Error).unwrap()),
+            "Alarm {{alarm_name}}"
+        );
+        let text = r#"
+            [[actions]]
+            name = "test-action"
+            type = "Log"
+            template = "Alarm {{alarm_name}}"
+            templates.Bad = {subject = "Alarm"}
+        "#;
+        let config = Config::try_from(text).unwrap();
+        assert!(config.actions[0].with_templates(AlarmState::Bad).is_err());
+    }
+
+    #[test]
+    fn test_template_files() {
+        let path = std::env::temp_dir().join(format!("minmon-{}.json", uuid::Uuid::new_v4()));
+        let config = |name: &str, format: &str| {
+            Config::try_from(
+                format!(
+                    r#"
+                    [snippets]
+                    header = "[{{{{hostname}}}}]"
+
+                    [[actions]]
+                    name = "test-action"
+                    type = "Log"
+                    template_files = {{{} = {{path = "{}", format = "{}"}}}}
+                    "#,
+                    name,
+                    path.display(),
+                    format
+                )
+                .as_str(),
+            )
+        };
+        // missing file
+        assert!(config("template", "Text").is_err());
+        std::fs::write(
+            &path,
+            r#"{"text": "{{>header}} {{alarm_name}}", "level": {{level}}}"#,
+        )
+        .unwrap();
+        let json = config("template", "Json");
+        let unknown = config("subject", "Json");
+        std::fs::write(&path, r#"{"text": "{{alarm_name}}""#).unwrap();
+        let invalid_json = config("template", "Json");
+        let text = config("template", "Text");
+        std::fs::remove_file(&path).unwrap();
+        match &json.unwrap().actions[0].type_ {
+            ActionType::Log(log) => assert_eq!(
+                log.template,
+                r#"{"text": "[{{hostname}}] {{alarm_name}}", "level": {{level}}}"#
+            ),
+            _ => panic!(),
+        }
+        assert!(unknown.is_err());
+        assert!(invalid_json.is_err());
+        assert!(text.is_ok());
+    }
+
+    #[test]
+    fn test_template_files_all_fields() {
+        let path = std::env::temp_dir().join(format!("minmon-{}.txt", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "{{alarm_name}} from file").unwrap();
+        let config = |type_: &str, name: &str| {
+            let config = Config::try_from(
+                format!(
+                    r#"
+                    [[actions]]
+                    name = "test-action"
+                    {}
+                    template_files = {{"{}" = {{path = "{}"}}}}
+                    "#,
+                    type_,
+                    name,
+                    path.display()
+                )
+                .as_str(),
+            )
+            .unwrap();
+            config.actions[0].type_.clone()
+        };
+        let discord = config(
+            "type = \"Discord\"\nurl = \"\"\ncontent = \"\"\nembeds = [{title = \"\"}]",
+            "embeds.0.title",
+        );
+        let pager_duty = config(
+            "type = \"PagerDuty\"\nrouting_key = \"\"\nsummary = \"\"",
+            "dedup_key",
+        );
+        let ntfy = config(
+            "type = \"Ntfy\"\nurl = \"\"\ntopic = \"\"\ntitle = \"\"\nmessage = \"\"",
+            "priority",
+        );
+        let nagios = config(
+            "type = \"Nagios\"\nhost = \"\"\nservice = \"\"\noutput = \"\"",
+            "host",
+        );
+        let aws_sns = config(
+            "type = \"AwsSns\"\ntopic_arn = \"\"\nregion = \"\"\nmessage_attributes = {source = \"\"}",
+            "message_attributes.source",
+        );
+        std::fs::remove_file(&path).unwrap();
+        let template = "{{alarm_name}} from file";
+        match discord {
+            ActionType::Discord(discord) => assert_eq!(discord.embeds[0].title, template),
+            _ => panic!(),
+        }
+        match pager_duty {
+            ActionType::PagerDuty(pager_duty) => assert_eq!(pager_duty.dedup_key, template),
+            _ => panic!(),
+        }
+        match ntfy {
+            ActionType::Ntfy(ntfy) => assert_eq!(ntfy.priority, template),
+            _ => panic!(),
+        }
+        match nagios {
+            ActionType::Nagios(nagios) => assert_eq!(nagios.host, template),
+            _ => panic!(),
+        }
+        match aws_sns {
+            ActionType::AwsSns(aws_sns) => {
+                assert_eq!(aws_sns.message_attributes.get("source").unwrap(), template)
+            }
+            _ => panic!(),
+        }
+    }
 }
@@ -0,0 +1,156 @@
+use crate::config;
+use crate::Error;
+use std::collections::HashMap;
+
+// Self-protection for overloaded hosts: if many checks fail at the same time, the checks back off
+// and only critical actions are executed until the failures normalize.
+pub struct DegradedMode {
+    failing_percent: u8,
+    min_checks: u32,
+    backoff: u32,
+    // whether the last cycle of the check got no data at all, by check index
+    checks: std::sync::Mutex<HashMap<usize, bool>>,
+    active: std::sync::atomic::AtomicBool,
+}
+
+impl DegradedMode {
+    pub fn is_active(&self) -> bool {
+        self.active.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    // Records the last cycle of a check and enters or exits the degraded mode accordingly.
+    pub fn update(&self, check: usize, failing: bool) {
+        let mut checks = self.checks.lock().unwrap();
+        checks.insert(check, failing);
+        self.evaluate(&checks);
+    }
+
+    // Disabled checks don't run anymore, so they are no longer taken into account.
+    pub fn remove(&self, check: usize) {
+        let mut checks = self.checks.lock().unwrap();
+        checks.remove(&check);
+        self.evaluate(&checks);
+    }
+
+    fn evaluate(&self, checks: &HashMap<usize, bool>) {
+        let failing = checks.values().filter(|x| **x).count();
+        let degraded = failing >= self.min_checks as usize
+            && failing * 100 >= checks.len() * self.failing_percent as usize;
+        if degraded == self.is_active() {
+            return;
+        }
+        self.active
+            .store(degraded, std::sync::atomic::Ordering::Relaxed);
+        if degraded {
+            log::warn!(
+                "{} of {} checks are failing. Entering degraded mode, check intervals are multiplied by {} and only critical actions are executed.",
+                failing,
+                checks.len(),
+                self.backoff
+            );
+        } else {
+            log::info!(
+                "{} of {} checks are failing. Leaving degraded mode.",
+                failing,
+                checks.len()
+            );
+        }
+    }
+
+    pub fn interval(&self, interval: std::time::Duration) -> std::time::Duration {
+        if self.is_active() {
+            interval * self.backoff
+        } else {
+            interval
+        }
+    }
+}
+
+impl TryFrom<&config::DegradedMode> for DegradedMode {
+    type Error = Error;
+
+    fn try_from(degraded_mode: &config::DegradedMode) -> std::result::Result<Self, Self::Error> {
+        if degraded_mode.failing_percent == 0 || degraded_mode.failing_percent > 100 {
+            Err(Error(String::from(
+                "'failing_percent' must be between 1 and 100.",
+            )))
+        } else if degraded_mode.min_checks == 0 {
+            Err(Error(String::from("'min_checks' cannot be 0.")))
+        } else if degraded_mode.backoff == 0 {
+            Err(Error(String::from("'backoff' cannot be 0.")))
+        } else {
+            Ok(Self {
+                failing_percent: degraded_mode.failing_percent,
+                min_checks: degraded_mode.min_checks,
+                backoff: degraded_mode.backoff,
+                checks: std::sync::Mutex::new(HashMap::new()),
+                active: std::sync::atomic::AtomicBool::new(false),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn degraded_mode() -> DegradedMode {
+        let config = config::Config::try_from(
+            r#"
+            [degraded_mode]
+            failing_percent = 50
+            min_checks = 2
+            backoff = 4
+            "#,
+        )
+        .unwrap();
+        DegradedMode::try_from(config.degraded_mode.as_ref().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_backoff() {
+        let degraded_mode = degraded_mode();
+        let interval = std::time::Duration::from_secs(60);
+        for check in [0, 1, 2, 3] {
+            degraded_mode.update(check, false);
+        }
+        degraded_mode.update(0, true);
+        assert!(!degraded_mode.is_active());
+        // widespread failures
+        degraded_mode.update(1, true);
+        assert!(degraded_mode.is_active());
+        assert_eq!(
+            degraded_mode.interval(interval),
+            std::time::Duration::from_secs(240)
+        );
+        degraded_mode.update(2, true);
+        degraded_mode.update(0, false);
+        assert!(degraded_mode.is_active());
+        // the failures normalize
+        degraded_mode.update(1, false);
+        assert!(!degraded_mode.is_active());
+        assert_eq!(degraded_mode.interval(interval), interval);
+    }
+
+    #[test]
+    fn test_min_checks() {
+        let degraded_mode = degraded_mode();
+        // a single failing check is never a host-wide problem
+        degraded_mode.update(0, true);
+        assert!(!degraded_mode.is_active());
+        degraded_mode.update(1, true);
+        assert!(degraded_mode.is_active());
+    }
+
+    #[test]
+    fn test_remove() {
+        let degraded_mode = degraded_mode();
+        degraded_mode.update(0, true);
+        degraded_mode.update(1, true);
+        degraded_mode.update(2, false);
+        assert!(degraded_mode.is_active());
+        // e.g. an auto-disabled check
+        degraded_mode.remove(1);
+        assert!(!degraded_mode.is_active());
+    }
+}
@@ -3,9 +3,16 @@ compile_error!("Only Linux is supported");
 
 mod action;
 mod alarm;
+mod canary;
+mod capture;
 mod check;
 pub mod config;
+mod degraded_mode;
+mod redaction;
 mod report;
+mod resolver;
+#[cfg(test)]
+mod test_util;
 pub mod uptime;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -26,6 +33,13 @@ impl std::fmt::Display for Error {
     }
 }
 
+// All HTTP clients use the configured resolver, if any.
+fn http_client_builder(resolver: Option<&config::Resolver>) -> Result<reqwest::ClientBuilder> {
+    Ok(resolver::http_client_builder(
+        resolver::from_config(resolver)?.as_ref(),
+    ))
+}
+
 fn global_placeholders() -> PlaceholderMap {
     let mut res = PlaceholderMap::new();
     res.insert(
@@ -45,6 +59,9 @@ fn merge_placeholders(target: &mut PlaceholderMap, source: &PlaceholderMap) {
     }
 }
 
+// The template is parsed once and every placeholder is replaced exactly once. Values are inserted
+// verbatim and never scanned for placeholders themselves, so user controlled data cannot cause any
+// further (or recursive) expansion.
 fn fill_placeholders(template: &str, placeholders: &PlaceholderMap) -> String {
     let template = text_placeholder::Template::new(template);
     template.fill_with_hashmap(
@@ -55,22 +72,132 @@ fn fill_placeholders(template: &str, placeholders: &PlaceholderMap) -> String {
     )
 }
 
+const SNIPPET_START: &str = "{{>";
+const SNIPPET_END: &str = "}}";
+
+// Replaces `{{> name}}` with the snippet `name`. Snippets may include other snippets.
+fn expand_snippets(template: &str, snippets: &PlaceholderMap) -> Result<String> {
+    fn expand(
+        template: &str,
+        snippets: &PlaceholderMap,
+        stack: &mut Vec<String>,
+    ) -> Result<String> {
+        let mut res = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find(SNIPPET_START) {
+            res.push_str(&rest[..start]);
+            rest = &rest[start + SNIPPET_START.len()..];
+            let end = rest
+                .find(SNIPPET_END)
+                .ok_or_else(|| Error(String::from("Unterminated snippet include.")))?;
+            let name = rest[..end].trim();
+            rest = &rest[end + SNIPPET_END.len()..];
+            let snippet = snippets
+                .get(name)
+                .ok_or_else(|| Error(format!("Snippet '{}' not found.", name)))?;
+            if stack.iter().any(|x| x == name) {
+                return Err(Error(format!(
+                    "Circular snippet include: {} -> {}",
+                    stack.join(" -> "),
+                    name
+                )));
+            }
+            stack.push(String::from(name));
+            res.push_str(&expand(snippet, snippets, stack)?);
+            stack.pop();
+        }
+        res.push_str(rest);
+        Ok(res)
+    }
+    expand(template, snippets, &mut Vec::new())
+}
+
+// Simple wildcard matching: "*" matches any sequence of characters, "?" a single character.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    fn matches(pattern: &[char], text: &[char]) -> bool {
+        match (pattern.first(), text.first()) {
+            (None, None) => true,
+            (Some('*'), _) => {
+                matches(&pattern[1..], text) || (!text.is_empty() && matches(pattern, &text[1..]))
+            }
+            (Some('?'), Some(_)) => matches(&pattern[1..], &text[1..]),
+            (Some(x), Some(y)) if x == y => matches(&pattern[1..], &text[1..]),
+            _ => false,
+        }
+    }
+    matches(
+        &pattern.chars().collect::<Vec<char>>(),
+        &text.chars().collect::<Vec<char>>(),
+    )
+}
+
 fn iso8601(system_time: std::time::SystemTime) -> String {
     let date_time: chrono::DateTime<chrono::Utc> = system_time.into();
     date_time.format("%FT%TZ").to_string()
 }
 
-fn init_actions(config: &config::Config) -> Result<ActionMap> {
+// Duplicate names are an error unless they are configured to be a warning only.
+fn duplicate_name(config: &config::Config, message: String) -> Result<()> {
+    match config.duplicate_names {
+        config::DuplicateNames::Error => Err(Error(message)),
+        config::DuplicateNames::Warn => {
+            log::warn!("{}", message);
+            Ok(())
+        }
+    }
+}
+
+// One semaphore per action type, shared by all actions of that type.
+fn init_action_concurrency(
+    config: &config::Config,
+) -> Result<std::collections::HashMap<String, std::sync::Arc<tokio::sync::Semaphore>>> {
+    let mut res = std::collections::HashMap::new();
+    for (type_, limit) in config.action_concurrency.iter() {
+        if !config::ActionType::NAMES.contains(&type_.as_str()) {
+            return Err(Error(format!(
+                "Unknown action type '{}' in 'action_concurrency'.",
+                type_
+            )));
+        } else if *limit == 0 {
+            return Err(Error(format!(
+                "Concurrency of action type '{}' cannot be 0.",
+                type_
+            )));
+        }
+        res.insert(
+            type_.clone(),
+            std::sync::Arc::new(tokio::sync::Semaphore::new(*limit as usize)),
+        );
+        log::info!(
+            "At most {} action(s) of type {} run concurrently.",
+            limit,
+            type_
+        );
+    }
+    Ok(res)
+}
+
+fn init_actions(
+    config: &config::Config,
+    degraded_mode: Option<&std::sync::Arc<degraded_mode::DegradedMode>>,
+    error_count: &std::sync::Arc<std::sync::atomic::AtomicU64>,
+) -> Result<ActionMap> {
     log::info!("Initializing {} actions(s)..", config.actions.len());
+    let concurrency = init_action_concurrency(config)?;
     let mut res = ActionMap::new();
     for action_config in config.actions.iter() {
         if res.contains_key(&action_config.name) {
-            return Err(Error(format!(
-                "Found duplicate action name: {}",
-                action_config.name
-            )));
+            duplicate_name(
+                config,
+                format!("Found duplicate action name: {}", action_config.name),
+            )?;
         }
-        let action = action::from_action_config(action_config)?;
+        let action = action::from_action_config(
+            action_config,
+            concurrency.get(&action_config.type_.to_string()).cloned(),
+            degraded_mode.cloned(),
+            error_count.clone(),
+        )?;
         res.insert(action_config.name.clone(), action);
         log::info!(
             "Action {}::'{}' initialized.",
@@ -96,16 +223,34 @@ fn init_report(config: &config::Config, actions: &ActionMap) -> Result<Option<re
     Ok(Some(report))
 }
 
-fn init_checks(config: &config::Config, actions: &ActionMap) -> Result<Vec<Box<dyn check::Check>>> {
+fn init_checks(
+    config: &config::Config,
+    actions: &ActionMap,
+    incidents: &std::sync::Arc<alarm::Incidents>,
+    error_count: &std::sync::Arc<std::sync::atomic::AtomicU64>,
+) -> Result<Vec<Box<dyn check::Check>>> {
     log::info!("Initializing {} check(s)..", config.checks.len());
     let mut res: Vec<Box<dyn check::Check>> = Vec::new();
     let mut used_names = std::collections::HashSet::new();
-    for check_config in config.checks.iter() {
+    let values = check::ValueStore::default();
+    for (index, check_config) in config.checks.iter().enumerate() {
         if !used_names.insert(check_config.name.clone()) {
-            return Err(Error(format!(
-                "Found duplicate check name: {}",
-                check_config.name
-            )));
+            duplicate_name(
+                config,
+                format!("Found duplicate check name: {}", check_config.name),
+            )?;
+        }
+        let mut used_alarm_names = std::collections::HashSet::new();
+        for alarm_config in check_config.alarms.iter() {
+            if !used_alarm_names.insert(&alarm_config.name) {
+                duplicate_name(
+                    config,
+                    format!(
+                        "Found duplicate alarm name in check '{}': {}",
+                        check_config.name, alarm_config.name
+                    ),
+                )?;
+            }
         }
         if check_config.disable {
             log::info!(
@@ -115,7 +260,28 @@ fn init_checks(config: &config::Config, actions: &ActionMap) -> Result<Vec<Box<d
             );
             continue;
         }
-        let check = check::from_check_config(check_config, actions)?;
+        if let config::CheckType::Derived(derived) = &check_config.type_ {
+            for input in derived.inputs.values() {
+                if !config
+                    .checks
+                    .iter()
+                    .any(|x| !x.disable && x.name == input.check)
+                {
+                    return Err(Error(format!(
+                        "Check '{}' references unknown or disabled check '{}'.",
+                        check_config.name, input.check
+                    )));
+                }
+            }
+        }
+        let check = check::from_check_config(
+            check_config,
+            index,
+            actions,
+            &values,
+            incidents,
+            error_count,
+        )?;
         log::info!(
             "Check '{}' will be triggered every {} seconds.",
             check.name(),
@@ -126,13 +292,146 @@ fn init_checks(config: &config::Config, actions: &ActionMap) -> Result<Vec<Box<d
     Ok(res)
 }
 
-pub fn from_config(
+fn init_canaries(config: &config::Config, actions: &ActionMap) -> Result<Vec<canary::Canary>> {
+    log::info!("Initializing {} canary(s)..", config.canaries.len());
+    let mut res = Vec::new();
+    let mut used_names = std::collections::HashSet::new();
+    for canary_config in config.canaries.iter() {
+        if !used_names.insert(canary_config.name.clone()) {
+            duplicate_name(
+                config,
+                format!("Found duplicate canary name: {}", canary_config.name),
+            )?;
+        }
+        if canary_config.disable {
+            log::info!("Canary '{}' is disabled.", canary_config.name);
+            continue;
+        }
+        let canary = canary::from_canary_config(canary_config, actions)?;
+        log::info!(
+            "Canary '{}' will be triggered every {} seconds.",
+            canary.name(),
+            canary.interval().as_secs()
+        );
+        res.push(canary);
+    }
+    Ok(res)
+}
+
+fn init_degraded_mode(
     config: &config::Config,
-) -> Result<(Option<report::Report>, Vec<Box<dyn check::Check>>)> {
-    let actions = init_actions(config)?;
+) -> Result<Option<std::sync::Arc<degraded_mode::DegradedMode>>> {
+    let degraded_mode_config = match &config.degraded_mode {
+        Some(degraded_mode_config) => degraded_mode_config,
+        None => return Ok(None),
+    };
+    let degraded_mode = degraded_mode::DegradedMode::try_from(degraded_mode_config)
+        .map_err(|x| Error(format!("Failed to initialize degraded mode: {}", x)))?;
+    log::info!(
+        "Degraded mode will be entered if {}% of the checks (at least {}) are failing.",
+        degraded_mode_config.failing_percent,
+        degraded_mode_config.min_checks
+    );
+    Ok(Some(std::sync::Arc::new(degraded_mode)))
+}
+
+fn init_resolver(config: &config::Config) -> Result<()> {
+    let resolver_config = match &config.resolver {
+        Some(resolver_config) => resolver_config,
+        None => return Ok(()),
+    };
+    // Only validated here, the checks and actions create their own from the config.
+    resolver::Resolver::try_from(resolver_config)
+        .map_err(|x| Error(format!("Failed to initialize resolver: {}", x)))?;
+    log::info!("Names will be resolved using the configured resolver.");
+    Ok(())
+}
+
+fn init_capture(config: &config::Config) -> Result<()> {
+    let capture_config = match &config.capture {
+        Some(capture_config) => capture_config,
+        None => return Ok(()),
+    };
+    // Only validated here, the checks create their own from the config.
+    capture::Capture::try_from(capture_config)
+        .map_err(|x| Error(format!("Failed to initialize capture limit: {}", x)))?;
+    log::info!(
+        "Captured output is limited to {} bytes.",
+        capture_config.max_size
+    );
+    Ok(())
+}
+
+// Everything that is scheduled by the main loop.
+pub struct Monitor {
+    pub report: Option<report::Report>,
+    pub checks: Vec<Box<dyn check::Check>>,
+    pub canaries: Vec<canary::Canary>,
+    // only set if the degraded mode is configured, see `run_check`
+    pub degraded_mode: Option<std::sync::Arc<degraded_mode::DegradedMode>>,
+}
+
+pub fn from_config(config: &config::Config) -> Result<Monitor> {
+    let degraded_mode = init_degraded_mode(config)?;
+    init_resolver(config)?;
+    init_capture(config)?;
+    // number of internal errors (data errors and failed actions), see the ErrorRate check
+    let error_count = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
+    let actions = init_actions(config, degraded_mode.as_ref(), &error_count)?;
     let report = init_report(config, &actions)?;
-    let checks = init_checks(config, &actions)?;
-    Ok((report, checks))
+    // open incidents of the alarms of all checks
+    let incidents = std::sync::Arc::new(alarm::Incidents::default());
+    let checks = init_checks(config, &actions, &incidents, &error_count)?;
+    let canaries = init_canaries(config, &actions)?;
+    Ok(Monitor {
+        report,
+        checks,
+        canaries,
+        degraded_mode,
+    })
+}
+
+// `index` identifies the check within the degraded mode.
+pub async fn run_check(
+    mut check: Box<dyn check::Check>,
+    index: usize,
+    degraded_mode: Option<std::sync::Arc<degraded_mode::DegradedMode>>,
+) {
+    let period = check.interval();
+    let mut interval = tokio::time::interval(period);
+    loop {
+        let start = interval.tick().await;
+        check.trigger().await;
+        if check.disabled() {
+            if let Some(degraded_mode) = &degraded_mode {
+                degraded_mode.remove(index);
+            }
+            break;
+        }
+        // The checks back off while the degraded mode is active.
+        let current = match &degraded_mode {
+            Some(degraded_mode) => {
+                degraded_mode.update(index, check.failing());
+                degraded_mode.interval(period)
+            }
+            None => period,
+        };
+        if check.skip_overlapping() {
+            let skipped = (start.elapsed().as_nanos() / current.as_nanos()) as u32;
+            if skipped > 0 {
+                log::warn!(
+                    "Check '{}' took longer than its interval, skipping {} cycle(s).",
+                    check.name(),
+                    skipped
+                );
+                interval = tokio::time::interval_at(start + current * (skipped + 1), period);
+                continue;
+            }
+        }
+        if current != period {
+            interval = tokio::time::interval_at(start + current, period);
+        }
+    }
 }
 
 fn get_number<T>(error_message: &str, line: &str, column: usize) -> Result<T>
@@ -154,6 +453,114 @@ where
 mod test {
     use super::*;
 
+    fn duplicate_config(duplicate_names: &str) -> config::Config {
+        config::Config::try_from(
+            format!(
+                r#"
+                duplicate_names = "{}"
+
+                [report]
+                disable = true
+
+                [[actions]]
+                name = "Log"
+                type = "Log"
+                template = "first"
+
+                [[actions]]
+                name = "Log"
+                type = "Log"
+                template = "second"
+
+                [[checks]]
+                name = "Memory"
+                type = "MemoryUsage"
+
+                [[checks.alarms]]
+                name = "High"
+                action = "Log"
+                level = 80
+
+                [[checks.alarms]]
+                name = "High"
+                action = "Log"
+                level = 90
+
+                [[checks]]
+                name = "Memory"
+                type = "MemoryUsage"
+                "#,
+                duplicate_names
+            )
+            .as_str(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_duplicate_names_error() {
+        let mut config = duplicate_config("Error");
+        assert_eq!(
+            from_config(&config).err().unwrap().to_string(),
+            "Found duplicate action name: Log"
+        );
+        config.actions.pop();
+        assert_eq!(
+            from_config(&config).err().unwrap().to_string(),
+            "Found duplicate alarm name in check 'Memory': High"
+        );
+        config.checks[0].alarms.pop();
+        assert_eq!(
+            from_config(&config).err().unwrap().to_string(),
+            "Found duplicate check name: Memory"
+        );
+        config.checks.pop();
+        assert!(from_config(&config).is_ok());
+    }
+
+    #[test]
+    fn test_action_concurrency() {
+        let mut config =
+            config::Config::try_from("action_concurrency = {Email = 2, Log = 1}").unwrap();
+        let concurrency = init_action_concurrency(&config).unwrap();
+        assert_eq!(concurrency.get("Email").unwrap().available_permits(), 2);
+        assert_eq!(concurrency.get("Log").unwrap().available_permits(), 1);
+        config.action_concurrency.insert(String::from("Pager"), 1);
+        assert_eq!(
+            init_action_concurrency(&config).err().unwrap().to_string(),
+            "Unknown action type 'Pager' in 'action_concurrency'."
+        );
+        config.action_concurrency.remove("Pager");
+        config.action_concurrency.insert(String::from("Webhook"), 0);
+        assert!(init_action_concurrency(&config).is_err());
+    }
+
+    #[test]
+    fn test_from_config_twice() {
+        let config = config::Config::try_from(
+            r#"
+            degraded_mode = {}
+            resolver = {address = "192.0.2.53"}
+            capture = {max_size = 1024}
+
+            [[checks]]
+            name = "Memory"
+            type = "MemoryUsage"
+            "#,
+        )
+        .unwrap();
+        assert!(config.checks[0].resolver.is_some());
+        assert!(config.checks[0].capture.is_some());
+        assert!(from_config(&config).unwrap().degraded_mode.is_some());
+        assert!(from_config(&config).unwrap().degraded_mode.is_some());
+    }
+
+    #[test]
+    fn test_duplicate_names_warn() {
+        let config = duplicate_config("Warn");
+        assert_eq!(from_config(&config).unwrap().checks.len(), 2);
+    }
+
     #[test]
     fn test_merge_placeholders() {
         let mut target = PlaceholderMap::from([(String::from("A"), String::from("?"))]);
@@ -174,6 +581,130 @@ mod test {
         assert_eq!(filled, "XYZ");
     }
 
+    #[test]
+    fn test_fill_placeholders_single_pass() {
+        let template = "{{A}}|{{B}}";
+        let placeholders = PlaceholderMap::from([
+            (String::from("A"), String::from("{{B}}")),
+            (String::from("B"), String::from("{{A}}{{A}}")),
+        ]);
+        let filled = fill_placeholders(template, &placeholders);
+        assert_eq!(filled, "{{B}}|{{A}}{{A}}");
+    }
+
+    #[test]
+    fn test_fill_placeholders_self_reference() {
+        let template = "{{A}}";
+        let placeholders = PlaceholderMap::from([(String::from("A"), String::from("x{{A}}y"))]);
+        let filled = fill_placeholders(template, &placeholders);
+        assert_eq!(filled, "x{{A}}y");
+    }
+
+    struct SlowCheck {
+        duration: std::time::Duration,
+        skip_overlapping: bool,
+        runs: std::sync::Arc<std::sync::atomic::AtomicU32>,
+    }
+
+    #[async_trait::async_trait]
+    impl check::Check for SlowCheck {
+        async fn trigger(&mut self) {
+            self.runs.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            tokio::time::sleep(self.duration).await;
+        }
+
+        fn interval(&self) -> std::time::Duration {
+            std::time::Duration::from_secs(10)
+        }
+
+        fn skip_overlapping(&self) -> bool {
+            self.skip_overlapping
+        }
+
+        fn name(&self) -> &str {
+            "slow"
+        }
+    }
+
+    async fn count_runs(skip_overlapping: bool) -> u32 {
+        let runs = std::sync::Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let check = Box::new(SlowCheck {
+            duration: std::time::Duration::from_secs(15),
+            skip_overlapping,
+            runs: runs.clone(),
+        });
+        let handle = tokio::spawn(run_check(check, 0, None));
+        tokio::time::sleep(std::time::Duration::from_secs(95)).await;
+        handle.abort();
+        runs.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_check_overlapping() {
+        // runs start at 0, 15, 30, 45, 60, 75 and 90
+        assert_eq!(count_runs(false).await, 7);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_check_skip_overlapping() {
+        // runs start at 0, 20, 40, 60 and 80
+        assert_eq!(count_runs(true).await, 5);
+    }
+
+    #[test]
+    fn test_expand_snippets() {
+        let snippets = PlaceholderMap::from([
+            (
+                String::from("header"),
+                String::from("[{{>host}} {{alarm_timestamp}}]"),
+            ),
+            (String::from("host"), String::from("{{hostname}}")),
+            (String::from("footer"), String::from("-- MinMon")),
+        ]);
+        let template = "{{> header }} Alarm '{{alarm_name}}'.\n{{>footer}}";
+        let expanded = expand_snippets(template, &snippets).unwrap();
+        assert_eq!(
+            expanded,
+            "[{{hostname}} {{alarm_timestamp}}] Alarm '{{alarm_name}}'.\n-- MinMon"
+        );
+        let placeholders = PlaceholderMap::from([
+            (String::from("hostname"), String::from("server")),
+            (String::from("alarm_timestamp"), String::from("now")),
+            (String::from("alarm_name"), String::from("Foo")),
+        ]);
+        assert_eq!(
+            fill_placeholders(&expanded, &placeholders),
+            "[server now] Alarm 'Foo'.\n-- MinMon"
+        );
+    }
+
+    #[test]
+    fn test_expand_snippets_invalid() {
+        let snippets = PlaceholderMap::from([
+            (String::from("a"), String::from("A{{>b}}")),
+            (String::from("b"), String::from("B{{>a}}")),
+            (String::from("c"), String::from("C{{>c}}")),
+        ]);
+        assert_eq!(
+            expand_snippets("{{>a}}", &snippets).unwrap_err().0,
+            "Circular snippet include: a -> b -> a"
+        );
+        assert!(expand_snippets("{{>c}}", &snippets).is_err());
+        assert!(expand_snippets("{{>missing}}", &snippets).is_err());
+        assert!(expand_snippets("{{>a", &snippets).is_err());
+        assert_eq!(expand_snippets("{{a}}", &snippets).unwrap(), "{{a}}");
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*", "foo.job"));
+        assert!(wildcard_match("*.job", "foo.job"));
+        assert!(wildcard_match("f?o*", "foo.job"));
+        assert!(wildcard_match("*o*o*", "foo.job"));
+        assert!(!wildcard_match("*.job", "foo.job.tmp"));
+        assert!(!wildcard_match("?", ""));
+    }
+
     #[test]
     fn test_iso8601() {
         let system_time = std::time::SystemTime::UNIX_EPOCH;
@@ -58,23 +58,31 @@ async fn main_wrapper() -> Result<()> {
         systemd::init();
     }
 
-    let (report, checks) = minmon::from_config(&config)?;
-    for mut check in checks {
+    let monitor = minmon::from_config(&config)?;
+    for (index, check) in monitor.checks.into_iter().enumerate() {
+        tokio::spawn(minmon::run_check(
+            check,
+            index,
+            monitor.degraded_mode.clone(),
+        ));
+    }
+
+    if let Some(mut report) = monitor.report {
         tokio::spawn(async move {
-            let mut interval = tokio::time::interval(check.interval());
+            let mut interval = tokio::time::interval(report.interval());
             loop {
                 interval.tick().await;
-                check.trigger().await;
+                report.trigger().await;
             }
         });
     }
 
-    if let Some(mut report) = report {
+    for mut canary in monitor.canaries {
         tokio::spawn(async move {
-            let mut interval = tokio::time::interval(report.interval());
+            let mut interval = tokio::time::interval(canary.interval());
             loop {
                 interval.tick().await;
-                report.trigger().await;
+                canary.trigger().await;
             }
         });
     }
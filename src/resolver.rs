@@ -0,0 +1,461 @@
+use crate::config;
+use crate::{Error, Result};
+use std::net::{IpAddr, SocketAddr};
+
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+
+enum Target {
+    // plain DNS over UDP
+    Dns(SocketAddr),
+    // DNS-over-HTTPS using the JSON API, e.g. https://cloudflare-dns.com/dns-query
+    Doh {
+        url: String,
+        client: reqwest::Client,
+    },
+}
+
+// Resolves host names using a configured name server instead of the system's resolver, e.g. to
+// not depend on a broken /etc/resolv.conf or to monitor a specific DNS path.
+pub struct Resolver {
+    target: Target,
+    timeout: std::time::Duration,
+}
+
+impl Resolver {
+    fn query(id: u16, host: &str, type_: u16) -> Vec<u8> {
+        let mut res = Vec::new();
+        res.extend_from_slice(&id.to_be_bytes());
+        // recursion desired, one question
+        res.extend_from_slice(&[0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+        for label in host.trim_end_matches('.').split('.') {
+            res.push(label.len() as u8);
+            res.extend_from_slice(label.as_bytes());
+        }
+        res.push(0);
+        res.extend_from_slice(&type_.to_be_bytes());
+        // class IN
+        res.extend_from_slice(&[0, 1]);
+        res
+    }
+
+    // Returns the offset after the (possibly compressed) name at `offset`.
+    fn skip_name(message: &[u8], mut offset: usize) -> Option<usize> {
+        loop {
+            let length = *message.get(offset)?;
+            if length == 0 {
+                return Some(offset + 1);
+            } else if length & 0xc0 == 0xc0 {
+                return Some(offset + 2);
+            }
+            offset += 1 + length as usize;
+        }
+    }
+
+    fn parse_response(id: u16, message: &[u8]) -> Result<Vec<IpAddr>> {
+        let invalid = || Error(String::from("Invalid DNS response."));
+        let get_u16 = |offset: usize| -> Result<u16> {
+            Ok(u16::from_be_bytes(
+                message
+                    .get(offset..offset + 2)
+                    .ok_or_else(invalid)?
+                    .try_into()
+                    .unwrap(),
+            ))
+        };
+        if get_u16(0)? != id {
+            return Err(invalid());
+        }
+        let flags = get_u16(2)?;
+        if flags & 0x0200 != 0 {
+            return Err(Error(String::from("DNS response is truncated.")));
+        }
+        match flags & 0x000f {
+            0 => {}
+            3 => return Err(Error(String::from("Name does not exist."))),
+            rcode => return Err(Error(format!("DNS query failed with rcode {}.", rcode))),
+        }
+        let mut offset = 12;
+        for _ in 0..get_u16(4)? {
+            offset = Self::skip_name(message, offset).ok_or_else(invalid)? + 4;
+        }
+        let mut res = Vec::new();
+        for _ in 0..get_u16(6)? {
+            offset = Self::skip_name(message, offset).ok_or_else(invalid)?;
+            let type_ = get_u16(offset)?;
+            let length = get_u16(offset + 8)? as usize;
+            let data = message
+                .get(offset + 10..offset + 10 + length)
+                .ok_or_else(invalid)?;
+            match (type_, length) {
+                (TYPE_A, 4) => res.push(IpAddr::from(<[u8; 4]>::try_from(data).unwrap())),
+                (TYPE_AAAA, 16) => res.push(IpAddr::from(<[u8; 16]>::try_from(data).unwrap())),
+                _ => {}
+            }
+            offset += 10 + length;
+        }
+        Ok(res)
+    }
+
+    async fn lookup_dns(address: &SocketAddr, host: &str, type_: u16) -> Result<Vec<IpAddr>> {
+        let bind_address = if address.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = tokio::net::UdpSocket::bind(bind_address)
+            .await
+            .map_err(|x| Error(format!("Could not bind UDP socket: {}", x)))?;
+        // Connected, the socket only receives datagrams from the name server.
+        socket
+            .connect(address)
+            .await
+            .map_err(|x| Error(format!("Could not connect UDP socket: {}", x)))?;
+        let random = uuid::Uuid::new_v4();
+        let id = u16::from_be_bytes([random.as_bytes()[0], random.as_bytes()[1]]);
+        socket
+            .send(&Self::query(id, host, type_))
+            .await
+            .map_err(|x| Error(format!("Could not send DNS query: {}", x)))?;
+        let mut buffer = [0; 4096];
+        let length = socket
+            .recv(&mut buffer)
+            .await
+            .map_err(|x| Error(format!("Could not receive DNS response: {}", x)))?;
+        Self::parse_response(id, &buffer[..length])
+    }
+
+    // see https://developers.google.com/speed/public-dns/docs/doh/json
+    async fn lookup_doh(
+        client: &reqwest::Client,
+        url: &str,
+        host: &str,
+        type_: u16,
+    ) -> Result<Vec<IpAddr>> {
+        let response = client
+            .get(url)
+            .query(&[("name", host), ("type", &type_.to_string())])
+            .header(reqwest::header::ACCEPT, "application/dns-json")
+            .send()
+            .await
+            .map_err(|x| Error(format!("HTTP request failed: {}", x)))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error(format!(
+                "HTTP status code {} indicates error.",
+                status.as_u16()
+            )));
+        }
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|x| Error(format!("Could not parse DNS response: {}", x)))?;
+        match json["Status"].as_u64() {
+            Some(0) => {}
+            Some(3) => return Err(Error(String::from("Name does not exist."))),
+            Some(rcode) => return Err(Error(format!("DNS query failed with rcode {}.", rcode))),
+            None => return Err(Error(String::from("Invalid DNS response."))),
+        }
+        Ok(json["Answer"]
+            .as_array()
+            .map(|x| {
+                x.iter()
+                    .filter(|x| x["type"].as_u64() == Some(type_.into()))
+                    .filter_map(|x| x["data"].as_str()?.parse().ok())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    async fn lookup_type(&self, host: &str, type_: u16) -> Result<Vec<IpAddr>> {
+        tokio::time::timeout(self.timeout, async {
+            match &self.target {
+                Target::Dns(address) => Self::lookup_dns(address, host, type_).await,
+                Target::Doh { url, client } => Self::lookup_doh(client, url, host, type_).await,
+            }
+        })
+        .await
+        .map_err(|_| {
+            Error(format!(
+                "DNS query timed out after {} seconds.",
+                self.timeout.as_secs()
+            ))
+        })?
+    }
+
+    // A failed query doesn't discard the addresses of the other one, e.g. if the name server
+    // doesn't answer AAAA queries.
+    pub async fn lookup(&self, host: &str) -> Result<Vec<IpAddr>> {
+        let mut res = Vec::new();
+        let mut error = None;
+        for type_ in [TYPE_A, TYPE_AAAA] {
+            match self.lookup_type(host, type_).await {
+                Ok(addresses) => res.extend(addresses),
+                Err(err) => {
+                    error.get_or_insert(err);
+                }
+            }
+        }
+        if res.is_empty() {
+            Err(error.unwrap_or_else(|| Error(format!("No addresses found for {}.", host))))
+        } else {
+            Ok(res)
+        }
+    }
+}
+
+impl TryFrom<&config::Resolver> for Resolver {
+    type Error = Error;
+
+    fn try_from(resolver: &config::Resolver) -> std::result::Result<Self, self::Error> {
+        if resolver.timeout == 0 {
+            return Err(Error(String::from("'timeout' cannot be 0.")));
+        }
+        let target = match (&resolver.address, &resolver.doh_url) {
+            (Some(address), None) => Target::Dns(match address.parse::<IpAddr>() {
+                Ok(ip) => SocketAddr::new(ip, 53),
+                Err(_) => address
+                    .parse()
+                    .map_err(|_| Error(format!("Invalid address '{}'.", address)))?,
+            }),
+            (None, Some(url)) if !url.is_empty() => Target::Doh {
+                url: url.clone(),
+                // The DoH server itself is resolved by the system's resolver.
+                client: reqwest::Client::builder()
+                    .user_agent(crate::user_agent())
+                    .build()
+                    .map_err(|x| Error(format!("Could not create HTTP client: {}", x)))?,
+            },
+            (None, Some(_)) => return Err(Error(String::from("'doh_url' cannot be empty."))),
+            _ => {
+                return Err(Error(String::from(
+                    "Either 'address' or 'doh_url' must be set.",
+                )))
+            }
+        };
+        Ok(Self {
+            target,
+            timeout: std::time::Duration::from_secs(resolver.timeout.into()),
+        })
+    }
+}
+
+// The checks and actions get a copy of the `resolver` section of the config.
+pub fn from_config(
+    resolver: Option<&config::Resolver>,
+) -> Result<Option<std::sync::Arc<Resolver>>> {
+    resolver
+        .map(|x| Resolver::try_from(x).map(std::sync::Arc::new))
+        .transpose()
+}
+
+struct HttpResolver(std::sync::Arc<Resolver>);
+
+impl reqwest::dns::Resolve for HttpResolver {
+    fn resolve(&self, name: hyper::client::connect::dns::Name) -> reqwest::dns::Resolving {
+        let resolver = self.0.clone();
+        Box::pin(async move {
+            let addresses = resolver.lookup(name.as_str()).await?;
+            // The port is set by reqwest.
+            let addresses: reqwest::dns::Addrs =
+                Box::new(addresses.into_iter().map(|x| SocketAddr::new(x, 0)));
+            Ok(addresses)
+        })
+    }
+}
+
+// All HTTP clients use the configured resolver, if any.
+pub fn http_client_builder(resolver: Option<&std::sync::Arc<Resolver>>) -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder();
+    match resolver {
+        Some(resolver) => builder.dns_resolver(std::sync::Arc::new(HttpResolver(resolver.clone()))),
+        None => builder,
+    }
+}
+
+// Resolves an address like "example.com:443" or "[::1]:80".
+pub async fn lookup_host(resolver: Option<&Resolver>, address: &str) -> Result<Vec<SocketAddr>> {
+    let error = |x: Error| Error(format!("Could not resolve address: {}", x));
+    let (host, port) = address
+        .rsplit_once(':')
+        .ok_or_else(|| error(Error(format!("Port missing in '{}'.", address))))?;
+    let port: u16 = port
+        .parse()
+        .map_err(|_| error(Error(format!("Invalid port in '{}'.", address))))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return Ok(vec![SocketAddr::new(ip, port)]);
+    }
+    match resolver {
+        Some(resolver) => Ok(resolver
+            .lookup(host)
+            .await
+            .map_err(error)?
+            .into_iter()
+            .map(|x| SocketAddr::new(x, port))
+            .collect()),
+        None => Ok(tokio::net::lookup_host(address)
+            .await
+            .map_err(|x| error(Error(x.to_string())))?
+            .collect()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::mock_server;
+
+    // Answers "monitored.test" and "servfail.test" with 127.0.0.1 (and no IPv6 address), the AAAA
+    // query of the latter fails. Other names don't exist.
+    async fn mock_dns_server() -> SocketAddr {
+        let socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buffer = [0; 512];
+            loop {
+                let (length, peer) = socket.recv_from(&mut buffer).await.unwrap();
+                let query = &buffer[..length];
+                let question_end = Resolver::skip_name(query, 12).unwrap() + 4;
+                let name = &query[12..question_end - 4];
+                let type_ = u16::from_be_bytes([query[question_end - 4], query[question_end - 3]]);
+                let servfail = name == b"\x08servfail\x04test\x00";
+                let known = servfail || name == b"\x09monitored\x04test\x00";
+                let answer = known && type_ == TYPE_A;
+                let rcode = if servfail && type_ == TYPE_AAAA {
+                    2
+                } else if known {
+                    0
+                } else {
+                    3
+                };
+                let mut response = query[..2].to_vec();
+                response.extend_from_slice(&[0x81, 0x80 | rcode]);
+                response.extend_from_slice(&[0, 1, 0, answer as u8, 0, 0, 0, 0]);
+                response.extend_from_slice(&query[12..question_end]);
+                if answer {
+                    // pointer to the name in the question, type A, class IN, TTL 60
+                    response.extend_from_slice(&[0xc0, 12, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4]);
+                    response.extend_from_slice(&[127, 0, 0, 1]);
+                }
+                socket.send_to(&response, peer).await.unwrap();
+            }
+        });
+        address
+    }
+
+    fn resolver(config: &str) -> Resolver {
+        let config = config::Config::try_from(format!("[resolver]\n{}", config).as_str()).unwrap();
+        Resolver::try_from(config.resolver.as_ref().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_dns() {
+        let address = mock_dns_server().await;
+        let resolver = resolver(&format!("address = \"{}\"", address));
+        assert_eq!(
+            resolver.lookup("monitored.test").await.unwrap(),
+            vec![IpAddr::from([127, 0, 0, 1])]
+        );
+        assert_eq!(
+            resolver
+                .lookup("unknown.test")
+                .await
+                .unwrap_err()
+                .to_string(),
+            "Name does not exist."
+        );
+        // the A record is kept although the AAAA query failed
+        assert_eq!(
+            resolver.lookup("servfail.test").await.unwrap(),
+            vec![IpAddr::from([127, 0, 0, 1])]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_doh() {
+        let url = mock_server(|request| {
+            if !request.contains("accept: application/dns-json") {
+                (400, String::new())
+            } else if request.starts_with("GET /dns-query?name=monitored.test&type=1 ") {
+                (
+                    200,
+                    String::from(
+                        r#"{"Status": 0, "Answer": [{"name": "monitored.test", "type": 5, "data": "x.test."}, {"name": "x.test", "type": 1, "data": "127.0.0.2"}]}"#,
+                    ),
+                )
+            } else if request.starts_with("GET /dns-query?name=monitored.test&type=28 ") {
+                (
+                    200,
+                    String::from(r#"{"Status": 0, "Answer": [{"name": "x.test", "type": 28, "data": "::1"}]}"#),
+                )
+            } else {
+                (200, String::from(r#"{"Status": 3}"#))
+            }
+        })
+        .await;
+        let resolver = resolver(&format!("doh_url = \"{}/dns-query\"", url));
+        assert_eq!(
+            resolver.lookup("monitored.test").await.unwrap(),
+            vec![IpAddr::from([127, 0, 0, 2]), "::1".parse().unwrap()]
+        );
+        assert!(resolver.lookup("unknown.test").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_lookup_host() {
+        let address = mock_dns_server().await;
+        let resolver = resolver(&format!("address = \"{}\"", address));
+        assert_eq!(
+            lookup_host(Some(&resolver), "monitored.test:8080")
+                .await
+                .unwrap(),
+            vec!["127.0.0.1:8080".parse().unwrap()]
+        );
+        // IP addresses are not resolved
+        assert_eq!(
+            lookup_host(Some(&resolver), "[::1]:80").await.unwrap(),
+            vec!["[::1]:80".parse().unwrap()]
+        );
+        assert!(lookup_host(Some(&resolver), "unknown.test:80")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_http_client() {
+        let address = mock_dns_server().await;
+        let resolver = std::sync::Arc::new(resolver(&format!("address = \"{}\"", address)));
+        let url = mock_server(|request| {
+            if request.contains("host: monitored.test:") {
+                (200, String::from("OK"))
+            } else {
+                (400, String::new())
+            }
+        })
+        .await;
+        let port = url.rsplit_once(':').unwrap().1;
+        let client = http_client_builder(Some(&resolver)).build().unwrap();
+        let response = client
+            .get(format!("http://monitored.test:{}/", port))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        for config in [
+            "",
+            "address = \"foo\"",
+            "doh_url = \"\"",
+            "address = \"127.0.0.1\"\ndoh_url = \"https://x\"",
+            "address = \"127.0.0.1\"\ntimeout = 0",
+        ] {
+            let config =
+                config::Config::try_from(format!("[resolver]\n{}", config).as_str()).unwrap();
+            assert!(Resolver::try_from(config.resolver.as_ref().unwrap()).is_err());
+        }
+    }
+}
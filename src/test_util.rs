@@ -0,0 +1,115 @@
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+// Minimal HTTP server that answers every request with the response returned by `respond` for
+// the request (head and body). Returns the base URL.
+pub async fn mock_server<F>(respond: F) -> String
+where
+    F: Fn(&str) -> (u16, String) + Send + Sync + 'static,
+{
+    mock_server_with_headers(move |request| {
+        let (status, body) = respond(request);
+        (status, Vec::new(), body)
+    })
+    .await
+}
+
+// Same as `mock_server` but `respond` also returns additional response headers.
+pub async fn mock_server_with_headers<F>(respond: F) -> String
+where
+    F: Fn(&str) -> (u16, Vec<(String, String)>, String) + Send + Sync + 'static,
+{
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let respond = std::sync::Arc::new(respond);
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let respond = respond.clone();
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buffer = [0; 4096];
+                loop {
+                    let n = stream.read(&mut buffer).await.unwrap();
+                    if n == 0 {
+                        return;
+                    }
+                    request.extend_from_slice(&buffer[..n]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let content_length = head
+                            .lines()
+                            .find_map(|x| {
+                                x.to_ascii_lowercase()
+                                    .strip_prefix("content-length:")
+                                    .map(|x| x.trim().parse::<usize>().unwrap())
+                            })
+                            .unwrap_or(0);
+                        if body.len() >= content_length {
+                            break;
+                        }
+                    }
+                }
+                let (status, headers, body) = respond(&String::from_utf8_lossy(&request));
+                let headers: String = headers
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}\r\n", name, value))
+                    .collect();
+                let response = format!(
+                    "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    headers,
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            });
+        }
+    });
+    url
+}
+
+// Logger that keeps the messages of all log records, so tests can check what gets logged.
+struct CaptureLogger {
+    messages: std::sync::Mutex<Vec<String>>,
+}
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        self.messages
+            .lock()
+            .unwrap()
+            .push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURE_LOGGER: CaptureLogger = CaptureLogger {
+    messages: std::sync::Mutex::new(Vec::new()),
+};
+
+// Installs the capturing logger. Tests run in parallel, so the messages of other tests are
+// captured as well.
+pub fn capture_logs() {
+    static INIT: std::sync::Once = std::sync::Once::new();
+    INIT.call_once(|| {
+        log::set_logger(&CAPTURE_LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+    });
+}
+
+// Captured messages that contain `filter`.
+pub fn captured_logs(filter: &str) -> Vec<String> {
+    CAPTURE_LOGGER
+        .messages
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|x| x.contains(filter))
+        .cloned()
+        .collect()
+}